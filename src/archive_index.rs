@@ -0,0 +1,282 @@
+use crate::exif::{DateTimeZone, EntryContext};
+use crate::meta_export::MetaMediaIndex;
+use crate::sidecar::{AlbumMetadataIndex, Sidecar, SidecarIndex};
+use crate::snapchat::SnapchatMemoryIndex;
+use crate::zip_image_reader::{EntryInfo, ZipEntry};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+
+/// Still image and video extensions of the two halves of a Live Photo
+const LIVE_PHOTO_IMAGE_EXTENSIONS: &[&str] = &["heic", "heif", "jpg", "jpeg"];
+const LIVE_PHOTO_VIDEO_EXTENSIONS: &[&str] = &["mov", "mp4"];
+
+/// Takeout's `Photos from YYYY` folders, which hold the photos of a year that are in no album
+static TAKEOUT_YEAR_FOLDER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^Photos from (\d{4})$").expect("valid regex"));
+
+/// Global view of an archive, built in a first pass over all entries so that
+/// per-entry decisions (filters, album detection) can look at the other entries
+pub struct ArchiveIndex {
+    entry_names: HashSet<String>,
+    entry_sizes: HashMap<String, u64>,
+    /// Entry name -> name of the album folder it is in
+    entry_albums: HashMap<String, String>,
+    sidecars: SidecarIndex,
+    meta_media: MetaMediaIndex,
+    snapchat_memories: SnapchatMemoryIndex,
+    album_metadata: AlbumMetadataIndex,
+    /// Each half of a Live Photo -> the other half
+    live_photo_partners: HashMap<String, String>,
+}
+
+impl ArchiveIndex {
+    pub fn new(entries: &[ZipEntry]) -> Self {
+        let listing: Vec<EntryInfo> = entries
+            .iter()
+            .map(|entry| EntryInfo {
+                name: entry.name.clone(),
+                size: entry.data.len() as u64,
+            })
+            .collect();
+        Self::from_listing(&listing)
+    }
+
+    /// Builds the index from names and sizes only, without any entry data
+    pub fn from_listing(listing: &[EntryInfo]) -> Self {
+        let mut index = Self {
+            entry_names: HashSet::new(),
+            entry_sizes: HashMap::new(),
+            entry_albums: HashMap::new(),
+            sidecars: SidecarIndex::default(),
+            meta_media: MetaMediaIndex::default(),
+            snapchat_memories: SnapchatMemoryIndex::default(),
+            album_metadata: AlbumMetadataIndex::default(),
+            live_photo_partners: HashMap::new(),
+        };
+        // (folder and stem, lowercase) -> image and video names with that stem
+        let mut live_photo_candidates: HashMap<String, (Vec<&str>, Vec<&str>)> = HashMap::new();
+
+        for entry in listing {
+            index.entry_names.insert(entry.name.clone());
+            index.entry_sizes.insert(entry.name.clone(), entry.size);
+
+            if let Some(album) = Self::album_folder(&entry.name) {
+                index
+                    .entry_albums
+                    .insert(entry.name.clone(), album.to_string());
+            }
+
+            if let Some((stem, extension)) = entry.name.rsplit_once('.') {
+                let extension = extension.to_lowercase();
+                let halves = live_photo_candidates.entry(stem.to_lowercase()).or_default();
+                if LIVE_PHOTO_IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+                    halves.0.push(&entry.name);
+                } else if LIVE_PHOTO_VIDEO_EXTENSIONS.contains(&extension.as_str()) {
+                    halves.1.push(&entry.name);
+                }
+            }
+        }
+
+        // Only an unambiguous pair: one still image and one video with the same stem
+        for (images, videos) in live_photo_candidates.into_values() {
+            if let ([image], [video]) = (images.as_slice(), videos.as_slice()) {
+                index.live_photo_partners.insert(image.to_string(), video.to_string());
+                index.live_photo_partners.insert(video.to_string(), image.to_string());
+            }
+        }
+
+        index
+    }
+
+    /// The other half of a Live Photo (`IMG_1234.HEIC` and `IMG_1234.MOV` in one folder)
+    pub fn live_photo_partner(&self, entry_name: &str) -> Option<&str> {
+        self.live_photo_partners.get(entry_name).map(String::as_str)
+    }
+
+    /// Whether the entry is the video half of a Live Photo
+    pub fn is_live_photo_video(&self, entry_name: &str) -> bool {
+        self.live_photo_partner(entry_name).is_some()
+            && entry_name.rsplit_once('.').is_some_and(|(_, extension)| {
+                LIVE_PHOTO_VIDEO_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+            })
+    }
+
+    /// Adds the Takeout JSON sidecars paired with the entries
+    pub fn with_sidecars(mut self, sidecars: SidecarIndex) -> Self {
+        self.sidecars = sidecars;
+        self
+    }
+
+    /// Parsed sidecar of an entry, if the input had one for it
+    pub fn sidecar_of(&self, entry_name: &str) -> Option<&Sidecar> {
+        self.sidecars.get(entry_name)
+    }
+
+    /// Number of entries with a sidecar
+    pub fn sidecar_count(&self) -> usize {
+        self.sidecars.len()
+    }
+
+    /// Adds the Facebook/Instagram export records paired with the entries
+    pub fn with_meta_media(mut self, meta_media: MetaMediaIndex) -> Self {
+        self.meta_media = meta_media;
+        self
+    }
+
+    /// Adds the Snapchat Memories paired with the entries
+    pub fn with_snapchat_memories(mut self, snapchat_memories: SnapchatMemoryIndex) -> Self {
+        self.snapchat_memories = snapchat_memories;
+        self
+    }
+
+    /// Adds the `metadata.json` files of the album folders
+    pub fn with_album_metadata(mut self, album_metadata: AlbumMetadataIndex) -> Self {
+        self.album_metadata = album_metadata;
+        self
+    }
+
+    /// Everything known about an entry besides its content, for the date extractors
+    pub fn context_of<'e>(&'e self, entry: &'e ZipEntry) -> EntryContext<'e> {
+        EntryContext {
+            sidecar: self.sidecars.get(&entry.name),
+            meta: self.meta_media.get(&entry.name),
+            snapchat: self.snapchat_memories.get(&entry.name),
+            metadata: Some(&entry.metadata),
+            album: self.album_metadata.get(&entry.name),
+            exif: None,
+            time_zone: DateTimeZone::default(),
+        }
+    }
+
+    pub fn contains_entry(&self, name: &str) -> bool {
+        self.entry_names.contains(name)
+    }
+
+    pub fn entry_count(&self) -> usize {
+        self.entry_names.len()
+    }
+
+    pub fn total_size(&self) -> u64 {
+        self.entry_sizes.values().sum()
+    }
+
+    /// Names of all album folders, i.e. folders other than Takeout's "Photos from YYYY"
+    pub fn album_names(&self) -> impl Iterator<Item = &str> {
+        let unique: HashSet<&str> = self.entry_albums.values().map(String::as_str).collect();
+        unique.into_iter()
+    }
+
+    /// Album folder the entry belongs to, if any
+    pub fn album_of(&self, entry_name: &str) -> Option<&str> {
+        self.entry_albums.get(entry_name).map(String::as_str)
+    }
+
+    /// Year of the Takeout `Photos from YYYY` folder an entry is in, for entries that
+    /// nothing else dates
+    pub fn takeout_year_of(entry_name: &str) -> Option<i32> {
+        entry_name
+            .rsplit('/')
+            .skip(1)
+            .find_map(|folder| TAKEOUT_YEAR_FOLDER.captures(folder)?.get(1)?.as_str().parse().ok())
+    }
+
+    fn album_folder(entry_name: &str) -> Option<&str> {
+        let mut components = entry_name.rsplit('/');
+        components.next()?;
+        let folder = components.next()?;
+
+        if TAKEOUT_YEAR_FOLDER.is_match(folder) || folder == "Google Photos" {
+            None
+        } else {
+            Some(folder)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, size: usize) -> ZipEntry {
+        ZipEntry {
+            name: name.to_string(),
+            data: vec![0; size].into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_index_records_names_and_sizes() {
+        // Arrange
+        let entries = vec![entry("a.jpg", 10), entry("dir/b.jpg", 5)];
+
+        // Act
+        let index = ArchiveIndex::new(&entries);
+
+        // Assert
+        assert_eq!(index.entry_count(), 2);
+        assert!(index.contains_entry("dir/b.jpg"));
+        assert!(!index.contains_entry("b.jpg"));
+        assert_eq!(index.total_size(), 15);
+    }
+
+    #[test]
+    fn test_index_detects_albums_but_not_year_folders() {
+        // Arrange
+        let entries = vec![
+            entry("Takeout/Google Photos/Photos from 2014/a.jpg", 1),
+            entry("Takeout/Google Photos/Wedding 2018/b.jpg", 1),
+            entry("Takeout/Google Photos/Wedding 2018/c.jpg", 1),
+            entry("Takeout/Google Photos/d.jpg", 1),
+        ];
+
+        // Act
+        let index = ArchiveIndex::new(&entries);
+
+        // Assert
+        let albums: Vec<&str> = index.album_names().collect();
+        assert_eq!(albums, vec!["Wedding 2018"]);
+        assert_eq!(
+            index.album_of("Takeout/Google Photos/Wedding 2018/b.jpg"),
+            Some("Wedding 2018")
+        );
+        assert_eq!(index.album_of("Takeout/Google Photos/Photos from 2014/a.jpg"), None);
+    }
+
+    #[test]
+    fn test_takeout_year_of_entry() {
+        // Act & Assert
+        assert_eq!(
+            ArchiveIndex::takeout_year_of("Takeout/Google Photos/Photos from 2012/a.jpg"),
+            Some(2012)
+        );
+        assert_eq!(ArchiveIndex::takeout_year_of("Takeout/Google Photos/Wedding 2018/b.jpg"), None);
+        assert_eq!(ArchiveIndex::takeout_year_of("Photos from 2012.jpg"), None);
+    }
+
+    #[test]
+    fn test_index_pairs_live_photo_halves() {
+        // Arrange
+        let entries = vec![
+            entry("Trip/IMG_1234.HEIC", 1),
+            entry("Trip/IMG_1234.MOV", 1),
+            entry("Trip/IMG_5678.jpg", 1),
+            entry("Other/IMG_5678.mov", 1),
+            entry("Both/IMG_9.jpg", 1),
+            entry("Both/IMG_9.heic", 1),
+            entry("Both/IMG_9.mov", 1),
+        ];
+
+        // Act
+        let index = ArchiveIndex::new(&entries);
+
+        // Assert
+        assert_eq!(index.live_photo_partner("Trip/IMG_1234.HEIC"), Some("Trip/IMG_1234.MOV"));
+        assert_eq!(index.live_photo_partner("Trip/IMG_1234.MOV"), Some("Trip/IMG_1234.HEIC"));
+        assert!(index.is_live_photo_video("Trip/IMG_1234.MOV"));
+        assert!(!index.is_live_photo_video("Trip/IMG_1234.HEIC"));
+        assert_eq!(index.live_photo_partner("Trip/IMG_5678.jpg"), None, "Different folders");
+        assert_eq!(index.live_photo_partner("Both/IMG_9.mov"), None, "Ambiguous pair");
+    }
+}
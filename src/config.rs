@@ -0,0 +1,206 @@
+use crate::photo_filter::DuplicateLocale;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// Settings read from the TOML file given with `--config`
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default)]
+    pub notify: NotifyConfig,
+    #[serde(default)]
+    pub extensions: ExtensionsConfig,
+    #[serde(default)]
+    pub dates: DatesConfig,
+    #[serde(default)]
+    pub duplicates: DuplicatesConfig,
+}
+
+/// Suffixes by which Google-generated copies (`-edited`, `-MIX`, ...) are recognized
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DuplicatesConfig {
+    /// Replaces the built-in list, like `--duplicate-suffixes`
+    pub only: Option<Vec<String>>,
+    #[serde(default)]
+    pub add: Vec<String>,
+    /// Languages whose suffixes are recognized too, like `--duplicate-locale`
+    #[serde(default)]
+    pub locales: Vec<DuplicateLocale>,
+}
+
+/// How dates are read
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DatesConfig {
+    /// Filename regexes with `year`, `month` and `day` groups, like `--filename-pattern`
+    #[serde(default)]
+    pub filename_patterns: Vec<String>,
+}
+
+/// Changes to the file extensions that are read from the input
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ExtensionsConfig {
+    /// Replaces the built-in list, like `--extensions`
+    pub only: Option<Vec<String>>,
+    #[serde(default)]
+    pub add: Vec<String>,
+    #[serde(default)]
+    pub remove: Vec<String>,
+}
+
+/// Push notifications sent when a run finishes or fails
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct NotifyConfig {
+    /// ntfy topic to publish to
+    pub ntfy_topic: Option<String>,
+    /// ntfy server, defaults to https://ntfy.sh
+    pub ntfy_server: Option<String>,
+    pub email: Option<EmailConfig>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct EmailConfig {
+    pub smtp_server: String,
+    /// Defaults to 587 (STARTTLS)
+    pub smtp_port: Option<u16>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+    pub to: String,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+        Self::parse(&content)
+            .with_context(|| format!("Failed to parse config file: {}", path.display()))
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_empty_config() {
+        // Act
+        let config = Config::parse("").unwrap();
+
+        // Assert
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn test_parse_notify_section() {
+        // Arrange
+        let content = r#"
+            [notify]
+            ntfy_topic = "photo-imports"
+
+            [notify.email]
+            smtp_server = "smtp.example.com"
+            from = "nas@example.com"
+            to = "me@example.com"
+        "#;
+
+        // Act
+        let config = Config::parse(content).unwrap();
+
+        // Assert
+        assert_eq!(config.notify.ntfy_topic.as_deref(), Some("photo-imports"));
+        let email = config.notify.email.unwrap();
+        assert_eq!(email.smtp_server, "smtp.example.com");
+        assert_eq!(email.smtp_port, None);
+    }
+
+    #[test]
+    fn test_parse_extensions_section() {
+        // Arrange
+        let content = r#"
+            [extensions]
+            add = ["dng", "avif"]
+        "#;
+
+        // Act
+        let config = Config::parse(content).unwrap();
+
+        // Assert
+        assert_eq!(config.extensions.add, vec!["dng", "avif"]);
+        assert!(config.extensions.remove.is_empty());
+        assert_eq!(config.extensions.only, None);
+    }
+
+    #[test]
+    fn test_parse_dates_section() {
+        // Arrange
+        let content = r#"
+            [dates]
+            filename_patterns = ['^P(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})-']
+        "#;
+
+        // Act
+        let config = Config::parse(content).unwrap();
+
+        // Assert
+        assert_eq!(
+            config.dates.filename_patterns,
+            vec![r"^P(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})-"]
+        );
+    }
+
+    #[test]
+    fn test_parse_extensions_allowlist() {
+        // Arrange
+        let content = r#"
+            [extensions]
+            only = ["jpg", "heic", "mp4"]
+        "#;
+
+        // Act
+        let config = Config::parse(content).unwrap();
+
+        // Assert
+        assert_eq!(
+            config.extensions.only,
+            Some(vec!["jpg".to_string(), "heic".to_string(), "mp4".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_parse_duplicates_section() {
+        // Arrange
+        let content = r#"
+            [duplicates]
+            add = ["-MOTION"]
+            locales = ["de", "fr"]
+        "#;
+
+        // Act
+        let config = Config::parse(content).unwrap();
+
+        // Assert
+        assert_eq!(config.duplicates.add, vec!["-MOTION"]);
+        assert_eq!(config.duplicates.locales, vec![DuplicateLocale::De, DuplicateLocale::Fr]);
+        assert_eq!(config.duplicates.only, None);
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_keys() {
+        // Act
+        let result = Config::parse("[notify]\nntfy_topik = \"typo\"");
+
+        // Assert
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,111 @@
+use crate::exif::ParsedExif;
+use exif::{In, Tag};
+
+/// Folder name used when neither EXIF nor the filename identify the device
+pub const UNKNOWN_DEVICE: &str = "Unknown device";
+
+/// Filename prefixes that identify the device family when EXIF has no model
+const FILENAME_PREFIX_DEVICES: &[(&str, &str)] = &[
+    ("PXL_", "Google Pixel"),
+    ("DSC_", "DSC camera"),
+    ("DSCF", "Fujifilm camera"),
+    ("DSCN", "Nikon camera"),
+    ("GOPR", "GoPro"),
+    ("DJI_", "DJI"),
+];
+
+/// Name of the device that took a photo, used to group photos of several phones
+/// pooled into one Takeout. Prefers EXIF Model (e.g. `Pixel 5`), falls back to
+/// well-known filename prefixes (`PXL_`, `DSC_`, ...).
+pub fn device_name(filename: &str, image_data: &[u8], exif: &ParsedExif) -> String {
+    exif_model(image_data, exif)
+        .or_else(|| device_from_filename(filename))
+        .unwrap_or_else(|| UNKNOWN_DEVICE.to_string())
+}
+
+fn exif_model(image_data: &[u8], exif: &ParsedExif) -> Option<String> {
+    let exif_data = exif.get(image_data).ok()?;
+    let field = exif_data.get_field(Tag::Model, In::PRIMARY)?;
+
+    let model = match &field.value {
+        exif::Value::Ascii(values) => values
+            .first()
+            .map(|bytes| String::from_utf8_lossy(bytes).into_owned())?,
+        _ => field.display_value().to_string(),
+    };
+    let model = clean_folder_name(&model);
+    (!model.is_empty()).then_some(model)
+}
+
+fn device_from_filename(filename: &str) -> Option<String> {
+    let base_name = filename.rsplit(['/', '\\']).next().unwrap_or(filename);
+    let upper = base_name.to_uppercase();
+
+    FILENAME_PREFIX_DEVICES
+        .iter()
+        .find(|(prefix, _)| upper.starts_with(prefix))
+        .map(|(_, device)| device.to_string())
+}
+
+/// Keeps a model string usable as a folder name: no separators, control or
+/// reserved characters, no surrounding whitespace or trailing dots
+fn clean_folder_name(model: &str) -> String {
+    let cleaned: String = model
+        .chars()
+        .map(|c| {
+            if c.is_control() || "<>:\"/\\|?*".contains(c) {
+                ' '
+            } else {
+                c
+            }
+        })
+        .collect();
+    cleaned
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_end_matches('.')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[test]
+    fn test_device_name_prefers_exif_model() {
+        // Arrange
+        // Real Google Photos image (1x1 pixel) with EXIF Model: NIKON D7000
+        let image_data: &[u8] = include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg");
+
+        // Act
+        let result = device_name("PXL_20210502_101010.jpg", image_data, &ParsedExif::new());
+
+        // Assert
+        assert_eq!(result, "NIKON D7000");
+    }
+
+    #[rstest]
+    #[case("Takeout/Google Photos/2021/PXL_20210502_101010.jpg", "Google Pixel")]
+    #[case("dsc_0042.JPG", "DSC camera")]
+    #[case("GOPR0001.MP4", "GoPro")]
+    #[case("IMG_1234.jpg", UNKNOWN_DEVICE)]
+    fn test_device_name_falls_back_to_filename_prefix(
+        #[case] filename: &str,
+        #[case] expected: &str,
+    ) {
+        // Act
+        let result = device_name(filename, &[0xFF, 0xD8, 0xFF, 0xD9], &ParsedExif::new());
+
+        // Assert
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_clean_folder_name() {
+        // Act & Assert
+        assert_eq!(clean_folder_name("  Canon EOS 5D/Mark II\0 "), "Canon EOS 5D Mark II");
+        assert_eq!(clean_folder_name("Model..."), "Model");
+    }
+}
@@ -0,0 +1,160 @@
+use crate::organizer::{EntryOutcome, OrganizeResult, WriteAction};
+
+/// Progress of an organize run, in the order it happens
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrganizeEvent {
+    Started {
+        total_files: usize,
+    },
+    /// The index pass over all entries is done
+    Indexed {
+        entries: usize,
+        total_bytes: u64,
+        albums: usize,
+        /// Entries paired with a Takeout JSON sidecar
+        sidecars: usize,
+    },
+    /// Entry was rejected by the photo filter
+    FileFiltered {
+        entry: String,
+    },
+    /// Entry passed the filter; the outcome tells whether it was written or skipped
+    FileProcessed {
+        entry: String,
+        outcome: EntryOutcome,
+    },
+    FileFailed {
+        entry: String,
+        error: String,
+    },
+    Finished {
+        result: OrganizeResult,
+    },
+}
+
+/// Receives progress events from the organizer
+pub trait OrganizeObserver {
+    fn on_event(&self, event: &OrganizeEvent);
+}
+
+/// Default observer that prints one line per file to stdout
+pub struct ConsoleObserver {
+    dry_run: bool,
+}
+
+impl ConsoleObserver {
+    pub fn new(dry_run: bool) -> Self {
+        Self { dry_run }
+    }
+
+    fn print_outcome(&self, entry: &str, outcome: &EntryOutcome) {
+        let target = outcome.target_path.display();
+
+        if self.dry_run {
+            let renamed_note = if outcome.renamed { " (renamed, name collision)" } else { "" };
+            println!(
+                "{:<9} {} <- {}{}",
+                outcome.action.label(),
+                target,
+                entry,
+                renamed_note
+            );
+            return;
+        }
+
+        match (outcome.action, &outcome.backup_path) {
+            (WriteAction::Identical, _) => println!("{}: already present at {}", entry, target),
+            (WriteAction::KeepExisting, _) => println!(
+                "{}: {} exists with different content, not overwritten (use --force)",
+                entry, target
+            ),
+            (_, Some(backup_path)) => println!(
+                "{}: copied to {} (existing file moved to {})",
+                entry,
+                target,
+                backup_path.display()
+            ),
+            _ if outcome.renamed => println!(
+                "{}: copied to {} (renamed, name already used in this run)",
+                entry, target
+            ),
+            _ => println!("{}: copied to {}", entry, target),
+        }
+    }
+}
+
+impl OrganizeObserver for ConsoleObserver {
+    fn on_event(&self, event: &OrganizeEvent) {
+        match event {
+            OrganizeEvent::Indexed {
+                entries,
+                total_bytes,
+                albums,
+                sidecars,
+            } => println!(
+                "Indexed {} entries ({} bytes, {} albums, {} sidecars)",
+                entries, total_bytes, albums, sidecars
+            ),
+            OrganizeEvent::FileFiltered { entry } => println!("{}: filtered out", entry),
+            OrganizeEvent::FileProcessed { entry, outcome } => self.print_outcome(entry, outcome),
+            OrganizeEvent::FileFailed { entry, error } => println!("{}: error - {}", entry, error),
+            OrganizeEvent::Started { .. } | OrganizeEvent::Finished { .. } => {}
+        }
+    }
+}
+
+/// Observer that forwards events into an async channel, see [`event_stream`]
+#[cfg(feature = "stream")]
+pub struct ChannelObserver {
+    sender: futures::channel::mpsc::UnboundedSender<OrganizeEvent>,
+}
+
+#[cfg(feature = "stream")]
+impl OrganizeObserver for ChannelObserver {
+    fn on_event(&self, event: &OrganizeEvent) {
+        // A dropped receiver just means nobody is listening anymore
+        self.sender.unbounded_send(event.clone()).ok();
+    }
+}
+
+/// Creates an observer and the `Stream` of events it feeds. Run the organizer with
+/// the observer on a blocking thread and consume the stream from async code.
+#[cfg(feature = "stream")]
+pub fn event_stream() -> (
+    ChannelObserver,
+    impl futures::Stream<Item = OrganizeEvent> + Send + Unpin,
+) {
+    let (sender, receiver) = futures::channel::mpsc::unbounded();
+    (ChannelObserver { sender }, receiver)
+}
+
+#[cfg(all(test, feature = "stream"))]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    #[test]
+    fn test_event_stream_yields_events_in_order() {
+        // Arrange
+        let (observer, stream) = event_stream();
+
+        // Act
+        observer.on_event(&OrganizeEvent::Started { total_files: 3 });
+        observer.on_event(&OrganizeEvent::FileFiltered {
+            entry: "a.gif".to_string(),
+        });
+        drop(observer);
+        let events: Vec<OrganizeEvent> = futures::executor::block_on(stream.collect());
+
+        // Assert
+        assert_eq!(
+            events,
+            vec![
+                OrganizeEvent::Started { total_files: 3 },
+                OrganizeEvent::FileFiltered {
+                    entry: "a.gif".to_string()
+                },
+            ]
+        );
+    }
+}
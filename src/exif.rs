@@ -1,300 +1,3375 @@
 use anyhow::{Context, Result};
-use chrono::NaiveDate;
+use chrono::{DateTime, Datelike, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Timelike, Utc};
+use crate::archive_index::ArchiveIndex;
+use crate::geo_time_zone::time_zone_at;
+use crate::isobmff::heif_exif;
+use crate::meta_export::MetaMedia;
+use crate::png_metadata::PngTextDateExtractor;
+use crate::sidecar::{edited_original, AlbumMetadata, Sidecar};
+use crate::snapchat::SnapchatMemory;
+use crate::video_metadata::{riff_chunks, VideoDateExtractor};
+use crate::xmp::XmpDateExtractor;
+use crate::zip_image_reader::EntryMetadata;
 use exif::{In, Tag};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::str::FromStr;
 
-/// Trait for extracting date information from image data
+/// Trait for extracting the capture time from image data. Sources that only know the day
+/// give its midnight (see `start_of_day`).
 pub trait DateExtractor {
-    fn extract_date(&self, filename: &str, image_data: &[u8]) -> Result<NaiveDate>;
+    fn extract_date_time(&self, filename: &str, image_data: &[u8]) -> Result<NaiveDateTime>;
+
+    /// Like `extract_date_time`, with what else is known about the entry at hand.
+    /// Extractors that only look at the name and data keep this default.
+    fn extract_date_time_in_context(
+        &self,
+        filename: &str,
+        image_data: &[u8],
+        _context: &EntryContext,
+    ) -> Result<NaiveDateTime> {
+        self.extract_date_time(filename, image_data)
+    }
+
+    /// How far the dates of this extractor can be trusted
+    fn confidence(&self) -> Confidence;
+
+    /// Name in the reasons of a failed extraction; the type name unless overridden
+    fn name(&self) -> &str {
+        let type_name = std::any::type_name::<Self>();
+        type_name.rsplit("::").next().unwrap_or(type_name)
+    }
+
+    /// `extract_date_time_in_context` with how far the time can be trusted. Extractors that
+    /// combine sources of different trust override this.
+    fn extract_with_confidence_in_context(
+        &self,
+        filename: &str,
+        image_data: &[u8],
+        context: &EntryContext,
+    ) -> Result<ExtractedDate> {
+        let time = self.extract_date_time_in_context(filename, image_data, context)?;
+        Ok(ExtractedDate {
+            time,
+            confidence: self.confidence(),
+        })
+    }
+
+    /// The times embedded in the file and in its Takeout JSON sidecar, when it has both, to
+    /// spot suspicious dates. Extractors that don't read both keep this default.
+    fn embedded_and_sidecar_times(
+        &self,
+        _filename: &str,
+        _image_data: &[u8],
+        _context: &EntryContext,
+    ) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        None
+    }
+
+    /// The calendar date of `extract_date_time`
+    fn extract_date(&self, filename: &str, image_data: &[u8]) -> Result<NaiveDate> {
+        self.extract_date_time(filename, image_data).map(|time| time.date())
+    }
+
+    /// The calendar date of `extract_date_time_in_context`
+    fn extract_date_in_context(
+        &self,
+        filename: &str,
+        image_data: &[u8],
+        context: &EntryContext,
+    ) -> Result<NaiveDate> {
+        self.extract_date_time_in_context(filename, image_data, context)
+            .map(|time| time.date())
+    }
+}
+
+/// How far a date can be trusted, from least to most
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, clap::ValueEnum)]
+pub enum Confidence {
+    /// A modification time, which copying and uploading change
+    Mtime,
+    /// Only the year, from the Takeout `Photos from YYYY` folder
+    Year,
+    /// A date in the file or folder name
+    Filename,
+    /// Metadata stored next to the file: Takeout JSON sidecars and album metadata,
+    /// Facebook/Instagram exports
+    Sidecar,
+    /// Metadata embedded in the file by the camera or editor
+    Exif,
+}
+
+/// A capture time and how far it can be trusted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtractedDate {
+    pub time: NaiveDateTime,
+    pub confidence: Confidence,
+}
+
+/// Why an extractor found no date. Extractors return it inside their `anyhow::Error`, where
+/// `DateExtractionError::of` finds it again, so failures can be counted by reason.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DateExtractionError {
+    /// The file is not of a type the extractor reads
+    UnsupportedFormat(&'static str),
+    /// The file has no EXIF data that could be read
+    NoExif,
+    /// The metadata has no field, box or chunk with a date
+    NoDateTag(&'static str),
+    /// A date field holds something that is no date
+    UnparseableValue(String),
+    /// No known pattern dates the file name
+    NoFilenamePattern,
+    /// No folder in the path is dated
+    NoFolderDate,
+    /// The input has no sidecar, export record or file time for the file
+    NoRecord(&'static str),
+    /// The source is not consulted for this input
+    SourceDisabled(&'static str),
+    /// A date was found, but is not believable
+    Implausible { date: NaiveDate, reason: String },
+    /// No source gave a date, with the failure of each
+    NoDate(Vec<SourceFailure>),
+}
+
+/// Why one source of the composite extractor gave no date
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceFailure {
+    /// The source, or the name of a registered extractor
+    pub source: String,
+    /// The reason, unless the extractor did not give a `DateExtractionError`
+    pub error: Option<DateExtractionError>,
+    pub message: String,
+}
+
+impl DateExtractionError {
+    /// The reason in `error`, wherever in its context chain it is
+    pub fn of(error: &anyhow::Error) -> Option<&DateExtractionError> {
+        error.downcast_ref()
+    }
+
+    /// Short name of the reason, to group failures by
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DateExtractionError::UnsupportedFormat(_) => "unsupported_format",
+            DateExtractionError::NoExif => "no_exif",
+            DateExtractionError::NoDateTag(_) => "no_date_tag",
+            DateExtractionError::UnparseableValue(_) => "unparseable_value",
+            DateExtractionError::NoFilenamePattern => "no_filename_pattern",
+            DateExtractionError::NoFolderDate => "no_folder_date",
+            DateExtractionError::NoRecord(_) => "no_record",
+            DateExtractionError::SourceDisabled(_) => "source_disabled",
+            DateExtractionError::Implausible { .. } => "implausible",
+            DateExtractionError::NoDate(_) => "no_date",
+        }
+    }
+}
+
+impl std::fmt::Display for DateExtractionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DateExtractionError::UnsupportedFormat(format) => write!(f, "Not {}", format),
+            DateExtractionError::NoExif => write!(f, "No readable EXIF data"),
+            DateExtractionError::NoDateTag(tag) => write!(f, "No {} found", tag),
+            DateExtractionError::UnparseableValue(value) => write!(f, "Unparseable date: {:?}", value),
+            DateExtractionError::NoFilenamePattern => write!(f, "No date pattern matches the filename"),
+            DateExtractionError::NoFolderDate => write!(f, "No dated folder name"),
+            DateExtractionError::NoRecord(record) => write!(f, "No {} for this file", record),
+            DateExtractionError::SourceDisabled(source) => write!(f, "{} are not used for this input", source),
+            DateExtractionError::Implausible { date, reason } => write!(f, "implausible date {} ({})", date, reason),
+            DateExtractionError::NoDate(failures) => {
+                let failures: Vec<String> = failures
+                    .iter()
+                    .map(|failure| format!("{}: {}", failure.source, failure.message))
+                    .collect();
+                write!(f, "No date found ({})", failures.join("; "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for DateExtractionError {}
+
+/// The time a date-only source reports for a day
+pub fn start_of_day(date: NaiveDate) -> NaiveDateTime {
+    date.and_time(NaiveTime::MIN)
+}
+
+/// Time zone in which UTC timestamps (sidecars, GPS, video headers) are turned into dates,
+/// parsed from `local` or an IANA name like `Europe/Amsterdam`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DateTimeZone {
+    /// The zone of the machine running the tool
+    #[default]
+    Local,
+    Named(chrono_tz::Tz),
+}
+
+impl DateTimeZone {
+    /// Calendar date of a UTC time in this zone
+    pub fn date_of(self, time: DateTime<Utc>) -> NaiveDate {
+        self.local_time_of(time).date()
+    }
+
+    /// Wall-clock time of a UTC time in this zone
+    pub fn local_time_of(self, time: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            DateTimeZone::Local => time.with_timezone(&Local).naive_local(),
+            DateTimeZone::Named(zone) => time.with_timezone(&zone).naive_local(),
+        }
+    }
+
+    /// The zone at a (latitude, longitude) location when there is one, else this zone
+    pub fn at(self, location: Option<(f64, f64)>) -> DateTimeZone {
+        match location {
+            Some((latitude, longitude)) => DateTimeZone::Named(time_zone_at(latitude, longitude)),
+            None => self,
+        }
+    }
+}
+
+impl FromStr for DateTimeZone {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value.eq_ignore_ascii_case("local") {
+            return Ok(DateTimeZone::Local);
+        }
+        value
+            .parse()
+            .map(DateTimeZone::Named)
+            .map_err(|_| format!("unknown time zone '{}', expected 'local' or a name like Europe/Amsterdam", value))
+    }
+}
+
+/// What is known about an entry besides its name and content
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EntryContext<'e> {
+    /// The Takeout JSON sidecar paired with the entry
+    pub sidecar: Option<&'e Sidecar>,
+    /// What a Facebook or Instagram export records about the entry
+    pub meta: Option<&'e MetaMedia>,
+    /// What a Snapchat export's `memories_history.json` records about the entry
+    pub snapchat: Option<&'e SnapchatMemory>,
+    /// What the archive records about the entry, such as its last-modified time
+    pub metadata: Option<&'e EntryMetadata>,
+    /// The `metadata.json` of the Takeout album folder the entry is in
+    pub album: Option<&'e AlbumMetadata>,
+    /// The entry's EXIF, decoded once for all steps of the pipeline
+    pub exif: Option<&'e ParsedExif>,
+    /// The zone UTC times (sidecars, Meta exports, GPS, video headers, Unix-time filenames,
+    /// album dates) are dated in, the local one by default
+    pub time_zone: DateTimeZone,
+}
+
+impl<'e> EntryContext<'e> {
+    /// Share this already (or yet to be) decoded EXIF with the extractors
+    pub fn with_exif(mut self, exif: &'e ParsedExif) -> Self {
+        self.exif = Some(exif);
+        self
+    }
+
+    /// Date UTC times in this zone instead of the local one
+    pub fn with_time_zone(mut self, time_zone: DateTimeZone) -> Self {
+        self.time_zone = time_zone;
+        self
+    }
+}
+
+/// The EXIF of one entry, decoded the first time a step asks for it and kept for the steps
+/// after it (filter, date extractors, device folders), so each image is decoded only once
+#[derive(Default)]
+pub struct ParsedExif {
+    parsed: once_cell::unsync::OnceCell<Result<exif::Exif, String>>,
+}
+
+impl std::fmt::Debug for ParsedExif {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParsedExif")
+            .field("decoded", &self.parsed.get().is_some())
+            .finish()
+    }
+}
+
+impl ParsedExif {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The EXIF of `image_data`, which must be the data of the entry this was made for
+    pub fn get(&self, image_data: &[u8]) -> Result<&exif::Exif> {
+        self.parsed
+            .get_or_init(|| read_exif(image_data).map_err(|e| format!("{:#}", e)))
+            .as_ref()
+            .map_err(|e| anyhow::Error::new(DateExtractionError::NoExif).context(e.clone()))
+    }
+}
+
+const RAF_MAGIC: &[u8] = b"FUJIFILMCCD-RAW";
+const RAF_JPEG_OFFSET_POSITION: usize = 84;
+const ORF_MAGICS: [&[u8]; 3] = [b"IIRO", b"IIRS", b"MMOR"];
+const JXL_CONTAINER_SIGNATURE: &[u8] = b"\0\0\0\x0cJXL \r\n\x87\n";
+const RIFF_MAGIC: &[u8] = b"RIFF";
+const WEBP_FORM_TYPE: &[u8] = b"WEBP";
+const TAG_DATE_TIME: u16 = 0x0132;
+const TAG_EXIF_IFD_POINTER: u16 = 0x8769;
+const TAG_DATE_TIME_ORIGINAL: u16 = 0x9003;
+const TYPE_ASCII: u16 = 2;
+/// Header some encoders put before the TIFF data of a WebP `EXIF` chunk, as in JPEG
+const EXIF_HEADER: &[u8] = b"Exif\0\0";
+
+/// Reads EXIF from any supported container: JPEG (and MPO), PNG, HEIF (and AVIF), WebP,
+/// JPEG XL and TIFF-based RAW (DNG, NEF, CR2, ARW read as plain TIFF; ORF has its own
+/// magic and RAF wraps a JPEG)
+pub(crate) fn read_exif(image_data: &[u8]) -> Result<exif::Exif> {
+    let exif_reader = exif::Reader::new();
+    if image_data.starts_with(JXL_CONTAINER_SIGNATURE) {
+        let tiff = jxl_exif_box(image_data).context("No Exif box found in JPEG XL container")?;
+        return exif_reader
+            .read_raw(tiff.to_vec())
+            .context("Failed to read EXIF data from JPEG XL");
+    }
+    if let Some(tiff) = heif_exif(image_data) {
+        return exif_reader
+            .read_raw(tiff.to_vec())
+            .context("Failed to read EXIF data from HEIF");
+    }
+    if image_data.starts_with(RIFF_MAGIC) && image_data.get(8..12) == Some(WEBP_FORM_TYPE) {
+        let tiff = webp_exif_chunk(image_data).context("No EXIF chunk found in WebP")?;
+        return exif_reader
+            .read_raw(tiff.to_vec())
+            .context("Failed to read EXIF data from WebP");
+    }
+    if image_data.starts_with(RAF_MAGIC) {
+        let jpeg = raf_embedded_jpeg(image_data).context("Truncated RAF header")?;
+        return exif_reader
+            .read_from_container(&mut std::io::Cursor::new(jpeg))
+            .context("Failed to read EXIF data from RAF preview");
+    }
+    if ORF_MAGICS.iter().any(|magic| image_data.starts_with(magic)) {
+        let mut tiff = image_data.to_vec();
+        let forty_two: [u8; 2] = if tiff[0] == b'I' { [0x2a, 0x00] } else { [0x00, 0x2a] };
+        tiff[2..4].copy_from_slice(&forty_two);
+        return exif_reader
+            .read_raw(tiff)
+            .context("Failed to read EXIF data from ORF");
+    }
+    exif_reader
+        .read_from_container(&mut std::io::Cursor::new(image_data))
+        .context("Failed to read EXIF data from image")
+}
+
+/// The full-size JPEG preview in a Fujifilm RAF, which carries the camera's EXIF
+fn raf_embedded_jpeg(data: &[u8]) -> Option<&[u8]> {
+    let be_u32 = |at: usize| -> Option<usize> {
+        let bytes = data.get(at..at + 4)?;
+        Some(u32::from_be_bytes(bytes.try_into().ok()?) as usize)
+    };
+    let offset = be_u32(RAF_JPEG_OFFSET_POSITION)?;
+    let length = be_u32(RAF_JPEG_OFFSET_POSITION + 4)?;
+    // Large entries only come with their head, so a cut-off preview is still tried
+    let end = offset.checked_add(length)?.min(data.len());
+    data.get(offset..end)
+}
+
+/// The TIFF data of the `EXIF` chunk of a WebP. Unlike the exif crate this does not
+/// insist on a RIFF size that matches the data (Google Photos downloads and large entries
+/// read in part don't), and accepts a chunk starting with a JPEG-style `Exif` header.
+fn webp_exif_chunk(data: &[u8]) -> Option<&[u8]> {
+    let (_, chunk) = riff_chunks(data.get(12..)?).find(|(chunk_id, _)| *chunk_id == b"EXIF")?;
+    Some(chunk.strip_prefix(EXIF_HEADER).unwrap_or(chunk))
+}
+
+/// The TIFF data of the `Exif` box of a JPEG XL container. A bare JPEG XL codestream has
+/// no room for EXIF, and a brotli-compressed (`brob`) Exif box is not supported.
+fn jxl_exif_box(data: &[u8]) -> Option<&[u8]> {
+    let mut rest = data;
+    while rest.len() >= 8 {
+        let size = u32::from_be_bytes(rest[..4].try_into().ok()?) as u64;
+        let box_type = &rest[4..8];
+        let (header_len, box_len) = match size {
+            0 => (8, rest.len() as u64),
+            1 => (16, u64::from_be_bytes(rest.get(8..16)?.try_into().ok()?)),
+            _ => (8, size),
+        };
+        let end = usize::try_from(box_len).ok()?.min(rest.len());
+        let body = rest.get(header_len..end)?;
+        if box_type == b"Exif" {
+            // The body starts with the offset of the TIFF header within the rest of it
+            let tiff_offset = u32::from_be_bytes(body.get(..4)?.try_into().ok()?) as usize;
+            return body.get(4usize.checked_add(tiff_offset)?..);
+        }
+        if box_len < header_len as u64 {
+            return None;
+        }
+        rest = &rest[end..];
+    }
+    None
+}
+
+/// A correction for a camera whose clock was off, parsed from `[MODEL=]OFFSET` with the
+/// offset in hours, minutes and seconds (`NIKON D90=-3720s`, `-1h02m`). Without a model it
+/// applies to every camera that has no correction of its own.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClockOffset {
+    model: Option<String>,
+    offset: TimeDelta,
+}
+
+impl ClockOffset {
+    pub fn parse(value: &str) -> Result<Self> {
+        let (model, offset) = match value.rsplit_once('=') {
+            Some((model, offset)) => (Some(model.trim().to_string()), offset.trim()),
+            None => (None, value.trim()),
+        };
+        let captures = CLOCK_OFFSET
+            .captures(offset)
+            .filter(|captures| (2..=4).any(|group| captures.get(group).is_some()))
+            .with_context(|| format!("Invalid time offset, expected e.g. -3720s or +1h02m: {}", value))?;
+        let seconds = [(2, 3600), (3, 60), (4, 1)]
+            .into_iter()
+            .try_fold(0i64, |total, (group, unit)| {
+                let amount = match captures.get(group) {
+                    Some(amount) => amount.as_str().parse::<i64>().ok()?,
+                    None => 0,
+                };
+                total.checked_add(amount.checked_mul(unit)?)
+            })
+            .and_then(TimeDelta::try_seconds)
+            .with_context(|| format!("Time offset out of range: {}", value))?;
+        let negative = captures.get(1).is_some_and(|sign| sign.as_str() == "-");
+
+        Ok(Self {
+            model: model.filter(|model| !model.is_empty()),
+            offset: if negative { -seconds } else { seconds },
+        })
+    }
+}
+
+/// `--time-offset` amount: an optional sign, then hours, minutes and seconds (`-1h02m`)
+static CLOCK_OFFSET: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^([+-])?(?:(\d+)h)?(?:(\d+)m)?(?:(\d+)s)?$").expect("valid regex"));
+
+/// An EXIF date as cameras and editors really write it: `2012:10:06 13:09:32`, but also
+/// `2012/10/06 13:09`, `2012-10-06T13:09:32` or without seconds
+static EXIF_DATE_TIME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(\d{4})[:/.-](\d{1,2})[:/.-](\d{1,2})(?:[T ]+(\d{1,2}):(\d{1,2})(?::(\d{1,2}))?)?").expect("valid regex")
+});
+
+/// Concrete implementation that extracts dates from EXIF metadata
+#[derive(Default)]
+pub struct ExifDateExtractor {
+    clock_offsets: Vec<ClockOffset>,
+}
+
+impl ExifDateExtractor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Correct the capture times of cameras whose clock was off
+    pub fn with_clock_offsets(mut self, clock_offsets: Vec<ClockOffset>) -> Self {
+        self.clock_offsets = clock_offsets;
+        self
+    }
+
+    /// The correction for a camera model: its own, else the one for all cameras
+    fn clock_offset_for(&self, model: Option<&str>) -> Option<TimeDelta> {
+        let own = model.and_then(|model| {
+            self.clock_offsets.iter().find(|clock_offset| {
+                clock_offset
+                    .model
+                    .as_deref()
+                    .is_some_and(|offset_model| offset_model.eq_ignore_ascii_case(model.trim()))
+            })
+        });
+        own.or_else(|| self.clock_offsets.iter().find(|clock_offset| clock_offset.model.is_none()))
+            .map(|clock_offset| clock_offset.offset)
+    }
+
+    fn camera_model(exif_data: &exif::Exif) -> Option<String> {
+        match &exif_data.get_field(Tag::Model, In::PRIMARY)?.value {
+            exif::Value::Ascii(values) => values
+                .first()
+                .map(|bytes| String::from_utf8_lossy(bytes).trim_end_matches('\0').to_string()),
+            _ => None,
+        }
+    }
+
+    /// Time of day of an EXIF date string, `YYYY:MM:DD HH:MM:SS`; seconds may be missing
+    fn parse_exif_time_string(exif_date_string: &str) -> Option<NaiveTime> {
+        let captures = EXIF_DATE_TIME.captures(Self::trim_exif_date_string(exif_date_string))?;
+        let hour = captures.get(4)?.as_str().parse().ok()?;
+        let minute = captures.get(5)?.as_str().parse().ok()?;
+        let second = captures.get(6).map_or(Some(0), |digits| digits.as_str().parse().ok())?;
+        NaiveTime::from_hms_opt(hour, minute, second)
+    }
+
+    /// Date and time of an EXIF date string; midnight when the time is missing or blank
+    fn parse_exif_date_time_string(exif_date_string: &str) -> Result<NaiveDateTime> {
+        let date = Self::parse_exif_date_string(exif_date_string)?;
+        let time = Self::parse_exif_time_string(exif_date_string).unwrap_or(NaiveTime::MIN);
+        Ok(date.and_time(time))
+    }
+
+    /// Fraction of a second of SubSecTimeOriginal (`"12"` is 0.12 s) in nanoseconds, which
+    /// keeps the photos of a burst taken within one second in order
+    fn sub_second_nanos(exif_data: &exif::Exif) -> Option<u32> {
+        let exif::Value::Ascii(values) = &exif_data.get_field(Tag::SubSecTimeOriginal, In::PRIMARY)?.value else {
+            return None;
+        };
+        let digits = std::str::from_utf8(values.first()?).ok()?.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        format!("{:0<9}", digits.get(..9).unwrap_or(digits)).parse().ok()
+    }
+
+    fn get_datetime_original_field(exif_data: &exif::Exif) -> Result<&exif::Field> {
+        exif_data
+            .get_field(Tag::DateTimeOriginal, In::PRIMARY)
+            .context(DateExtractionError::NoDateTag("DateTimeOriginal field in EXIF data"))
+    }
+
+    /// The text of a date field as written, not reformatted like `display_value` does
+    fn exif_date_string(field: &exif::Field) -> String {
+        match &field.value {
+            exif::Value::Ascii(values) if !values.is_empty() => String::from_utf8_lossy(&values[0]).to_string(),
+            _ => field.display_value().to_string(),
+        }
+    }
+
+    /// Without the nulls and blanks some cameras pad the field with
+    fn trim_exif_date_string(exif_date_string: &str) -> &str {
+        exif_date_string.trim_matches(|c: char| c == '\0' || c.is_whitespace())
+    }
+
+    /// Date of an EXIF date string. A blank or all-zero date (`0000:00:00 00:00:00`), which
+    /// cameras write when their clock was never set, counts as no date at all.
+    fn parse_exif_date_string(exif_date_string: &str) -> Result<NaiveDate> {
+        let trimmed = Self::trim_exif_date_string(exif_date_string);
+        if trimmed.chars().all(|c| matches!(c, '0' | ':' | '/' | '-' | '.' | 'T' | ' ')) {
+            return Err(DateExtractionError::NoDateTag("date in DateTimeOriginal (blank or all zeros)").into());
+        }
+        let captures = EXIF_DATE_TIME
+            .captures(trimmed)
+            .with_context(|| DateExtractionError::UnparseableValue(trimmed.to_string()))?;
+        let year = captures[1].parse().ok();
+        let (month, day) = (captures[2].parse().ok(), captures[3].parse().ok());
+        year.zip(month.zip(day))
+            .and_then(|(year, (month, day))| NaiveDate::from_ymd_opt(year, month, day))
+            .with_context(|| DateExtractionError::UnparseableValue(trimmed.to_string()))
+    }
+}
+
+impl DateExtractor for ExifDateExtractor {
+    fn extract_date_time(&self, filename: &str, image_data: &[u8]) -> Result<NaiveDateTime> {
+        self.extract_date_time_in_context(filename, image_data, &EntryContext::default())
+    }
+
+    fn extract_date_time_in_context(
+        &self,
+        _filename: &str,
+        image_data: &[u8],
+        context: &EntryContext,
+    ) -> Result<NaiveDateTime> {
+        let parsed = ParsedExif::new();
+        let time = context.exif.unwrap_or(&parsed).get(image_data).and_then(|exif_data| {
+            let datetime_original_field = Self::get_datetime_original_field(exif_data)?;
+            let date_string = Self::exif_date_string(datetime_original_field);
+            let time = Self::parse_exif_date_time_string(&date_string)?;
+            let time = Self::sub_second_nanos(exif_data)
+                .filter(|_| Self::parse_exif_time_string(&date_string).is_some())
+                .and_then(|nanos| time.with_nanosecond(nanos))
+                .unwrap_or(time);
+            Ok((time, date_string, Self::camera_model(exif_data)))
+        });
+        let (time, date_string, model) = time.or_else(|e| match raw_tiff_date_string(image_data) {
+            Some(date_string) => Ok((Self::parse_exif_date_time_string(&date_string)?, date_string, None)),
+            None => Err(e),
+        })?;
+
+        // A clock correction moves the capture time, which can move it to another day; a
+        // date without a time of day is left as it is
+        let Some(offset) = self
+            .clock_offset_for(model.as_deref())
+            .filter(|_| Self::parse_exif_time_string(&date_string).is_some())
+        else {
+            return Ok(time);
+        };
+        time.checked_add_signed(offset)
+            .with_context(|| format!("Clock offset moves date out of range: {}", date_string))
+    }
+
+    fn confidence(&self) -> Confidence {
+        Confidence::Exif
+    }
+}
+
+/// The UTC offset of the camera's clock at capture, from OffsetTimeOriginal (`+02:00`),
+/// which newer cameras and phones write next to DateTimeOriginal
+pub fn capture_utc_offset(exif_data: &exif::Exif) -> Option<FixedOffset> {
+    let exif::Value::Ascii(values) = &exif_data.get_field(Tag::OffsetTimeOriginal, In::PRIMARY)?.value else {
+        return None;
+    };
+    let offset = std::str::from_utf8(values.first()?).ok()?.trim_end_matches('\0').trim();
+    let sign = match offset.get(..1)? {
+        "+" => 1,
+        "-" => -1,
+        _ => return None,
+    };
+    let (hours, minutes) = offset[1..].split_once(':')?;
+    let (hours, minutes): (i32, i32) = (hours.parse().ok()?, minutes.parse().ok()?);
+    if minutes >= 60 {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// The capture time read straight from the IFDs of a TIFF-based RAW file (NEF, CR2, ARW,
+/// DNG, ORF, RW2, ...), for files the exif crate rejects as a whole: cut off after their
+/// first megabyte (large entries), with a non-standard magic, or with an IFD pointing
+/// past the data. DateTimeOriginal from the Exif IFD, else IFD0's DateTime, which cameras
+/// set to the capture time as well.
+fn raw_tiff_date_string(data: &[u8]) -> Option<String> {
+    let little_endian = match data.get(..2)? {
+        b"II" => true,
+        b"MM" => false,
+        _ => return None,
+    };
+    let u16_at = |at: usize| -> Option<u16> {
+        let bytes: [u8; 2] = data.get(at..at.checked_add(2)?)?.try_into().ok()?;
+        Some(if little_endian { u16::from_le_bytes(bytes) } else { u16::from_be_bytes(bytes) })
+    };
+    let u32_at = |at: usize| -> Option<u32> {
+        let bytes: [u8; 4] = data.get(at..at.checked_add(4)?)?.try_into().ok()?;
+        Some(if little_endian { u32::from_le_bytes(bytes) } else { u32::from_be_bytes(bytes) })
+    };
+    // Position of the 12-byte entry of `tag` in the IFD at `ifd`
+    let find_entry = |ifd: usize, tag: u16| -> Option<usize> {
+        let count = u16_at(ifd)? as usize;
+        (0..count)
+            .map(|index| ifd + 2 + index * 12)
+            .find(|&entry| u16_at(entry) == Some(tag))
+    };
+    let ascii_value = |entry: usize| -> Option<String> {
+        if u16_at(entry + 2)? != TYPE_ASCII {
+            return None;
+        }
+        let count = u32_at(entry + 4)? as usize;
+        let at = if count <= 4 { entry + 8 } else { u32_at(entry + 8)? as usize };
+        let value = data.get(at..at.checked_add(count)?)?;
+        Some(String::from_utf8_lossy(value).trim_end_matches('\0').to_string())
+    };
+
+    let ifd0 = u32_at(4)? as usize;
+    let original = find_entry(ifd0, TAG_EXIF_IFD_POINTER)
+        .and_then(|entry| u32_at(entry + 8))
+        .and_then(|exif_ifd| find_entry(exif_ifd as usize, TAG_DATE_TIME_ORIGINAL))
+        .and_then(ascii_value);
+    original.or_else(|| find_entry(ifd0, TAG_DATE_TIME).and_then(ascii_value))
+}
+
+/// Extracts dates from the GPS date and time in EXIF, which some action cameras write
+/// instead of DateTimeOriginal. GPS time is UTC; with a time stamp it is converted to
+/// local time (by the EXIF OffsetTimeOriginal when there is one), without one the UTC date
+/// is used as it is.
+#[derive(Default)]
+pub struct GpsDateExtractor {
+    location_time_zones: bool,
+}
+
+impl GpsDateExtractor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Date UTC times of photos without OffsetTimeOriginal in the zone looked up for their
+    /// location (off by default, as the lookup is approximate near borders)
+    pub fn with_location_time_zones(mut self, enabled: bool) -> Self {
+        self.location_time_zones = enabled;
+        self
+    }
+
+    /// GPSLatitude and GPSLongitude as signed decimal degrees
+    fn gps_location(exif_data: &exif::Exif) -> Option<(f64, f64)> {
+        let degrees = |tag: Tag, negative_ref: &[u8]| -> Option<f64> {
+            let exif::Value::Rational(parts) = &exif_data.get_field(tag, In::PRIMARY)?.value else {
+                return None;
+            };
+            let [degrees, minutes, seconds] = parts.as_slice() else {
+                return None;
+            };
+            let value = degrees.to_f64() + minutes.to_f64() / 60.0 + seconds.to_f64() / 3600.0;
+            let reference_tag = match tag {
+                Tag::GPSLatitude => Tag::GPSLatitudeRef,
+                _ => Tag::GPSLongitudeRef,
+            };
+            let negative = matches!(
+                &exif_data.get_field(reference_tag, In::PRIMARY)?.value,
+                exif::Value::Ascii(values) if values.first().is_some_and(|value| value.as_slice() == negative_ref)
+            );
+            Some(if negative { -value } else { value })
+        };
+        Some((degrees(Tag::GPSLatitude, b"S")?, degrees(Tag::GPSLongitude, b"W")?))
+    }
+
+    fn gps_date(exif_data: &exif::Exif) -> Result<NaiveDate> {
+        let field = exif_data
+            .get_field(Tag::GPSDateStamp, In::PRIMARY)
+            .context(DateExtractionError::NoDateTag("GPSDateStamp field in EXIF data"))?;
+        let exif::Value::Ascii(values) = &field.value else {
+            return Err(DateExtractionError::UnparseableValue(field.display_value().to_string()).into());
+        };
+        let date_string = values
+            .first()
+            .map(|value| String::from_utf8_lossy(value).into_owned())
+            .context(DateExtractionError::UnparseableValue(String::new()))?;
+        NaiveDate::parse_from_str(date_string.trim(), "%Y:%m:%d")
+            .with_context(|| DateExtractionError::UnparseableValue(date_string.clone()))
+    }
+
+    fn gps_time(exif_data: &exif::Exif) -> Option<NaiveTime> {
+        let field = exif_data.get_field(Tag::GPSTimeStamp, In::PRIMARY)?;
+        let exif::Value::Rational(parts) = &field.value else {
+            return None;
+        };
+        let [hours, minutes, seconds] = parts.as_slice() else {
+            return None;
+        };
+        NaiveTime::from_hms_opt(
+            hours.to_f64() as u32,
+            minutes.to_f64() as u32,
+            seconds.to_f64() as u32,
+        )
+    }
+}
+
+impl DateExtractor for GpsDateExtractor {
+    fn extract_date_time(&self, filename: &str, image_data: &[u8]) -> Result<NaiveDateTime> {
+        self.extract_date_time_in_context(filename, image_data, &EntryContext::default())
+    }
+
+    fn extract_date_time_in_context(
+        &self,
+        _filename: &str,
+        image_data: &[u8],
+        context: &EntryContext,
+    ) -> Result<NaiveDateTime> {
+        let parsed = ParsedExif::new();
+        let exif_data = context.exif.unwrap_or(&parsed).get(image_data)?;
+        let date = Self::gps_date(exif_data)?;
+        Ok(match Self::gps_time(exif_data) {
+            Some(time) => {
+                let utc = date.and_time(time).and_utc();
+                match capture_utc_offset(exif_data) {
+                    Some(offset) => utc.with_timezone(&offset).naive_local(),
+                    None => {
+                        let location = Self::gps_location(exif_data).filter(|_| self.location_time_zones);
+                        context.time_zone.at(location).local_time_of(utc)
+                    }
+                }
+            }
+            None => start_of_day(date),
+        })
+    }
+
+    fn confidence(&self) -> Confidence {
+        Confidence::Exif
+    }
+}
+
+/// A user-defined filename regex whose named groups `year`, `month` and `day` give the
+/// date, like `^P(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})-` for `P20120506-001.jpg`.
+/// Without `day` the date is the 1st of the month, without `month` January 1st. Optional
+/// `hour`, `minute` and `second` groups give the time of day, which is midnight otherwise.
+#[derive(Debug, Clone)]
+pub struct FilenamePattern {
+    regex: regex::Regex,
+}
+
+impl FilenamePattern {
+    pub fn parse(pattern: &str) -> Result<Self> {
+        let regex = regex::Regex::new(pattern)
+            .with_context(|| format!("Invalid filename pattern: {}", pattern))?;
+        let has_group = |name: &str| regex.capture_names().any(|group| group == Some(name));
+        if !has_group("year") {
+            anyhow::bail!("Filename pattern has no (?<year>...) group: {}", pattern);
+        }
+        if has_group("day") && !has_group("month") {
+            anyhow::bail!("Filename pattern has a day but no (?<month>...) group: {}", pattern);
+        }
+        Ok(Self { regex })
+    }
+
+    fn date_time_of(&self, filename: &str) -> Option<NaiveDateTime> {
+        let captures = self.regex.captures(filename)?;
+        let number = |group: &str, missing: u32| -> Option<u32> {
+            match captures.name(group) {
+                Some(value) => value.as_str().parse().ok(),
+                None => Some(missing),
+            }
+        };
+        NaiveDate::from_ymd_opt(number("year", 1)? as i32, number("month", 1)?, number("day", 1)?)?
+            .and_hms_opt(number("hour", 0)?, number("minute", 0)?, number("second", 0)?)
+    }
+}
+
+/// Extracts dates from filename patterns: the user's own first, then the built-in ones
+#[derive(Default)]
+pub struct FilenameBasedDateExtractor {
+    custom_patterns: Vec<FilenamePattern>,
+}
+
+impl FilenameBasedDateExtractor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also try these patterns, before the built-in ones
+    pub fn with_patterns(mut self, patterns: Vec<FilenamePattern>) -> Self {
+        self.custom_patterns = patterns;
+        self
+    }
+
+    fn try_parse_patterns(&self, filename: &str, time_zone: DateTimeZone) -> Option<NaiveDateTime> {
+        self.custom_patterns
+            .iter()
+            .find_map(|pattern| pattern.date_time_of(filename))
+            .or_else(|| Self::try_parse_telegram_pattern(filename))
+            .or_else(|| Self::try_parse_date_with_dashes(filename).map(start_of_day))
+            .or_else(|| Self::try_parse_compact_datetime_pattern(filename))
+            .or_else(|| Self::try_parse_img_underscore_pattern(filename))
+            .or_else(|| Self::try_parse_img_dash_pattern(filename).map(start_of_day))
+            .or_else(|| Self::burst_date_time(filename))
+            .or_else(|| Self::try_parse_separated_date(filename).map(start_of_day))
+            .or_else(|| Self::try_parse_epoch_pattern(filename, time_zone))
+    }
+
+    fn try_parse_date_with_dashes(filename: &str) -> Option<NaiveDate> {
+        Self::date_of_captures(&DASHED_DATE.captures(filename)?)
+    }
+
+    /// The date in capture groups 1 to 3, as year, month and day
+    fn date_of_captures(captures: &regex::Captures) -> Option<NaiveDate> {
+        let year: i32 = captures.get(1)?.as_str().parse().ok()?;
+        let month: u32 = captures.get(2)?.as_str().parse().ok()?;
+        let day: u32 = captures.get(3)?.as_str().parse().ok()?;
+
+        NaiveDate::from_ymd_opt(year, month, day)
+    }
+
+    /// Time of a frame of a Pixel burst, `00000IMG_00000_BURSTYYYYMMDDHHMMSS_COVER.jpg`: every
+    /// frame of the burst carries the same timestamp
+    fn burst_date_time(filename: &str) -> Option<NaiveDateTime> {
+        let captures = BURST.captures(filename)?;
+        let date_time_str = captures.get(1)?.as_str();
+        NaiveDateTime::parse_from_str(date_time_str, "%Y%m%d%H%M%S").ok()
+    }
+
+    /// `YYYY.MM.DD`, `YYYY MM DD` or `YYYY_MM_DD`, as screenshot tools in some languages and
+    /// hand-named files write dates (the separators must agree)
+    fn try_parse_separated_date(filename: &str) -> Option<NaiveDate> {
+        SEPARATED_DATES
+            .iter()
+            .find_map(|pattern| Self::date_of_captures(&pattern.captures(filename)?))
+    }
+
+    /// Telegram Desktop's chat exports: `photo_123@15-01-2023_12-30-45.jpg`, also for videos,
+    /// files and thumbnails, with day and month first and the local time of the export
+    fn try_parse_telegram_pattern(filename: &str) -> Option<NaiveDateTime> {
+        let captures = TELEGRAM.captures(filename)?;
+        NaiveDateTime::parse_from_str(captures.get(1)?.as_str(), "%d-%m-%Y_%H-%M-%S").ok()
+    }
+
+    /// `YYYYMMDD_HHMMSS` (also Pixel's `PXL_YYYYMMDD_HHMMSSmmm`) and Samsung's `YYYYMMDD-HHMMSS`
+    fn try_parse_compact_datetime_pattern(filename: &str) -> Option<NaiveDateTime> {
+        let captures = COMPACT_DATE_TIME.captures(filename)?;
+        let date_str = captures.get(1)?.as_str();
+        let date = NaiveDate::parse_from_str(date_str, "%Y%m%d").ok()?;
+        // A number that is no time of day still dates the file
+        let time = NaiveTime::parse_from_str(captures.get(2)?.as_str(), "%H%M%S").unwrap_or(NaiveTime::MIN);
+        Some(date.and_time(time))
+    }
+
+    fn try_parse_img_underscore_pattern(filename: &str) -> Option<NaiveDateTime> {
+        let captures = IMG_UNDERSCORE.captures(filename)?;
+        let date_time_str = captures.get(1)?.as_str();
+        NaiveDateTime::parse_from_str(date_time_str, "%Y%m%d_%H%M%S").ok()
+    }
+
+    /// WhatsApp images and videos: `IMG-YYYYMMDD-WA0001`, `VID-YYYYMMDD-WA0001`
+    fn try_parse_img_dash_pattern(filename: &str) -> Option<NaiveDate> {
+        let captures = IMG_DASH.captures(filename)?;
+        let date_str = captures.get(1)?.as_str();
+        NaiveDate::parse_from_str(date_str, "%Y%m%d").ok()
+    }
+
+    /// Unix time in seconds (10 digits) or milliseconds (13 digits), as Telegram and some
+    /// camera apps name files (`1436824799639.jpg`); other numbers are left alone by only
+    /// accepting times from 2000 up to now
+    fn try_parse_epoch_pattern(filename: &str, time_zone: DateTimeZone) -> Option<NaiveDateTime> {
+        let captures = EPOCH.captures(filename)?;
+        let digits = captures.get(1)?.as_str();
+        let mut seconds: i64 = digits.parse().ok()?;
+        if digits.len() == 13 {
+            seconds /= 1000;
+        }
+        if !(EARLIEST_EPOCH_FILENAME..=Local::now().timestamp()).contains(&seconds) {
+            return None;
+        }
+        Some(time_zone.local_time_of(chrono::DateTime::from_timestamp(seconds, 0)?))
+    }
+}
+
+/// Separators between year, month and day besides the dash
+const DATE_SEPARATORS: &[&str] = &[".", " ", "_"];
+
+// The built-in filename patterns, compiled once instead of for every file
+static DASHED_DATE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\d{4})-(\d{2})-(\d{2})").expect("valid regex"));
+static BURST: Lazy<Regex> = Lazy::new(|| Regex::new(r"BURST(\d{14})").expect("valid regex"));
+static COMPACT_DATE_TIME: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\d{8})[_-](\d{6})").expect("valid regex"));
+static IMG_UNDERSCORE: Lazy<Regex> = Lazy::new(|| Regex::new(r"IMG_(\d{8}_\d{6})").expect("valid regex"));
+static TELEGRAM: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"@(\d{2}-\d{2}-\d{4}_\d{2}-\d{2}-\d{2})").expect("valid regex"));
+static IMG_DASH: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?:IMG|VID)-(\d{8})").expect("valid regex"));
+static EPOCH: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?:^|\D)(\d{13}|\d{10})(?:\D|$)").expect("valid regex"));
+/// One pattern per separator in `DATE_SEPARATORS`, so the separators of a date must agree
+static SEPARATED_DATES: Lazy<Vec<Regex>> = Lazy::new(|| {
+    DATE_SEPARATORS
+        .iter()
+        .map(|separator| {
+            let separator = regex::escape(separator);
+            Regex::new(&format!(r"(?:^|\D)(\d{{4}}){separator}(\d{{2}}){separator}(\d{{2}})(?:\D|$)"))
+                .expect("valid regex")
+        })
+        .collect()
+});
+static FOLDER_DATE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(\d{4})(?:[-_.](\d{2}))?(?:[-_.](\d{2}))?(?:[^\d]|$)").expect("valid regex"));
+
+/// 2000-01-01, before which a 10 or 13 digit number in a filename is unlikely to be a time
+const EARLIEST_EPOCH_FILENAME: i64 = 946_684_800;
+
+impl DateExtractor for FilenameBasedDateExtractor {
+    fn extract_date_time(&self, filename: &str, image_data: &[u8]) -> Result<NaiveDateTime> {
+        self.extract_date_time_in_context(filename, image_data, &EntryContext::default())
+    }
+
+    fn extract_date_time_in_context(
+        &self,
+        filename: &str,
+        _image_data: &[u8],
+        context: &EntryContext,
+    ) -> Result<NaiveDateTime> {
+        self.try_parse_patterns(filename, context.time_zone)
+            .context(DateExtractionError::NoFilenamePattern)
+    }
+
+    fn confidence(&self) -> Confidence {
+        Confidence::Filename
+    }
+}
+
+/// Extracts dates from the names of the folders containing a file, for hand-made trees
+/// like `2019-07 Tuscany/` or `2015 Christmas/`. The innermost dated folder wins; a
+/// folder with only a month is dated on the 1st, one with only a year on January 1st.
+#[derive(Default)]
+pub struct FolderNameDateExtractor;
+
+impl FolderNameDateExtractor {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn try_parse_folder_name(folder_name: &str) -> Option<NaiveDate> {
+        let captures = FOLDER_DATE.captures(folder_name)?;
+
+        let year: i32 = captures.get(1)?.as_str().parse().ok()?;
+        let month: u32 = captures.get(2).map_or(Some(1), |m| m.as_str().parse().ok())?;
+        let day: u32 = captures.get(3).map_or(Some(1), |d| d.as_str().parse().ok())?;
+
+        if !(1900..=2100).contains(&year) {
+            return None;
+        }
+        NaiveDate::from_ymd_opt(year, month, day)
+    }
+
+    fn refine_with_numbered_folders(year_start: NaiveDate, below: &[&str]) -> NaiveDate {
+        let number = |folder: Option<&&str>| -> Option<u32> {
+            folder.filter(|folder| folder.len() <= 2)?.parse().ok()
+        };
+        let Some(month) = number(below.first()) else {
+            return year_start;
+        };
+        let Some(month_start) = NaiveDate::from_ymd_opt(year_start.year(), month, 1) else {
+            return year_start;
+        };
+        number(below.get(1))
+            .and_then(|day| NaiveDate::from_ymd_opt(year_start.year(), month, day))
+            .unwrap_or(month_start)
+    }
+}
+
+impl DateExtractor for FolderNameDateExtractor {
+    fn extract_date_time(&self, filename: &str, _image_data: &[u8]) -> Result<NaiveDateTime> {
+        let mut folders: Vec<&str> = filename.split(['/', '\\']).collect();
+        folders.pop();
+
+        let (position, date) = folders
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(position, folder)| Some((position, Self::try_parse_folder_name(folder)?)))
+            .context(DateExtractionError::NoFolderDate)?;
+
+        // A bare year folder may hold numbered month (and day) folders: `2019/07/14/`
+        if folders[position].len() == 4 {
+            return Ok(start_of_day(Self::refine_with_numbered_folders(date, &folders[position + 1..])));
+        }
+        Ok(start_of_day(date))
+    }
+
+    fn confidence(&self) -> Confidence {
+        Confidence::Filename
+    }
 }
 
-/// Concrete implementation that extracts dates from EXIF metadata
-pub struct ExifDateExtractor;
+/// Dates Takeout media by the year of the `Photos from YYYY` folder they are in, as January
+/// 1st of that year, for media nothing else dates. Its `Confidence::Year` says only the year
+/// is known.
+#[derive(Default)]
+pub struct TakeoutYearDateExtractor;
+
+impl TakeoutYearDateExtractor {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl DateExtractor for TakeoutYearDateExtractor {
+    fn extract_date_time(&self, filename: &str, _image_data: &[u8]) -> Result<NaiveDateTime> {
+        let year = ArchiveIndex::takeout_year_of(filename).context(DateExtractionError::NoFolderDate)?;
+        NaiveDate::from_ymd_opt(year, 1, 1)
+            .map(start_of_day)
+            .context(DateExtractionError::UnparseableValue(year.to_string()))
+    }
+
+    fn confidence(&self) -> Confidence {
+        Confidence::Year
+    }
+}
+
+/// Dates Takeout media by the date in the `metadata.json` of the album folder they are in,
+/// for album photos with no date of their own. Local time of that moment.
+#[derive(Default)]
+pub struct AlbumDateExtractor;
+
+impl AlbumDateExtractor {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl DateExtractor for AlbumDateExtractor {
+    fn extract_date_time(&self, filename: &str, image_data: &[u8]) -> Result<NaiveDateTime> {
+        self.extract_date_time_in_context(filename, image_data, &EntryContext::default())
+    }
+
+    fn extract_date_time_in_context(
+        &self,
+        _filename: &str,
+        _image_data: &[u8],
+        context: &EntryContext,
+    ) -> Result<NaiveDateTime> {
+        let date = context
+            .album
+            .and_then(|album| album.date)
+            .context(DateExtractionError::NoRecord("date in an album metadata.json"))?;
+        Ok(context.time_zone.local_time_of(date))
+    }
+
+    fn confidence(&self) -> Confidence {
+        Confidence::Sidecar
+    }
+}
+
+/// Dates Takeout media by `photoTakenTime` in their JSON sidecar, the date Google Photos
+/// shows. Often the only date of screenshots, WhatsApp images and uploads stripped of EXIF.
+/// The UTC time is dated by the photo's EXIF OffsetTimeOriginal when it has one.
+#[derive(Default)]
+pub struct JsonSidecarDateExtractor {
+    location_time_zones: bool,
+}
+
+impl JsonSidecarDateExtractor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Date UTC times of photos without OffsetTimeOriginal in the zone looked up for their
+    /// sidecar location (off by default, as the lookup is approximate near borders)
+    pub fn with_location_time_zones(mut self, enabled: bool) -> Self {
+        self.location_time_zones = enabled;
+        self
+    }
+}
+
+impl DateExtractor for JsonSidecarDateExtractor {
+    fn extract_date_time(&self, filename: &str, image_data: &[u8]) -> Result<NaiveDateTime> {
+        self.extract_date_time_in_context(filename, image_data, &EntryContext::default())
+    }
+
+    fn extract_date_time_in_context(
+        &self,
+        _filename: &str,
+        image_data: &[u8],
+        context: &EntryContext,
+    ) -> Result<NaiveDateTime> {
+        let sidecar = context.sidecar.context(DateExtractionError::NoRecord("JSON sidecar"))?;
+        let taken_time = sidecar
+            .photo_taken_time
+            .context(DateExtractionError::NoDateTag("photoTakenTime in the JSON sidecar"))?;
+        let parsed = ParsedExif::new();
+        let exif_data = context.exif.unwrap_or(&parsed).get(image_data).ok();
+        if let Some(offset) = exif_data.and_then(capture_utc_offset) {
+            return Ok(taken_time.with_timezone(&offset).naive_local());
+        }
+        let location = sidecar
+            .geo
+            .filter(|_| self.location_time_zones)
+            .map(|geo| (geo.latitude, geo.longitude));
+        Ok(context.time_zone.at(location).local_time_of(taken_time))
+    }
+
+    fn confidence(&self) -> Confidence {
+        Confidence::Sidecar
+    }
+}
+
+/// Dates Snapchat Memories by the `memories_history.json` of their export, which has the
+/// time of day that their file names lack. Local time of that moment.
+#[derive(Default)]
+pub struct SnapchatMemoryDateExtractor;
+
+impl SnapchatMemoryDateExtractor {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl DateExtractor for SnapchatMemoryDateExtractor {
+    fn extract_date_time(&self, filename: &str, image_data: &[u8]) -> Result<NaiveDateTime> {
+        self.extract_date_time_in_context(filename, image_data, &EntryContext::default())
+    }
+
+    fn extract_date_time_in_context(
+        &self,
+        _filename: &str,
+        _image_data: &[u8],
+        context: &EntryContext,
+    ) -> Result<NaiveDateTime> {
+        let memory = context
+            .snapchat
+            .context(DateExtractionError::NoRecord("Snapchat memories_history.json record"))?;
+        Ok(context.time_zone.local_time_of(memory.date_time))
+    }
+
+    fn confidence(&self) -> Confidence {
+        Confidence::Sidecar
+    }
+}
+
+/// Dates media of Facebook and Instagram exports by the JSON files listing them, for
+/// uploads Meta stripped of their EXIF data. Local time of the taken (or upload) moment.
+#[derive(Default)]
+pub struct MetaExportDateExtractor;
+
+impl MetaExportDateExtractor {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl DateExtractor for MetaExportDateExtractor {
+    fn extract_date_time(&self, filename: &str, image_data: &[u8]) -> Result<NaiveDateTime> {
+        self.extract_date_time_in_context(filename, image_data, &EntryContext::default())
+    }
+
+    fn extract_date_time_in_context(
+        &self,
+        _filename: &str,
+        _image_data: &[u8],
+        context: &EntryContext,
+    ) -> Result<NaiveDateTime> {
+        let media = context
+            .meta
+            .context(DateExtractionError::NoRecord("Facebook or Instagram metadata"))?;
+        Ok(context.time_zone.local_time_of(media.date_time()))
+    }
+
+    fn confidence(&self) -> Confidence {
+        Confidence::Sidecar
+    }
+}
+
+/// Dates an entry by the last-modified time its archive records. Takeout sets it to
+/// roughly when the file was uploaded, so this is only a last resort. Times of files in
+/// a directory input are left to `FileModifiedDateExtractor`.
+#[derive(Default)]
+pub struct ArchiveModifiedDateExtractor;
+
+impl ArchiveModifiedDateExtractor {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl DateExtractor for ArchiveModifiedDateExtractor {
+    fn extract_date_time(&self, filename: &str, image_data: &[u8]) -> Result<NaiveDateTime> {
+        self.extract_date_time_in_context(filename, image_data, &EntryContext::default())
+    }
+
+    fn extract_date_time_in_context(
+        &self,
+        _filename: &str,
+        _image_data: &[u8],
+        context: &EntryContext,
+    ) -> Result<NaiveDateTime> {
+        let modified = context
+            .metadata
+            .filter(|metadata| !metadata.from_file_system)
+            .and_then(|metadata| metadata.modified)
+            .context(DateExtractionError::NoRecord("modification time recorded"))?;
+        Ok(modified)
+    }
+
+    fn confidence(&self) -> Confidence {
+        Confidence::Mtime
+    }
+}
+
+/// Dates a file of a directory input by its modification time on disk, which survives
+/// extracting a Takeout with most tools but says nothing once files were copied around
+#[derive(Default)]
+pub struct FileModifiedDateExtractor;
+
+impl FileModifiedDateExtractor {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl DateExtractor for FileModifiedDateExtractor {
+    fn extract_date_time(&self, filename: &str, image_data: &[u8]) -> Result<NaiveDateTime> {
+        self.extract_date_time_in_context(filename, image_data, &EntryContext::default())
+    }
+
+    fn extract_date_time_in_context(
+        &self,
+        _filename: &str,
+        _image_data: &[u8],
+        context: &EntryContext,
+    ) -> Result<NaiveDateTime> {
+        let modified = context
+            .metadata
+            .filter(|metadata| metadata.from_file_system)
+            .and_then(|metadata| metadata.modified)
+            .context(DateExtractionError::NoRecord("file modification time"))?;
+        Ok(modified)
+    }
+
+    fn confidence(&self) -> Confidence {
+        Confidence::Mtime
+    }
+}
+
+/// A kind of date the composite extractor can consult, for `--date-sources`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum DateSource {
+    /// Metadata embedded in the file: EXIF, video metadata, PNG text and XMP
+    Exif,
+    /// The Takeout JSON sidecar, or the metadata of a Facebook/Instagram export
+    Json,
+    /// Dates in the filename
+    Filename,
+    /// Dates in the names of the containing folders, where folder dating is on
+    Folder,
+    /// The GPS date in the EXIF data
+    Gps,
+    /// The date in the `metadata.json` of the Takeout album folder
+    Album,
+    /// The year of the Takeout `Photos from YYYY` folder, for files filed by year alone
+    TakeoutYear,
+    /// The modification time in the archive or, unless disabled, on disk
+    Mtime,
+}
+
+impl DateSource {
+    /// The source called `name`, which is also the name of its built-in extractor
+    fn named(name: &str) -> Option<Self> {
+        <Self as clap::ValueEnum>::value_variants()
+            .iter()
+            .copied()
+            .find(|source| source.name() == name)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            DateSource::Exif => "exif",
+            DateSource::Json => "json",
+            DateSource::Filename => "filename",
+            DateSource::Folder => "folder",
+            DateSource::Gps => "gps",
+            DateSource::Album => "album",
+            DateSource::TakeoutYear => "takeout-year",
+            DateSource::Mtime => "mtime",
+        }
+    }
+
+    /// How far the dates of this source can be trusted
+    pub fn confidence(self) -> Confidence {
+        match self {
+            DateSource::Exif | DateSource::Gps => Confidence::Exif,
+            DateSource::Json | DateSource::Album => Confidence::Sidecar,
+            DateSource::Filename | DateSource::Folder => Confidence::Filename,
+            DateSource::TakeoutYear => Confidence::Year,
+            DateSource::Mtime => Confidence::Mtime,
+        }
+    }
+}
+
+/// The order in which dates are looked for unless `with_sources` changes it
+pub const DEFAULT_DATE_SOURCES: &[DateSource] = &[
+    DateSource::Exif,
+    DateSource::Json,
+    DateSource::Filename,
+    DateSource::Folder,
+    DateSource::Gps,
+    DateSource::Album,
+    DateSource::TakeoutYear,
+    DateSource::Mtime,
+];
+
+/// Dates cameras fall back to when their clock battery ran out: the Unix epoch, the DOS
+/// epoch (also the modification time of ZIP entries without one) and 2001-01-01
+pub const DEAD_CLOCK_DATES: &[(i32, u32, u32)] = &[(1970, 1, 1), (1980, 1, 1), (2001, 1, 1)];
+
+/// Which extracted dates are believable. The composite extractor passes over a source whose
+/// date is not, and tries the next one, instead of filing the photo under a bogus year.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DatePlausibility {
+    earliest: NaiveDate,
+    future_dates: bool,
+    dead_clock_dates: Vec<NaiveDate>,
+}
+
+impl Default for DatePlausibility {
+    fn default() -> Self {
+        Self {
+            earliest: NaiveDate::from_ymd_opt(1900, 1, 1).expect("valid date"),
+            future_dates: false,
+            dead_clock_dates: DEAD_CLOCK_DATES
+                .iter()
+                .filter_map(|&(year, month, day)| NaiveDate::from_ymd_opt(year, month, day))
+                .collect(),
+        }
+    }
+}
+
+impl DatePlausibility {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reject dates before this one (default 1900-01-01)
+    pub fn with_earliest(mut self, earliest: NaiveDate) -> Self {
+        self.earliest = earliest;
+        self
+    }
+
+    /// Accept dates after today (off by default, as only corrupted metadata gives them)
+    pub fn with_future_dates(mut self, allowed: bool) -> Self {
+        self.future_dates = allowed;
+        self
+    }
+
+    /// Reject these days, in place of `DEAD_CLOCK_DATES`
+    pub fn with_dead_clock_dates(mut self, dates: Vec<NaiveDate>) -> Self {
+        self.dead_clock_dates = dates;
+        self
+    }
+
+    /// Why `time` cannot be a capture time, if it cannot
+    pub fn check(&self, time: NaiveDateTime) -> Result<()> {
+        let date = time.date();
+        let reason = if date < self.earliest {
+            format!("before {}", self.earliest)
+        } else if self.dead_clock_dates.contains(&date) {
+            "camera clock reset".to_string()
+        } else if !self.future_dates && time > Local::now().naive_local() + TimeDelta::days(1) {
+            // A day of slack, for times in a zone ahead of the local one
+            "in the future".to_string()
+        } else {
+            return Ok(());
+        };
+        Err(DateExtractionError::Implausible { date, reason }.into())
+    }
+
+    /// Why `year` cannot be the year of a capture, if it cannot; for sources that only know
+    /// the year, whose January 1st says nothing of a reset clock
+    pub fn check_year(&self, year: i32) -> Result<()> {
+        let reason = if year < self.earliest.year() {
+            format!("before {}", self.earliest)
+        } else if !self.future_dates && year > Local::now().year() {
+            "in the future".to_string()
+        } else {
+            return Ok(());
+        };
+        let date = NaiveDate::from_ymd_opt(year, 1, 1).unwrap_or(self.earliest);
+        Err(DateExtractionError::Implausible { date, reason }.into())
+    }
+}
+
+/// What the built-in extractors of the composite are configured with, kept to rebuild them
+/// when a builder changes it
+#[derive(Clone)]
+struct SourceSettings {
+    filename_patterns: Vec<FilenamePattern>,
+    clock_offsets: Vec<ClockOffset>,
+    location_time_zones: bool,
+    folder_names: bool,
+    file_times: bool,
+}
+
+impl Default for SourceSettings {
+    fn default() -> Self {
+        Self {
+            filename_patterns: Vec::new(),
+            clock_offsets: Vec::new(),
+            location_time_zones: false,
+            folder_names: false,
+            file_times: true,
+        }
+    }
+}
+
+impl SourceSettings {
+    /// The extractors behind `source`, in the order they are tried
+    fn build(&self, source: DateSource) -> SourceDateExtractor {
+        let extractors: Vec<Box<dyn DateExtractor>> = match source {
+            DateSource::Exif => vec![
+                Box::new(ExifDateExtractor::new().with_clock_offsets(self.clock_offsets.clone())),
+                Box::new(VideoDateExtractor::new()),
+                Box::new(PngTextDateExtractor::new()),
+                Box::new(XmpDateExtractor::new()),
+            ],
+            DateSource::Json => vec![
+                Box::new(self.sidecar_extractor()),
+                Box::new(MetaExportDateExtractor::new()),
+                Box::new(SnapchatMemoryDateExtractor::new()),
+            ],
+            DateSource::Filename => {
+                vec![Box::new(FilenameBasedDateExtractor::new().with_patterns(self.filename_patterns.clone()))]
+            }
+            DateSource::Folder => vec![Box::new(FolderNameDateExtractor::new())],
+            DateSource::Gps => vec![Box::new(GpsDateExtractor::new().with_location_time_zones(self.location_time_zones))],
+            DateSource::Album => vec![Box::new(AlbumDateExtractor::new())],
+            DateSource::TakeoutYear => vec![Box::new(TakeoutYearDateExtractor::new())],
+            DateSource::Mtime => {
+                let mut extractors: Vec<Box<dyn DateExtractor>> = vec![Box::new(ArchiveModifiedDateExtractor::new())];
+                if self.file_times {
+                    extractors.push(Box::new(FileModifiedDateExtractor::new()));
+                }
+                extractors
+            }
+        };
+        SourceDateExtractor {
+            source,
+            extractors,
+            disabled: (source == DateSource::Folder && !self.folder_names).then_some("Folder names"),
+        }
+    }
+
+    fn sidecar_extractor(&self) -> JsonSidecarDateExtractor {
+        JsonSidecarDateExtractor::new().with_location_time_zones(self.location_time_zones)
+    }
+}
+
+/// The built-in extractors behind one `DateSource`, tried in turn, named after the source
+struct SourceDateExtractor {
+    source: DateSource,
+    extractors: Vec<Box<dyn DateExtractor>>,
+    /// What is switched off, when the source gives no dates for this input
+    disabled: Option<&'static str>,
+}
+
+impl DateExtractor for SourceDateExtractor {
+    fn extract_date_time(&self, filename: &str, image_data: &[u8]) -> Result<NaiveDateTime> {
+        self.extract_date_time_in_context(filename, image_data, &EntryContext::default())
+    }
+
+    fn extract_date_time_in_context(
+        &self,
+        filename: &str,
+        image_data: &[u8],
+        context: &EntryContext,
+    ) -> Result<NaiveDateTime> {
+        if let Some(disabled) = self.disabled {
+            return Err(DateExtractionError::SourceDisabled(disabled).into());
+        }
+        // The first extractor's error says best what was missing
+        let mut first_error = None;
+        for extractor in &self.extractors {
+            match extractor.extract_date_time_in_context(filename, image_data, context) {
+                Ok(time) => return Ok(time),
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        Err(first_error.expect("every source has an extractor"))
+    }
+
+    fn confidence(&self) -> Confidence {
+        self.source.confidence()
+    }
+
+    fn name(&self) -> &str {
+        self.source.name()
+    }
+}
+
+/// Composite extractor that tries its date sources in order, by default the metadata
+/// embedded in the file first (EXIF, video metadata, PNG text, XMP), then the Takeout JSON
+/// sidecar or Facebook/Instagram export metadata, then the filename (and optionally the
+/// containing folder names), then the GPS date, then the album's date, and finally the
+/// entry's modification time in the archive or, unless disabled, on disk. Extractors of
+/// your own can be registered anywhere in that order. Implausible dates are passed over.
+pub struct CompositeDateExtractor {
+    /// The built-in sources, named after their `DateSource`, and the registered extractors,
+    /// in the order they are tried
+    extractors: Vec<Box<dyn DateExtractor>>,
+    settings: SourceSettings,
+    plausibility: Option<DatePlausibility>,
+    sidecar_first_for_edits: bool,
+    time_zone: Option<DateTimeZone>,
+}
+
+impl Default for CompositeDateExtractor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CompositeDateExtractor {
+    pub fn new() -> Self {
+        let settings = SourceSettings::default();
+        Self {
+            extractors: DEFAULT_DATE_SOURCES
+                .iter()
+                .map(|&source| Box::new(settings.build(source)) as Box<dyn DateExtractor>)
+                .collect(),
+            settings,
+            plausibility: Some(DatePlausibility::new()),
+            sidecar_first_for_edits: true,
+            time_zone: None,
+        }
+    }
+
+    /// Consult only these sources, in this order; an empty list keeps the default order.
+    /// Registered extractors are kept, after the sources.
+    pub fn with_sources(mut self, sources: &[DateSource]) -> Self {
+        if !sources.is_empty() {
+            let registered = std::mem::take(&mut self.extractors)
+                .into_iter()
+                .filter(|extractor| DateSource::named(extractor.name()).is_none());
+            self.extractors = sources
+                .iter()
+                .map(|&source| Box::new(self.settings.build(source)) as Box<dyn DateExtractor>)
+                .chain(registered)
+                .collect();
+        }
+        self
+    }
+
+    /// Try `extractor` after everything else. The names of the sources (`exif`, `json`, ...)
+    /// are taken by the built-in extractors; one of yours that answers to one is replaced.
+    pub fn with_extractor(mut self, extractor: Box<dyn DateExtractor>) -> Self {
+        self.extractors.push(extractor);
+        self
+    }
+
+    /// Try `extractor` at `position` in the order, counting the sources (eight by default,
+    /// see `DEFAULT_DATE_SOURCES`) and the extractors registered before; 0 tries it first.
+    /// Panics when `position` is past the end, like `Vec::insert`.
+    pub fn with_extractor_at(mut self, position: usize, extractor: Box<dyn DateExtractor>) -> Self {
+        self.extractors.insert(position, extractor);
+        self
+    }
+
+    /// Rebuild the built-in extractors in place after a change to their settings
+    fn with_settings(mut self, settings: SourceSettings) -> Self {
+        for extractor in &mut self.extractors {
+            if let Some(source) = DateSource::named(extractor.name()) {
+                *extractor = Box::new(settings.build(source));
+            }
+        }
+        self.settings = settings;
+        self
+    }
+
+    /// Try these filename patterns before the built-in ones
+    pub fn with_filename_patterns(self, patterns: Vec<FilenamePattern>) -> Self {
+        let settings = SourceSettings {
+            filename_patterns: patterns,
+            ..self.settings.clone()
+        };
+        self.with_settings(settings)
+    }
+
+    /// Turn UTC times (sidecars, Meta exports, GPS, video headers, Unix-time filenames,
+    /// album dates) into dates in this zone instead of the local one
+    pub fn with_time_zone(mut self, time_zone: DateTimeZone) -> Self {
+        self.time_zone = Some(time_zone);
+        self
+    }
+
+    /// `context` with the zone set by `with_time_zone`, if any, for the sources to read
+    fn zoned<'e>(&self, context: &EntryContext<'e>) -> EntryContext<'e> {
+        match self.time_zone {
+            Some(time_zone) => context.with_time_zone(time_zone),
+            None => *context,
+        }
+    }
+
+    /// Correct the EXIF capture times of cameras whose clock was off
+    pub fn with_clock_offsets(self, clock_offsets: Vec<ClockOffset>) -> Self {
+        let settings = SourceSettings {
+            clock_offsets,
+            ..self.settings.clone()
+        };
+        self.with_settings(settings)
+    }
+
+    /// Date sidecar and GPS times of photos with a location but no EXIF OffsetTimeOriginal in
+    /// the zone looked up for that place (off by default, as the lookup is approximate)
+    pub fn with_location_time_zones(self, enabled: bool) -> Self {
+        let settings = SourceSettings {
+            location_time_zones: enabled,
+            ..self.settings.clone()
+        };
+        self.with_settings(settings)
+    }
+
+    /// Fall back to the modification time of files in a directory input (on by default)
+    pub fn with_file_times(self, enabled: bool) -> Self {
+        let settings = SourceSettings {
+            file_times: enabled,
+            ..self.settings.clone()
+        };
+        self.with_settings(settings)
+    }
+
+    /// Pass over dates these checks reject; `None` takes every date as found
+    pub fn with_plausibility(mut self, plausibility: Option<DatePlausibility>) -> Self {
+        self.plausibility = plausibility;
+        self
+    }
+
+    /// Try the `json` source before `exif` for `-edited`/`-EFFECTS` copies, whose EXIF may hold
+    /// the time of the edit instead of the capture (on by default)
+    pub fn with_sidecar_first_for_edits(mut self, enabled: bool) -> Self {
+        self.sidecar_first_for_edits = enabled;
+        self
+    }
+
+    /// The built-in extractor of `source`, when it is consulted
+    fn source(&self, source: DateSource) -> Option<&dyn DateExtractor> {
+        self.extractors
+            .iter()
+            .find(|extractor| extractor.name() == source.name())
+            .map(|extractor| extractor.as_ref())
+    }
+
+    /// The extractors to try for `filename`: the order, with `json` moved before `exif` for edits
+    fn order_for(&self, filename: &str) -> Vec<&dyn DateExtractor> {
+        let mut order: Vec<&dyn DateExtractor> = self.extractors.iter().map(|extractor| extractor.as_ref()).collect();
+        if !self.sidecar_first_for_edits || edited_original(filename).is_none() {
+            return order;
+        }
+        let position = |source: DateSource| order.iter().position(|extractor| extractor.name() == source.name());
+        if let (Some(exif), Some(json)) = (position(DateSource::Exif), position(DateSource::Json)) {
+            if json > exif {
+                let json = order.remove(json);
+                order.insert(exif, json);
+            }
+        }
+        order
+    }
+
+    fn check_plausible(&self, extracted: &ExtractedDate) -> Result<()> {
+        match &self.plausibility {
+            Some(plausibility) if extracted.confidence == Confidence::Year => plausibility.check_year(extracted.time.year()),
+            Some(plausibility) => plausibility.check(extracted.time),
+            None => Ok(()),
+        }
+    }
+
+    /// Fall back to dates in folder names when EXIF and filename give nothing
+    pub fn with_folder_names(self, enabled: bool) -> Self {
+        let settings = SourceSettings {
+            folder_names: enabled,
+            ..self.settings.clone()
+        };
+        self.with_settings(settings)
+    }
+}
+
+impl DateExtractor for CompositeDateExtractor {
+    fn extract_date_time(&self, filename: &str, image_data: &[u8]) -> Result<NaiveDateTime> {
+        self.extract_date_time_in_context(filename, image_data, &EntryContext::default())
+    }
+
+    fn extract_date_time_in_context(
+        &self,
+        filename: &str,
+        image_data: &[u8],
+        context: &EntryContext,
+    ) -> Result<NaiveDateTime> {
+        self.extract_with_confidence_in_context(filename, image_data, context)
+            .map(|extracted| extracted.time)
+    }
+
+    fn embedded_and_sidecar_times(
+        &self,
+        filename: &str,
+        image_data: &[u8],
+        context: &EntryContext,
+    ) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        let context = &self.zoned(context);
+        // The sidecar is cheap to look at, the embedded metadata means parsing the file
+        let sidecar = self
+            .settings
+            .sidecar_extractor()
+            .extract_date_time_in_context(filename, image_data, context)
+            .ok()?;
+        let embedded = self
+            .source(DateSource::Exif)?
+            .extract_date_time_in_context(filename, image_data, context)
+            .ok()?;
+        Some((embedded, sidecar))
+    }
+
+    /// The least trusted of the sources consulted
+    fn confidence(&self) -> Confidence {
+        self.extractors
+            .iter()
+            .map(|extractor| extractor.confidence())
+            .min()
+            .unwrap_or(Confidence::Mtime)
+    }
+
+    fn extract_with_confidence_in_context(
+        &self,
+        filename: &str,
+        image_data: &[u8],
+        context: &EntryContext,
+    ) -> Result<ExtractedDate> {
+        let context = &self.zoned(context);
+        let mut failures = Vec::new();
+        for extractor in self.order_for(filename) {
+            let extracted = extractor.extract_with_confidence_in_context(filename, image_data, context);
+            match extracted.and_then(|extracted| self.check_plausible(&extracted).map(|_| extracted)) {
+                Ok(extracted) => return Ok(extracted),
+                Err(e) => failures.push(SourceFailure {
+                    source: extractor.name().to_string(),
+                    error: DateExtractionError::of(&e).cloned(),
+                    message: e.to_string(),
+                }),
+            }
+        }
+        Err(DateExtractionError::NoDate(failures).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[test]
+    fn test_extract_date_from_valid_exif() {
+        // Arrange
+        let extractor = ExifDateExtractor::new();
+
+        // Real Google Photos image (1x1 pixel) with DateTimeOriginal: 2012:10:06 13:09:32
+        let sample_image_data: &[u8] = include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg");
+
+        // Act
+        let result = extractor.extract_date("photo.jpg", sample_image_data);
+
+        // Assert
+        assert!(result.is_ok(), "Failed to extract date: {:?}", result.err());
+        let date = result.unwrap();
+        assert_eq!(date, NaiveDate::from_ymd_opt(2012, 10, 6).unwrap());
+    }
+
+    #[test]
+    fn test_extract_date_time_from_valid_exif() {
+        // Arrange
+        let extractor = ExifDateExtractor::new();
+        let sample_image_data: &[u8] = include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg");
+
+        // Act
+        let result = extractor.extract_date_time("photo.jpg", sample_image_data);
+
+        // Assert
+        assert_eq!(
+            result.unwrap(),
+            NaiveDate::from_ymd_opt(2012, 10, 6).unwrap().and_hms_milli_opt(13, 9, 32, 800).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parsed_exif_decodes_once() {
+        // Arrange
+        let exif = ParsedExif::new();
+        let data: &[u8] = include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg");
+
+        // Act
+        let first = exif.get(data).unwrap() as *const exif::Exif;
+        // Later steps get the decoded EXIF, whatever they pass
+        let second = exif.get(&[]).unwrap() as *const exif::Exif;
+
+        // Assert
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_exif_extractor_reads_shared_parsed_exif() {
+        // Arrange
+        let extractor = ExifDateExtractor::new();
+        let exif = ParsedExif::new();
+        exif.get(include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg")).unwrap();
+        let context = EntryContext::default().with_exif(&exif);
+
+        // Act
+        let result = extractor.extract_date_in_context("photo.jpg", &[], &context);
+
+        // Assert
+        assert_eq!(result.unwrap(), NaiveDate::from_ymd_opt(2012, 10, 6).unwrap());
+    }
+
+    #[test]
+    fn test_parsed_exif_keeps_decoding_error() {
+        // Arrange
+        let exif = ParsedExif::new();
+
+        // Act
+        let first = exif.get(&[0xFF, 0xD8, 0xFF, 0xD9]).map(|_| ()).unwrap_err().to_string();
+        let second = exif.get(&[0xFF, 0xD8, 0xFF, 0xD9]).map(|_| ()).unwrap_err().to_string();
+
+        // Assert
+        assert!(first.starts_with("Failed to read EXIF data from image"), "{}", first);
+        assert_eq!(first, second);
+    }
+
+    #[rstest]
+    #[case("2012:10:06 13:09:32", "2012-10-06 13:09:32")]
+    #[case("2012/10/06 13:09", "2012-10-06 13:09:00")]
+    #[case("2012:10:06 13:09", "2012-10-06 13:09:00")]
+    #[case("2012-10-06T13:09:32", "2012-10-06 13:09:32")]
+    #[case("2012:10:06 13:09:32\0\0\0", "2012-10-06 13:09:32")]
+    #[case(" 2012:10:06 13:09:32 ", "2012-10-06 13:09:32")]
+    #[case("2012:10:06   :  :  ", "2012-10-06 00:00:00")]
+    #[case("2012:10:06", "2012-10-06 00:00:00")]
+    fn test_extract_date_time_from_irregular_exif_date(#[case] value: &str, #[case] expected: &str) {
+        // Arrange
+        let extractor = ExifDateExtractor::new();
+        let tiff = tiff_with_date_time_original(value, true);
+
+        // Act
+        let result = extractor.extract_date_time("photo.jpg", &tiff);
+
+        // Assert
+        assert_eq!(
+            result.unwrap(),
+            NaiveDateTime::parse_from_str(expected, "%Y-%m-%d %H:%M:%S").unwrap()
+        );
+    }
+
+    #[rstest]
+    #[case("0000:00:00 00:00:00", "no_date_tag")]
+    #[case("    :  :     :  :  ", "no_date_tag")]
+    #[case("\0\0\0\0", "no_date_tag")]
+    #[case("2012:13:06 13:09:32", "unparseable_value")]
+    #[case("yesterday", "unparseable_value")]
+    fn test_extract_date_rejects_blank_and_invalid_exif_date(#[case] value: &str, #[case] expected_kind: &str) {
+        // Arrange
+        let extractor = ExifDateExtractor::new();
+        let tiff = tiff_with_date_time_original(value, true);
+
+        // Act
+        let error = extractor.extract_date("photo.jpg", &tiff).unwrap_err();
+
+        // Assert
+        assert_eq!(DateExtractionError::of(&error).map(DateExtractionError::kind), Some(expected_kind));
+    }
+
+    #[test]
+    fn test_composite_extractor_passes_over_all_zero_exif_date() {
+        // Arrange
+        let extractor = CompositeDateExtractor::new();
+        let tiff = tiff_with_date_time_original("0000:00:00 00:00:00", true);
+
+        // Act
+        let result = extractor.extract_date("IMG_20150130_000000.jpg", &tiff);
+
+        // Assert
+        assert_eq!(result.unwrap(), NaiveDate::from_ymd_opt(2015, 1, 30).unwrap());
+    }
+
+    /// A bare TIFF holding only DateTimeOriginal, laid out like DNG/NEF/CR2/ARW headers
+    fn tiff_with_date_time_original(value: &str, little_endian: bool) -> Vec<u8> {
+        let field = exif::Field {
+            tag: Tag::DateTimeOriginal,
+            ifd_num: In::PRIMARY,
+            value: exif::Value::Ascii(vec![value.as_bytes().to_vec()]),
+        };
+        let mut writer = exif::experimental::Writer::new();
+        writer.push_field(&field);
+        let mut tiff = std::io::Cursor::new(Vec::new());
+        writer.write(&mut tiff, little_endian).unwrap();
+        tiff.into_inner()
+    }
+
+    fn tiff_with_gps_date(date: &str, time: Option<[u32; 3]>) -> Vec<u8> {
+        let date_field = exif::Field {
+            tag: Tag::GPSDateStamp,
+            ifd_num: In::PRIMARY,
+            value: exif::Value::Ascii(vec![date.as_bytes().to_vec()]),
+        };
+        let time_field = time.map(|parts| exif::Field {
+            tag: Tag::GPSTimeStamp,
+            ifd_num: In::PRIMARY,
+            value: exif::Value::Rational(parts.iter().map(|&part| exif::Rational::from((part, 1))).collect()),
+        });
+        let mut writer = exif::experimental::Writer::new();
+        writer.push_field(&date_field);
+        if let Some(time_field) = &time_field {
+            writer.push_field(time_field);
+        }
+        let mut tiff = std::io::Cursor::new(Vec::new());
+        writer.write(&mut tiff, true).unwrap();
+        tiff.into_inner()
+    }
+
+    #[rstest]
+    #[case(true, NaiveDate::from_ymd_opt(2016, 6, 17).unwrap())]
+    #[case(false, NaiveDate::from_ymd_opt(2016, 6, 16).unwrap())]
+    fn test_gps_extractor_dates_in_time_zone_of_location(#[case] location_time_zones: bool, #[case] expected: NaiveDate) {
+        // Arrange
+        let extractor = GpsDateExtractor::new().with_location_time_zones(location_time_zones);
+        let context = EntryContext::default().with_time_zone(DateTimeZone::Named(chrono_tz::Europe::London));
+        // 2016-06-16 20:00 UTC in Tokyo (35° 40' 48" N, 139° 41' 24" E)
+        let rational = |parts: [u32; 3]| {
+            exif::Value::Rational(parts.iter().map(|&part| exif::Rational::from((part, 1))).collect())
+        };
+        let fields = [
+            (Tag::GPSDateStamp, exif::Value::Ascii(vec![b"2016:06:16".to_vec()])),
+            (Tag::GPSTimeStamp, rational([20, 0, 0])),
+            (Tag::GPSLatitudeRef, exif::Value::Ascii(vec![b"N".to_vec()])),
+            (Tag::GPSLatitude, rational([35, 40, 48])),
+            (Tag::GPSLongitudeRef, exif::Value::Ascii(vec![b"E".to_vec()])),
+            (Tag::GPSLongitude, rational([139, 41, 24])),
+        ]
+        .map(|(tag, value)| exif::Field {
+            tag,
+            ifd_num: In::PRIMARY,
+            value,
+        });
+        let mut writer = exif::experimental::Writer::new();
+        fields.iter().for_each(|field| writer.push_field(field));
+        let mut tiff = std::io::Cursor::new(Vec::new());
+        writer.write(&mut tiff, true).unwrap();
+
+        // Act
+        let date = extractor.extract_date_in_context("IMG_0001.JPG", &tiff.into_inner(), &context);
+
+        // Assert
+        assert_eq!(date.unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case(true, NaiveDate::from_ymd_opt(2015, 1, 1).unwrap())]
+    #[case(false, NaiveDate::from_ymd_opt(2014, 12, 31).unwrap())]
+    fn test_sidecar_extractor_dates_in_time_zone_of_location(
+        #[case] location_time_zones: bool,
+        #[case] expected: NaiveDate,
+    ) {
+        // Arrange
+        let extractor = JsonSidecarDateExtractor::new().with_location_time_zones(location_time_zones);
+        // 2014-12-31 23:30 UTC in Tokyo
+        let sidecar = Sidecar {
+            photo_taken_time: chrono::DateTime::from_timestamp(1_420_068_600, 0),
+            geo: Some(crate::sidecar::GeoPosition {
+                latitude: 35.68,
+                longitude: 139.69,
+                altitude: 0.0,
+            }),
+            ..Sidecar::default()
+        };
+        let context = EntryContext {
+            sidecar: Some(&sidecar),
+            ..EntryContext::default()
+        }
+        .with_time_zone(DateTimeZone::Named(chrono_tz::America::Los_Angeles));
+
+        // Act
+        let date = extractor.extract_date_in_context("IMG_0001.jpg", &[], &context);
+
+        // Assert
+        assert_eq!(date.unwrap(), expected);
+    }
+
+    fn tiff_with_offset_time_original(offset: &str) -> Vec<u8> {
+        let rational = |parts: [u32; 3]| {
+            exif::Value::Rational(parts.iter().map(|&part| exif::Rational::from((part, 1))).collect())
+        };
+        let fields = [
+            (Tag::OffsetTimeOriginal, exif::Value::Ascii(vec![offset.as_bytes().to_vec()])),
+            (Tag::GPSDateStamp, exif::Value::Ascii(vec![b"2014:12:31".to_vec()])),
+            (Tag::GPSTimeStamp, rational([23, 30, 0])),
+        ]
+        .map(|(tag, value)| exif::Field {
+            tag,
+            ifd_num: In::PRIMARY,
+            value,
+        });
+        let mut writer = exif::experimental::Writer::new();
+        fields.iter().for_each(|field| writer.push_field(field));
+        let mut tiff = std::io::Cursor::new(Vec::new());
+        writer.write(&mut tiff, true).unwrap();
+        tiff.into_inner()
+    }
+
+    #[rstest]
+    #[case("+09:00", Some(9 * 3600))]
+    #[case("-05:30", Some(-(5 * 3600 + 30 * 60)))]
+    #[case("+00:00", Some(0))]
+    #[case("   :  ", None)]
+    #[case("+25:00", None)]
+    #[case("09:00", None)]
+    fn test_capture_utc_offset(#[case] offset: &str, #[case] expected_seconds: Option<i32>) {
+        // Arrange
+        let tiff = tiff_with_offset_time_original(offset);
+        let exif_data = ParsedExif::new();
+
+        // Act
+        let result = capture_utc_offset(exif_data.get(&tiff).unwrap());
+
+        // Assert
+        assert_eq!(result.map(|offset| offset.local_minus_utc()), expected_seconds);
+    }
+
+    #[test]
+    fn test_sidecar_and_gps_times_are_dated_by_offset_time_original() {
+        // Arrange: 2014-12-31 23:30 UTC, taken in Tokyo by a camera set to +09:00
+        let tiff = tiff_with_offset_time_original("+09:00");
+        let sidecar = Sidecar {
+            photo_taken_time: chrono::DateTime::from_timestamp(1_420_068_600, 0),
+            ..Sidecar::default()
+        };
+        let context = EntryContext {
+            sidecar: Some(&sidecar),
+            ..EntryContext::default()
+        }
+        .with_time_zone(DateTimeZone::Named(chrono_tz::America::Los_Angeles));
+        let sidecar_extractor = JsonSidecarDateExtractor::new();
+        let gps_extractor = GpsDateExtractor::new();
+
+        // Act
+        let sidecar_date = sidecar_extractor.extract_date_in_context("IMG_0001.jpg", &tiff, &context);
+        let gps_date = gps_extractor.extract_date_in_context("IMG_0001.jpg", &tiff, &context);
+
+        // Assert
+        let tokyo_date = NaiveDate::from_ymd_opt(2015, 1, 1).unwrap();
+        assert_eq!(sidecar_date.unwrap(), tokyo_date);
+        assert_eq!(gps_date.unwrap(), tokyo_date);
+    }
+
+    #[test]
+    fn test_gps_extractor_reads_gps_date_and_time() {
+        // Arrange
+        let extractor = GpsDateExtractor::new();
+        let noon_utc = chrono::DateTime::from_timestamp(1_466_078_400, 0).unwrap();
+        let data = tiff_with_gps_date("2016:06:16", Some([12, 0, 0]));
+        let without_time = tiff_with_gps_date("2016:06:16", None);
+
+        // Act
+        let date = extractor.extract_date("GOPR0001.JPG", &data);
+        let date_without_time = extractor.extract_date("GOPR0001.JPG", &without_time);
+
+        // Assert
+        assert_eq!(date.unwrap(), noon_utc.with_timezone(&Local).date_naive());
+        assert_eq!(date_without_time.unwrap(), NaiveDate::from_ymd_opt(2016, 6, 16).unwrap());
+    }
+
+    #[rstest]
+    #[case("local", Some(DateTimeZone::Local))]
+    #[case("Local", Some(DateTimeZone::Local))]
+    #[case("Europe/Amsterdam", Some(DateTimeZone::Named(chrono_tz::Europe::Amsterdam)))]
+    #[case("Mars/Olympus_Mons", None)]
+    fn test_parse_date_time_zone(#[case] value: &str, #[case] expected: Option<DateTimeZone>) {
+        // Act & Assert
+        assert_eq!(value.parse::<DateTimeZone>().ok(), expected);
+    }
+
+    #[rstest]
+    #[case(chrono_tz::Europe::Amsterdam, 2015, 1, 1)]
+    #[case(chrono_tz::America::Los_Angeles, 2014, 12, 31)]
+    fn test_composite_extractor_dates_utc_times_in_time_zone(
+        #[case] zone: chrono_tz::Tz,
+        #[case] year: i32,
+        #[case] month: u32,
+        #[case] day: u32,
+    ) {
+        // Arrange
+        let extractor = CompositeDateExtractor::new().with_time_zone(DateTimeZone::Named(zone));
+        // 2014-12-31 23:30 UTC
+        let sidecar = Sidecar {
+            photo_taken_time: chrono::DateTime::from_timestamp(1_420_068_600, 0),
+            ..Sidecar::default()
+        };
+        let context = EntryContext {
+            sidecar: Some(&sidecar),
+            ..EntryContext::default()
+        };
+
+        // Act
+        let result = extractor.extract_date_in_context("IMG-WA0001.jpg", &[], &context);
+
+        // Assert
+        assert_eq!(result.unwrap(), NaiveDate::from_ymd_opt(year, month, day).unwrap());
+    }
+
+    #[test]
+    fn test_composite_extractor_falls_back_to_gps_date() {
+        // Arrange
+        let extractor = CompositeDateExtractor::new();
+        let data = tiff_with_gps_date("2016:06:16", None);
+
+        // Act
+        let result = extractor.extract_date("GOPR0001.JPG", &data);
+
+        // Assert
+        assert_eq!(result.unwrap(), NaiveDate::from_ymd_opt(2016, 6, 16).unwrap());
+    }
+
+    fn webp_with_exif_chunk(exif_chunk: &[u8], riff_size: Option<u32>) -> Vec<u8> {
+        let mut chunks = b"WEBP".to_vec();
+        for (chunk_id, content) in [(&b"VP8X"[..], &[0u8; 10][..]), (b"VP8L", &[0x2f, 0, 0, 0, 0]), (b"EXIF", exif_chunk)] {
+            chunks.extend_from_slice(chunk_id);
+            chunks.extend_from_slice(&(content.len() as u32).to_le_bytes());
+            chunks.extend_from_slice(content);
+            if content.len() % 2 == 1 {
+                chunks.push(0);
+            }
+        }
+        let mut webp = b"RIFF".to_vec();
+        webp.extend_from_slice(&riff_size.unwrap_or(chunks.len() as u32).to_le_bytes());
+        webp.extend(chunks);
+        webp
+    }
+
+    #[rstest]
+    #[case(false, None)]
+    #[case(true, None)]
+    #[case(false, Some(8))]
+    #[case(true, Some(u32::MAX))]
+    fn test_extract_date_from_webp(#[case] exif_header: bool, #[case] riff_size: Option<u32>) {
+        // Arrange
+        let extractor = ExifDateExtractor::new();
+        let mut exif_chunk = if exif_header { EXIF_HEADER.to_vec() } else { Vec::new() };
+        exif_chunk.extend(tiff_with_date_time_original("2019:07:14 10:30:20", true));
+        let webp = webp_with_exif_chunk(&exif_chunk, riff_size);
+
+        // Act
+        let date = extractor.extract_date("IMG_0001.webp", &webp);
+
+        // Assert
+        assert_eq!(date.unwrap(), NaiveDate::from_ymd_opt(2019, 7, 14).unwrap());
+    }
+
+    #[test]
+    fn test_extract_date_from_heic() {
+        // Arrange
+        let extractor = ExifDateExtractor::new();
+        let heic = include_bytes!("../tests/fixtures/heic_with_exif.heic");
+
+        // Act
+        let date = extractor.extract_date("IMG_0001.HEIC", heic);
+
+        // Assert
+        assert_eq!(date.unwrap(), NaiveDate::from_ymd_opt(2021, 6, 5).unwrap());
+    }
+
+    #[test]
+    fn test_extract_date_from_webp_without_exif_returns_error() {
+        // Arrange
+        let webp = b"RIFF\x0e\0\0\0WEBPVP8L\x05\0\0\0\x2f\0\0\0\0\0";
+
+        // Act
+        let result = ExifDateExtractor::new().extract_date("IMG_0001.webp", webp);
+
+        // Assert
+        assert!(result.is_err());
+    }
+
+    #[rstest]
+    #[case(true)]
+    #[case(false)]
+    fn test_extract_date_from_tiff_based_raw(#[case] little_endian: bool) {
+        // Arrange
+        let extractor = ExifDateExtractor::new();
+        let raw = tiff_with_date_time_original("2021:03:04 05:06:07", little_endian);
+
+        // Act
+        let result = extractor.extract_date("DSC_0001.NEF", &raw);
+
+        // Assert
+        assert_eq!(result.unwrap(), NaiveDate::from_ymd_opt(2021, 3, 4).unwrap());
+    }
+
+    /// A RAW-style TIFF: IFD0 with a DateTime and, optionally, an Exif IFD with the
+    /// DateTimeOriginal; the next IFD (the full-size image) lies beyond the data
+    fn raw_head(magic: &[u8; 4], date_time: &str, date_time_original: Option<&str>) -> Vec<u8> {
+        let entry_count: u16 = if date_time_original.is_some() { 2 } else { 1 };
+        let ifd0_end = 8 + 2 + 12 * entry_count as u32 + 4;
+        let exif_ifd = ifd0_end;
+        let exif_ifd_end = exif_ifd + 2 + 12 + 4;
+        let date_time_at = exif_ifd_end;
+        let original_at = date_time_at + 20;
+
+        let entry = |tag: u16, field_type: u16, count: u32, value: u32| {
+            let mut entry = tag.to_le_bytes().to_vec();
+            entry.extend_from_slice(&field_type.to_le_bytes());
+            entry.extend_from_slice(&count.to_le_bytes());
+            entry.extend_from_slice(&value.to_le_bytes());
+            entry
+        };
+        let mut raw = magic.to_vec();
+        raw.extend_from_slice(&8u32.to_le_bytes());
+        raw.extend_from_slice(&entry_count.to_le_bytes());
+        raw.extend(entry(TAG_DATE_TIME, TYPE_ASCII, 20, date_time_at));
+        if date_time_original.is_some() {
+            raw.extend(entry(TAG_EXIF_IFD_POINTER, 4, 1, exif_ifd));
+        }
+        raw.extend_from_slice(&50_000_000u32.to_le_bytes());
+        raw.extend_from_slice(&1u16.to_le_bytes());
+        raw.extend(entry(TAG_DATE_TIME_ORIGINAL, TYPE_ASCII, 20, original_at));
+        raw.extend_from_slice(&0u32.to_le_bytes());
+        raw.extend_from_slice(date_time.as_bytes());
+        raw.push(0);
+        raw.extend_from_slice(date_time_original.unwrap_or("0000:00:00 00:00:00").as_bytes());
+        raw.push(0);
+        raw
+    }
+
+    #[rstest]
+    #[case(b"II*\0", "DSC_0001.NEF", Some("2021:03:04 05:06:07"))]
+    #[case(b"IIU\0", "P1000001.RW2", Some("2021:03:04 05:06:07"))]
+    #[case(b"II*\0", "IMG_0001.CR2", None)]
+    fn test_extract_date_from_cut_off_raw(
+        #[case] magic: &[u8; 4],
+        #[case] filename: &str,
+        #[case] date_time_original: Option<&str>,
+    ) {
+        // Arrange
+        let extractor = ExifDateExtractor::new();
+        let raw = raw_head(magic, "2021:03:04 23:59:59", date_time_original);
+
+        // Act
+        let result = extractor.extract_date(filename, &raw);
+
+        // Assert
+        assert_eq!(result.unwrap(), NaiveDate::from_ymd_opt(2021, 3, 4).unwrap());
+    }
+
+    #[rstest]
+    #[case(true, b"IIRO")]
+    #[case(false, b"MMOR")]
+    fn test_extract_date_from_orf(#[case] little_endian: bool, #[case] magic: &[u8; 4]) {
+        // Arrange
+        let extractor = ExifDateExtractor::new();
+        let mut orf = tiff_with_date_time_original("2019:08:09 10:11:12", little_endian);
+        orf[..4].copy_from_slice(magic);
+
+        // Act
+        let result = extractor.extract_date("P1010001.ORF", &orf);
+
+        // Assert
+        assert_eq!(result.unwrap(), NaiveDate::from_ymd_opt(2019, 8, 9).unwrap());
+    }
+
+    #[test]
+    fn test_extract_date_from_raf_preview() {
+        // Arrange
+        let extractor = ExifDateExtractor::new();
+        let jpeg: &[u8] = include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg");
+        let mut raf = b"FUJIFILMCCD-RAW 0201FF383501".to_vec();
+        raf.resize(RAF_JPEG_OFFSET_POSITION, 0);
+        let jpeg_offset = RAF_JPEG_OFFSET_POSITION as u32 + 16;
+        raf.extend_from_slice(&jpeg_offset.to_be_bytes());
+        raf.extend_from_slice(&(jpeg.len() as u32).to_be_bytes());
+        raf.resize(jpeg_offset as usize, 0);
+        raf.extend_from_slice(jpeg);
+        raf.extend_from_slice(&[0u8; 64]); // CFA data follows the preview
+
+        // Act
+        let result = extractor.extract_date("DSCF0001.RAF", &raf);
+
+        // Assert
+        assert_eq!(result.unwrap(), NaiveDate::from_ymd_opt(2012, 10, 6).unwrap());
+    }
+
+    #[test]
+    fn test_extract_date_from_truncated_raf_returns_error() {
+        // Arrange
+        let extractor = ExifDateExtractor::new();
+
+        // Act
+        let result = extractor.extract_date("DSCF0001.RAF", b"FUJIFILMCCD-RAW 0201");
+
+        // Assert
+        assert!(result.is_err());
+    }
+
+    fn jxl_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut jxl_box = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+        jxl_box.extend_from_slice(box_type);
+        jxl_box.extend_from_slice(body);
+        jxl_box
+    }
+
+    #[test]
+    fn test_extract_date_from_jxl_container() {
+        // Arrange
+        let extractor = ExifDateExtractor::new();
+        let mut exif_body = 0u32.to_be_bytes().to_vec();
+        exif_body.extend(tiff_with_date_time_original("2023:05:06 07:08:09", true));
+        let mut jxl = JXL_CONTAINER_SIGNATURE.to_vec();
+        jxl.extend(jxl_box(b"ftyp", b"jxl \0\0\0\0jxl "));
+        jxl.extend(jxl_box(b"Exif", &exif_body));
+        jxl.extend(jxl_box(b"jxlc", &[0xFF, 0x0A, 0, 0]));
+
+        // Act
+        let result = extractor.extract_date("photo.jxl", &jxl);
+
+        // Assert
+        assert_eq!(result.unwrap(), NaiveDate::from_ymd_opt(2023, 5, 6).unwrap());
+    }
+
+    #[test]
+    fn test_extract_date_from_jxl_without_exif_box_returns_error() {
+        // Arrange
+        let extractor = ExifDateExtractor::new();
+        let mut jxl = JXL_CONTAINER_SIGNATURE.to_vec();
+        jxl.extend(jxl_box(b"ftyp", b"jxl \0\0\0\0jxl "));
+        jxl.extend(jxl_box(b"jxlc", &[0xFF, 0x0A, 0, 0]));
+
+        // Act
+        let result = extractor.extract_date("photo.jxl", &jxl);
+
+        // Assert
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_extract_date_from_mpo_first_image() {
+        // Arrange
+        let extractor = ExifDateExtractor::new();
+        let jpeg: &[u8] = include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg");
+        // An MPO is a sequence of JPEGs; the first one carries the EXIF
+        let mpo = [jpeg, jpeg].concat();
+
+        // Act
+        let result = extractor.extract_date("DSCF0001.MPO", &mpo);
+
+        // Assert
+        assert_eq!(result.unwrap(), NaiveDate::from_ymd_opt(2012, 10, 6).unwrap());
+    }
+
+    fn tiff_taken_by(model: &str, date_time_original: &str) -> Vec<u8> {
+        let fields = [
+            (Tag::Model, model),
+            (Tag::DateTimeOriginal, date_time_original),
+        ]
+        .map(|(tag, value)| exif::Field {
+            tag,
+            ifd_num: In::PRIMARY,
+            value: exif::Value::Ascii(vec![value.as_bytes().to_vec()]),
+        });
+        let mut writer = exif::experimental::Writer::new();
+        fields.iter().for_each(|field| writer.push_field(field));
+        let mut tiff = std::io::Cursor::new(Vec::new());
+        writer.write(&mut tiff, false).unwrap();
+        tiff.into_inner()
+    }
+
+    fn tiff_with_sub_seconds(date_time_original: &str, sub_seconds: &str) -> Vec<u8> {
+        let fields = [
+            (Tag::DateTimeOriginal, date_time_original),
+            (Tag::SubSecTimeOriginal, sub_seconds),
+        ]
+        .map(|(tag, value)| exif::Field {
+            tag,
+            ifd_num: In::PRIMARY,
+            value: exif::Value::Ascii(vec![value.as_bytes().to_vec()]),
+        });
+        let mut writer = exif::experimental::Writer::new();
+        fields.iter().for_each(|field| writer.push_field(field));
+        let mut tiff = std::io::Cursor::new(Vec::new());
+        writer.write(&mut tiff, false).unwrap();
+        tiff.into_inner()
+    }
+
+    #[rstest]
+    #[case("2012:10:06 13:09:32", "12", 120_000_000)]
+    #[case("2012:10:06 13:09:32", "007", 7_000_000)]
+    #[case("2012:10:06 13:09:32", "5 ", 500_000_000)]
+    #[case("2012:10:06 13:09:32", "1234567891", 123_456_789)]
+    #[case("2012:10:06 13:09:32", "  ", 0)]
+    #[case("2012:10:06 13:09:32", "abc", 0)]
+    #[case("2012:10:06", "12", 0)]
+    fn test_exif_extractor_reads_sub_second_time(
+        #[case] date_time_original: &str,
+        #[case] sub_seconds: &str,
+        #[case] expected_nanos: u32,
+    ) {
+        // Arrange
+        let tiff = tiff_with_sub_seconds(date_time_original, sub_seconds);
+
+        // Act
+        let time = ExifDateExtractor::new().extract_date_time("burst.jpg", &tiff);
+
+        // Assert
+        assert_eq!(time.unwrap().nanosecond(), expected_nanos);
+    }
+
+    #[test]
+    fn test_sub_second_time_orders_photos_taken_within_one_second() {
+        // Arrange
+        let extractor = ExifDateExtractor::new();
+        let later = tiff_with_sub_seconds("2012:10:06 13:09:32", "80");
+        let earlier = tiff_with_sub_seconds("2012:10:06 13:09:32", "25");
+
+        // Act
+        let later = extractor.extract_date_time("IMG_0002.jpg", &later).unwrap();
+        let earlier = extractor.extract_date_time("IMG_0001.jpg", &earlier).unwrap();
+
+        // Assert
+        assert!(earlier < later);
+        assert_eq!(later.date(), NaiveDate::from_ymd_opt(2012, 10, 6).unwrap());
+    }
+
+    #[rstest]
+    #[case("NIKON D90=-3720s", "NIKON D90", NaiveDate::from_ymd_opt(2012, 10, 5).unwrap())]
+    #[case("nikon d90=-3720s", "NIKON D90", NaiveDate::from_ymd_opt(2012, 10, 5).unwrap())]
+    #[case("NIKON D90=-3720s", "Pixel 5", NaiveDate::from_ymd_opt(2012, 10, 6).unwrap())]
+    #[case("-1h02m", "Pixel 5", NaiveDate::from_ymd_opt(2012, 10, 5).unwrap())]
+    #[case("+30m", "Pixel 5", NaiveDate::from_ymd_opt(2012, 10, 6).unwrap())]
+    #[case("+23h30m", "Pixel 5", NaiveDate::from_ymd_opt(2012, 10, 7).unwrap())]
+    fn test_exif_extractor_applies_clock_offset(
+        #[case] clock_offset: &str,
+        #[case] model: &str,
+        #[case] expected: NaiveDate,
+    ) {
+        // Arrange
+        let extractor = ExifDateExtractor::new().with_clock_offsets(vec![ClockOffset::parse(clock_offset).unwrap()]);
+        let tiff = tiff_taken_by(model, "2012:10:06 00:30:00");
+
+        // Act
+        let result = extractor.extract_date("DSC_0001.JPG", &tiff);
+
+        // Assert
+        assert_eq!(result.unwrap(), expected);
+    }
+
+    #[test]
+    fn test_exif_extractor_prefers_camera_clock_offset_over_general_one() {
+        // Arrange
+        let extractor = ExifDateExtractor::new().with_clock_offsets(vec![
+            ClockOffset::parse("-1h").unwrap(),
+            ClockOffset::parse("NIKON D90=+1h").unwrap(),
+        ]);
+        let tiff = tiff_taken_by("NIKON D90", "2012:10:06 23:30:00");
+
+        // Act
+        let result = extractor.extract_date("DSC_0001.JPG", &tiff);
+
+        // Assert
+        assert_eq!(result.unwrap(), NaiveDate::from_ymd_opt(2012, 10, 7).unwrap());
+    }
+
+    #[rstest]
+    #[case("NIKON D90=")]
+    #[case("-1 hour")]
+    #[case("+")]
+    #[case("1m2h")]
+    #[case("9999999999999999s")]
+    #[case("99999999999999999999h")]
+    #[case("3000000000000000h")]
+    fn test_parse_clock_offset_rejects_invalid_values(#[case] value: &str) {
+        // Act & Assert
+        assert!(ClockOffset::parse(value).is_err());
+    }
+
+    #[test]
+    fn test_extract_date_missing_exif_returns_error() {
+        // Arrange
+        let extractor = ExifDateExtractor::new();
+        let invalid_data: &[u8] = &[0, 1, 2, 3]; // Not a valid image
+
+        // Act
+        let result = extractor.extract_date("photo.jpg", invalid_data);
+
+        // Assert
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_extract_date_empty_data_returns_error() {
+        // Arrange
+        let extractor = ExifDateExtractor::new();
+        let empty_data: &[u8] = &[];
+
+        // Act
+        let result = extractor.extract_date("photo.jpg", empty_data);
+
+        // Assert
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_filename_extractor_screenshot_pattern() {
+        // Arrange
+        let extractor = FilenameBasedDateExtractor::new();
+        let filename = "Screenshot_2013-04-19-19-46-43.png";
+
+        // Act
+        let result = extractor.extract_date(filename, &[]);
+
+        // Assert
+        assert!(result.is_ok(), "Failed to extract date: {:?}", result.err());
+        let date = result.unwrap();
+        assert_eq!(date, NaiveDate::from_ymd_opt(2013, 4, 19).unwrap());
+    }
+
+    #[test]
+    fn test_filename_extractor_animation_pattern() {
+        // Arrange
+        let extractor = FilenameBasedDateExtractor::new();
+        let filename = "20151115_143914-ANIMATION.gif";
+
+        // Act
+        let result = extractor.extract_date(filename, &[]);
+
+        // Assert
+        assert!(result.is_ok(), "Failed to extract date: {:?}", result.err());
+        let date = result.unwrap();
+        assert_eq!(date, NaiveDate::from_ymd_opt(2015, 11, 15).unwrap());
+    }
+
+    #[test]
+    fn test_filename_extractor_img_underscore_pattern() {
+        // Arrange
+        let extractor = FilenameBasedDateExtractor::new();
+        let filename = "IMG_20130106_160818.JPG";
+
+        // Act
+        let result = extractor.extract_date(filename, &[]);
+
+        // Assert
+        assert!(result.is_ok(), "Failed to extract date: {:?}", result.err());
+        let date = result.unwrap();
+        assert_eq!(date, NaiveDate::from_ymd_opt(2013, 1, 6).unwrap());
+    }
+
+    #[test]
+    fn test_filename_extractor_img_dash_pattern() {
+        // Arrange
+        let extractor = FilenameBasedDateExtractor::new();
+        let filename = "IMG-20150130-WA0001.jpg";
+
+        // Act
+        let result = extractor.extract_date(filename, &[]);
+
+        // Assert
+        assert!(result.is_ok(), "Failed to extract date: {:?}", result.err());
+        let date = result.unwrap();
+        assert_eq!(date, NaiveDate::from_ymd_opt(2015, 1, 30).unwrap());
+    }
+
+    #[rstest]
+    #[case("PXL_20230115_093012345.jpg")]
+    #[case("PXL_20230115_093012345.MP.jpg")]
+    #[case("VID-20230115-WA0001.mp4")]
+    #[case("20230115-093012.jpg")]
+    #[case("20230115-093012(1).mp4")]
+    fn test_filename_extractor_phone_patterns(#[case] filename: &str) {
+        // Arrange
+        let extractor = FilenameBasedDateExtractor::new();
+
+        // Act
+        let result = extractor.extract_date(filename, &[]);
+
+        // Assert
+        assert_eq!(result.unwrap(), NaiveDate::from_ymd_opt(2023, 1, 15).unwrap());
+    }
+
+    #[rstest]
+    #[case("Captura de pantalla 2016-07-01 a las 10.21.33.png")]
+    #[case("Bildschirmfoto 2016-07-01 um 18.00.01.png")]
+    #[case("Capture d’écran 2016-07-01 à 10.21.33.png")]
+    #[case("スクリーンショット 2016-07-01 10.21.33.png")]
+    #[case("Schermafbeelding 2016.07.01 om 10.21.33.png")]
+    #[case("Screenshot 2016 07 01 at 10 21 33.png")]
+    #[case("Zrzut ekranu_2016_07_01.png")]
+    fn test_filename_extractor_localized_screenshot_patterns(#[case] filename: &str) {
+        // Arrange
+        let extractor = FilenameBasedDateExtractor::new();
+
+        // Act
+        let result = extractor.extract_date(filename, &[]);
+
+        // Assert
+        assert_eq!(result.unwrap(), NaiveDate::from_ymd_opt(2016, 7, 1).unwrap());
+    }
+
+    #[rstest]
+    #[case("Version 2016.07_01.png")]
+    #[case("Scan 12016.07.01.png")]
+    #[case("Photo 2016.13.01.png")]
+    fn test_filename_extractor_ignores_mixed_or_invalid_separated_dates(#[case] filename: &str) {
+        // Act & Assert
+        assert!(FilenameBasedDateExtractor::new().extract_date(filename, &[]).is_err());
+    }
+
+    #[rstest]
+    #[case("00000IMG_00000_BURST20180101123456_COVER.jpg")]
+    #[case("Photos from 2018/00001IMG_00001_BURST20180101123456.jpg")]
+    #[case("00012PXL_00012_BURST20180101123456.jpg")]
+    fn test_filename_extractor_burst_pattern(#[case] filename: &str) {
+        // Arrange
+        let extractor = FilenameBasedDateExtractor::new();
+
+        // Act
+        let result = extractor.extract_date(filename, &[]);
+
+        // Assert
+        assert_eq!(result.unwrap(), NaiveDate::from_ymd_opt(2018, 1, 1).unwrap());
+    }
+
+    #[rstest]
+    #[case("PXL_20230115_093012345.jpg", NaiveTime::from_hms_opt(9, 30, 12).unwrap())]
+    #[case("IMG_20230115_093012.jpg", NaiveTime::from_hms_opt(9, 30, 12).unwrap())]
+    #[case("20230115-093012.jpg", NaiveTime::from_hms_opt(9, 30, 12).unwrap())]
+    #[case("00000IMG_00000_BURST20230115093012_COVER.jpg", NaiveTime::from_hms_opt(9, 30, 12).unwrap())]
+    #[case("2023-01-15 party.jpg", NaiveTime::from_hms_opt(0, 0, 0).unwrap())]
+    #[case("IMG-20230115-WA0001.jpg", NaiveTime::from_hms_opt(0, 0, 0).unwrap())]
+    fn test_filename_extractor_time_of_day(#[case] filename: &str, #[case] expected: NaiveTime) {
+        // Arrange
+        let extractor = FilenameBasedDateExtractor::new();
+
+        // Act
+        let result = extractor.extract_date_time(filename, &[]);
+
+        // Assert
+        assert_eq!(
+            result.unwrap(),
+            NaiveDate::from_ymd_opt(2023, 1, 15).unwrap().and_time(expected)
+        );
+    }
+
+    #[rstest]
+    #[case("Takeout/Google Photos/Photos from 2012/IMG_0001.jpg", 2012)]
+    // 2001-01-01 is a dead-clock date, but the year of the folder is real
+    #[case("Takeout/Google Photos/Photos from 2001/IMG_0001.jpg", 2001)]
+    fn test_composite_extractor_dates_takeout_year_folder_before_mtime(#[case] filename: &str, #[case] year: i32) {
+        // Arrange
+        let extractor = CompositeDateExtractor::new();
+        let metadata = EntryMetadata {
+            modified: NaiveDate::from_ymd_opt(2019, 5, 1).unwrap().and_hms_opt(10, 0, 0),
+            ..EntryMetadata::default()
+        };
+        let context = EntryContext {
+            metadata: Some(&metadata),
+            ..EntryContext::default()
+        };
+
+        // Act
+        let extracted = extractor.extract_with_confidence_in_context(filename, &[0xFF, 0xD8, 0xFF, 0xD9], &context);
+
+        // Assert
+        let extracted = extracted.unwrap();
+        assert_eq!(extracted.time.date(), NaiveDate::from_ymd_opt(year, 1, 1).unwrap());
+        assert_eq!(extracted.confidence, Confidence::Year);
+    }
+
+    #[test]
+    fn test_composite_extractor_dates_burst_frames_at_the_filename_position() {
+        // Arrange
+        let frame_with_exif: &[u8] = include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg");
+        let exif_first = CompositeDateExtractor::new();
+        let filename_first = CompositeDateExtractor::new().with_sources(&[DateSource::Filename, DateSource::Exif]);
+
+        // Act
+        let by_exif = exif_first.extract_date("00001IMG_00001_BURST20180101123456.jpg", frame_with_exif);
+        let by_burst = filename_first.extract_date("00001IMG_00001_BURST20180101123456.jpg", frame_with_exif);
+
+        // Assert
+        assert_eq!(by_exif.unwrap(), NaiveDate::from_ymd_opt(2012, 10, 6).unwrap());
+        assert_eq!(by_burst.unwrap(), NaiveDate::from_ymd_opt(2018, 1, 1).unwrap());
+    }
+
+    #[rstest]
+    #[case("photo_123@15-01-2023_12-30-45.jpg", "2023-01-15 12:30:45")]
+    #[case("ChatExport_2023-02-01/photos/photo_7@31-12-2022_23-59-59_thumb.jpg", "2022-12-31 23:59:59")]
+    #[case("ChatExport_2023-02-01/video_files/video_2@01-02-2023_08-05-00.mp4", "2023-02-01 08:05:00")]
+    #[case("round_video_messages/file_5@09-06-2021_18-00-01.mp4", "2021-06-09 18:00:01")]
+    fn test_filename_extractor_telegram_export_pattern(#[case] filename: &str, #[case] expected: &str) {
+        // Act
+        let result = FilenameBasedDateExtractor::new().extract_date_time(filename, &[]);
+
+        // Assert
+        assert_eq!(
+            result.unwrap(),
+            NaiveDateTime::parse_from_str(expected, "%Y-%m-%d %H:%M:%S").unwrap()
+        );
+    }
+
+    #[rstest]
+    #[case("photo_123@32-01-2023_12-30-45.jpg")]
+    #[case("photo_123@15-13-2023_12-30-45.jpg")]
+    fn test_filename_extractor_ignores_invalid_telegram_dates(#[case] filename: &str) {
+        // Act & Assert
+        assert!(FilenameBasedDateExtractor::new().extract_date(filename, &[]).is_err());
+    }
+
+    #[test]
+    fn test_composite_extractor_dates_snapchat_memory_by_its_history_record() {
+        // Arrange
+        let extractor = CompositeDateExtractor::new().with_time_zone(DateTimeZone::Named(chrono_tz::Europe::Amsterdam));
+        let memory = SnapchatMemory {
+            date_time: DateTime::parse_from_rfc3339("2019-07-04T22:30:00Z").unwrap().to_utc(),
+        };
+        let context = EntryContext {
+            snapchat: Some(&memory),
+            ..EntryContext::default()
+        };
+        let no_exif: &[u8] = &[0xFF, 0xD8, 0xFF, 0xD9];
+
+        // Act
+        let with_history = extractor.extract_with_confidence_in_context("memories/2019-07-04_ab12-main.jpg", no_exif, &context);
+        let name_only = extractor.extract_date_time("memories/2019-07-04_ab12-main.jpg", no_exif);
+
+        // Assert
+        let with_history = with_history.unwrap();
+        assert_eq!(
+            with_history.time,
+            NaiveDate::from_ymd_opt(2019, 7, 5).unwrap().and_hms_opt(0, 30, 0).unwrap()
+        );
+        assert_eq!(with_history.confidence, Confidence::Sidecar);
+        assert_eq!(name_only.unwrap(), start_of_day(NaiveDate::from_ymd_opt(2019, 7, 4).unwrap()));
+    }
+
+    #[test]
+    fn test_filename_extractor_simple_date_pattern() {
+        // Arrange
+        let extractor = FilenameBasedDateExtractor::new();
+        let filename = "2014-09-29.jpg";
+
+        // Act
+        let result = extractor.extract_date(filename, &[]);
+
+        // Assert
+        assert!(result.is_ok(), "Failed to extract date: {:?}", result.err());
+        let date = result.unwrap();
+        assert_eq!(date, NaiveDate::from_ymd_opt(2014, 9, 29).unwrap());
+    }
+
+    #[rstest]
+    #[case("1436824799639.jpg", 1_436_824_799)]
+    #[case("photo_1436824799.jpg", 1_436_824_799)]
+    #[case("IMG_1436824799639_edit.jpg", 1_436_824_799)]
+    fn test_filename_extractor_epoch_pattern(#[case] filename: &str, #[case] seconds: i64) {
+        // Arrange
+        let extractor = FilenameBasedDateExtractor::new();
+        let expected = chrono::DateTime::from_timestamp(seconds, 0).unwrap().with_timezone(&Local).date_naive();
+
+        // Act
+        let result = extractor.extract_date(filename, &[]);
 
-impl ExifDateExtractor {
-    pub fn new() -> Self {
-        Self
+        // Assert
+        assert_eq!(result.unwrap(), expected);
     }
 
-    fn read_exif_from_image(image_data: &[u8]) -> Result<exif::Exif> {
-        let mut cursor = std::io::Cursor::new(image_data);
-        let exif_reader = exif::Reader::new();
-        exif_reader
-            .read_from_container(&mut cursor)
-            .context("Failed to read EXIF data from image")
+    #[rstest]
+    #[case("0612345678.jpg")]
+    #[case("9999999999999.jpg")]
+    #[case("12345678901234.jpg")]
+    #[case("DSC_123456789.jpg")]
+    fn test_filename_extractor_ignores_implausible_epoch(#[case] filename: &str) {
+        // Act & Assert
+        assert!(FilenameBasedDateExtractor::new().extract_date(filename, &[]).is_err());
     }
 
-    fn get_datetime_original_field(exif_data: &exif::Exif) -> Result<&exif::Field> {
-        exif_data
-            .get_field(Tag::DateTimeOriginal, In::PRIMARY)
-            .context("No DateTimeOriginal field found in EXIF data")
+    #[test]
+    fn test_filename_extractor_no_pattern_returns_error() {
+        // Arrange
+        let extractor = FilenameBasedDateExtractor::new();
+        let filename = "random_file.jpg";
+
+        // Act
+        let result = extractor.extract_date(filename, &[]);
+
+        // Assert
+        assert!(result.is_err());
     }
 
-    fn parse_exif_date_string(exif_date_string: &str) -> Result<NaiveDate> {
-        let date_part = exif_date_string
-            .split_whitespace()
-            .next()
-            .context("Invalid EXIF date format")?;
+    #[rstest]
+    #[case(r"^P(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})-", "P20120506-001.jpg", Some((2012, 5, 6)))]
+    #[case(r"^(?<day>\d{2})\.(?<month>\d{2})\.(?<year>\d{4})", "06.05.2012 party.jpg", Some((2012, 5, 6)))]
+    #[case(r"^Scan (?<year>\d{4})", "Scan 1987 holiday.jpg", Some((1987, 1, 1)))]
+    #[case(r"^P(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})-", "P20121306-001.jpg", None)]
+    #[case(r"^P(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})-", "random_file.jpg", None)]
+    fn test_filename_extractor_custom_pattern(
+        #[case] pattern: &str,
+        #[case] filename: &str,
+        #[case] expected: Option<(i32, u32, u32)>,
+    ) {
+        // Arrange
+        let extractor = FilenameBasedDateExtractor::new().with_patterns(vec![FilenamePattern::parse(pattern).unwrap()]);
 
-        let normalized_date = date_part.replace(':', "-");
+        // Act
+        let result = extractor.extract_date(filename, &[]);
 
-        NaiveDate::parse_from_str(&normalized_date, "%Y-%m-%d")
-            .context("Failed to parse date from EXIF")
+        // Assert
+        assert_eq!(
+            result.ok(),
+            expected.map(|(year, month, day)| NaiveDate::from_ymd_opt(year, month, day).unwrap())
+        );
     }
-}
 
-impl DateExtractor for ExifDateExtractor {
-    fn extract_date(&self, _filename: &str, image_data: &[u8]) -> Result<NaiveDate> {
-        let exif_data = Self::read_exif_from_image(image_data)?;
-        let datetime_original_field = Self::get_datetime_original_field(&exif_data)?;
-        let date_string = datetime_original_field.display_value().to_string();
-        Self::parse_exif_date_string(&date_string)
+    #[test]
+    fn test_filename_extractor_custom_pattern_with_time() {
+        // Arrange
+        let pattern =
+            FilenamePattern::parse(r"^P(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})_(?<hour>\d{2})(?<minute>\d{2})").unwrap();
+        let extractor = FilenameBasedDateExtractor::new().with_patterns(vec![pattern]);
+
+        // Act
+        let result = extractor.extract_date_time("P20120506_2215.jpg", &[]);
+
+        // Assert
+        assert_eq!(result.unwrap(), NaiveDate::from_ymd_opt(2012, 5, 6).unwrap().and_hms_opt(22, 15, 0).unwrap());
     }
-}
 
-/// Extracts dates from filename patterns
-pub struct FilenameBasedDateExtractor;
+    #[test]
+    fn test_filename_extractor_custom_pattern_wins_over_built_in() {
+        // Arrange
+        let pattern = FilenamePattern::parse(r"^(?<day>\d{2})-(?<month>\d{2})-(?<year>\d{4})").unwrap();
+        let extractor = FilenameBasedDateExtractor::new().with_patterns(vec![pattern]);
 
-impl FilenameBasedDateExtractor {
-    pub fn new() -> Self {
-        Self
+        // Act
+        let result = extractor.extract_date("06-05-2012_20130101_120000.jpg", &[]);
+
+        // Assert
+        assert_eq!(result.unwrap(), NaiveDate::from_ymd_opt(2012, 5, 6).unwrap());
     }
 
-    fn try_parse_patterns(filename: &str) -> Option<NaiveDate> {
-        Self::try_parse_date_with_dashes(filename)
-            .or_else(|| Self::try_parse_compact_datetime_pattern(filename))
-            .or_else(|| Self::try_parse_img_underscore_pattern(filename))
-            .or_else(|| Self::try_parse_img_dash_pattern(filename))
+    #[rstest]
+    #[case(r"^P(\d{4})(\d{2})(\d{2})")]
+    #[case(r"^P(?<year>\d{4})(?<day>\d{2})")]
+    #[case(r"^P(?<year>\d{4}")]
+    fn test_filename_pattern_rejects_invalid_patterns(#[case] pattern: &str) {
+        // Act & Assert
+        assert!(FilenamePattern::parse(pattern).is_err());
     }
 
-    fn try_parse_date_with_dashes(filename: &str) -> Option<NaiveDate> {
-        let pattern = regex::Regex::new(r"(\d{4})-(\d{2})-(\d{2})").ok()?;
-        let captures = pattern.captures(filename)?;
+    #[test]
+    fn test_composite_extractor_uses_exif_first() {
+        // Arrange
+        let extractor = CompositeDateExtractor::new();
+        let sample_image_data: &[u8] = include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg");
+        // Filename suggests 2015, but EXIF says 2012 - EXIF should win
+        let filename = "IMG_20150130_000000.jpg";
 
-        let year: i32 = captures.get(1)?.as_str().parse().ok()?;
-        let month: u32 = captures.get(2)?.as_str().parse().ok()?;
-        let day: u32 = captures.get(3)?.as_str().parse().ok()?;
+        // Act
+        let result = extractor.extract_date(filename, sample_image_data);
 
-        NaiveDate::from_ymd_opt(year, month, day)
+        // Assert
+        assert!(result.is_ok());
+        let date = result.unwrap();
+        assert_eq!(date, NaiveDate::from_ymd_opt(2012, 10, 6).unwrap(), "Should use EXIF date, not filename");
     }
 
-    fn try_parse_compact_datetime_pattern(filename: &str) -> Option<NaiveDate> {
-        let pattern = regex::Regex::new(r"(\d{8})_\d{6}").ok()?;
-        let captures = pattern.captures(filename)?;
-        let date_str = captures.get(1)?.as_str();
-        NaiveDate::parse_from_str(date_str, "%Y%m%d").ok()
-    }
+    #[rstest]
+    #[case(NaiveDate::from_ymd_opt(1970, 1, 1).unwrap(), "camera clock reset")]
+    #[case(NaiveDate::from_ymd_opt(2001, 1, 1).unwrap(), "camera clock reset")]
+    #[case(NaiveDate::from_ymd_opt(1899, 12, 31).unwrap(), "before 1900-01-01")]
+    #[case(NaiveDate::from_ymd_opt(2999, 6, 1).unwrap(), "in the future")]
+    fn test_date_plausibility_rejects_bogus_dates(#[case] date: NaiveDate, #[case] reason: &str) {
+        // Act
+        let result = DatePlausibility::new().check(start_of_day(date));
 
-    fn try_parse_img_underscore_pattern(filename: &str) -> Option<NaiveDate> {
-        let pattern = regex::Regex::new(r"IMG_(\d{8})_\d{6}").ok()?;
-        let captures = pattern.captures(filename)?;
-        let date_str = captures.get(1)?.as_str();
-        NaiveDate::parse_from_str(date_str, "%Y%m%d").ok()
+        // Assert
+        let message = result.unwrap_err().to_string();
+        assert!(message.contains(reason), "{}", message);
     }
 
-    fn try_parse_img_dash_pattern(filename: &str) -> Option<NaiveDate> {
-        let pattern = regex::Regex::new(r"IMG-(\d{8})").ok()?;
-        let captures = pattern.captures(filename)?;
-        let date_str = captures.get(1)?.as_str();
-        NaiveDate::parse_from_str(date_str, "%Y%m%d").ok()
+    #[rstest]
+    #[case(DatePlausibility::new(), NaiveDate::from_ymd_opt(2012, 10, 6).unwrap())]
+    #[case(DatePlausibility::new(), NaiveDate::from_ymd_opt(1970, 1, 2).unwrap())]
+    #[case(DatePlausibility::new().with_dead_clock_dates(vec![]), NaiveDate::from_ymd_opt(1970, 1, 1).unwrap())]
+    #[case(DatePlausibility::new().with_earliest(NaiveDate::from_ymd_opt(1850, 1, 1).unwrap()), NaiveDate::from_ymd_opt(1890, 5, 1).unwrap())]
+    #[case(DatePlausibility::new().with_future_dates(true), NaiveDate::from_ymd_opt(2999, 6, 1).unwrap())]
+    fn test_date_plausibility_accepts_plausible_dates(#[case] plausibility: DatePlausibility, #[case] date: NaiveDate) {
+        // Act & Assert
+        assert!(plausibility.check(start_of_day(date)).is_ok());
     }
-}
 
-impl DateExtractor for FilenameBasedDateExtractor {
-    fn extract_date(&self, filename: &str, _image_data: &[u8]) -> Result<NaiveDate> {
-        Self::try_parse_patterns(filename)
-            .context("Failed to extract date from filename")
-    }
-}
+    #[rstest]
+    #[case(Some(DatePlausibility::new()), NaiveDate::from_ymd_opt(2015, 1, 30).unwrap())]
+    #[case(None, NaiveDate::from_ymd_opt(1970, 1, 1).unwrap())]
+    fn test_composite_extractor_passes_over_implausible_date(
+        #[case] plausibility: Option<DatePlausibility>,
+        #[case] expected: NaiveDate,
+    ) {
+        // Arrange
+        let extractor = CompositeDateExtractor::new().with_plausibility(plausibility);
+        let tiff = tiff_with_date_time_original("1970:01:01 00:00:12", false);
 
-/// Composite extractor that tries EXIF first, then falls back to filename
-pub struct CompositeDateExtractor {
-    exif_extractor: ExifDateExtractor,
-    filename_extractor: FilenameBasedDateExtractor,
-}
+        // Act
+        let result = extractor.extract_date("IMG_20150130_000000.jpg", &tiff);
 
-impl CompositeDateExtractor {
-    pub fn new() -> Self {
-        Self {
-            exif_extractor: ExifDateExtractor::new(),
-            filename_extractor: FilenameBasedDateExtractor::new(),
-        }
+        // Assert
+        assert_eq!(result.unwrap(), expected);
     }
-}
 
-impl DateExtractor for CompositeDateExtractor {
-    fn extract_date(&self, filename: &str, image_data: &[u8]) -> Result<NaiveDate> {
-        self.exif_extractor
-            .extract_date(filename, image_data)
-            .or_else(|_| self.filename_extractor.extract_date(filename, image_data))
-    }
-}
+    #[test]
+    fn test_composite_extractor_reports_implausible_dates() {
+        // Arrange
+        let extractor = CompositeDateExtractor::new().with_sources(&[DateSource::Exif, DateSource::Filename]);
+        let tiff = tiff_with_date_time_original("2999:01:01 00:00:00", false);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        // Act
+        let result = extractor.extract_date("IMG_19700101_000000.jpg", &tiff);
+
+        // Assert
+        let message = result.unwrap_err().to_string();
+        assert!(message.contains("in the future"), "{}", message);
+        assert!(message.contains("camera clock reset"), "{}", message);
+    }
 
     #[test]
-    fn test_extract_date_from_valid_exif() {
+    fn test_composite_extractor_reports_why_each_source_failed() {
         // Arrange
-        let extractor = ExifDateExtractor::new();
+        let extractor = CompositeDateExtractor::new().with_sources(&[DateSource::Exif, DateSource::Filename]);
+        let tiff = tiff_with_date_time_original("2999:01:01 00:00:00", false);
+        let no_exif_data: &[u8] = &[0xFF, 0xD8, 0xFF, 0xD9];
 
-        // Real Google Photos image (1x1 pixel) with DateTimeOriginal: 2012:10:06 13:09:32
-        let sample_image_data: &[u8] = include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg");
+        // Act
+        let implausible = extractor.extract_date("IMG_19700101_000000.jpg", &tiff).unwrap_err();
+        let missing = extractor.extract_date("random.jpg", no_exif_data).unwrap_err();
+
+        // Assert
+        let kinds = |error: &anyhow::Error| -> Vec<(String, Option<&'static str>)> {
+            let Some(DateExtractionError::NoDate(failures)) = DateExtractionError::of(error) else {
+                panic!("No NoDate error: {:#}", error);
+            };
+            failures
+                .iter()
+                .map(|failure| (failure.source.clone(), failure.error.as_ref().map(DateExtractionError::kind)))
+                .collect()
+        };
+        assert_eq!(
+            kinds(&implausible),
+            vec![
+                ("exif".to_string(), Some("implausible")),
+                ("filename".to_string(), Some("implausible")),
+            ]
+        );
+        assert_eq!(
+            kinds(&missing),
+            vec![
+                ("exif".to_string(), Some("no_exif")),
+                ("filename".to_string(), Some("no_filename_pattern")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_composite_extractor_falls_back_to_filename() {
+        // Arrange
+        let extractor = CompositeDateExtractor::new();
+        let no_exif_data: &[u8] = &[0xFF, 0xD8, 0xFF, 0xD9]; // Minimal JPEG without EXIF
+        let filename = "Screenshot_2013-04-19-19-46-43.png";
 
         // Act
-        let result = extractor.extract_date("photo.jpg", sample_image_data);
+        let result = extractor.extract_date(filename, no_exif_data);
 
         // Assert
         assert!(result.is_ok(), "Failed to extract date: {:?}", result.err());
         let date = result.unwrap();
-        assert_eq!(date, NaiveDate::from_ymd_opt(2012, 10, 6).unwrap());
+        assert_eq!(date, NaiveDate::from_ymd_opt(2013, 4, 19).unwrap(), "Should fall back to filename");
+    }
+
+    #[rstest]
+    #[case("photo.jpg", include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg").as_slice(), Confidence::Exif)]
+    #[case("Screenshot_2013-04-19-19-46-43.png", &[0xFF, 0xD8, 0xFF, 0xD9], Confidence::Filename)]
+    #[case("00000IMG_00000_BURST20180101123456.jpg", &[0xFF, 0xD8, 0xFF, 0xD9], Confidence::Filename)]
+    fn test_composite_extractor_reports_confidence_of_source(
+        #[case] filename: &str,
+        #[case] data: &[u8],
+        #[case] expected: Confidence,
+    ) {
+        // Arrange
+        let extractor = CompositeDateExtractor::new();
+
+        // Act
+        let result = extractor.extract_with_confidence_in_context(filename, data, &EntryContext::default());
+
+        // Assert
+        assert_eq!(result.unwrap().confidence, expected);
     }
 
     #[test]
-    fn test_extract_date_missing_exif_returns_error() {
+    fn test_composite_extractor_reports_sidecar_confidence() {
         // Arrange
-        let extractor = ExifDateExtractor::new();
-        let invalid_data: &[u8] = &[0, 1, 2, 3]; // Not a valid image
+        let extractor = CompositeDateExtractor::new();
+        let sidecar = Sidecar {
+            photo_taken_time: chrono::DateTime::from_timestamp(1_420_113_600, 0),
+            ..Sidecar::default()
+        };
+        let context = EntryContext {
+            sidecar: Some(&sidecar),
+            ..EntryContext::default()
+        };
 
         // Act
-        let result = extractor.extract_date("photo.jpg", invalid_data);
+        let result = extractor.extract_with_confidence_in_context("IMG_20130419_194643.jpg", &[], &context);
 
         // Assert
-        assert!(result.is_err());
+        assert_eq!(result.unwrap().confidence, Confidence::Sidecar);
+    }
+
+    #[rstest]
+    #[case("IMG_1234-edited.jpg", true, 2015)]
+    #[case("IMG_1234-EFFECTS.jpg", true, 2015)]
+    #[case("IMG_1234.jpg", true, 2012)]
+    #[case("IMG_1234-edited.jpg", false, 2012)]
+    fn test_composite_extractor_prefers_sidecar_for_edited_copies(
+        #[case] filename: &str,
+        #[case] sidecar_first_for_edits: bool,
+        #[case] expected_year: i32,
+    ) {
+        // Arrange
+        let extractor = CompositeDateExtractor::new().with_sidecar_first_for_edits(sidecar_first_for_edits);
+        let sidecar = Sidecar {
+            photo_taken_time: chrono::DateTime::from_timestamp(1_420_113_600, 0),
+            ..Sidecar::default()
+        };
+        let context = EntryContext {
+            sidecar: Some(&sidecar),
+            ..EntryContext::default()
+        };
+        let data: &[u8] = include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg");
+
+        // Act
+        let result = extractor.extract_date_in_context(filename, data, &context);
+
+        // Assert
+        assert_eq!(result.unwrap().year(), expected_year);
     }
 
     #[test]
-    fn test_extract_date_empty_data_returns_error() {
+    fn test_composite_extractor_keeps_chosen_order_for_edited_copies() {
         // Arrange
-        let extractor = ExifDateExtractor::new();
-        let empty_data: &[u8] = &[];
+        let extractor = CompositeDateExtractor::new().with_sources(&[DateSource::Exif, DateSource::Filename]);
+        let sidecar = Sidecar {
+            photo_taken_time: chrono::DateTime::from_timestamp(1_420_113_600, 0),
+            ..Sidecar::default()
+        };
+        let context = EntryContext {
+            sidecar: Some(&sidecar),
+            ..EntryContext::default()
+        };
+        let data: &[u8] = include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg");
 
         // Act
-        let result = extractor.extract_date("photo.jpg", empty_data);
+        let result = extractor.extract_date_in_context("IMG_1234-edited.jpg", data, &context);
 
         // Assert
-        assert!(result.is_err());
+        assert_eq!(result.unwrap().year(), 2012, "No json source to prefer");
     }
 
     #[test]
-    fn test_filename_extractor_screenshot_pattern() {
+    fn test_composite_extractor_gives_embedded_and_sidecar_times() {
         // Arrange
-        let extractor = FilenameBasedDateExtractor::new();
-        let filename = "Screenshot_2013-04-19-19-46-43.png";
+        let extractor = CompositeDateExtractor::new().with_time_zone(DateTimeZone::Named(chrono_tz::UTC));
+        let sidecar = Sidecar {
+            photo_taken_time: chrono::DateTime::from_timestamp(1_420_113_600, 0),
+            ..Sidecar::default()
+        };
+        let context = EntryContext {
+            sidecar: Some(&sidecar),
+            ..EntryContext::default()
+        };
+        let data: &[u8] = include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg");
 
         // Act
-        let result = extractor.extract_date(filename, &[]);
+        let with_sidecar = extractor.embedded_and_sidecar_times("photo.jpg", data, &context);
+        let without_sidecar = extractor.embedded_and_sidecar_times("photo.jpg", data, &EntryContext::default());
 
         // Assert
-        assert!(result.is_ok(), "Failed to extract date: {:?}", result.err());
-        let date = result.unwrap();
-        assert_eq!(date, NaiveDate::from_ymd_opt(2013, 4, 19).unwrap());
+        assert_eq!(
+            with_sidecar,
+            Some((
+                NaiveDate::from_ymd_opt(2012, 10, 6).unwrap().and_hms_milli_opt(13, 9, 32, 800).unwrap(),
+                NaiveDate::from_ymd_opt(2015, 1, 1).unwrap().and_hms_opt(12, 0, 0).unwrap(),
+            ))
+        );
+        assert_eq!(without_sidecar, None);
+    }
+
+    /// A company scheme like `ACME-20190714-0042.jpg`
+    struct AcmeDateExtractor;
+
+    impl DateExtractor for AcmeDateExtractor {
+        fn extract_date_time(&self, filename: &str, _image_data: &[u8]) -> Result<NaiveDateTime> {
+            let digits = filename.strip_prefix("ACME-").and_then(|rest| rest.get(..8)).context("Not an ACME name")?;
+            Ok(start_of_day(NaiveDate::parse_from_str(digits, "%Y%m%d")?))
+        }
+
+        fn confidence(&self) -> Confidence {
+            Confidence::Filename
+        }
     }
 
     #[test]
-    fn test_filename_extractor_animation_pattern() {
+    fn test_composite_extractor_tries_pushed_extractor_last() {
         // Arrange
-        let extractor = FilenameBasedDateExtractor::new();
-        let filename = "20151115_143914-ANIMATION.gif";
+        let extractor = CompositeDateExtractor::new()
+            .with_sources(&[DateSource::Exif])
+            .with_extractor(Box::new(AcmeDateExtractor));
+        let no_exif: &[u8] = &[0xFF, 0xD8, 0xFF, 0xD9];
 
         // Act
-        let result = extractor.extract_date(filename, &[]);
+        let dated = extractor.extract_with_confidence_in_context("ACME-20190714-0042.jpg", no_exif, &EntryContext::default());
+        let undated = extractor.extract_date("IMG_0042.jpg", no_exif);
 
         // Assert
-        assert!(result.is_ok(), "Failed to extract date: {:?}", result.err());
-        let date = result.unwrap();
-        assert_eq!(date, NaiveDate::from_ymd_opt(2015, 11, 15).unwrap());
+        let dated = dated.unwrap();
+        assert_eq!(dated.time.date(), NaiveDate::from_ymd_opt(2019, 7, 14).unwrap());
+        assert_eq!(dated.confidence, Confidence::Filename);
+        let message = undated.unwrap_err().to_string();
+        assert!(message.contains("AcmeDateExtractor: Not an ACME name"), "{}", message);
     }
 
     #[test]
-    fn test_filename_extractor_img_underscore_pattern() {
+    fn test_composite_extractor_tries_inserted_extractor_at_its_position() {
         // Arrange
-        let extractor = FilenameBasedDateExtractor::new();
-        let filename = "IMG_20130106_160818.JPG";
+        let extractor = CompositeDateExtractor::new().with_extractor_at(0, Box::new(AcmeDateExtractor));
+        let data: &[u8] = include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg");
 
         // Act
-        let result = extractor.extract_date(filename, &[]);
+        let result = extractor.extract_date("ACME-20190714-0042.jpg", data);
 
         // Assert
-        assert!(result.is_ok(), "Failed to extract date: {:?}", result.err());
-        let date = result.unwrap();
-        assert_eq!(date, NaiveDate::from_ymd_opt(2013, 1, 6).unwrap());
+        assert_eq!(result.unwrap(), NaiveDate::from_ymd_opt(2019, 7, 14).unwrap());
     }
 
     #[test]
-    fn test_filename_extractor_img_dash_pattern() {
+    fn test_confidence_orders_exif_over_sidecar_over_filename_over_mtime() {
+        // Act & Assert
+        assert!(Confidence::Exif > Confidence::Sidecar);
+        assert!(Confidence::Sidecar > Confidence::Filename);
+        assert!(Confidence::Filename > Confidence::Mtime);
+    }
+
+    #[test]
+    fn test_sidecar_extractor_uses_photo_taken_time() {
         // Arrange
-        let extractor = FilenameBasedDateExtractor::new();
-        let filename = "IMG-20150130-WA0001.jpg";
+        let extractor = JsonSidecarDateExtractor::new();
+        let sidecar = Sidecar {
+            photo_taken_time: chrono::DateTime::from_timestamp(1_420_113_600, 0),
+            ..Sidecar::default()
+        };
+        let context = EntryContext {
+            sidecar: Some(&sidecar),
+            ..EntryContext::default()
+        };
 
         // Act
-        let result = extractor.extract_date(filename, &[]);
+        let with_sidecar = extractor.extract_date_in_context("IMG-WA0001.jpg", &[], &context);
+        let without_sidecar = extractor.extract_date("IMG-WA0001.jpg", &[]);
 
         // Assert
-        assert!(result.is_ok(), "Failed to extract date: {:?}", result.err());
-        let date = result.unwrap();
-        assert_eq!(date, NaiveDate::from_ymd_opt(2015, 1, 30).unwrap());
+        assert_eq!(with_sidecar.unwrap(), NaiveDate::from_ymd_opt(2015, 1, 1).unwrap());
+        assert!(without_sidecar.is_err());
     }
 
     #[test]
-    fn test_filename_extractor_simple_date_pattern() {
+    fn test_composite_extractor_prefers_sidecar_over_filename() {
         // Arrange
-        let extractor = FilenameBasedDateExtractor::new();
-        let filename = "2014-09-29.jpg";
+        let extractor = CompositeDateExtractor::new();
+        let no_exif_data: &[u8] = &[0xFF, 0xD8, 0xFF, 0xD9];
+        let sidecar = Sidecar {
+            photo_taken_time: chrono::DateTime::from_timestamp(1_420_113_600, 0),
+            ..Sidecar::default()
+        };
+        let context = EntryContext {
+            sidecar: Some(&sidecar),
+            ..EntryContext::default()
+        };
 
         // Act
-        let result = extractor.extract_date(filename, &[]);
+        let result = extractor.extract_date_in_context("Screenshot_2013-04-19-19-46-43.png", no_exif_data, &context);
 
         // Assert
-        assert!(result.is_ok(), "Failed to extract date: {:?}", result.err());
-        let date = result.unwrap();
-        assert_eq!(date, NaiveDate::from_ymd_opt(2014, 9, 29).unwrap());
+        assert_eq!(
+            result.unwrap(),
+            NaiveDate::from_ymd_opt(2015, 1, 1).unwrap(),
+            "Should use the sidecar date, not the filename"
+        );
+    }
+
+    #[rstest]
+    #[case(&[], 2012)]
+    #[case(&[DateSource::Json, DateSource::Exif], 2015)]
+    #[case(&[DateSource::Filename], 2013)]
+    fn test_composite_extractor_consults_sources_in_given_order(
+        #[case] sources: &[DateSource],
+        #[case] expected_year: i32,
+    ) {
+        // Arrange
+        let extractor = CompositeDateExtractor::new().with_sources(sources);
+        let sample_image_data: &[u8] = include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg");
+        let sidecar = Sidecar {
+            photo_taken_time: chrono::DateTime::from_timestamp(1_420_113_600, 0),
+            ..Sidecar::default()
+        };
+        let context = EntryContext {
+            sidecar: Some(&sidecar),
+            ..EntryContext::default()
+        };
+
+        // Act
+        let result = extractor.extract_date_in_context("IMG_20130419_000000.jpg", sample_image_data, &context);
+
+        // Assert
+        assert_eq!(result.unwrap().year(), expected_year);
     }
 
     #[test]
-    fn test_filename_extractor_no_pattern_returns_error() {
+    fn test_composite_extractor_skips_sources_left_out() {
         // Arrange
-        let extractor = FilenameBasedDateExtractor::new();
-        let filename = "random_file.jpg";
+        let extractor = CompositeDateExtractor::new().with_sources(&[DateSource::Exif, DateSource::Json]);
+        let no_exif_data: &[u8] = &[0xFF, 0xD8, 0xFF, 0xD9];
 
         // Act
-        let result = extractor.extract_date(filename, &[]);
+        let result = extractor.extract_date("Screenshot_2013-04-19-19-46-43.png", no_exif_data);
 
         // Assert
-        assert!(result.is_err());
+        let message = result.unwrap_err().to_string();
+        assert!(message.starts_with("No date found (exif: "), "{}", message);
+        assert!(!message.contains("filename"), "{}", message);
     }
 
     #[test]
-    fn test_composite_extractor_uses_exif_first() {
+    fn test_composite_extractor_falls_back_to_album_date() {
         // Arrange
         let extractor = CompositeDateExtractor::new();
-        let sample_image_data: &[u8] = include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg");
-        // Filename suggests 2015, but EXIF says 2012 - EXIF should win
-        let filename = "IMG_20150130_000000.jpg";
+        let no_exif_data: &[u8] = &[0xFF, 0xD8, 0xFF, 0xD9];
+        let album = AlbumMetadata {
+            date: chrono::DateTime::from_timestamp(1_420_113_600, 0),
+            ..AlbumMetadata::default()
+        };
+        let metadata = EntryMetadata {
+            modified: NaiveDate::from_ymd_opt(2016, 8, 3).unwrap().and_hms_opt(12, 0, 0),
+            ..EntryMetadata::default()
+        };
+        let context = EntryContext {
+            album: Some(&album),
+            metadata: Some(&metadata),
+            ..EntryContext::default()
+        };
 
         // Act
-        let result = extractor.extract_date(filename, sample_image_data);
+        let undated = extractor.extract_date_in_context("scan.jpg", no_exif_data, &context);
+        let dated_by_name = extractor.extract_date_in_context("IMG_20150130_000000.jpg", no_exif_data, &context);
 
         // Assert
-        assert!(result.is_ok());
-        let date = result.unwrap();
-        assert_eq!(date, NaiveDate::from_ymd_opt(2012, 10, 6).unwrap(), "Should use EXIF date, not filename");
+        assert_eq!(undated.unwrap(), NaiveDate::from_ymd_opt(2015, 1, 1).unwrap());
+        assert_eq!(dated_by_name.unwrap(), NaiveDate::from_ymd_opt(2015, 1, 30).unwrap());
     }
 
     #[test]
-    fn test_composite_extractor_falls_back_to_filename() {
+    fn test_composite_extractor_falls_back_to_archive_modified_time() {
         // Arrange
         let extractor = CompositeDateExtractor::new();
-        let no_exif_data: &[u8] = &[0xFF, 0xD8, 0xFF, 0xD9]; // Minimal JPEG without EXIF
-        let filename = "Screenshot_2013-04-19-19-46-43.png";
+        let no_exif_data: &[u8] = &[0xFF, 0xD8, 0xFF, 0xD9];
+        let metadata = EntryMetadata {
+            modified: NaiveDate::from_ymd_opt(2016, 8, 3).unwrap().and_hms_opt(23, 59, 0),
+            ..EntryMetadata::default()
+        };
+        let context = EntryContext {
+            metadata: Some(&metadata),
+            ..EntryContext::default()
+        };
 
         // Act
-        let result = extractor.extract_date(filename, no_exif_data);
+        let undated = extractor.extract_date_in_context("random_file.jpg", no_exif_data, &context);
+        let dated_by_name = extractor.extract_date_in_context("IMG_20150130_000000.jpg", no_exif_data, &context);
 
         // Assert
-        assert!(result.is_ok(), "Failed to extract date: {:?}", result.err());
-        let date = result.unwrap();
-        assert_eq!(date, NaiveDate::from_ymd_opt(2013, 4, 19).unwrap(), "Should fall back to filename");
+        assert_eq!(undated.unwrap(), NaiveDate::from_ymd_opt(2016, 8, 3).unwrap());
+        assert_eq!(
+            dated_by_name.unwrap(),
+            NaiveDate::from_ymd_opt(2015, 1, 30).unwrap(),
+            "The file name still wins over the modification time"
+        );
+    }
+
+    #[rstest]
+    #[case(false, true, Some(NaiveDate::from_ymd_opt(2016, 8, 3).unwrap()))]
+    #[case(true, true, Some(NaiveDate::from_ymd_opt(2016, 8, 3).unwrap()))]
+    #[case(true, false, None)]
+    fn test_composite_extractor_file_time_fallback(
+        #[case] from_file_system: bool,
+        #[case] file_times: bool,
+        #[case] expected: Option<NaiveDate>,
+    ) {
+        // Arrange
+        let extractor = CompositeDateExtractor::new().with_file_times(file_times);
+        let metadata = EntryMetadata {
+            modified: NaiveDate::from_ymd_opt(2016, 8, 3).unwrap().and_hms_opt(12, 0, 0),
+            from_file_system,
+            ..EntryMetadata::default()
+        };
+        let context = EntryContext {
+            metadata: Some(&metadata),
+            ..EntryContext::default()
+        };
+
+        // Act
+        let result = extractor.extract_date_in_context("random_file.jpg", &[0xFF, 0xD8, 0xFF, 0xD9], &context);
+
+        // Assert
+        assert_eq!(result.ok(), expected);
     }
 
     #[test]
@@ -310,4 +3385,75 @@ mod tests {
         // Assert
         assert!(result.is_err(), "Should fail when both EXIF and filename patterns are missing");
     }
+
+    #[rstest]
+    #[case("Pictures/2019/07/14/IMG_0001.jpg", 2019, 7, 14)]
+    #[case("Pictures/2019/07/IMG_0001.jpg", 2019, 7, 1)]
+    #[case("Pictures/2019/Phone/IMG_0001.jpg", 2019, 1, 1)]
+    #[case("Pictures/2019/13/IMG_0001.jpg", 2019, 1, 1)]
+    #[case("2015 Christmas/12/tree.jpg", 2015, 1, 1)]
+    fn test_folder_extractor_numbered_month_and_day_folders(
+        #[case] filename: &str,
+        #[case] year: i32,
+        #[case] month: u32,
+        #[case] day: u32,
+    ) {
+        // Arrange
+        let extractor = FolderNameDateExtractor::new();
+
+        // Act
+        let date = extractor.extract_date(filename, &[]).unwrap();
+
+        // Assert
+        assert_eq!(date, NaiveDate::from_ymd_opt(year, month, day).unwrap());
+    }
+
+    #[test]
+    fn test_folder_extractor_month_and_year_folders() {
+        // Arrange
+        let extractor = FolderNameDateExtractor::new();
+
+        // Act & Assert
+        assert_eq!(
+            extractor.extract_date("trips/2019-07 Tuscany/DSC0001.jpg", &[]).unwrap(),
+            NaiveDate::from_ymd_opt(2019, 7, 1).unwrap()
+        );
+        assert_eq!(
+            extractor.extract_date("2015 Christmas/tree.jpg", &[]).unwrap(),
+            NaiveDate::from_ymd_opt(2015, 1, 1).unwrap()
+        );
+        assert_eq!(
+            extractor.extract_date("2015\\2015-12-24 Eve\\tree.jpg", &[]).unwrap(),
+            NaiveDate::from_ymd_opt(2015, 12, 24).unwrap(),
+            "Innermost dated folder should win"
+        );
+    }
+
+    #[test]
+    fn test_folder_extractor_ignores_undated_folders_and_filename() {
+        // Arrange
+        let extractor = FolderNameDateExtractor::new();
+
+        // Act & Assert
+        assert!(extractor.extract_date("Holiday/2019-07-04.jpg", &[]).is_err());
+        assert!(extractor.extract_date("Best of 2015/photo.jpg", &[]).is_err());
+        assert!(extractor.extract_date("12345 scans/photo.jpg", &[]).is_err());
+    }
+
+    #[test]
+    fn test_composite_extractor_falls_back_to_folder_name_when_enabled() {
+        // Arrange
+        let no_exif_data: &[u8] = &[0xFF, 0xD8, 0xFF, 0xD9];
+        let filename = "2019-07 Tuscany/DSC0001.jpg";
+
+        // Act
+        let without_folders = CompositeDateExtractor::new().extract_date(filename, no_exif_data);
+        let with_folders = CompositeDateExtractor::new()
+            .with_folder_names(true)
+            .extract_date(filename, no_exif_data);
+
+        // Assert
+        assert!(without_folders.is_err());
+        assert_eq!(with_folders.unwrap(), NaiveDate::from_ymd_opt(2019, 7, 1).unwrap());
+    }
 }
@@ -1,14 +1,32 @@
 use anyhow::{Context, Result};
-use std::fs;
-use std::io::Write;
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{self, File};
+use std::hash::Hasher;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 
+/// Number of bytes hashed at the start and end of a file for the partial hash
+const PARTIAL_HASH_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Content that is copied to the output piece by piece instead of being held in memory
+pub trait StreamContent {
+    fn size(&self) -> u64;
+    fn copy_to(&self, out: &mut dyn Write) -> Result<u64>;
+}
+
 #[cfg_attr(test, mockall::automock)]
 pub trait FileSystemWriter {
     fn write_file(&self, path: &Path, data: &[u8]) -> Result<()>;
+    fn write_stream(&self, path: &Path, content: &dyn StreamContent) -> Result<()>;
+    fn append_file(&self, path: &Path, data: &[u8]) -> Result<()>;
     fn create_directory(&self, path: &Path) -> Result<()>;
     fn get_full_path(&self, path: &Path) -> PathBuf;
     fn find_existing_date_directory(&self, year_path: &Path, date_prefix: &str) -> Option<String>;
+    fn contains_identical_file(&self, path: &Path, data: &[u8]) -> bool;
+    fn contains_identical_stream(&self, path: &Path, head: &[u8], content: &dyn StreamContent) -> bool;
+    fn file_exists(&self, path: &Path) -> bool;
+    fn is_output_empty(&self) -> bool;
+    fn move_aside(&self, path: &Path) -> Result<PathBuf>;
 }
 
 /// Concrete implementation that writes to the actual filesystem
@@ -26,7 +44,7 @@ impl FileSystemWriter for RealFileSystemWriter {
     fn write_file(&self, path: &Path, data: &[u8]) -> Result<()> {
         let full_path = PathBuf::from(&self.base_output_dir).join(path);
 
-        let mut file = fs::File::create(&full_path)
+        let mut file = File::create(&full_path)
             .with_context(|| format!("Failed to create file: {}", full_path.display()))?;
 
         file.write_all(data)
@@ -35,6 +53,36 @@ impl FileSystemWriter for RealFileSystemWriter {
         Ok(())
     }
 
+    fn write_stream(&self, path: &Path, content: &dyn StreamContent) -> Result<()> {
+        let full_path = PathBuf::from(&self.base_output_dir).join(path);
+
+        let file = File::create(&full_path)
+            .with_context(|| format!("Failed to create file: {}", full_path.display()))?;
+        let mut writer = BufWriter::new(file);
+
+        content
+            .copy_to(&mut writer)
+            .and_then(|_| Ok(writer.flush()?))
+            .with_context(|| format!("Failed to write data to file: {}", full_path.display()))?;
+
+        Ok(())
+    }
+
+    fn append_file(&self, path: &Path, data: &[u8]) -> Result<()> {
+        let full_path = PathBuf::from(&self.base_output_dir).join(path);
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&full_path)
+            .with_context(|| format!("Failed to open file for appending: {}", full_path.display()))?;
+
+        file.write_all(data)
+            .with_context(|| format!("Failed to append data to file: {}", full_path.display()))?;
+
+        Ok(())
+    }
+
     fn create_directory(&self, path: &Path) -> Result<()> {
         let full_path = PathBuf::from(&self.base_output_dir).join(path);
 
@@ -65,9 +113,162 @@ impl FileSystemWriter for RealFileSystemWriter {
 
         None
     }
+
+    /// Checks whether the target already holds exactly this data.
+    /// Compares size first, then a hash of the head and tail, and only reads
+    /// the whole file when both match, so large unchanged videos are cheap to skip.
+    fn contains_identical_file(&self, path: &Path, data: &[u8]) -> bool {
+        let full_path = self.get_full_path(path);
+        Self::has_identical_content(&full_path, data).unwrap_or(false)
+    }
+
+    /// Streaming counterpart of `contains_identical_file`: compares sizes, then a hash of
+    /// the `head` of the content already in memory, and only when both match the whole
+    /// content chunk by chunk while it is produced, stopping at the first difference
+    fn contains_identical_stream(&self, path: &Path, head: &[u8], content: &dyn StreamContent) -> bool {
+        let full_path = self.get_full_path(path);
+        if !fs::metadata(&full_path).is_ok_and(|m| m.len() == content.size()) {
+            return false;
+        }
+        let Ok(mut file) = File::open(&full_path) else {
+            return false;
+        };
+        let head = &head[..head.len().min(content.size() as usize)];
+        let same_head = Self::partial_hash_of_file(&mut file, head.len())
+            .is_ok_and(|hash| hash == Self::partial_hash_of_data(head));
+        if !same_head || file.rewind().is_err() {
+            return false;
+        }
+
+        let mut comparer = ComparingWriter {
+            existing: BufReader::new(file),
+        };
+        content.copy_to(&mut comparer).is_ok()
+    }
+
+    fn file_exists(&self, path: &Path) -> bool {
+        self.get_full_path(path).is_file()
+    }
+
+    fn is_output_empty(&self) -> bool {
+        match fs::read_dir(&self.base_output_dir) {
+            Ok(mut entries) => entries.next().is_none(),
+            Err(_) => true,
+        }
+    }
+
+    /// Renames an existing file to `<name>.bak` (or `<name>.bak1`, `<name>.bak2`, ...)
+    /// and returns the new relative path
+    fn move_aside(&self, path: &Path) -> Result<PathBuf> {
+        let backup_path = self.next_free_backup_path(path);
+        let full_path = self.get_full_path(path);
+        let full_backup_path = self.get_full_path(&backup_path);
+
+        fs::rename(&full_path, &full_backup_path).with_context(|| {
+            format!(
+                "Failed to move {} aside to {}",
+                full_path.display(),
+                full_backup_path.display()
+            )
+        })?;
+
+        Ok(backup_path)
+    }
+}
+
+/// Writer that checks that everything written equals the next bytes of an existing
+/// file, failing with an error on the first difference
+struct ComparingWriter<R: Read> {
+    existing: R,
+}
+
+impl<R: Read> Write for ComparingWriter<R> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut expected = vec![0u8; buf.len()];
+        self.existing.read_exact(&mut expected)?;
+        if expected != buf {
+            return Err(std::io::Error::other("content differs"));
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
 }
 
 impl RealFileSystemWriter {
+    fn next_free_backup_path(&self, path: &Path) -> PathBuf {
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let mut counter = 0;
+
+        loop {
+            let suffix = if counter == 0 {
+                ".bak".to_string()
+            } else {
+                format!(".bak{}", counter)
+            };
+            let candidate = path.with_file_name(format!("{}{}", file_name, suffix));
+            if !self.get_full_path(&candidate).exists() {
+                return candidate;
+            }
+            counter += 1;
+        }
+    }
+
+    fn has_identical_content(full_path: &Path, data: &[u8]) -> Result<bool> {
+        let metadata = fs::metadata(full_path)?;
+        if metadata.len() != data.len() as u64 {
+            return Ok(false);
+        }
+
+        let mut file = File::open(full_path)?;
+        if Self::partial_hash_of_file(&mut file, data.len())? != Self::partial_hash_of_data(data) {
+            return Ok(false);
+        }
+
+        Self::file_content_equals(&mut file, data)
+    }
+
+    fn partial_hash_of_data(data: &[u8]) -> u64 {
+        let chunk_size = PARTIAL_HASH_CHUNK_SIZE.min(data.len());
+        let mut hasher = DefaultHasher::new();
+        hasher.write(&data[..chunk_size]);
+        hasher.write(&data[data.len() - chunk_size..]);
+        hasher.finish()
+    }
+
+    fn partial_hash_of_file(file: &mut File, len: usize) -> Result<u64> {
+        let chunk_size = PARTIAL_HASH_CHUNK_SIZE.min(len);
+        let mut buffer = vec![0u8; chunk_size];
+        let mut hasher = DefaultHasher::new();
+
+        file.seek(SeekFrom::Start(0))?;
+        file.read_exact(&mut buffer)?;
+        hasher.write(&buffer);
+
+        file.seek(SeekFrom::Start((len - chunk_size) as u64))?;
+        file.read_exact(&mut buffer)?;
+        hasher.write(&buffer);
+
+        Ok(hasher.finish())
+    }
+
+    fn file_content_equals(file: &mut File, data: &[u8]) -> Result<bool> {
+        let mut buffer = vec![0u8; PARTIAL_HASH_CHUNK_SIZE];
+        file.seek(SeekFrom::Start(0))?;
+
+        for expected in data.chunks(PARTIAL_HASH_CHUNK_SIZE) {
+            let actual = &mut buffer[..expected.len()];
+            file.read_exact(actual)?;
+            if actual != expected {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
     fn get_matching_directory(entry: &fs::DirEntry, date_prefix: &str) -> Option<String> {
         if !entry.file_type().ok()?.is_dir() {
             return None;
@@ -189,6 +390,185 @@ mod tests {
         fs::remove_dir_all(temp_dir).ok();
     }
 
+    #[test]
+    fn test_contains_identical_file_detects_same_content() {
+        // Arrange
+        let temp_dir = "/tmp/test_identical_same";
+        let writer = RealFileSystemWriter::new(temp_dir.to_string());
+        let file_path = PathBuf::from("2024/photo.jpg");
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        writer.create_directory(&PathBuf::from("2024")).unwrap();
+        writer.write_file(&file_path, &data).unwrap();
+
+        // Act
+        let result = writer.contains_identical_file(&file_path, &data);
+
+        // Assert
+        assert!(result);
+
+        // Cleanup
+        fs::remove_dir_all(temp_dir).ok();
+    }
+
+    #[test]
+    fn test_contains_identical_file_rejects_different_content() {
+        // Arrange
+        let temp_dir = "/tmp/test_identical_different";
+        let writer = RealFileSystemWriter::new(temp_dir.to_string());
+        let file_path = PathBuf::from("2024/photo.jpg");
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        let mut changed_in_middle = data.clone();
+        changed_in_middle[100_000] ^= 0xFF;
+        writer.create_directory(&PathBuf::from("2024")).unwrap();
+        writer.write_file(&file_path, &data).unwrap();
+
+        // Act & Assert
+        assert!(!writer.contains_identical_file(&file_path, &changed_in_middle));
+        assert!(!writer.contains_identical_file(&file_path, &data[..1000]));
+        assert!(!writer.contains_identical_file(&PathBuf::from("2024/missing.jpg"), &data));
+
+        // Cleanup
+        fs::remove_dir_all(temp_dir).ok();
+    }
+
+    #[test]
+    fn test_is_output_empty() {
+        // Arrange
+        let temp_dir = "/tmp/test_output_empty";
+        fs::remove_dir_all(temp_dir).ok();
+        let writer = RealFileSystemWriter::new(temp_dir.to_string());
+
+        // Act & Assert
+        assert!(writer.is_output_empty(), "Missing directory counts as empty");
+        writer.create_directory(&PathBuf::from("")).unwrap();
+        assert!(writer.is_output_empty());
+        writer.create_directory(&PathBuf::from("2024")).unwrap();
+        assert!(!writer.is_output_empty());
+
+        // Cleanup
+        fs::remove_dir_all(temp_dir).ok();
+    }
+
+    #[test]
+    fn test_move_aside_picks_free_backup_name() {
+        // Arrange
+        let temp_dir = "/tmp/test_move_aside";
+        let writer = RealFileSystemWriter::new(temp_dir.to_string());
+        let file_path = PathBuf::from("2024/photo.jpg");
+        writer.create_directory(&PathBuf::from("2024")).unwrap();
+        writer.write_file(&file_path, b"first").unwrap();
+        writer.move_aside(&file_path).unwrap();
+        writer.write_file(&file_path, b"second").unwrap();
+
+        // Act
+        let result = writer.move_aside(&file_path);
+
+        // Assert
+        assert_eq!(result.unwrap(), PathBuf::from("2024/photo.jpg.bak1"));
+        assert!(!writer.file_exists(&file_path));
+        let full_dir = PathBuf::from(temp_dir).join("2024");
+        assert_eq!(fs::read(full_dir.join("photo.jpg.bak")).unwrap(), b"first");
+        assert_eq!(fs::read(full_dir.join("photo.jpg.bak1")).unwrap(), b"second");
+
+        // Cleanup
+        fs::remove_dir_all(temp_dir).ok();
+    }
+
+    struct InMemoryStream(Vec<u8>);
+
+    impl StreamContent for InMemoryStream {
+        fn size(&self) -> u64 {
+            self.0.len() as u64
+        }
+
+        fn copy_to(&self, out: &mut dyn Write) -> Result<u64> {
+            for chunk in self.0.chunks(3) {
+                out.write_all(chunk)?;
+            }
+            Ok(self.0.len() as u64)
+        }
+    }
+
+    /// Stream that fails when copied, for checks that must not read the content
+    struct UnreadableStream(u64);
+
+    impl StreamContent for UnreadableStream {
+        fn size(&self) -> u64 {
+            self.0
+        }
+
+        fn copy_to(&self, _out: &mut dyn Write) -> Result<u64> {
+            panic!("the content was streamed");
+        }
+    }
+
+    #[test]
+    fn test_contains_identical_stream_rejects_different_head_without_streaming() {
+        // Arrange
+        let temp_dir = "/tmp/test_identical_stream_head";
+        let writer = RealFileSystemWriter::new(temp_dir.to_string());
+        writer.create_directory(&PathBuf::from("2024")).unwrap();
+        let file_path = PathBuf::from("2024/video.mp4");
+        writer.write_file(&file_path, b"existing video content").unwrap();
+
+        // Act
+        let identical = writer.contains_identical_stream(&file_path, b"different", &UnreadableStream(22));
+
+        // Assert
+        assert!(!identical);
+
+        // Cleanup
+        fs::remove_dir_all(temp_dir).ok();
+    }
+
+    #[test]
+    fn test_write_stream_and_contains_identical_stream() {
+        // Arrange
+        let temp_dir = "/tmp/test_write_stream";
+        let writer = RealFileSystemWriter::new(temp_dir.to_string());
+        writer.create_directory(&PathBuf::from("2024")).unwrap();
+        let file_path = PathBuf::from("2024/video.mp4");
+        let content = InMemoryStream(b"streamed video content".to_vec());
+
+        // Act
+        writer.write_stream(&file_path, &content).unwrap();
+
+        // Assert
+        let written = fs::read(PathBuf::from(temp_dir).join(&file_path)).unwrap();
+        assert_eq!(written, b"streamed video content");
+        assert!(writer.contains_identical_stream(&file_path, b"streamed", &content));
+        assert!(!writer.contains_identical_stream(
+            &file_path,
+            b"streamed",
+            &InMemoryStream(b"streamed video CONTENT".to_vec())
+        ));
+        assert!(!writer.contains_identical_stream(&file_path, b"short", &InMemoryStream(b"short".to_vec())));
+        assert!(!writer.contains_identical_stream(&PathBuf::from("2024/missing.mp4"), b"streamed", &content));
+
+        // Cleanup
+        fs::remove_dir_all(temp_dir).ok();
+    }
+
+    #[test]
+    fn test_append_file_creates_and_extends_file() {
+        // Arrange
+        let temp_dir = "/tmp/test_append_file";
+        let writer = RealFileSystemWriter::new(temp_dir.to_string());
+        writer.create_directory(&PathBuf::from("2024")).unwrap();
+        let file_path = PathBuf::from("2024/index.csv");
+
+        // Act
+        writer.append_file(&file_path, b"first\n").unwrap();
+        writer.append_file(&file_path, b"second\n").unwrap();
+
+        // Assert
+        let content = fs::read(PathBuf::from(temp_dir).join(&file_path)).unwrap();
+        assert_eq!(content, b"first\nsecond\n");
+
+        // Cleanup
+        fs::remove_dir_all(temp_dir).ok();
+    }
+
     #[test]
     fn test_find_existing_date_directory_returns_none_when_not_found() {
         // Arrange
@@ -0,0 +1,138 @@
+// Offline time zone lookup for GPS coordinates, to date UTC timestamps in the local time of
+// where a photo was taken
+
+use chrono_tz::Tz;
+
+/// Regions by (zone, south, north, west, east) in decimal degrees, first match wins. The
+/// boxes are rough outlines of countries and states, a few of them per country where a
+/// border runs diagonally, so places close to a zone border may still get the neighbouring
+/// zone; that only matters for photos taken around midnight.
+const REGIONS: &[(Tz, f64, f64, f64, f64)] = &[
+    // Europe
+    (Tz::Europe__Lisbon, 39.6, 42.2, -9.6, -6.9),
+    (Tz::Europe__Lisbon, 38.2, 39.6, -9.6, -7.1),
+    (Tz::Europe__Lisbon, 36.9, 38.2, -9.6, -7.45),
+    (Tz::Atlantic__Canary, 27.6, 29.5, -18.2, -13.4),
+    // The French Channel coast, north of the southern tip of England
+    (Tz::Europe__Paris, 49.8, 51.05, 1.0, 2.6),
+    (Tz::Europe__London, 49.8, 61.0, -11.0, 1.8),
+    (Tz::Europe__Madrid, 36.0, 43.8, -9.4, 3.3),
+    (Tz::Europe__Helsinki, 59.7, 63.5, 19.5, 31.6),
+    (Tz::Europe__Helsinki, 63.5, 65.5, 22.5, 30.6),
+    (Tz::Europe__Helsinki, 65.5, 69.3, 24.2, 29.7),
+    (Tz::Europe__Stockholm, 55.3, 56.2, 12.8, 19.5),
+    (Tz::Europe__Stockholm, 56.2, 59.0, 11.1, 19.5),
+    (Tz::Europe__Stockholm, 59.0, 64.0, 12.5, 19.5),
+    (Tz::Europe__Stockholm, 64.0, 66.0, 14.5, 24.2),
+    (Tz::Europe__Stockholm, 66.0, 68.0, 16.5, 24.2),
+    (Tz::Europe__Stockholm, 68.0, 69.1, 18.3, 24.2),
+    (Tz::Europe__Copenhagen, 54.8, 57.8, 8.0, 15.2),
+    (Tz::Europe__Oslo, 57.9, 71.2, 4.5, 31.2),
+    (Tz::Europe__Paris, 42.3, 51.1, -4.8, 8.2),
+    (Tz::Europe__Amsterdam, 50.7, 53.6, 3.3, 7.3),
+    (Tz::Europe__Berlin, 47.3, 55.1, 5.9, 15.0),
+    (Tz::Europe__Rome, 36.6, 47.1, 6.6, 18.5),
+    (Tz::Europe__Athens, 34.8, 41.8, 19.9, 26.6),
+    (Tz::Europe__Istanbul, 36.0, 42.1, 26.6, 44.8),
+    // North America
+    (Tz::Pacific__Honolulu, 18.9, 22.3, -160.3, -154.8),
+    (Tz::America__Anchorage, 51.0, 71.5, -170.0, -130.0),
+    (Tz::America__Vancouver, 49.0, 60.0, -139.1, -120.0),
+    (Tz::America__Edmonton, 49.0, 60.0, -120.0, -110.0),
+    (Tz::America__Phoenix, 31.3, 37.0, -114.8, -109.0),
+    (Tz::America__Los_Angeles, 32.5, 49.0, -124.8, -114.0),
+    (Tz::America__Denver, 31.3, 49.0, -114.0, -102.0),
+    (Tz::America__Chicago, 25.8, 49.4, -102.0, -87.5),
+    (Tz::America__New_York, 24.5, 47.5, -87.5, -66.9),
+    (Tz::America__Hermosillo, 26.0, 32.5, -115.0, -108.5),
+    (Tz::America__Cancun, 17.8, 21.7, -88.5, -86.7),
+    (Tz::America__Mexico_City, 14.5, 26.0, -106.0, -86.7),
+    // South America, Africa
+    (Tz::America__Sao_Paulo, -33.8, -14.0, -57.6, -34.8),
+    (Tz::America__Santiago, -27.0, -18.3, -72.0, -67.0),
+    (Tz::America__Santiago, -39.0, -27.0, -75.8, -69.9),
+    (Tz::America__Santiago, -56.0, -39.0, -75.8, -71.6),
+    (Tz::America__Santiago, -56.0, -52.0, -75.8, -68.6),
+    (Tz::America__Argentina__Buenos_Aires, -55.1, -21.8, -73.6, -53.6),
+    (Tz::Africa__Johannesburg, -35.0, -22.1, 16.4, 32.9),
+    // Asia
+    (Tz::Asia__Dubai, 22.6, 26.1, 51.5, 56.4),
+    (Tz::Asia__Kolkata, 6.7, 35.5, 68.1, 88.0),
+    (Tz::Asia__Bangkok, 5.6, 20.5, 97.3, 105.7),
+    (Tz::Asia__Kuala_Lumpur, 1.0, 7.4, 99.6, 119.3),
+    (Tz::Asia__Tokyo, 24.0, 45.6, 122.9, 146.0),
+    (Tz::Asia__Shanghai, 18.1, 53.6, 97.4, 134.8),
+    // Oceania
+    (Tz::Australia__Perth, -35.2, -13.7, 112.9, 129.0),
+    (Tz::Australia__Brisbane, -28.2, -10.0, 138.0, 153.7),
+    (Tz::Australia__Sydney, -39.2, -28.2, 141.0, 153.7),
+    (Tz::Pacific__Auckland, -47.5, -34.3, 166.3, 178.6),
+];
+
+/// Time zone at the given coordinates: the zone of a known region, else the nautical zone
+/// of the longitude (a whole hour per 15 degrees, without daylight saving time)
+pub fn time_zone_at(latitude: f64, longitude: f64) -> Tz {
+    REGIONS
+        .iter()
+        .find(|(_, south, north, west, east)| {
+            (*south..=*north).contains(&latitude) && (*west..=*east).contains(&longitude)
+        })
+        .map(|(zone, ..)| *zone)
+        .unwrap_or_else(|| nautical_time_zone(longitude))
+}
+
+fn nautical_time_zone(longitude: f64) -> Tz {
+    let hours = (longitude / 15.0).round().clamp(-12.0, 12.0) as i32;
+    // `Etc/GMT-1` is one hour east of UTC: the sign is the other way around
+    let name = match hours {
+        0 => "Etc/GMT".to_string(),
+        _ => format!("Etc/GMT{:+}", -hours),
+    };
+    name.parse().unwrap_or(Tz::UTC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(52.37, 4.89, Tz::Europe__Amsterdam)]
+    #[case(52.52, 13.40, Tz::Europe__Berlin)]
+    #[case(40.42, -3.70, Tz::Europe__Madrid)]
+    #[case(37.77, -122.42, Tz::America__Los_Angeles)]
+    #[case(33.45, -112.07, Tz::America__Phoenix)]
+    #[case(35.68, 139.69, Tz::Asia__Tokyo)]
+    #[case(-33.87, 151.21, Tz::Australia__Sydney)]
+    #[case(-27.47, 153.03, Tz::Australia__Brisbane)]
+    #[case(38.72, -9.14, Tz::Europe__Lisbon)]
+    #[case(41.15, -8.61, Tz::Europe__Lisbon)]
+    #[case(38.88, -6.97, Tz::Europe__Madrid)]
+    #[case(37.26, -6.95, Tz::Europe__Madrid)]
+    #[case(42.88, -8.54, Tz::Europe__Madrid)]
+    #[case(49.92, 1.08, Tz::Europe__Paris)]
+    #[case(50.73, 1.61, Tz::Europe__Paris)]
+    #[case(50.82, -0.14, Tz::Europe__London)]
+    #[case(51.13, 1.31, Tz::Europe__London)]
+    #[case(59.91, 10.75, Tz::Europe__Oslo)]
+    #[case(59.33, 18.07, Tz::Europe__Stockholm)]
+    #[case(55.68, 12.57, Tz::Europe__Copenhagen)]
+    #[case(60.17, 24.94, Tz::Europe__Helsinki)]
+    #[case(19.43, -99.13, Tz::America__Mexico_City)]
+    #[case(-34.60, -58.38, Tz::America__Argentina__Buenos_Aires)]
+    #[case(-33.45, -70.67, Tz::America__Santiago)]
+    fn test_time_zone_of_known_region(#[case] latitude: f64, #[case] longitude: f64, #[case] expected: Tz) {
+        // Act & Assert
+        assert_eq!(time_zone_at(latitude, longitude), expected);
+    }
+
+    #[rstest]
+    #[case(-17.7, -149.4, "Etc/GMT+10")]
+    #[case(64.1, -21.9, "Etc/GMT+1")]
+    #[case(0.0, 0.0, "Etc/GMT")]
+    #[case(-8.5, 179.2, "Etc/GMT-12")]
+    fn test_time_zone_falls_back_to_nautical_zone(#[case] latitude: f64, #[case] longitude: f64, #[case] expected: &str) {
+        // Act & Assert
+        assert_eq!(time_zone_at(latitude, longitude).name(), expected);
+    }
+}
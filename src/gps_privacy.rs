@@ -0,0 +1,239 @@
+// Rounds GPS coordinates embedded in EXIF metadata, so copies keep enough
+// location context for sorting without revealing exact places such as home.
+
+const TAG_GPS_IFD_POINTER: u16 = 0x8825;
+const TAG_GPS_LATITUDE: u16 = 0x0002;
+const TAG_GPS_LONGITUDE: u16 = 0x0004;
+const TYPE_RATIONAL: u16 = 5;
+
+/// Returns a copy of the image with GPS latitude/longitude rounded to `decimals`
+/// decimal degrees (2 decimals is roughly 1 km), or `None` when the image has no
+/// GPS coordinates that could be rewritten. Supports JPEG and TIFF-based files;
+/// values are patched in place so the file layout does not change.
+pub fn round_gps_coordinates(image_data: &[u8], decimals: u32) -> Option<Vec<u8>> {
+    let tiff_start = find_tiff_header(image_data)?;
+    let mut data = image_data.to_vec();
+    let tiff = Tiff::new(&image_data[tiff_start..])?;
+
+    let gps_ifd_offset = tiff.find_entry(tiff.first_ifd_offset()?, TAG_GPS_IFD_POINTER)?.value_offset;
+    let mut rounded_any = false;
+
+    for tag in [TAG_GPS_LATITUDE, TAG_GPS_LONGITUDE] {
+        let Some(entry) = tiff.find_entry(gps_ifd_offset, tag) else {
+            continue;
+        };
+        if entry.field_type != TYPE_RATIONAL || entry.count != 3 {
+            continue;
+        }
+
+        let value_start = tiff_start + entry.value_offset as usize;
+        let degrees = tiff.read_degrees(entry.value_offset as usize)?;
+        let scale = 10u32.checked_pow(decimals)?;
+        let rounded = (degrees * scale as f64).round() as u32;
+
+        let replacement = [(rounded, scale), (0, 1), (0, 1)];
+        for (index, (numerator, denominator)) in replacement.iter().enumerate() {
+            let offset = value_start + index * 8;
+            data.get(offset..offset + 8)?;
+            data[offset..offset + 4].copy_from_slice(&tiff.encode_u32(*numerator));
+            data[offset + 4..offset + 8].copy_from_slice(&tiff.encode_u32(*denominator));
+        }
+        rounded_any = true;
+    }
+
+    rounded_any.then_some(data)
+}
+
+/// Offset of the TIFF header inside a JPEG APP1 Exif segment, or 0 for TIFF files
+fn find_tiff_header(data: &[u8]) -> Option<usize> {
+    if data.starts_with(b"II*\0") || data.starts_with(b"MM\0*") {
+        return Some(0);
+    }
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+
+    let mut position = 2;
+    while position + 4 <= data.len() && data[position] == 0xFF {
+        let marker = data[position + 1];
+        let length = u16::from_be_bytes([data[position + 2], data[position + 3]]) as usize;
+        let segment = data.get(position + 4..position + 2 + length)?;
+
+        if marker == 0xE1 && segment.starts_with(b"Exif\0\0") {
+            return Some(position + 4 + 6);
+        }
+        // Start of scan: no more metadata segments follow
+        if marker == 0xDA {
+            return None;
+        }
+        position += 2 + length;
+    }
+
+    None
+}
+
+struct IfdEntry {
+    field_type: u16,
+    count: u32,
+    value_offset: u32,
+}
+
+/// Minimal read-only view on a TIFF structure
+struct Tiff<'a> {
+    data: &'a [u8],
+    little_endian: bool,
+}
+
+impl<'a> Tiff<'a> {
+    fn new(data: &'a [u8]) -> Option<Self> {
+        let little_endian = match data.get(0..2)? {
+            b"II" => true,
+            b"MM" => false,
+            _ => return None,
+        };
+        Some(Self {
+            data,
+            little_endian,
+        })
+    }
+
+    fn first_ifd_offset(&self) -> Option<u32> {
+        self.read_u32(4)
+    }
+
+    fn find_entry(&self, ifd_offset: u32, tag: u16) -> Option<IfdEntry> {
+        let ifd_offset = ifd_offset as usize;
+        let entry_count = self.read_u16(ifd_offset)? as usize;
+
+        (0..entry_count).find_map(|index| {
+            let entry_offset = ifd_offset + 2 + index * 12;
+            (self.read_u16(entry_offset)? == tag).then_some(())?;
+            Some(IfdEntry {
+                field_type: self.read_u16(entry_offset + 2)?,
+                count: self.read_u32(entry_offset + 4)?,
+                value_offset: self.read_u32(entry_offset + 8)?,
+            })
+        })
+    }
+
+    /// Reads degrees/minutes/seconds rationals as decimal degrees
+    fn read_degrees(&self, offset: usize) -> Option<f64> {
+        let mut degrees = 0.0;
+        for (index, divisor) in [1.0, 60.0, 3600.0].iter().enumerate() {
+            let numerator = self.read_u32(offset + index * 8)? as f64;
+            let denominator = self.read_u32(offset + index * 8 + 4)? as f64;
+            if denominator != 0.0 {
+                degrees += numerator / denominator / divisor;
+            }
+        }
+        Some(degrees)
+    }
+
+    fn read_u16(&self, offset: usize) -> Option<u16> {
+        let bytes: [u8; 2] = self.data.get(offset..offset + 2)?.try_into().ok()?;
+        Some(if self.little_endian {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        })
+    }
+
+    fn read_u32(&self, offset: usize) -> Option<u32> {
+        let bytes: [u8; 4] = self.data.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if self.little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+
+    fn encode_u32(&self, value: u32) -> [u8; 4] {
+        if self.little_endian {
+            value.to_le_bytes()
+        } else {
+            value.to_be_bytes()
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use exif::{In, Tag, Value};
+
+    /// Builds a minimal JPEG whose EXIF only holds GPS latitude/longitude as D/M/S
+    pub(crate) fn jpeg_with_gps(latitude: [(u32, u32); 3], longitude: [(u32, u32); 3]) -> Vec<u8> {
+        let mut tiff = Vec::new();
+        tiff.extend_from_slice(b"II*\0");
+        tiff.extend_from_slice(&8u32.to_le_bytes());
+        // IFD0 at 8: one entry pointing to the GPS IFD at 26
+        tiff.extend_from_slice(&1u16.to_le_bytes());
+        tiff.extend_from_slice(&TAG_GPS_IFD_POINTER.to_le_bytes());
+        tiff.extend_from_slice(&4u16.to_le_bytes());
+        tiff.extend_from_slice(&1u32.to_le_bytes());
+        tiff.extend_from_slice(&26u32.to_le_bytes());
+        tiff.extend_from_slice(&0u32.to_le_bytes());
+        // GPS IFD at 26: two entries, values start at 26 + 2 + 24 + 4 = 56
+        tiff.extend_from_slice(&2u16.to_le_bytes());
+        for (tag, offset) in [(TAG_GPS_LATITUDE, 56u32), (TAG_GPS_LONGITUDE, 80u32)] {
+            tiff.extend_from_slice(&tag.to_le_bytes());
+            tiff.extend_from_slice(&TYPE_RATIONAL.to_le_bytes());
+            tiff.extend_from_slice(&3u32.to_le_bytes());
+            tiff.extend_from_slice(&offset.to_le_bytes());
+        }
+        tiff.extend_from_slice(&0u32.to_le_bytes());
+        for (numerator, denominator) in latitude.iter().chain(longitude.iter()) {
+            tiff.extend_from_slice(&numerator.to_le_bytes());
+            tiff.extend_from_slice(&denominator.to_le_bytes());
+        }
+
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE1];
+        jpeg.extend_from_slice(&((tiff.len() + 8) as u16).to_be_bytes());
+        jpeg.extend_from_slice(b"Exif\0\0");
+        jpeg.extend_from_slice(&tiff);
+        jpeg.extend_from_slice(&[0xFF, 0xD9]);
+        jpeg
+    }
+
+    fn read_degrees_with_exif_crate(data: &[u8], tag: Tag) -> f64 {
+        let exif = exif::Reader::new()
+            .read_from_container(&mut std::io::Cursor::new(data))
+            .unwrap();
+        match &exif.get_field(tag, In::PRIMARY).unwrap().value {
+            Value::Rational(parts) => {
+                parts[0].to_f64() + parts[1].to_f64() / 60.0 + parts[2].to_f64() / 3600.0
+            }
+            other => panic!("unexpected value {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_round_gps_coordinates_rounds_latitude_and_longitude() {
+        // Arrange: 52° 22' 21.1234" N, 4° 53' 41.9876" E
+        let original = jpeg_with_gps(
+            [(52, 1), (22, 1), (211234, 10000)],
+            [(4, 1), (53, 1), (419876, 10000)],
+        );
+
+        // Act
+        let rounded = round_gps_coordinates(&original, 2).unwrap();
+
+        // Assert
+        assert_eq!(rounded.len(), original.len());
+        let latitude = read_degrees_with_exif_crate(&rounded, Tag::GPSLatitude);
+        let longitude = read_degrees_with_exif_crate(&rounded, Tag::GPSLongitude);
+        assert!((latitude - 52.37).abs() < 1e-9, "latitude {}", latitude);
+        assert!((longitude - 4.89).abs() < 1e-9, "longitude {}", longitude);
+    }
+
+    #[test]
+    fn test_round_gps_coordinates_returns_none_without_gps() {
+        // Arrange
+        let without_gps = include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg");
+
+        // Act & Assert
+        assert!(round_gps_coordinates(without_gps, 2).is_none());
+        assert!(round_gps_coordinates(&[0xFF, 0xD8, 0xFF, 0xD9], 2).is_none());
+        assert!(round_gps_coordinates(b"not an image", 2).is_none());
+    }
+}
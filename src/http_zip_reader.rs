@@ -0,0 +1,456 @@
+use crate::zip_image_reader::{
+    indices_by_name, is_selected, normalize_entry_name, read_content, zip_file_name, EntryInfo, EntryIter, LargeContent,
+    EntryMetadata, ZipEntry, ZipImageReader, DEFAULT_LARGE_ENTRY_BYTES,
+};
+use crate::s3::S3Credentials;
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use std::collections::HashSet;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::time::Duration;
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(60);
+/// Bytes fetched by a range request after a seek; small, since the ZIP reader jumps
+/// around to read headers
+const MIN_BLOCK_BYTES: u64 = 64 * 1024;
+/// Upper bound for the block size, which doubles while reading sequentially
+const MAX_BLOCK_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Whether an `--input` is a download URL rather than a local path
+pub fn is_http_url(path: &str) -> bool {
+    path.starts_with("http://") || path.starts_with("https://")
+}
+
+/// Random access to a remote file through HTTP range requests, one block at a time
+pub struct HttpRangeReader {
+    agent: ureq::Agent,
+    url: String,
+    credentials: Option<S3Credentials>,
+    len: u64,
+    position: u64,
+    block_start: u64,
+    block: Vec<u8>,
+}
+
+impl HttpRangeReader {
+    /// Probes the server with a one-byte range request to learn the file size
+    pub fn open(url: &str) -> Result<Self> {
+        Self::open_with_credentials(url, None)
+    }
+
+    /// Like `open`, signing every request for S3
+    pub fn open_with_credentials(url: &str, credentials: Option<S3Credentials>) -> Result<Self> {
+        let mut reader = Self {
+            agent: ureq::AgentBuilder::new().timeout(HTTP_TIMEOUT).build(),
+            url: url.to_string(),
+            credentials,
+            len: 0,
+            position: 0,
+            block_start: 0,
+            block: Vec::new(),
+        };
+        let response = reader
+            .get_range("bytes=0-0")
+            .with_context(|| format!("Failed to request: {}", url))?;
+
+        if response.status() != 206 {
+            bail!("Server does not support range requests: {}", url);
+        }
+        reader.len = response
+            .header("Content-Range")
+            .and_then(content_range_len)
+            .with_context(|| format!("Missing or invalid Content-Range from: {}", url))?;
+
+        Ok(reader)
+    }
+
+    fn get_range(&self, range: &str) -> Result<ureq::Response> {
+        let request = self.agent.get(&self.url).set("Range", range);
+        let request = match &self.credentials {
+            Some(credentials) => credentials.sign(request, &[("range", range)], Utc::now()),
+            None => request,
+        };
+        Ok(request.call()?)
+    }
+
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn fetch_block(&mut self, start: u64) -> io::Result<()> {
+        let sequential = !self.block.is_empty() && start == self.block_start + self.block.len() as u64;
+        let block_bytes = if sequential {
+            (self.block.len() as u64 * 2).clamp(MIN_BLOCK_BYTES, MAX_BLOCK_BYTES)
+        } else {
+            MIN_BLOCK_BYTES
+        };
+        let end = (start + block_bytes).min(self.len) - 1;
+        let response = self
+            .get_range(&format!("bytes={}-{}", start, end))
+            .map_err(io::Error::other)?;
+        if response.status() != 206 {
+            return Err(io::Error::other(format!(
+                "Expected partial content for range {}-{}, got status {}",
+                start,
+                end,
+                response.status()
+            )));
+        }
+
+        self.block.clear();
+        response
+            .into_reader()
+            .take(end - start + 1)
+            .read_to_end(&mut self.block)?;
+        self.block_start = start;
+        Ok(())
+    }
+}
+
+/// Total size from a `Content-Range: bytes 0-0/12345` header
+fn content_range_len(header: &str) -> Option<u64> {
+    header.rsplit_once('/')?.1.trim().parse().ok()
+}
+
+impl Read for HttpRangeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.len || buf.is_empty() {
+            return Ok(0);
+        }
+
+        let block_end = self.block_start + self.block.len() as u64;
+        if self.position < self.block_start || self.position >= block_end {
+            self.fetch_block(self.position)?;
+        }
+
+        let offset = (self.position - self.block_start) as usize;
+        let available = &self.block[offset..];
+        let count = available.len().min(buf.len());
+        if count == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Server returned fewer bytes than requested",
+            ));
+        }
+
+        buf[..count].copy_from_slice(&available[..count]);
+        self.position += count as u64;
+        Ok(count)
+    }
+}
+
+impl Seek for HttpRangeReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+
+        self.position = position.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "Seek before start of remote file")
+        })?;
+        Ok(self.position)
+    }
+}
+
+/// Reads the files of a ZIP archive at an HTTP(S) URL without downloading all of it:
+/// the central directory and the entries read are fetched with range requests
+#[derive(Clone)]
+pub struct HttpZipImageReader {
+    url: String,
+    credentials: Option<S3Credentials>,
+    large_entry_bytes: u64,
+}
+
+impl HttpZipImageReader {
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            credentials: None,
+            large_entry_bytes: DEFAULT_LARGE_ENTRY_BYTES,
+        }
+    }
+
+    /// Signs the requests, for archives stored in S3
+    pub fn with_s3_credentials(mut self, credentials: Option<S3Credentials>) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
+    /// Checks the name from the central directory, which is already loaded; opening an
+    /// entry costs a request for its local header
+    fn may_be_file(archive: &zip::ZipArchive<HttpRangeReader>, index: usize) -> bool {
+        archive
+            .name_for_index(index)
+            .is_none_or(|name| !normalize_entry_name(name).ends_with('/'))
+    }
+
+    fn read_selected(&self, names: Option<HashSet<String>>) -> Result<EntryIter<'_>> {
+        let mut archive = open_remote_archive(&self.url, self.credentials.as_ref())?;
+        let order = indices_by_name(&archive);
+
+        Ok(Box::new(order.into_iter().filter_map(move |i| {
+            self.read_zip_entry(&mut archive, i, names.as_ref()).transpose()
+        })))
+    }
+
+    fn read_zip_entry(
+        &self,
+        archive: &mut zip::ZipArchive<HttpRangeReader>,
+        index: usize,
+        names: Option<&HashSet<String>>,
+    ) -> Result<Option<ZipEntry>> {
+        let unselected = archive
+            .name_for_index(index)
+            .is_some_and(|name| !is_selected(names, &normalize_entry_name(name)));
+        if unselected || !Self::may_be_file(archive, index) {
+            return Ok(None);
+        }
+
+        let mut zip_file = archive
+            .by_index(index)
+            .with_context(|| format!("Failed to read entry at index {}", index))?;
+        let Some(name) = zip_file_name(&zip_file).filter(|name| is_selected(names, name)) else {
+            return Ok(None);
+        };
+
+        let size = zip_file.size();
+        let data = read_content(&mut zip_file, size, self.large_entry_bytes)
+            .with_context(|| format!("Failed to read data for file: {}", name))?;
+        let large = (size > self.large_entry_bytes).then(|| LargeContent::HttpZipEntry {
+            url: self.url.clone(),
+            credentials: self.credentials.clone(),
+            index,
+            size,
+        });
+
+        let metadata = EntryMetadata::from_zip_file(&zip_file);
+
+        Ok(Some(ZipEntry {
+            name,
+            data: data.into(),
+            large,
+            metadata,
+        }))
+    }
+}
+
+impl ZipImageReader for HttpZipImageReader {
+    fn entries(&self) -> Result<EntryIter<'_>> {
+        self.read_selected(None)
+    }
+
+    fn entries_named(&self, names: HashSet<String>) -> Result<EntryIter<'_>> {
+        self.read_selected(Some(names))
+    }
+
+    fn list_entries(&self) -> Result<Vec<EntryInfo>> {
+        let mut archive = open_remote_archive(&self.url, self.credentials.as_ref())?;
+        let mut listing = Vec::new();
+
+        for i in indices_by_name(&archive) {
+            if !Self::may_be_file(&archive, i) {
+                continue;
+            }
+            let zip_file = archive
+                .by_index_raw(i)
+                .with_context(|| format!("Failed to read entry at index {}", i))?;
+
+            if let Some(name) = zip_file_name(&zip_file) {
+                listing.push(EntryInfo {
+                    name,
+                    size: zip_file.size(),
+                });
+            }
+        }
+
+        Ok(listing)
+    }
+}
+
+fn open_remote_archive(
+    url: &str,
+    credentials: Option<&S3Credentials>,
+) -> Result<zip::ZipArchive<HttpRangeReader>> {
+    let reader = HttpRangeReader::open_with_credentials(url, credentials.cloned())?;
+    zip::ZipArchive::new(reader).context("Failed to read ZIP archive")
+}
+
+/// Copies entry `index` of the remote ZIP archive at `url` to `out`
+pub(crate) fn copy_http_zip_entry(
+    url: &str,
+    credentials: Option<&S3Credentials>,
+    index: usize,
+    out: &mut dyn Write,
+) -> Result<u64> {
+    let mut archive = open_remote_archive(url, credentials)?;
+    let mut zip_file = archive
+        .by_index(index)
+        .with_context(|| format!("Failed to read entry at index {}", index))?;
+    Ok(io::copy(&mut zip_file, out)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_writer::StreamContent;
+    use crate::zip_image_reader::EntrySelectionReader;
+    use std::io::{BufRead, BufReader, Cursor};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use zip::write::{FileOptions, ZipWriter};
+
+    fn create_zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options: FileOptions<()> =
+            FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        for (name, data) in files {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    /// Serves `data` on a local port, answering `Range` requests with 206 when
+    /// `ranges` is set; returns the URL and the number of requests served
+    fn serve(data: Vec<u8>, ranges: bool) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/takeout.zip", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let served = Arc::clone(&requests);
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut reader = BufReader::new(stream.unwrap());
+                let mut range = None;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header == "\r\n" || header.is_empty() {
+                        break;
+                    }
+                    if let Some(value) = header.to_lowercase().strip_prefix("range: bytes=") {
+                        let (start, end) = value.trim().split_once('-').unwrap();
+                        range = Some((start.parse::<usize>().unwrap(), end.parse::<usize>().unwrap()));
+                    }
+                }
+                served.fetch_add(1, Ordering::SeqCst);
+
+                let (status, body, content_range) = match range.filter(|_| ranges) {
+                    Some((start, end)) => (
+                        "206 Partial Content",
+                        &data[start..=end],
+                        format!("Content-Range: bytes {}-{}/{}\r\n", start, end, data.len()),
+                    ),
+                    None => ("200 OK", &data[..], String::new()),
+                };
+                let stream = reader.get_mut();
+                write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n",
+                    status,
+                    body.len(),
+                    content_range
+                )
+                .unwrap();
+                stream.write_all(body).unwrap();
+            }
+        });
+
+        (url, requests)
+    }
+
+    #[test]
+    fn test_read_entries_from_url() {
+        // Arrange
+        let archive = create_zip(&[
+            ("Takeout/photo1.jpg", b"fake jpg data"),
+            ("Takeout/metadata.json", b"{}"),
+            ("Takeout/photo2.png", b"fake png data"),
+        ]);
+        let (url, _) = serve(archive, true);
+        let http_reader = HttpZipImageReader::new(url);
+        let reader = EntrySelectionReader::new(&http_reader);
+
+        // Act
+        let entries = reader.read_entries().unwrap();
+        let listing = reader.list_entries().unwrap();
+
+        // Assert
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].name, "Takeout/photo1.jpg");
+        assert_eq!(entries[0].data, b"fake jpg data");
+        assert_eq!(entries[1].data, b"fake png data");
+        assert_eq!(listing.len(), 2);
+    }
+
+    #[test]
+    fn test_large_remote_entries_are_streamed() {
+        // Arrange
+        let large_data = vec![3u8; 5000];
+        let (url, _) = serve(create_zip(&[("Takeout/clip.mp4", &large_data)]), true);
+        let reader = HttpZipImageReader {
+            large_entry_bytes: 100,
+            ..HttpZipImageReader::new(url)
+        };
+
+        // Act
+        let entries = reader.read_entries().unwrap();
+        let mut streamed = Vec::new();
+        entries[0].large.as_ref().unwrap().copy_to(&mut streamed).unwrap();
+
+        // Assert
+        assert!(entries[0].large.is_some());
+        assert_eq!(streamed, large_data);
+    }
+
+    #[test]
+    fn test_range_reader_reuses_fetched_block() {
+        // Arrange
+        let data: Vec<u8> = (0..=255).collect();
+        let (url, requests) = serve(data, true);
+        let mut reader = HttpRangeReader::open(&url).unwrap();
+
+        // Act
+        let mut head = [0u8; 4];
+        reader.seek(SeekFrom::Start(10)).unwrap();
+        reader.read_exact(&mut head).unwrap();
+        let mut tail = [0u8; 6];
+        reader.seek(SeekFrom::End(-6)).unwrap();
+        reader.read_exact(&mut tail).unwrap();
+
+        // Assert
+        assert_eq!(reader.len(), 256);
+        assert_eq!(tail, [250, 251, 252, 253, 254, 255]);
+        assert_eq!(head, [10, 11, 12, 13]);
+        // The probe and a single block, which also covers the tail
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_server_without_range_support_is_rejected() {
+        // Arrange
+        let (url, _) = serve(create_zip(&[("photo.jpg", b"data")]), false);
+
+        // Act
+        let result = HttpZipImageReader::new(url).list_entries();
+
+        // Assert
+        let error = format!("{:#}", result.err().unwrap());
+        assert!(error.contains("does not support range requests"), "{}", error);
+    }
+
+    #[test]
+    fn test_is_http_url() {
+        assert!(is_http_url("https://example.com/takeout.zip"));
+        assert!(is_http_url("http://nas.local/takeout.zip"));
+        assert!(!is_http_url("takeout.zip"));
+    }
+}
@@ -0,0 +1,263 @@
+// ISO base media file format: the boxes of MP4, QuickTime and HEIF (HEIC, AVIF) files
+
+/// Boxes directly inside `data`, as (type, content). A box running past the end of the
+/// data (an entry read in part) is cut off there.
+pub(crate) fn boxes(data: &[u8]) -> impl Iterator<Item = (&[u8], &[u8])> {
+    let mut rest = data;
+    std::iter::from_fn(move || {
+        let size = u32::from_be_bytes(rest.get(..4)?.try_into().ok()?) as u64;
+        let box_type = rest.get(4..8)?;
+        let (header_len, box_len) = match size {
+            0 => (8, rest.len() as u64),
+            1 => (16, u64::from_be_bytes(rest.get(8..16)?.try_into().ok()?)),
+            _ => (8, size),
+        };
+        if box_len < header_len as u64 {
+            return None;
+        }
+        let end = usize::try_from(box_len).ok()?.min(rest.len());
+        let content = rest.get(header_len..end)?;
+        rest = &rest[end..];
+        Some((box_type, content))
+    })
+}
+
+pub(crate) fn find_box<'d>(data: &'d [u8], box_type: &[u8; 4]) -> Option<&'d [u8]> {
+    boxes(data).find(|(found, _)| *found == box_type).map(|(_, content)| content)
+}
+
+/// The TIFF data of the `Exif` item of a HEIF image (HEIC, HEIF, AVIF): the item is looked
+/// up in `meta/iinf`, located through `meta/iloc` in the file or in `meta/idat`, and starts
+/// with the offset of the TIFF header within the rest of it
+pub(crate) fn heif_exif(data: &[u8]) -> Option<&[u8]> {
+    if data.get(4..8) != Some(b"ftyp") {
+        return None;
+    }
+    // meta is a full box: version and flags come first
+    let meta = find_box(data, b"meta")?.get(4..)?;
+    let item_id = exif_item_id(find_box(meta, b"iinf")?)?;
+    let location = item_locations(find_box(meta, b"iloc")?)?
+        .into_iter()
+        .find(|location| location.item_id == item_id)?;
+
+    let source = match location.construction_method {
+        0 => data,
+        1 => find_box(meta, b"idat")?,
+        _ => return None,
+    };
+    // Exif items have a single extent in practice; an extent length of 0 runs to the end
+    let &(offset, length) = location.extents.first()?;
+    let start = usize::try_from(location.base_offset.checked_add(offset)?).ok()?;
+    let end = match length {
+        0 => source.len(),
+        _ => start.checked_add(usize::try_from(length).ok()?)?.min(source.len()),
+    };
+    let item = source.get(start..end)?;
+
+    let tiff_offset = u32::from_be_bytes(item.get(..4)?.try_into().ok()?) as usize;
+    item.get(4usize.checked_add(tiff_offset)?..)
+}
+
+/// Item id of the first item of type `Exif` in an `iinf` box
+fn exif_item_id(iinf: &[u8]) -> Option<u32> {
+    let version = *iinf.first()?;
+    let entries = iinf.get(if version == 0 { 6 } else { 8 }..)?;
+
+    boxes(entries)
+        .filter(|(box_type, _)| *box_type == b"infe")
+        .find_map(|(_, infe)| {
+            let mut reader = ByteReader::new(infe);
+            let version = reader.uint(1)?;
+            reader.uint(3)?;
+            // Versions 0 and 1 have no item type
+            let item_id = match version {
+                2 => reader.uint(2)?,
+                3 => reader.uint(4)?,
+                _ => return None,
+            };
+            reader.uint(2)?;
+            (reader.bytes(4)? == b"Exif").then_some(item_id as u32)
+        })
+}
+
+struct ItemLocation {
+    item_id: u32,
+    construction_method: u8,
+    base_offset: u64,
+    /// (offset, length) of each extent
+    extents: Vec<(u64, u64)>,
+}
+
+fn item_locations(iloc: &[u8]) -> Option<Vec<ItemLocation>> {
+    let mut reader = ByteReader::new(iloc);
+    let version = reader.uint(1)?;
+    reader.uint(3)?;
+    let sizes = reader.uint(1)? as u8;
+    let (offset_size, length_size) = (sizes >> 4, sizes & 0x0f);
+    let sizes = reader.uint(1)? as u8;
+    let base_offset_size = sizes >> 4;
+    let index_size = if version > 0 { sizes & 0x0f } else { 0 };
+    let item_count = reader.uint(if version < 2 { 2 } else { 4 })?;
+
+    let mut locations = Vec::new();
+    for _ in 0..item_count {
+        let item_id = reader.uint(if version < 2 { 2 } else { 4 })? as u32;
+        let construction_method = match version {
+            0 => 0,
+            _ => (reader.uint(2)? & 0x0f) as u8,
+        };
+        reader.uint(2)?;
+        let base_offset = reader.uint(base_offset_size)?;
+        let extent_count = reader.uint(2)?;
+        let mut extents = Vec::new();
+        for _ in 0..extent_count {
+            reader.uint(index_size)?;
+            extents.push((reader.uint(offset_size)?, reader.uint(length_size)?));
+        }
+        locations.push(ItemLocation {
+            item_id,
+            construction_method,
+            base_offset,
+            extents,
+        });
+    }
+    Some(locations)
+}
+
+/// Reads big-endian fields front to back
+struct ByteReader<'d> {
+    data: &'d [u8],
+}
+
+impl<'d> ByteReader<'d> {
+    fn new(data: &'d [u8]) -> Self {
+        Self { data }
+    }
+
+    fn bytes(&mut self, len: usize) -> Option<&'d [u8]> {
+        let bytes = self.data.get(..len)?;
+        self.data = &self.data[len..];
+        Some(bytes)
+    }
+
+    /// An unsigned field of 0 to 8 bytes; 0 bytes read as 0
+    fn uint(&mut self, len: u8) -> Option<u64> {
+        if len > 8 {
+            return None;
+        }
+        Some(
+            self.bytes(len as usize)?
+                .iter()
+                .fold(0, |value, &byte| (value << 8) | byte as u64),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn heif_box(box_type: &[u8; 4], content: &[u8]) -> Vec<u8> {
+        let mut data = ((content.len() + 8) as u32).to_be_bytes().to_vec();
+        data.extend_from_slice(box_type);
+        data.extend_from_slice(content);
+        data
+    }
+
+    fn infe(item_id: u16, item_type: &[u8; 4]) -> Vec<u8> {
+        let mut content = vec![2, 0, 0, 0];
+        content.extend_from_slice(&item_id.to_be_bytes());
+        content.extend_from_slice(&[0, 0]);
+        content.extend_from_slice(item_type);
+        content.push(0);
+        heif_box(b"infe", &content)
+    }
+
+    /// A HEIF with an image item and an Exif item stored with the given construction method
+    fn heif_with_exif(tiff: &[u8], construction_method: u8) -> Vec<u8> {
+        let mut exif_item = 6u32.to_be_bytes().to_vec();
+        exif_item.extend_from_slice(b"Exif\0\0");
+        exif_item.extend_from_slice(tiff);
+
+        let mut iinf = vec![0, 0, 0, 0, 0, 2];
+        iinf.extend(infe(1, b"hvc1"));
+        iinf.extend(infe(2, b"Exif"));
+
+        let ftyp = heif_box(b"ftyp", b"heic\0\0\0\0mif1heic");
+        let iloc_len = 8 + 4 + 2 + 2 + 2 * (2 + 2 + 2 + 4 + 2 + 4 + 4);
+        let idat = heif_box(b"idat", &exif_item);
+        let meta_len = 8 + 4 + 8 + 24 + 8 + iinf.len() + iloc_len
+            + if construction_method == 1 { idat.len() } else { 0 };
+        let mdat_start = (ftyp.len() + meta_len + 8) as u32;
+
+        let mut iloc = vec![1, 0, 0, 0, 0x44, 0x40, 0, 2];
+        for (item_id, method, offset, length) in [
+            (1u16, 0u16, mdat_start, 4u32),
+            match construction_method {
+                1 => (2, 1, 0, exif_item.len() as u32),
+                _ => (2, 0, mdat_start + 4, exif_item.len() as u32),
+            },
+        ] {
+            iloc.extend_from_slice(&item_id.to_be_bytes());
+            iloc.extend_from_slice(&method.to_be_bytes());
+            iloc.extend_from_slice(&[0, 0]);
+            iloc.extend_from_slice(&0u32.to_be_bytes());
+            iloc.extend_from_slice(&1u16.to_be_bytes());
+            iloc.extend_from_slice(&offset.to_be_bytes());
+            iloc.extend_from_slice(&length.to_be_bytes());
+        }
+
+        let mut meta = vec![0, 0, 0, 0];
+        meta.extend(heif_box(b"hdlr", &[0; 24]));
+        meta.extend(heif_box(b"iinf", &iinf));
+        meta.extend(heif_box(b"iloc", &iloc));
+        if construction_method == 1 {
+            meta.extend(idat);
+        }
+        let mut mdat = vec![0; 4];
+        if construction_method == 0 {
+            mdat.extend(exif_item);
+        }
+
+        let mut heif = ftyp;
+        heif.extend(heif_box(b"meta", &meta));
+        assert_eq!(heif.len() + 8, mdat_start as usize);
+        heif.extend(heif_box(b"mdat", &mdat));
+        heif
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(1)]
+    fn test_heif_exif_finds_exif_item(#[case] construction_method: u8) {
+        // Arrange
+        let heif = heif_with_exif(b"II*\0tiff", construction_method);
+
+        // Act
+        let tiff = heif_exif(&heif);
+
+        // Assert
+        assert_eq!(tiff, Some(&b"II*\0tiff"[..]));
+    }
+
+    #[test]
+    fn test_heif_exif_of_fixture() {
+        // Arrange
+        let heic = include_bytes!("../tests/fixtures/heic_with_exif.heic");
+
+        // Act
+        let tiff = heif_exif(heic).expect("Exif item");
+
+        // Assert
+        assert!(tiff.starts_with(b"MM\0*"));
+    }
+
+    #[rstest]
+    #[case(b"\xFF\xD8\xFF\xD9".to_vec())]
+    #[case(heif_box(b"ftyp", b"heic\0\0\0\0"))]
+    fn test_heif_exif_without_exif_item(#[case] data: Vec<u8>) {
+        // Act & Assert
+        assert_eq!(heif_exif(&data), None);
+    }
+}
@@ -1,8 +1,34 @@
 // Library exports for integration tests and external use
 
+pub mod archive_index;
+pub mod config;
+pub mod device;
+pub mod events;
 pub mod exif;
 pub mod file_writer;
+pub mod metrics;
+pub mod motion_photo;
+pub mod notify;
+pub mod geo_time_zone;
+pub mod gps_privacy;
+pub mod http_zip_reader;
+pub mod isobmff;
+pub mod meta_export;
 pub mod organizer;
 pub mod path_generator;
 pub mod photo_filter;
+pub mod png_metadata;
+pub mod profile;
+pub mod provenance;
+pub mod s3;
+pub mod scan;
+pub mod sidecar;
+pub mod snapchat;
+pub mod spool;
+pub mod split_zip;
+#[cfg(unix)]
+pub mod systemd;
+pub mod tar_image_reader;
+pub mod video_metadata;
+pub mod xmp;
 pub mod zip_image_reader;
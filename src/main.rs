@@ -1,18 +1,36 @@
-mod exif;
-mod file_writer;
-mod organizer;
-mod path_generator;
-mod photo_filter;
-mod zip_image_reader;
-
+use anyhow::Context;
+use chrono::{NaiveDate, TimeDelta};
 use clap::Parser;
-use exif::CompositeDateExtractor;
-use file_writer::RealFileSystemWriter;
-use organizer::PhotoOrganizer;
-use path_generator::PathGenerator;
-use photo_filter::{ExistingCollectionFilter, NoFilter};
-use zip_image_reader::{DirectoryImageReader, FileZipImageReader, ZipImageReader};
+use organize_photo_zip::config::Config;
+use organize_photo_zip::exif::{
+    ClockOffset, CompositeDateExtractor, Confidence, DateExtractor, DatePlausibility, DateSource, DateTimeZone,
+    FilenamePattern,
+};
+use organize_photo_zip::file_writer::RealFileSystemWriter;
+use organize_photo_zip::metrics;
+use organize_photo_zip::motion_photo::MotionPhotoPolicy;
+use organize_photo_zip::notify;
+use organize_photo_zip::organizer::{self, OutputExistsPolicy, PhotoOrganizer, Sample};
+use organize_photo_zip::path_generator::{PathGenerator, YearRoot};
+use organize_photo_zip::photo_filter::{
+    self, AlbumFilter, DuplicateLocale, ExistingCollectionFilter, HiddenFolderFilter, NoFilter,
+    ServiceFolderFilter,
+};
+use organize_photo_zip::profile::ExportProfile;
+#[cfg(unix)]
+use organize_photo_zip::systemd::SystemdObserver;
+use organize_photo_zip::http_zip_reader::{is_http_url, HttpZipImageReader};
+use organize_photo_zip::s3::{is_s3_url, S3Credentials, S3DirectoryImageReader, S3Location};
+use organize_photo_zip::spool::{SpooledInput, STDIN_INPUT};
+use organize_photo_zip::split_zip::{join_split_zip, split_parts};
+use organize_photo_zip::tar_image_reader::TarImageReader;
+use organize_photo_zip::zip_image_reader::{
+    DirectoryImageReader, EntryPatterns, EntrySelectionReader, ExtensionSet, FileZipImageReader, InputFormat, InputSpec,
+    MinSizeReader, MultiInputReader, SymlinkPolicy, ZipImageReader, DEFAULT_MIN_ENTRY_BYTES,
+};
+use std::collections::BTreeMap;
 use std::path::Path;
+use std::thread;
 
 /// Organize Google Photos exports into date-based directory structure
 
@@ -20,9 +38,40 @@ use std::path::Path;
 #[command(name = "organize-photo-zip")]
 #[command(version, about, long_about = None)]
 struct Args {
-    /// Path to the Google Photos ZIP file or directory
-    #[arg(short, long)]
-    input: String,
+    /// Path to the Google Photos ZIP file or directory. Repeat it (or use a glob such as
+    /// 'takeout-*.zip') for the parts of a split export; label inputs as LABEL=PATH to merge
+    /// several accounts (e.g. --input anna=takeout-a.zip --input ben=takeout-b.zip); use -
+    /// to read the archive from stdin, an http(s):// URL to read a ZIP from a server, or
+    /// s3://bucket/key for a ZIP (or s3://bucket/prefix/ for an extracted export) in S3
+    #[arg(short, long, required = true, value_name = "[LABEL=]PATH")]
+    input: Vec<InputSpec>,
+
+    /// How to read the inputs; by default detected per input from its path (directory,
+    /// .tar, .tgz/.tar.gz, otherwise ZIP)
+    #[arg(long, value_enum, default_value_t = InputFormat::Auto)]
+    format: InputFormat,
+
+    /// Which service made the export; by default Amazon Photos downloads are recognized by
+    /// their name (AmazonPhotos*.zip) and everything else is read as a Google Takeout
+    #[arg(long, value_enum, default_value_t = ExportProfile::Auto)]
+    profile: ExportProfile,
+
+    /// Whether the walk through directory inputs follows symbolic links
+    #[arg(long, value_enum, default_value_t = SymlinkPolicy::Follow)]
+    symlinks: SymlinkPolicy,
+
+    /// How many folder levels below a directory input to read (0: only its own files)
+    #[arg(long, value_name = "N")]
+    max_depth: Option<usize>,
+
+    /// Leave out entries smaller than this many bytes (0 keeps even empty files)
+    #[arg(long, value_name = "BYTES", default_value_t = DEFAULT_MIN_ENTRY_BYTES)]
+    min_size: u64,
+
+    /// Put each account's photos in a folder named after its input label beneath the date
+    /// level; photos of unlabelled inputs stay directly in the date folder
+    #[arg(long)]
+    owner_folders: bool,
 
     /// Output directory for organized photos
     #[arg(short, long, default_value = "./organized_photos")]
@@ -31,21 +80,309 @@ struct Args {
     /// Disable filtering (by default, DSLR/Lightroom/Google -MIX/-edited files are skipped)
     #[arg(short, long)]
     no_filter: bool,
+
+    /// Recognize Google-generated copies by only these suffixes instead of the built-in list
+    /// (comma-separated or repeated, e.g. -edited,-MIX); --add-duplicate-suffix still applies
+    #[arg(long, value_name = "SUFFIX", value_delimiter = ',', allow_hyphen_values = true)]
+    duplicate_suffixes: Vec<String>,
+
+    /// Also recognize Google-generated copies by these suffixes (comma-separated or repeated,
+    /// e.g. -MOTION)
+    #[arg(long, value_name = "SUFFIX", value_delimiter = ',', allow_hyphen_values = true)]
+    add_duplicate_suffix: Vec<String>,
+
+    /// Also recognize the suffixes Google Photos gives edited copies in these languages
+    /// (comma-separated or repeated, e.g. de for -bearbeitet)
+    #[arg(long, value_enum, value_name = "LANG", value_delimiter = ',')]
+    duplicate_locale: Vec<DuplicateLocale>,
+
+    /// Group photos by source device beneath the date level (e.g. 2021/2021-05-02/Pixel 5/)
+    #[arg(long)]
+    by_device: bool,
+
+    /// Also organize Google service folders (print subscriptions, photo books, print orders)
+    #[arg(long)]
+    include_service_folders: bool,
+
+    /// Also organize deleted photos (Trash/) and videos Google failed to process (Failed Videos/)
+    #[arg(long)]
+    include_trash: bool,
+
+    /// Also organize archived photos (Archive/)
+    #[arg(long)]
+    include_archive: bool,
+
+    /// Only read entries whose path matches this glob (repeatable, e.g. '*2019*')
+    #[arg(long, value_name = "GLOB")]
+    include: Vec<glob::Pattern>,
+
+    /// Skip entries whose path matches this glob (repeatable, e.g. '*/Trash/*')
+    #[arg(long, value_name = "GLOB")]
+    exclude: Vec<glob::Pattern>,
+
+    /// Only organize entries from this Takeout album folder (repeatable, glob patterns allowed)
+    #[arg(long, value_name = "NAME")]
+    album: Vec<glob::Pattern>,
+
+    /// Trial run: only process the first N entries that pass the filter
+    #[arg(long, value_name = "N", conflicts_with = "sample")]
+    limit: Option<usize>,
+
+    /// Trial run: only process this share of the entries, spread over the archive (e.g. 1%)
+    #[arg(long, value_name = "PERCENT")]
+    sample: Option<Sample>,
+
+    /// Show what would be written (itemized per file) without touching the output directory
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Replace existing output files that have different content (by default they are kept)
+    #[arg(long)]
+    force: bool,
+
+    /// Replace control characters, emoji and reserved characters in output filenames
+    #[arg(long)]
+    sanitize_names: bool,
+
+    /// Abort the run after this many failed files
+    #[arg(long, value_name = "N")]
+    max_errors: Option<usize>,
+
+    /// Organize videos as well as photos (default)
+    #[arg(long, overrides_with = "photos_only")]
+    include_videos: bool,
+
+    /// Only organize still images, skip videos
+    #[arg(long, overrides_with = "include_videos")]
+    photos_only: bool,
+
+    /// What to do with the video in motion photos (MVIMG_*.jpg, *.MP.jpg): keep it, strip it,
+    /// or split it out as a companion .mp4 next to the still image
+    #[arg(long, value_enum, default_value_t = MotionPhotoPolicy::Keep)]
+    motion_photos: MotionPhotoPolicy,
+
+    /// Round GPS coordinates in the copies to this many decimal degrees (2 is about 1 km)
+    #[arg(long, value_name = "DECIMALS", value_parser = clap::value_parser!(u32).range(0..=6))]
+    gps_precision: Option<u32>,
+
+    /// Only read files with these extensions instead of the built-in list (comma-separated
+    /// or repeated, e.g. jpg,heic,mp4); --add-extension and --remove-extension still apply
+    #[arg(long, value_name = "EXT", value_delimiter = ',')]
+    extensions: Vec<String>,
+
+    /// Also read files with these extensions (comma-separated or repeated, e.g. rw2,srw)
+    #[arg(long, value_name = "EXT", value_delimiter = ',')]
+    add_extension: Vec<String>,
+
+    /// Skip files with these extensions (comma-separated or repeated, e.g. gif)
+    #[arg(long, value_name = "EXT", value_delimiter = ',')]
+    remove_extension: Vec<String>,
+
+    /// Leave files of a directory input that nothing else dates undated instead of dating
+    /// them by their modification time
+    #[arg(long)]
+    no_file_time_fallback: bool,
+
+    /// Date sources to consult, in order (comma-separated, e.g. json,exif,filename; default:
+    /// exif,json,filename,folder,gps,album,takeout-year,mtime); listing folder dates every input by
+    /// folder names
+    #[arg(long, value_enum, value_name = "SOURCE", value_delimiter = ',')]
+    date_sources: Vec<DateSource>,
+
+    /// Also date files by this filename regex, with named groups year, month and day
+    /// (e.g. '^P(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})-' for P20120506-001.jpg); repeatable
+    #[arg(long, value_name = "REGEX", value_parser = FilenamePattern::parse)]
+    filename_pattern: Vec<FilenamePattern>,
+
+    /// Time zone for dating UTC timestamps such as sidecar and GPS times: local (default)
+    /// or a name like Europe/Amsterdam
+    #[arg(long, value_name = "ZONE")]
+    timezone: Option<DateTimeZone>,
+
+    /// Date photos with a location (and no EXIF OffsetTimeOriginal) in the time zone looked
+    /// up for where they were taken; approximate near borders
+    #[arg(long, conflicts_with = "timezone")]
+    location_timezone: bool,
+
+    /// Correct the EXIF capture time of a camera whose clock was off, as [MODEL=]OFFSET
+    /// (e.g. "NIKON D90=-3720s" or -1h02m for all cameras); repeatable
+    #[arg(long, value_name = "[MODEL=]OFFSET", value_parser = ClockOffset::parse, allow_hyphen_values = true)]
+    time_offset: Vec<ClockOffset>,
+
+    /// Pass over dates before this day and try the next date source
+    #[arg(long, value_name = "YYYY-MM-DD", default_value = "1900-01-01")]
+    earliest_date: NaiveDate,
+
+    /// Pass over these days, which cameras fall back to when their clock battery ran out
+    /// (comma-separated or repeated)
+    #[arg(long, value_name = "YYYY-MM-DD", value_delimiter = ',', default_values = ["1970-01-01", "1980-01-01", "2001-01-01"])]
+    dead_clock_dates: Vec<NaiveDate>,
+
+    /// Accept dates in the future, which are passed over by default
+    #[arg(long)]
+    allow_future_dates: bool,
+
+    /// Date -edited/-EFFECTS copies by their EXIF like other files; by default the JSON
+    /// sidecar comes first for them, as their EXIF may hold the time of the edit
+    #[arg(long)]
+    exif_first_for_edits: bool,
+
+    /// Take every date as found, without passing over implausible ones
+    #[arg(long, conflicts_with_all = ["earliest_date", "dead_clock_dates", "allow_future_dates"])]
+    no_date_checks: bool,
+
+    /// Place files dated less reliably than this (exif > sidecar > filename > mtime) in
+    /// review/YYYY/YYYY-MM-DD/ instead of among the rest, to check them by hand
+    #[arg(long, value_enum, value_name = "LEVEL")]
+    min_confidence: Option<Confidence>,
+
+    /// List files whose EXIF and JSON sidecar times differ by more than this many hours in
+    /// the summary, to review suspicious dates (e.g. 24); reads the embedded date of every file
+    /// with a sidecar once more
+    #[arg(long, value_name = "HOURS")]
+    date_mismatch_hours: Option<u32>,
+
+    /// Copy uncompressed ZIP entries into memory instead of reading them from the
+    /// memory-mapped archive (for archives that may change while they are read)
+    #[arg(long)]
+    no_mmap: bool,
+
+    /// Decompress ZIP entries on this many threads (default: one per CPU core)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    threads: Option<u32>,
+
+    /// Write a .origin.csv in each date directory mapping files back to archive and entry path
+    #[arg(long)]
+    provenance: bool,
+
+    /// Put a range of years under another root, e.g. 1990..2014=/mnt/old or 2015..=/mnt/new
+    /// (inclusive, repeatable; relative roots are inside the output directory)
+    #[arg(long, value_name = "FIRST..LAST=ROOT")]
+    year_root: Vec<YearRoot>,
+
+    /// Write run metrics (durations, counts, bytes, error categories) as JSON to this file
+    #[arg(long, value_name = "PATH")]
+    metrics_file: Option<String>,
+
+    /// Report readiness, status and watchdog pings to systemd and log to the journal
+    #[cfg(unix)]
+    #[arg(long)]
+    systemd: bool,
+
+    /// TOML config file (e.g. ntfy / e-mail notification settings)
+    #[arg(long, value_name = "PATH")]
+    config: Option<String>,
+
+    /// POST the run summary as JSON to this URL when the run finishes or fails
+    #[arg(long, value_name = "URL")]
+    notify_url: Option<String>,
+
+    /// What to do when the output directory already contains files
+    #[arg(long, value_enum, default_value_t = OutputExistsPolicy::Merge)]
+    output_exists: OutputExistsPolicy,
 }
 
 fn main() {
-    let args = Args::parse();
+    let mut args = Args::parse();
+    args.input = expand_inputs_or_exit(std::mem::take(&mut args.input));
+    let config = load_config_or_exit(&args);
     display_configuration(&args);
 
-    let result = organize_photos_from_zip(&args);
+    let result = organize_photos_from_zip(&args, &config);
+
+    publish_summary(&args, &config, &result);
 
     display_results_and_exit(result);
 }
 
+fn expand_inputs_or_exit(inputs: Vec<InputSpec>) -> Vec<InputSpec> {
+    let mut expanded = Vec::new();
+    for input in inputs {
+        match input.expand() {
+            Ok(parts) => expanded.extend(parts),
+            Err(e) => {
+                eprintln!("✗ {:#}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    expanded
+}
+
+fn load_config_or_exit(args: &Args) -> Config {
+    let Some(config_path) = &args.config else {
+        return Config::default();
+    };
+
+    match Config::load(Path::new(config_path)) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("✗ {:#}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn publish_summary(
+    args: &Args,
+    config: &Config,
+    result: &Result<organizer::OrganizeResult, anyhow::Error>,
+) {
+    let summary = match result {
+        Ok(result) => metrics::metrics_json(result),
+        Err(e) => metrics::failure_metrics_json(e),
+    };
+
+    if let Some(metrics_file) = &args.metrics_file {
+        if let Err(e) = metrics::write_metrics_file(Path::new(metrics_file), &summary) {
+            eprintln!("✗ {:#}", e);
+        }
+    }
+
+    if let Some(notify_url) = &args.notify_url {
+        if let Err(e) = notify::post_summary(notify_url, &summary) {
+            eprintln!("✗ {:#}", e);
+        }
+    }
+
+    for e in notify::send_notifications(&config.notify, &summary) {
+        eprintln!("✗ {:#}", e);
+    }
+}
+
 fn display_configuration(args: &Args) {
-    println!("Organizing photos from: {}", args.input);
+    for input in &args.input {
+        match &input.label {
+            Some(label) => println!("Organizing photos from: {} ({})", input.path, label),
+            None => println!("Organizing photos from: {}", input.path),
+        }
+    }
     println!("Output directory: {}", args.output);
     display_filter_status(args.no_filter);
+    if !args.album.is_empty() {
+        let albums: Vec<&str> = args.album.iter().map(|album| album.as_str()).collect();
+        println!("Albums: {}", albums.join(", "));
+    }
+    if !args.include_service_folders {
+        println!("Service folders: Skipping print subscriptions, photo books and print orders");
+    }
+    match (args.include_trash, args.include_archive) {
+        (false, false) => println!("Hidden folders: Skipping Trash, Archive and Failed Videos"),
+        (false, true) => println!("Hidden folders: Skipping Trash and Failed Videos"),
+        (true, false) => println!("Hidden folders: Skipping Archive"),
+        (true, true) => {}
+    }
+    if let Some(decimals) = args.gps_precision {
+        println!("GPS precision: coordinates rounded to {} decimals", decimals);
+    }
+    match args.limit.map(Sample::Limit).or(args.sample) {
+        Some(Sample::Limit(limit)) => println!("Trial run: only the first {} entries", limit),
+        Some(Sample::Percent(percent)) => println!("Trial run: sampling {}% of the entries", percent),
+        None => {}
+    }
+    if args.dry_run {
+        println!("Dry run: no files will be written");
+    }
     println!();
 }
 
@@ -57,50 +394,264 @@ fn display_filter_status(filtering_disabled: bool) {
     }
 }
 
-fn organize_photos_from_zip(args: &Args) -> Result<organizer::OrganizeResult, anyhow::Error> {
-    let input_path = Path::new(&args.input);
+fn organize_photos_from_zip(
+    args: &Args,
+    config: &Config,
+) -> Result<organizer::OrganizeResult, anyhow::Error> {
+    let base_extensions = if !args.extensions.is_empty() {
+        ExtensionSet::only(&args.extensions)
+    } else if let Some(only) = &config.extensions.only {
+        ExtensionSet::only(only)
+    } else {
+        ExtensionSet::default()
+    };
+    let extensions = base_extensions
+        .with_added(&config.extensions.add)
+        .with_removed(&config.extensions.remove)
+        .with_added(&args.add_extension)
+        .with_removed(&args.remove_extension);
+
+    // Kept alive until the run is done: large entries are streamed from the spool files
+    let mut spooled = Vec::new();
+    let mut readers: Vec<Box<dyn ZipImageReader>> = Vec::new();
+    for input in &args.input {
+        let (path, format) = resolve_input(input, args.format, &mut spooled)?;
+        readers.push(input_reader(path, format, args)?);
+    }
+    let patterns = EntryPatterns::new(args.include.clone(), args.exclude.clone());
+    let selected_readers: Vec<EntrySelectionReader> = readers
+        .iter()
+        .map(|reader| {
+            EntrySelectionReader::new(reader.as_ref())
+                .with_videos(!args.photos_only)
+                .with_extensions(extensions.clone())
+                .with_patterns(patterns.clone())
+        })
+        .collect();
+    let reader = args
+        .input
+        .iter()
+        .zip(&selected_readers)
+        .fold(MultiInputReader::new(), |reader, (input, input_reader)| {
+            reader.with_input(input.label.clone(), input_reader)
+        });
+    let reader = MinSizeReader::new(&reader).with_min_size(args.min_size);
+
+    // Hand-made folder trees often carry the date in folder names (`2019-07 Tuscany/`),
+    // and so do the folders of Amazon Photos downloads
+    let has_directory_input = args
+        .input
+        .iter()
+        .any(|input| args.format.resolve(&input.path) == InputFormat::Dir);
+    let profile = args
+        .profile
+        .resolve(args.input.iter().map(|input| input.path.as_str()));
+    let mut filename_patterns = config
+        .dates
+        .filename_patterns
+        .iter()
+        .map(|pattern| FilenamePattern::parse(pattern))
+        .collect::<Result<Vec<_>, _>>()
+        .context("Invalid [dates] section in config file")?;
+    filename_patterns.extend(args.filename_pattern.iter().cloned());
+    let date_extractor = CompositeDateExtractor::new()
+        .with_sources(&args.date_sources)
+        .with_filename_patterns(filename_patterns)
+        .with_time_zone(args.timezone.unwrap_or_default())
+        .with_location_time_zones(args.location_timezone)
+        .with_clock_offsets(args.time_offset.clone())
+        .with_sidecar_first_for_edits(!args.exif_first_for_edits)
+        .with_plausibility((!args.no_date_checks).then(|| {
+            DatePlausibility::new()
+                .with_earliest(args.earliest_date)
+                .with_dead_clock_dates(args.dead_clock_dates.clone())
+                .with_future_dates(args.allow_future_dates)
+        }))
+        .with_folder_names(
+            has_directory_input
+                || profile.dates_from_folders()
+                || args.date_sources.contains(&DateSource::Folder),
+        )
+        .with_file_times(!args.no_file_time_fallback);
 
-    if input_path.is_dir() {
-        let reader = DirectoryImageReader::new(args.input.clone());
-        organize_with_reader(&reader, args)
+    let base_duplicates = if !args.duplicate_suffixes.is_empty() {
+        ExistingCollectionFilter::with_duplicate_patterns(&args.duplicate_suffixes)
+    } else if let Some(only) = &config.duplicates.only {
+        ExistingCollectionFilter::with_duplicate_patterns(only)
     } else {
-        let reader = FileZipImageReader::new(args.input.clone());
-        organize_with_reader(&reader, args)
+        ExistingCollectionFilter::new()
+    };
+    let existing_collection_filter = base_duplicates
+        .with_added_duplicate_patterns(&config.duplicates.add)
+        .with_locales(&config.duplicates.locales)
+        .with_added_duplicate_patterns(&args.add_duplicate_suffix)
+        .with_locales(&args.duplicate_locale);
+    organize_with_reader(&reader, &date_extractor, &existing_collection_filter, args)
+}
+
+/// Path and format to read an input from: stdin and split ZIP sets are first copied
+/// to a temporary file, which is added to `spooled`
+fn resolve_input(
+    input: &InputSpec,
+    format: InputFormat,
+    spooled: &mut Vec<SpooledInput>,
+) -> Result<(String, InputFormat), anyhow::Error> {
+    let resolved = format.resolve(&input.path);
+    if is_http_url(&input.path) && resolved != InputFormat::Zip {
+        anyhow::bail!("Only ZIP archives can be read from a URL: {}", input.path);
+    }
+    if is_s3_url(&input.path) && !matches!(resolved, InputFormat::Zip | InputFormat::Dir) {
+        anyhow::bail!("Only ZIP archives and prefixes can be read from S3: {}", input.path);
+    }
+    let split = split_parts(Path::new(&input.path)).filter(|_| resolved == InputFormat::Zip);
+
+    let spool = if input.path == STDIN_INPUT {
+        SpooledInput::spool(&mut std::io::stdin().lock(), &std::env::temp_dir())?
+    } else if let Some(parts) = split {
+        SpooledInput::create(&std::env::temp_dir(), |file| join_split_zip(&parts, file))?
+    } else {
+        return Ok((input.path.clone(), resolved));
+    };
+
+    let format = match format {
+        InputFormat::Auto => spool.format()?,
+        format => format,
+    };
+    let path = spool.path().to_string_lossy().into_owned();
+    spooled.push(spool);
+    Ok((path, format))
+}
+
+fn input_reader(
+    path: String,
+    format: InputFormat,
+    args: &Args,
+) -> Result<Box<dyn ZipImageReader>, anyhow::Error> {
+    if is_s3_url(&path) {
+        let location = S3Location::parse(&path)?;
+        let credentials = S3Credentials::from_env()?;
+        return Ok(if format == InputFormat::Dir {
+            Box::new(S3DirectoryImageReader::new(location, credentials))
+        } else {
+            let url = credentials.object_url(&location.bucket, &location.key);
+            Box::new(HttpZipImageReader::new(url).with_s3_credentials(Some(credentials)))
+        });
+    }
+
+    Ok(match format {
+        InputFormat::Dir => Box::new(
+            DirectoryImageReader::new(path)
+                .with_symlinks(args.symlinks)
+                .with_max_depth(args.max_depth),
+        ),
+        InputFormat::Tgz | InputFormat::Tar => {
+            Box::new(TarImageReader::new(path).with_gzip(format == InputFormat::Tgz))
+        }
+        InputFormat::Zip | InputFormat::Auto if is_http_url(&path) => Box::new(HttpZipImageReader::new(path)),
+        InputFormat::Zip | InputFormat::Auto => Box::new(
+            FileZipImageReader::new(path)
+                .with_memory_map(!args.no_mmap)
+                .with_threads(decompression_threads(args)),
+        ),
+    })
+}
+
+fn decompression_threads(args: &Args) -> usize {
+    match args.threads {
+        Some(threads) => threads as usize,
+        None => thread::available_parallelism().map_or(1, |threads| threads.get()),
     }
 }
 
 fn organize_with_reader(
     reader: &dyn ZipImageReader,
+    date_extractor: &dyn DateExtractor,
+    existing_collection_filter: &ExistingCollectionFilter,
     args: &Args,
 ) -> Result<organizer::OrganizeResult, anyhow::Error> {
-    let date_extractor = CompositeDateExtractor::new();
     let file_writer = RealFileSystemWriter::new(args.output.clone());
-    let path_generator = PathGenerator::new(&file_writer);
+    let path_generator =
+        PathGenerator::new(&file_writer)
+            .with_sanitize_names(args.sanitize_names)
+            .with_year_roots(args.year_root.clone());
 
-    let all_filenames = collect_filenames(reader)?;
-    let existing_collection_filter = ExistingCollectionFilter::new(all_filenames);
     let no_filter = NoFilter::new();
 
-    let filter: &dyn photo_filter::PhotoFilter = if args.no_filter {
+    let base_filter: &dyn photo_filter::PhotoFilter = if args.no_filter {
         &no_filter
     } else {
-        &existing_collection_filter
+        existing_collection_filter
+    };
+    let service_folder_filter = ServiceFolderFilter::new(base_filter);
+    let folder_filter: &dyn photo_filter::PhotoFilter = if args.include_service_folders {
+        base_filter
+    } else {
+        &service_folder_filter
+    };
+    let hidden_folder_filter = HiddenFolderFilter::new(folder_filter)
+        .with_trash(args.include_trash)
+        .with_archive(args.include_archive);
+    let album_filter = AlbumFilter::new(&hidden_folder_filter, args.album.clone());
+    let filter: &dyn photo_filter::PhotoFilter = if args.album.is_empty() {
+        &hidden_folder_filter
+    } else {
+        &album_filter
     };
 
+    #[cfg(unix)]
+    let systemd_observer = SystemdObserver::from_environment(args.dry_run);
+
     let organizer = PhotoOrganizer::new(
         reader,
-        &date_extractor,
+        date_extractor,
         &path_generator,
         &file_writer,
         filter,
-    );
+    )
+    .with_output_exists_policy(args.output_exists)
+    .with_dry_run(args.dry_run)
+    .with_force(args.force)
+    .with_max_errors(args.max_errors)
+    .with_gps_precision(args.gps_precision)
+    .with_motion_photos(args.motion_photos)
+    .with_min_confidence(args.min_confidence)
+    .with_date_mismatch_threshold(args.date_mismatch_hours.map(|hours| TimeDelta::hours(hours.into())))
+    .with_provenance(args.provenance.then(|| archive_label(&args.input)))
+    .with_sample(args.limit.map(Sample::Limit).or(args.sample))
+    .with_by_device(args.by_device)
+    .with_owner_folders(if args.owner_folders { input_labels(&args.input) } else { Vec::new() })
+    .with_deduplicate_content(args.input.iter().any(|input| input.label.is_some()));
+
+    #[cfg(unix)]
+    let organizer = if args.systemd {
+        organizer.with_observer(&systemd_observer)
+    } else {
+        organizer
+    };
 
     organizer.organize()
 }
 
-fn collect_filenames(reader: &dyn ZipImageReader) -> Result<Vec<String>, anyhow::Error> {
-    let entries = reader.read_entries()?;
-    Ok(entries.into_iter().map(|entry| entry.name).collect())
+/// Labels given to the inputs (`anna=takeout-a.zip`), the owners of their entries
+fn input_labels(inputs: &[InputSpec]) -> Vec<String> {
+    inputs.iter().filter_map(|input| input.label.clone()).collect()
+}
+
+/// File name(s) of the input archives (or directories) as recorded in provenance indexes
+fn archive_label(inputs: &[InputSpec]) -> String {
+    let names: Vec<String> = inputs
+        .iter()
+        .map(|input| {
+            if input.path == STDIN_INPUT {
+                return "stdin".to_string();
+            }
+            Path::new(&input.path)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| input.path.clone())
+        })
+        .collect();
+    names.join("+")
 }
 
 fn display_results_and_exit(result: Result<organizer::OrganizeResult, anyhow::Error>) -> ! {
@@ -121,10 +672,66 @@ fn display_success_summary(result: &organizer::OrganizeResult) {
     println!("  Total files: {}", result.total_files);
     println!("  Organized: {}", result.organized_files);
     println!("  Skipped: {}", result.skipped_files);
+    println!("  Already present: {}", result.already_present_files);
+    println!("  Overwritten: {}", result.overwritten_files);
+    println!("  Not overwritten: {}", result.not_overwritten_files);
+    println!("  Renamed (name collision): {}", result.conflicting_files);
+    if result.unsampled_files > 0 {
+        println!("  Not sampled (--limit/--sample): {}", result.unsampled_files);
+    }
+    if result.tiny_files > 0 {
+        println!("  Too small (--min-size): {}", result.tiny_files);
+    }
+    if result.year_only_files > 0 {
+        println!("  Year only (in YYYY/YYYY-unknown): {}", result.year_only_files);
+    }
+    if result.review_files > 0 {
+        println!("  To review (below --min-confidence, in review/): {}", result.review_files);
+    }
+    if !result.date_mismatches.is_empty() {
+        println!("  EXIF and JSON dates differ: {}", result.date_mismatches.len());
+    }
+
+    if result.not_overwritten_files > 0 {
+        println!(
+            "\n{} existing file(s) differ from the archive and were kept; use --force or --output-exists backup to replace them",
+            result.not_overwritten_files
+        );
+    }
 
+    display_date_mismatches_if_any(&result.date_mismatches);
+    display_undated_reasons_if_any(&result.undated_reasons);
+    display_shortened_paths_if_any(&result.shortened_paths);
     display_errors_if_any(&result.errors);
 }
 
+fn display_date_mismatches_if_any(date_mismatches: &[String]) {
+    if !date_mismatches.is_empty() {
+        println!("\nEXIF and JSON sidecar dates differ (check these):");
+        for mismatch in date_mismatches {
+            println!("  - {}", mismatch);
+        }
+    }
+}
+
+fn display_undated_reasons_if_any(undated_reasons: &BTreeMap<String, usize>) {
+    if !undated_reasons.is_empty() {
+        println!("\nWhy files got no date (per source):");
+        for (reason, count) in undated_reasons {
+            println!("  - {}: {}", reason, count);
+        }
+    }
+}
+
+fn display_shortened_paths_if_any(shortened_paths: &[String]) {
+    if !shortened_paths.is_empty() {
+        println!("\nShortened filenames (path too long):");
+        for mapping in shortened_paths {
+            println!("  - {}", mapping);
+        }
+    }
+}
+
 fn display_errors_if_any(errors: &[String]) {
     if !errors.is_empty() {
         println!("\nErrors:");
@@ -0,0 +1,199 @@
+// Facebook and Instagram data exports: media dates from the JSON files listing posts and albums
+
+use crate::zip_image_reader::ZipEntry;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// What a Meta export's JSON files record about one photo or video
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MetaMedia {
+    /// When the photo was taken, from the EXIF data Meta kept on upload
+    pub taken_time: Option<DateTime<Utc>>,
+    /// When the photo or video was uploaded
+    pub creation_time: DateTime<Utc>,
+}
+
+impl MetaMedia {
+    /// The best known moment of capture: the taken time, or else the upload time
+    pub fn date_time(&self) -> DateTime<Utc> {
+        self.taken_time.unwrap_or(self.creation_time)
+    }
+}
+
+/// Media of a Facebook or Instagram export by the name of their entry
+#[derive(Debug, Default)]
+pub struct MetaMediaIndex {
+    by_media: HashMap<String, MetaMedia>,
+}
+
+impl MetaMediaIndex {
+    /// Pairs media entries with the records in Meta's JSON files (`posts/*.json`,
+    /// `content/posts_1.json`, album files, ...). Records name their media by a `uri`
+    /// relative to the export root, which may sit in a folder (or input label) of its own.
+    pub fn pair<'n>(media_names: impl IntoIterator<Item = &'n str>, json_entries: &[ZipEntry]) -> Self {
+        let mut records = HashMap::new();
+        for entry in json_entries {
+            if let Ok(value) = serde_json::from_slice::<Value>(&entry.data) {
+                collect_media(&value, &mut records);
+            }
+        }
+        if records.is_empty() {
+            return Self::default();
+        }
+
+        let by_media = media_names
+            .into_iter()
+            .filter_map(|media_name| {
+                let media = name_suffixes(media_name).find_map(|suffix| records.get(suffix))?;
+                Some((media_name.to_string(), *media))
+            })
+            .collect();
+
+        Self { by_media }
+    }
+
+    pub fn get(&self, media_name: &str) -> Option<&MetaMedia> {
+        self.by_media.get(media_name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_media.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_media.is_empty()
+    }
+}
+
+/// Finds every object with a `uri` and a `creation_timestamp`, wherever the export
+/// nests it (post attachments, album photo lists, stories, ...)
+fn collect_media(value: &Value, records: &mut HashMap<String, MetaMedia>) {
+    match value {
+        Value::Object(object) => {
+            let uri = object.get("uri").and_then(Value::as_str);
+            let creation_time = object
+                .get("creation_timestamp")
+                .and_then(Value::as_i64)
+                .and_then(|secs| DateTime::from_timestamp(secs, 0));
+            if let (Some(uri), Some(creation_time)) = (uri, creation_time) {
+                records.insert(
+                    uri.trim_start_matches('/').to_string(),
+                    MetaMedia {
+                        taken_time: object.get("media_metadata").and_then(taken_time),
+                        creation_time,
+                    },
+                );
+            }
+            object.values().for_each(|child| collect_media(child, records));
+        }
+        Value::Array(items) => items.iter().for_each(|item| collect_media(item, records)),
+        _ => {}
+    }
+}
+
+/// `media_metadata.photo_metadata.exif_data[].taken_timestamp` (or `video_metadata`)
+fn taken_time(media_metadata: &Value) -> Option<DateTime<Utc>> {
+    media_metadata
+        .as_object()?
+        .values()
+        .filter_map(|metadata| metadata.get("exif_data")?.as_array())
+        .flatten()
+        .filter_map(|exif| exif.get("taken_timestamp")?.as_i64())
+        .find(|secs| *secs > 0)
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+}
+
+/// The name itself, then the name without its leading folders one at a time
+fn name_suffixes(name: &str) -> impl Iterator<Item = &str> {
+    std::iter::once(name).chain(name.match_indices('/').map(move |(slash, _)| &name[slash + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json_entry(name: &str, json: &str) -> ZipEntry {
+        ZipEntry {
+            name: name.to_string(),
+            data: json.as_bytes().to_vec().into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_pair_facebook_posts_and_albums() {
+        // Arrange
+        let posts = json_entry(
+            "facebook-anna/your_activity_across_facebook/posts/your_posts_1.json",
+            r#"[{"timestamp": 1600000000, "attachments": [{"data": [{"media": {
+                "uri": "your_activity_across_facebook/posts/media/Mobileuploads_1/1_n.jpg",
+                "creation_timestamp": 1600000000,
+                "media_metadata": {"photo_metadata": {"exif_data": [{"upload_ip": "::1", "taken_timestamp": 1420070400}]}},
+                "title": ""}}]}]}]"#,
+        );
+        let album = json_entry(
+            "facebook-anna/your_activity_across_facebook/posts/album/0.json",
+            r#"{"name": "Holiday", "photos": [
+                {"uri": "your_activity_across_facebook/posts/media/Holiday_2/2_n.jpg", "creation_timestamp": 1500000000}
+            ]}"#,
+        );
+
+        // Act
+        let index = MetaMediaIndex::pair(
+            [
+                "facebook-anna/your_activity_across_facebook/posts/media/Mobileuploads_1/1_n.jpg",
+                "facebook-anna/your_activity_across_facebook/posts/media/Holiday_2/2_n.jpg",
+                "facebook-anna/your_activity_across_facebook/posts/media/Holiday_2/3_n.jpg",
+            ],
+            &[posts, album],
+        );
+
+        // Assert
+        assert_eq!(index.len(), 2);
+        let post = index
+            .get("facebook-anna/your_activity_across_facebook/posts/media/Mobileuploads_1/1_n.jpg")
+            .unwrap();
+        assert_eq!(post.date_time(), DateTime::from_timestamp(1_420_070_400, 0).unwrap());
+        let album_photo = index
+            .get("facebook-anna/your_activity_across_facebook/posts/media/Holiday_2/2_n.jpg")
+            .unwrap();
+        assert_eq!(album_photo.taken_time, None);
+        assert_eq!(album_photo.date_time(), DateTime::from_timestamp(1_500_000_000, 0).unwrap());
+    }
+
+    #[test]
+    fn test_pair_instagram_posts() {
+        // Arrange
+        let posts = json_entry(
+            "your_instagram_activity/content/posts_1.json",
+            r#"[{"media": [{"uri": "media/posts/202001/17_a.jpg", "creation_timestamp": 1580000000, "title": ""}]}]"#,
+        );
+
+        // Act
+        let index = MetaMediaIndex::pair(["media/posts/202001/17_a.jpg", "media/posts/202001/other.jpg"], &[posts]);
+
+        // Assert
+        assert_eq!(index.len(), 1);
+        assert_eq!(
+            index.get("media/posts/202001/17_a.jpg").unwrap().date_time(),
+            DateTime::from_timestamp(1_580_000_000, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_pair_ignores_takeout_sidecars_and_invalid_json() {
+        // Arrange
+        let sidecar = json_entry(
+            "Takeout/Google Photos/IMG_1.jpg.json",
+            r#"{"title": "IMG_1.jpg", "photoTakenTime": {"timestamp": "1420070400"}}"#,
+        );
+        let broken = json_entry("posts/broken.json", "{");
+
+        // Act
+        let index = MetaMediaIndex::pair(["Takeout/Google Photos/IMG_1.jpg"], &[sidecar, broken]);
+
+        // Assert
+        assert!(index.is_empty());
+    }
+}
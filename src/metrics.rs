@@ -0,0 +1,108 @@
+use crate::organizer::OrganizeResult;
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+
+/// Builds the machine-readable metrics document for a finished run
+pub fn metrics_json(result: &OrganizeResult) -> Value {
+    json!({
+        "status": "completed",
+        "durations_seconds": {
+            "read": result.read_duration.as_secs_f64(),
+            "index": result.index_duration.as_secs_f64(),
+            "process": result.process_duration.as_secs_f64(),
+        },
+        "counts": {
+            "total": result.total_files,
+            "organized": result.organized_files,
+            "skipped": result.skipped_files,
+            "already_present": result.already_present_files,
+            "overwritten": result.overwritten_files,
+            "not_overwritten": result.not_overwritten_files,
+            "renamed": result.conflicting_files,
+            "unsampled": result.unsampled_files,
+            "too_small": result.tiny_files,
+            "year_only": result.year_only_files,
+            "review": result.review_files,
+            "date_mismatches": result.date_mismatches.len(),
+            "shortened": result.shortened_paths.len(),
+            "errors": result.errors.len(),
+        },
+        "bytes": {
+            "read": result.bytes_read,
+            "written": result.bytes_written,
+        },
+        "error_categories": result.error_categories,
+        "undated_reasons": result.undated_reasons,
+    })
+}
+
+/// Builds the metrics document for a run that failed as a whole
+pub fn failure_metrics_json(error: &anyhow::Error) -> Value {
+    json!({
+        "status": "failed",
+        "error": format!("{:#}", error),
+    })
+}
+
+pub fn write_metrics_file(path: &Path, metrics: &Value) -> Result<()> {
+    let content = serde_json::to_string_pretty(metrics).context("Failed to serialize metrics")?;
+    fs::write(path, content)
+        .with_context(|| format!("Failed to write metrics file: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_metrics_json_contains_counts_bytes_and_categories() {
+        // Arrange
+        let mut result = OrganizeResult {
+            total_files: 3,
+            organized_files: 2,
+            skipped_files: 1,
+            bytes_read: 300,
+            bytes_written: 200,
+            process_duration: Duration::from_millis(1500),
+            errors: vec!["a.jpg: Failed to extract date".to_string()],
+            ..OrganizeResult::default()
+        };
+        result
+            .error_categories
+            .insert("Failed to extract date".to_string(), 1);
+        result.undated_reasons.insert("exif: no_exif".to_string(), 1);
+
+        // Act
+        let metrics = metrics_json(&result);
+
+        // Assert
+        assert_eq!(metrics["status"], "completed");
+        assert_eq!(metrics["counts"]["organized"], 2);
+        assert_eq!(metrics["counts"]["errors"], 1);
+        assert_eq!(metrics["bytes"]["written"], 200);
+        assert_eq!(metrics["durations_seconds"]["process"], 1.5);
+        assert_eq!(metrics["error_categories"]["Failed to extract date"], 1);
+        assert_eq!(metrics["undated_reasons"]["exif: no_exif"], 1);
+    }
+
+    #[test]
+    fn test_write_metrics_file() {
+        // Arrange
+        let path = Path::new("/tmp/test_metrics.json");
+        let metrics = failure_metrics_json(&anyhow::anyhow!("Failed to read ZIP entries"));
+
+        // Act
+        let result = write_metrics_file(path, &metrics);
+
+        // Assert
+        assert!(result.is_ok());
+        let written: Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(written["status"], "failed");
+
+        // Cleanup
+        fs::remove_file(path).ok();
+    }
+}
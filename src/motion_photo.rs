@@ -0,0 +1,116 @@
+// Motion photos (Pixel `MVIMG_*.jpg`, `*.MP.jpg`, `*.MP`): JPEGs with a short MP4 appended
+
+use std::path::Path;
+
+const JPEG_START: &[u8] = &[0xFF, 0xD8];
+const JPEG_END: &[u8] = &[0xFF, 0xD9];
+const MP4_FILE_TYPE: &[u8] = b"ftyp";
+
+/// What to do with the video embedded in a motion photo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum MotionPhotoPolicy {
+    /// Write the file as it is, video included
+    #[default]
+    Keep,
+    /// Write only the still image
+    Strip,
+    /// Write the still image and the video as a companion `.mp4` next to it
+    Split,
+}
+
+/// Where the embedded MP4 starts: right after the JPEG's end-of-image marker, with its
+/// `ftyp` box. `None` for anything that isn't a motion photo.
+pub fn embedded_video_offset(data: &[u8]) -> Option<usize> {
+    if !data.starts_with(JPEG_START) {
+        return None;
+    }
+
+    // An MP4 box is a 4-byte size followed by its type
+    data.windows(MP4_FILE_TYPE.len())
+        .enumerate()
+        .skip(JPEG_START.len() + JPEG_END.len() + 4)
+        .filter(|(_, window)| *window == MP4_FILE_TYPE)
+        .map(|(type_position, _)| type_position - 4)
+        .find(|&video_start| data[..video_start].ends_with(JPEG_END))
+}
+
+/// Name of the still image: `PXL_20230101_120000.MP` becomes `PXL_20230101_120000.jpg`,
+/// JPEG names are kept
+pub fn still_image_name(filename: &str) -> String {
+    match filename.rsplit_once('.') {
+        Some((stem, extension)) if extension.eq_ignore_ascii_case("mp") => format!("{}.jpg", stem),
+        _ => filename.to_string(),
+    }
+}
+
+/// Name of the companion video for a still image: `MVIMG_20180101_120000.jpg` and
+/// `PXL_20230101_120000.MP.jpg` become `MVIMG_20180101_120000.mp4` and
+/// `PXL_20230101_120000.mp4`
+pub fn companion_video_name(image_path: &Path) -> String {
+    let stem = image_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let stem = match stem.rsplit_once('.') {
+        Some((base, extension)) if extension.eq_ignore_ascii_case("mp") => base.to_string(),
+        _ => stem,
+    };
+    format!("{}.mp4", stem)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    pub(crate) const STILL: &[u8] = &[0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x02, 0xFF, 0xD9];
+
+    /// A JPEG followed by a minimal MP4 header, laid out like a Pixel motion photo
+    pub(crate) fn motion_photo() -> (Vec<u8>, Vec<u8>) {
+        let mut video = vec![0x00, 0x00, 0x00, 0x10];
+        video.extend_from_slice(b"ftypmp42");
+        video.extend_from_slice(&[0x00; 4]);
+        video.extend_from_slice(b"\0\0\0\x08moov");
+        let mut data = STILL.to_vec();
+        data.extend_from_slice(&video);
+        (data, video)
+    }
+
+    #[test]
+    fn test_embedded_video_offset_finds_appended_mp4() {
+        // Arrange
+        let (data, video) = motion_photo();
+
+        // Act
+        let offset = embedded_video_offset(&data);
+
+        // Assert
+        assert_eq!(offset, Some(STILL.len()));
+        assert_eq!(&data[STILL.len()..], &video[..]);
+    }
+
+    #[rstest]
+    #[case(STILL)]
+    #[case(b"\0\0\0\x10ftypmp42\0\0\0\0")]
+    #[case(b"\xFF\xD8 mentions ftyp in a comment \xFF\xD9")]
+    fn test_embedded_video_offset_ignores_other_files(#[case] data: &[u8]) {
+        // Act & Assert
+        assert_eq!(embedded_video_offset(data), None);
+    }
+
+    #[rstest]
+    #[case("PXL_20230101_120000.MP", "PXL_20230101_120000.jpg")]
+    #[case("MVIMG_20180101_120000.jpg", "MVIMG_20180101_120000.jpg")]
+    fn test_still_image_name(#[case] filename: &str, #[case] expected: &str) {
+        // Act & Assert
+        assert_eq!(still_image_name(filename), expected);
+    }
+
+    #[rstest]
+    #[case("2023/2023-01-01/PXL_20230101_120000.MP.jpg", "PXL_20230101_120000.mp4")]
+    #[case("2018/2018-01-01/MVIMG_20180101_120000(1).jpg", "MVIMG_20180101_120000(1).mp4")]
+    fn test_companion_video_name(#[case] image_path: &str, #[case] expected: &str) {
+        // Act & Assert
+        assert_eq!(companion_video_name(Path::new(image_path)), expected);
+    }
+}
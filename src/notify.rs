@@ -0,0 +1,208 @@
+use crate::config::{EmailConfig, NotifyConfig};
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::time::Duration;
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_NTFY_SERVER: &str = "https://ntfy.sh";
+
+/// POSTs the run summary as JSON to a webhook URL
+pub fn post_summary(url: &str, summary: &Value) -> Result<()> {
+    ureq::post(url)
+        .timeout(WEBHOOK_TIMEOUT)
+        .send_json(summary)
+        .with_context(|| format!("Failed to notify webhook: {}", url))?;
+    Ok(())
+}
+
+/// Sends the push notifications configured in the config file. Every channel is
+/// tried; the errors of the ones that failed are returned.
+pub fn send_notifications(config: &NotifyConfig, summary: &Value) -> Vec<anyhow::Error> {
+    let (title, message) = summary_text(summary);
+    let mut errors = Vec::new();
+
+    if let Some(topic) = &config.ntfy_topic {
+        let server = config.ntfy_server.as_deref().unwrap_or(DEFAULT_NTFY_SERVER);
+        if let Err(e) = send_ntfy(server, topic, &title, &message) {
+            errors.push(e);
+        }
+    }
+
+    if let Some(email) = &config.email {
+        if let Err(e) = send_email(email, &title, &message) {
+            errors.push(e);
+        }
+    }
+
+    errors
+}
+
+/// Short human-readable title and message for a run summary (see `metrics`)
+pub fn summary_text(summary: &Value) -> (String, String) {
+    if summary["status"] == "failed" {
+        return (
+            "Photo organization failed".to_string(),
+            summary["error"].as_str().unwrap_or("unknown error").to_string(),
+        );
+    }
+
+    let counts = &summary["counts"];
+    (
+        "Photo organization complete".to_string(),
+        format!(
+            "Organized {} of {} files, skipped {}, {} errors",
+            counts["organized"], counts["total"], counts["skipped"], counts["errors"]
+        ),
+    )
+}
+
+fn send_ntfy(server: &str, topic: &str, title: &str, message: &str) -> Result<()> {
+    let url = format!("{}/{}", server.trim_end_matches('/'), topic);
+    ureq::post(&url)
+        .timeout(WEBHOOK_TIMEOUT)
+        .set("Title", title)
+        .send_string(message)
+        .with_context(|| format!("Failed to send ntfy notification to {}", url))?;
+    Ok(())
+}
+
+#[cfg(feature = "email")]
+fn send_email(config: &EmailConfig, title: &str, message: &str) -> Result<()> {
+    use lettre::transport::smtp::authentication::Credentials;
+    use lettre::{Message, SmtpTransport, Transport};
+
+    let email = Message::builder()
+        .from(config.from.parse().context("Invalid e-mail sender address")?)
+        .to(config.to.parse().context("Invalid e-mail recipient address")?)
+        .subject(title)
+        .body(message.to_string())
+        .context("Failed to build notification e-mail")?;
+
+    let mut transport = SmtpTransport::starttls_relay(&config.smtp_server)
+        .with_context(|| format!("Invalid SMTP server: {}", config.smtp_server))?
+        .port(config.smtp_port.unwrap_or(587))
+        .timeout(Some(WEBHOOK_TIMEOUT));
+    if let (Some(username), Some(password)) = (&config.username, &config.password) {
+        transport = transport.credentials(Credentials::new(username.clone(), password.clone()));
+    }
+
+    transport
+        .build()
+        .send(&email)
+        .with_context(|| format!("Failed to send e-mail via {}", config.smtp_server))?;
+    Ok(())
+}
+
+#[cfg(not(feature = "email"))]
+fn send_email(_config: &EmailConfig, _title: &str, _message: &str) -> Result<()> {
+    anyhow::bail!("E-mail notifications are configured, but this build lacks the `email` feature")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    fn serve_one_request(
+        listener: TcpListener,
+        status_line: &'static str,
+    ) -> thread::JoinHandle<String> {
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut content_length = 0;
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header == "\r\n" {
+                    break;
+                }
+                if let Some(value) = header.to_lowercase().strip_prefix("content-length:") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            reader
+                .get_mut()
+                .write_all(format!("{}\r\nContent-Length: 0\r\n\r\n", status_line).as_bytes())
+                .unwrap();
+
+            format!("{}{}", request_line, String::from_utf8(body).unwrap())
+        })
+    }
+
+    #[test]
+    fn test_post_summary_sends_json_body() {
+        // Arrange
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = serve_one_request(listener, "HTTP/1.1 200 OK");
+
+        // Act
+        let result = post_summary(&url, &json!({"status": "completed", "organized": 3}));
+
+        // Assert
+        assert!(result.is_ok(), "{:?}", result.err());
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /hook"));
+        assert!(request.contains("\"organized\":3"));
+    }
+
+    #[test]
+    fn test_send_notifications_publishes_to_ntfy_topic() {
+        // Arrange
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = NotifyConfig {
+            ntfy_topic: Some("photo-imports".to_string()),
+            ntfy_server: Some(format!("http://{}", listener.local_addr().unwrap())),
+            email: None,
+        };
+        let server = serve_one_request(listener, "HTTP/1.1 200 OK");
+        let summary = json!({
+            "status": "completed",
+            "counts": {"organized": 2, "total": 3, "skipped": 1, "errors": 0},
+        });
+
+        // Act
+        let errors = send_notifications(&config, &summary);
+
+        // Assert
+        assert!(errors.is_empty(), "{:?}", errors);
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /photo-imports"));
+        assert!(request.ends_with("Organized 2 of 3 files, skipped 1, 0 errors"));
+    }
+
+    #[test]
+    fn test_summary_text_for_failed_run() {
+        // Act
+        let (title, message) = summary_text(&json!({"status": "failed", "error": "disk full"}));
+
+        // Assert
+        assert_eq!(title, "Photo organization failed");
+        assert_eq!(message, "disk full");
+    }
+
+    #[test]
+    fn test_post_summary_reports_http_errors() {
+        // Arrange
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = serve_one_request(listener, "HTTP/1.1 500 Internal Server Error");
+
+        // Act
+        let result = post_summary(&url, &json!({"status": "failed"}));
+
+        // Assert
+        assert!(result.is_err());
+        server.join().unwrap();
+    }
+}
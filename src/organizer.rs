@@ -1,9 +1,80 @@
-use crate::exif::DateExtractor;
-use crate::file_writer::FileSystemWriter;
-use crate::path_generator::PathGenerator;
+use crate::archive_index::ArchiveIndex;
+use crate::device::device_name;
+use crate::events::{ConsoleObserver, OrganizeEvent, OrganizeObserver};
+use crate::exif::{Confidence, DateExtractionError, DateExtractor, EntryContext, ParsedExif};
+use crate::file_writer::{FileSystemWriter, StreamContent};
+use crate::gps_privacy::round_gps_coordinates;
+use crate::meta_export::MetaMediaIndex;
+use crate::motion_photo::{self, MotionPhotoPolicy};
+use crate::path_generator::{numbered_path, PathGenerator};
 use crate::photo_filter::PhotoFilter;
-use crate::zip_image_reader::{ZipEntry, ZipImageReader};
-use anyhow::{Context, Result};
+use crate::provenance::{origin_index_path, origin_record, ORIGIN_INDEX_HEADER};
+use crate::sidecar::{AlbumMetadataIndex, SidecarIndex};
+use crate::snapchat::SnapchatMemoryIndex;
+use crate::zip_image_reader::{LargeContent, ZipEntry, ZipImageReader};
+use anyhow::{bail, Context, Result};
+use chrono::{Datelike, NaiveDate, TimeDelta};
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// How to treat an output directory that already contains files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum OutputExistsPolicy {
+    /// Abort when the output directory is not empty
+    RequireEmpty,
+    /// Write into the existing directory, keeping existing files unless --force is given
+    #[default]
+    Merge,
+    /// Write into the existing directory, moving conflicting files aside first
+    Backup,
+}
+
+/// Subset of the (filtered) entries to process, for trial runs on big archives
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sample {
+    /// Only the first N entries
+    Limit(usize),
+    /// This percentage of the entries, spread evenly over the archive
+    Percent(f64),
+}
+
+impl Sample {
+    /// Whether the entry at `position` (counting entries that passed the filter) is processed
+    fn selects(&self, position: usize) -> bool {
+        match *self {
+            Sample::Limit(limit) => position < limit,
+            Sample::Percent(percent) => {
+                let taken_before = (position as f64 * percent / 100.0).floor();
+                let taken_after = ((position + 1) as f64 * percent / 100.0).floor();
+                taken_after > taken_before
+            }
+        }
+    }
+}
+
+impl FromStr for Sample {
+    type Err = String;
+
+    /// Parses `500` as a limit and `1%` or `0.5%` as a percentage
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().strip_suffix('%') {
+            Some(percent) => match percent.trim().parse::<f64>() {
+                Ok(percent) if percent > 0.0 && percent <= 100.0 => Ok(Sample::Percent(percent)),
+                _ => Err(format!("expected a percentage between 0 and 100, got '{}'", value)),
+            },
+            None => value
+                .trim()
+                .parse()
+                .map(Sample::Limit)
+                .map_err(|_| format!("expected a number of entries or a percentage, got '{}'", value)),
+        }
+    }
+}
 
 /// Main orchestrator service that coordinates photo organization
 pub struct PhotoOrganizer<'a> {
@@ -12,6 +83,20 @@ pub struct PhotoOrganizer<'a> {
     path_generator: &'a PathGenerator<'a>,
     file_writer: &'a dyn FileSystemWriter,
     photo_filter: &'a dyn PhotoFilter,
+    output_exists_policy: OutputExistsPolicy,
+    dry_run: bool,
+    force: bool,
+    observer: Option<&'a dyn OrganizeObserver>,
+    max_errors: Option<usize>,
+    gps_precision: Option<u32>,
+    provenance_archive: Option<String>,
+    sample: Option<Sample>,
+    by_device: bool,
+    owner_labels: Vec<String>,
+    deduplicate_content: bool,
+    motion_photos: MotionPhotoPolicy,
+    min_confidence: Option<Confidence>,
+    date_mismatch_threshold: Option<TimeDelta>,
 }
 
 impl<'a> PhotoOrganizer<'a> {
@@ -28,72 +113,714 @@ impl<'a> PhotoOrganizer<'a> {
             path_generator,
             file_writer,
             photo_filter,
+            output_exists_policy: OutputExistsPolicy::default(),
+            dry_run: false,
+            force: false,
+            observer: None,
+            max_errors: None,
+            gps_precision: None,
+            provenance_archive: None,
+            sample: None,
+            by_device: false,
+            owner_labels: Vec::new(),
+            deduplicate_content: false,
+            motion_photos: MotionPhotoPolicy::default(),
+            min_confidence: None,
+            date_mismatch_threshold: None,
         }
     }
 
+    pub fn with_output_exists_policy(mut self, policy: OutputExistsPolicy) -> Self {
+        self.output_exists_policy = policy;
+        self
+    }
+
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Allow replacing existing files that have different content
+    pub fn with_force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    /// Send progress events to `observer` instead of printing them to stdout
+    pub fn with_observer(mut self, observer: &'a dyn OrganizeObserver) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Abort the run once this many entries have failed
+    pub fn with_max_errors(mut self, max_errors: Option<usize>) -> Self {
+        self.max_errors = max_errors;
+        self
+    }
+
+    /// Keep, strip or split out the video embedded in motion photos
+    pub fn with_motion_photos(mut self, motion_photos: MotionPhotoPolicy) -> Self {
+        self.motion_photos = motion_photos;
+        self
+    }
+
+    /// Place files dated less reliably than this in `review/` instead of among the rest
+    pub fn with_min_confidence(mut self, min_confidence: Option<Confidence>) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+
+    /// List files whose embedded and JSON sidecar times differ by more than this
+    pub fn with_date_mismatch_threshold(mut self, threshold: Option<TimeDelta>) -> Self {
+        self.date_mismatch_threshold = threshold;
+        self
+    }
+
+    /// Round GPS coordinates in written copies to this many decimal degrees
+    pub fn with_gps_precision(mut self, gps_precision: Option<u32>) -> Self {
+        self.gps_precision = gps_precision;
+        self
+    }
+
+    /// Record every written file in a `.origin.csv` in its date directory, naming
+    /// `archive` and the entry path it came from
+    pub fn with_provenance(mut self, archive: Option<String>) -> Self {
+        self.provenance_archive = archive;
+        self
+    }
+
+    /// Only process a subset of the entries that pass the filter
+    pub fn with_sample(mut self, sample: Option<Sample>) -> Self {
+        self.sample = sample;
+        self
+    }
+
+    /// Group photos by the device that took them, beneath the date directory
+    pub fn with_by_device(mut self, by_device: bool) -> Self {
+        self.by_device = by_device;
+        self
+    }
+
+    /// Put each file of a labelled input in a folder named after its label beneath the
+    /// date directory. Entry names of labelled inputs start with the label (see
+    /// `MultiInputReader`); files of unlabelled inputs get no owner folder.
+    pub fn with_owner_folders(mut self, labels: Vec<String>) -> Self {
+        self.owner_labels = labels;
+        self
+    }
+
+    /// Treat an entry whose content was already organized in this run under another
+    /// name or folder as identical, e.g. a photo shared between two accounts
+    pub fn with_deduplicate_content(mut self, deduplicate_content: bool) -> Self {
+        self.deduplicate_content = deduplicate_content;
+        self
+    }
+
     /// Organize photos from ZIP archive into date-based directory structure
     pub fn organize(&self) -> Result<OrganizeResult> {
-        let entries = self
+        if self.output_exists_policy == OutputExistsPolicy::RequireEmpty
+            && !self.file_writer.is_output_empty()
+        {
+            bail!(
+                "Output directory is not empty (use --output-exists merge or backup to write into it)"
+            );
+        }
+
+        // Listing pass: names and sizes only, entry data is read one entry at a time below
+        let read_started = Instant::now();
+        let listing = self
             .zip_reader
-            .read_entries()
+            .list_entries()
             .context("Failed to read ZIP entries")?;
+        let read_duration = read_started.elapsed();
+
+        self.emit(OrganizeEvent::Started {
+            total_files: listing.len(),
+        });
+
+        // Index pass: build a global view of the archive before deciding on any entry
+        let index_started = Instant::now();
+        let sidecars = self
+            .zip_reader
+            .sidecars()
+            .context("Failed to read JSON sidecars")?;
+        let media_names = || listing.iter().map(|entry| entry.name.as_str());
+        let index = ArchiveIndex::from_listing(&listing)
+            .with_sidecars(SidecarIndex::pair(media_names(), &sidecars))
+            .with_meta_media(MetaMediaIndex::pair(media_names(), &sidecars))
+            .with_snapchat_memories(SnapchatMemoryIndex::pair(media_names(), &sidecars))
+            .with_album_metadata(AlbumMetadataIndex::from_sidecars(&sidecars));
+        let index_duration = index_started.elapsed();
+        self.emit(OrganizeEvent::Indexed {
+            entries: index.entry_count(),
+            total_bytes: index.total_size(),
+            albums: index.album_names().count(),
+            sidecars: index.sidecar_count(),
+        });
 
-        let total_files = entries.len();
-        let mut organized_files = 0;
-        let mut skipped_files = 0;
-        let mut errors = Vec::new();
+        // Processing pass
+        let process_started = Instant::now();
+        let mut result = OrganizeResult {
+            total_files: listing.len(),
+            tiny_files: self.zip_reader.tiny_entry_count(),
+            bytes_read: index.total_size(),
+            read_duration,
+            index_duration,
+            ..OrganizeResult::default()
+        };
+        let mut plan = RunPlan::default();
+        let mut waiting_live_photo_halves: HashMap<String, (ZipEntry, ParsedExif)> = HashMap::new();
+        let mut included_position = 0;
+
+        // Entries the filter rejects on their name are never read
+        let mut names_to_read = HashSet::new();
+        for entry in &listing {
+            if self.photo_filter.rejects_by_name(&entry.name, &index) {
+                self.emit(OrganizeEvent::FileFiltered {
+                    entry: entry.name.clone(),
+                });
+                result.skipped_files += 1;
+            } else {
+                names_to_read.insert(entry.name.clone());
+            }
+        }
+
+        let entries = self
+            .zip_reader
+            .entries_named(names_to_read)
+            .context("Failed to read ZIP entries")?;
 
         for entry in entries {
+            let entry = entry.context("Failed to read ZIP entries")?;
+            // Decoded at most once, by the first step that needs it
+            let exif = ParsedExif::new();
             // Apply filter first
-            if !self.photo_filter.should_include(&entry.name, &entry.data) {
-                println!("{}: filtered out", entry.name);
-                skipped_files += 1;
+            if !self
+                .photo_filter
+                .should_include_with_exif(&entry.name, &entry.data, &index, &exif)
+            {
+                self.emit(OrganizeEvent::FileFiltered {
+                    entry: entry.name.clone(),
+                });
+                result.skipped_files += 1;
                 continue;
             }
 
-            match self.process_entry(&entry) {
-                Ok(target_path) => {
-                    println!("{}: copied to {}", entry.name, target_path.display());
-                    organized_files += 1;
+            let sampled = self
+                .sample
+                .is_none_or(|sample| sample.selects(included_position));
+            included_position += 1;
+            if !sampled {
+                result.unsampled_files += 1;
+                continue;
+            }
+
+            // The first half of a Live Photo waits for the other one, so both are placed together
+            let Some(partner) = index.live_photo_partner(&entry.name) else {
+                self.organize_entry(&entry, &exif, &index, None, &mut plan, &mut result)?;
+                continue;
+            };
+            match waiting_live_photo_halves.remove(partner) {
+                Some(first_half) => self.organize_live_photo(
+                    first_half,
+                    (entry, exif),
+                    &index,
+                    &mut plan,
+                    &mut result,
+                )?,
+                None => {
+                    waiting_live_photo_halves.insert(entry.name.clone(), (entry, exif));
+                }
+            }
+        }
+
+        // Halves whose partner was filtered out or failed to read
+        let mut unpaired: Vec<(ZipEntry, ParsedExif)> = waiting_live_photo_halves.into_values().collect();
+        unpaired.sort_by(|(a, _), (b, _)| a.name.cmp(&b.name));
+        for (entry, exif) in unpaired {
+            self.organize_entry(&entry, &exif, &index, None, &mut plan, &mut result)?;
+        }
+
+        result.process_duration = process_started.elapsed();
+        self.emit(OrganizeEvent::Finished {
+            result: result.clone(),
+        });
+        Ok(result)
+    }
+
+    /// Processes one entry and records the outcome in `result`; an error only when the
+    /// run has to stop
+    fn organize_entry(
+        &self,
+        entry: &ZipEntry,
+        exif: &ParsedExif,
+        index: &ArchiveIndex,
+        live_photo: Option<&mut LivePhotoPlacement>,
+        plan: &mut RunPlan,
+        result: &mut OrganizeResult,
+    ) -> Result<()> {
+        let context = index.context_of(entry).with_exif(exif);
+        match self.process_entry(entry, &context, live_photo, plan) {
+            Ok(outcome) => {
+                if outcome.shortened {
+                    result.shortened_paths.push(format!(
+                        "{} -> {}",
+                        entry.name,
+                        outcome.target_path.display()
+                    ));
+                }
+                if outcome.renamed {
+                    result.conflicting_files += 1;
+                }
+                if outcome.year_only {
+                    result.year_only_files += 1;
+                }
+                if outcome.review {
+                    result.review_files += 1;
+                }
+                if let Some(mismatch) = self.date_mismatch(entry, &context) {
+                    result.date_mismatches.push(mismatch);
+                }
+                result.record(outcome.action);
+                if outcome.action.writes() && !self.dry_run {
+                    result.bytes_written += entry.size();
+                }
+                self.emit(OrganizeEvent::FileProcessed {
+                    entry: entry.name.clone(),
+                    outcome,
+                });
+                Ok(())
+            }
+            Err(e) => {
+                self.emit(OrganizeEvent::FileFailed {
+                    entry: entry.name.clone(),
+                    error: e.to_string(),
+                });
+                result.skipped_files += 1;
+                result.errors.push(format!("{}: {}", entry.name, e));
+                *result.error_categories.entry(e.to_string()).or_default() += 1;
+                result.record_undated(&e);
+
+                if self
+                    .max_errors
+                    .is_some_and(|max_errors| result.errors.len() >= max_errors)
+                {
+                    bail!(
+                        "Aborted after {} errors, last one: {}: {}",
+                        result.errors.len(),
+                        entry.name,
+                        e
+                    );
                 }
-                Err(e) => {
-                    println!("{}: error - {}", entry.name, e);
-                    skipped_files += 1;
-                    errors.push(format!("{}: {}", entry.name, e));
+                Ok(())
+            }
+        }
+    }
+
+    /// Files both halves of a Live Photo under one date, the still image's unless only
+    /// the video has one, and gives the second half the folder and name of the first
+    fn organize_live_photo(
+        &self,
+        first_half: (ZipEntry, ParsedExif),
+        second_half: (ZipEntry, ParsedExif),
+        index: &ArchiveIndex,
+        plan: &mut RunPlan,
+        result: &mut OrganizeResult,
+    ) -> Result<()> {
+        let ((image, image_exif), (video, video_exif)) = if index.is_live_photo_video(&first_half.0.name) {
+            (second_half, first_half)
+        } else {
+            (first_half, second_half)
+        };
+        let date_of = |entry: &ZipEntry, exif: &ParsedExif| {
+            self.date_extractor.extract_with_confidence_in_context(
+                &entry.name,
+                &entry.data,
+                &index.context_of(entry).with_exif(exif),
+            )
+        };
+
+        let mut placement = date_of(&image, &image_exif)
+            .or_else(|_| date_of(&video, &video_exif))
+            .ok()
+            .map(|extracted| LivePhotoPlacement {
+                date: extracted.time.date(),
+                confidence: extracted.confidence,
+                first_target: None,
+            });
+        self.organize_entry(&image, &image_exif, index, placement.as_mut(), plan, result)?;
+        self.organize_entry(&video, &video_exif, index, placement.as_mut(), plan, result)
+    }
+
+    fn emit(&self, event: OrganizeEvent) {
+        match self.observer {
+            Some(observer) => observer.on_event(&event),
+            None => ConsoleObserver::new(self.dry_run).on_event(&event),
+        }
+    }
+
+    fn process_entry(
+        &self,
+        entry: &ZipEntry,
+        context: &EntryContext,
+        mut live_photo: Option<&mut LivePhotoPlacement>,
+        plan: &mut RunPlan,
+    ) -> Result<EntryOutcome> {
+        let dating = match &live_photo {
+            Some(placement) => self.dating(placement.date, placement.confidence),
+            None => match self
+                .date_extractor
+                .extract_with_confidence_in_context(&entry.name, &entry.data, context)
+            {
+                Ok(extracted) => self.dating(extracted.time.date(), extracted.confidence),
+                Err(e) => return Err(e.context("Failed to extract date")),
+            },
+        };
+
+        // Large entries are streamed as they are, motion photos are a few MB
+        let video_start = (self.motion_photos != MotionPhotoPolicy::Keep && entry.large.is_none())
+            .then(|| motion_photo::embedded_video_offset(&entry.data))
+            .flatten();
+        let filename = match video_start {
+            Some(_) => Cow::Owned(motion_photo::still_image_name(self.extract_filename_from_path(&entry.name))),
+            None => Cow::Borrowed(self.extract_filename_from_path(&entry.name)),
+        };
+        let filename = filename.as_ref();
+        let first_target = live_photo.as_ref().and_then(|placement| placement.first_target.as_ref());
+        let generated_path = match (first_target, Path::new(filename).extension()) {
+            (Some(first_target), Some(extension)) => first_target.with_extension(extension),
+            _ => {
+                let subdirectory = self.subdirectory_for(entry, context);
+                match dating {
+                    Dating::Date(date) => self.path_generator.generate_path_in_subdirectory(
+                        &date,
+                        subdirectory.as_deref(),
+                        filename,
+                    ),
+                    Dating::YearOnly(year) => self
+                        .path_generator
+                        .generate_year_only_path_in_subdirectory(year, subdirectory.as_deref(), filename),
+                    Dating::Review(date) => self.path_generator.generate_review_path_in_subdirectory(
+                        &date,
+                        subdirectory.as_deref(),
+                        filename,
+                    ),
                 }
             }
+        };
+        let shortened = generated_path
+            .file_name()
+            .is_some_and(|target_name| target_name != filename);
+        let year_only = matches!(dating, Dating::YearOnly(_));
+        let review = matches!(dating, Dating::Review(_));
+
+        let data = self.output_data(entry, video_start);
+        // Hashing a streamed entry reads all of it, which only deduplication needs; without
+        // it the head in memory stands in, and repeats on disk are compared byte for byte
+        let content_hash = match &entry.large {
+            Some(large) if self.deduplicate_content => Self::stream_hash(large).context("Failed to read file")?,
+            _ => Self::content_hash(&data),
+        };
+        let content = (&*data, entry.large.as_ref());
+        if let Some(earlier_target) = plan
+            .contents
+            .get(&content_hash)
+            .filter(|_| self.deduplicate_content)
+            .filter(|earlier_target| {
+                plan.targets
+                    .get(*earlier_target)
+                    .is_some_and(|planned| self.is_planned_content(earlier_target, planned, content, content_hash))
+            })
+        {
+            return Ok(EntryOutcome {
+                action: WriteAction::Identical,
+                target_path: self.file_writer.get_full_path(earlier_target),
+                backup_path: None,
+                shortened,
+                renamed: false,
+                year_only,
+                review,
+            });
+        }
+        let (target_path, action) =
+            self.resolve_name_collision(&generated_path, content, content_hash, &plan.targets);
+        let renamed = target_path != generated_path;
+        plan.targets
+            .insert(target_path.clone(), self.planned_content(content, content_hash, action));
+        plan.contents
+            .entry(content_hash)
+            .or_insert_with(|| target_path.clone());
+        if let Some(placement) = live_photo.as_mut().filter(|placement| placement.first_target.is_none()) {
+            placement.first_target = Some(target_path.clone());
+        }
+
+        if let Some(video_start) = video_start.filter(|_| self.motion_photos == MotionPhotoPolicy::Split) {
+            self.write_motion_video(&target_path, &entry.data[video_start..], &mut plan.targets)?;
+        }
+
+        let full_path = self.file_writer.get_full_path(&target_path);
+        if self.dry_run || !action.writes() {
+            return Ok(EntryOutcome {
+                action,
+                target_path: full_path,
+                backup_path: None,
+                shortened,
+                renamed,
+                year_only,
+                review,
+            });
+        }
+
+        let backup_path = if action == WriteAction::Backup {
+            Some(self.move_conflicting_file_aside(&target_path)?)
+        } else {
+            None
+        };
+
+        self.ensure_parent_directory_exists(&target_path)?;
+        match &entry.large {
+            Some(large) => self.file_writer.write_stream(&target_path, large),
+            None => self.file_writer.write_file(&target_path, &data),
         }
+        .context("Failed to write file")?;
+        self.record_provenance(&target_path, entry)?;
 
-        Ok(OrganizeResult {
-            total_files,
-            organized_files,
-            skipped_files,
-            errors,
+        Ok(EntryOutcome {
+            action,
+            target_path: full_path,
+            backup_path,
+            shortened,
+            renamed,
+            year_only,
+            review,
         })
     }
 
-    fn process_entry(&self, entry: &ZipEntry) -> Result<std::path::PathBuf> {
-        let date = self
-            .date_extractor
-            .extract_date(&entry.name, &entry.data)
-            .context("Failed to extract date")?;
+    /// `entry: EXIF <time>, JSON <time>` when the times in the file and its sidecar differ
+    /// by more than the threshold
+    fn date_mismatch(&self, entry: &ZipEntry, context: &EntryContext) -> Option<String> {
+        let threshold = self.date_mismatch_threshold?;
+        let (embedded, sidecar) =
+            self.date_extractor
+                .embedded_and_sidecar_times(&entry.name, &entry.data, context)?;
+        ((embedded - sidecar).abs() > threshold)
+            .then(|| {
+                format!(
+                    "{}: EXIF {}, JSON {}",
+                    entry.name,
+                    embedded.format("%Y-%m-%d %H:%M:%S"),
+                    sidecar.format("%Y-%m-%d %H:%M:%S")
+                )
+            })
+    }
 
-        let filename = self.extract_filename_from_path(&entry.name);
-        let target_path = self.path_generator.generate_path(&date, filename);
+    /// How a date is placed: under its year alone when only that is known, else under its
+    /// day, or in `review/` when it is less trusted than `--min-confidence` asks
+    fn dating(&self, date: NaiveDate, confidence: Confidence) -> Dating {
+        match self.min_confidence {
+            _ if confidence == Confidence::Year => Dating::YearOnly(date.year()),
+            Some(min_confidence) if confidence < min_confidence => Dating::Review(date),
+            _ => Dating::Date(date),
+        }
+    }
 
-        self.ensure_parent_directory_exists(&target_path)?;
+    /// Folder(s) beneath the date directory: owner and/or device, when enabled
+    fn subdirectory_for(&self, entry: &ZipEntry, context: &EntryContext) -> Option<PathBuf> {
+        let mut subdirectory = PathBuf::new();
+        let owner = entry
+            .name
+            .split_once('/')
+            .map(|(first, _)| first)
+            .filter(|first| self.owner_labels.iter().any(|label| label == first));
+        if let Some(owner) = owner {
+            subdirectory.push(owner);
+        }
+        if self.by_device {
+            let parsed = ParsedExif::new();
+            subdirectory.push(device_name(&entry.name, &entry.data, context.exif.unwrap_or(&parsed)));
+        }
+        (!subdirectory.as_os_str().is_empty()).then_some(subdirectory)
+    }
+
+    /// Writes the video of a motion photo next to its still image, numbered like any
+    /// other name that is already taken
+    fn write_motion_video(
+        &self,
+        image_path: &Path,
+        video: &[u8],
+        planned_targets: &mut HashMap<PathBuf, PlannedContent>,
+    ) -> Result<()> {
+        let generated_path = image_path.with_file_name(motion_photo::companion_video_name(image_path));
+        let content_hash = Self::content_hash(video);
+        let (video_path, action) =
+            self.resolve_name_collision(&generated_path, (video, None), content_hash, planned_targets);
+        planned_targets.insert(video_path.clone(), self.planned_content((video, None), content_hash, action));
+        if self.dry_run || !action.writes() {
+            return Ok(());
+        }
+
+        if action == WriteAction::Backup {
+            self.move_conflicting_file_aside(&video_path)?;
+        }
+        self.ensure_parent_directory_exists(&video_path)?;
         self.file_writer
-            .write_file(&target_path, &entry.data)
-            .context("Failed to write file")?;
+            .write_file(&video_path, video)
+            .context("Failed to write motion photo video")
+    }
+
+    /// The bytes to write for an entry: the original data (only the still image of a
+    /// motion photo when `video_start` is given), or a copy with rounded GPS coordinates
+    /// when a GPS precision is configured (not for large, streamed entries)
+    fn output_data<'d>(&self, entry: &'d ZipEntry, video_start: Option<usize>) -> Cow<'d, [u8]> {
+        let data = &entry.data[..video_start.unwrap_or(entry.data.len())];
+        self.gps_precision
+            .filter(|_| entry.large.is_none())
+            .and_then(|decimals| round_gps_coordinates(data, decimals))
+            .map_or(Cow::Borrowed(data), Cow::Owned)
+    }
+
+    /// Two different entries in one run can map to the same target (same filename in
+    /// different Takeout folders). Instead of letting one replace the other, later
+    /// entries get a numbered name (`photo(1).jpg`, `photo(2).jpg`, ...). An entry with
+    /// the same content as an earlier one is simply treated as identical.
+    fn resolve_name_collision(
+        &self,
+        generated_path: &Path,
+        content: (&[u8], Option<&LargeContent>),
+        content_hash: u64,
+        planned_targets: &HashMap<PathBuf, PlannedContent>,
+    ) -> (PathBuf, WriteAction) {
+        let mut target_path = generated_path.to_path_buf();
+        let mut counter = 0;
+
+        while let Some(planned) = planned_targets.get(&target_path) {
+            if self.is_planned_content(&target_path, planned, content, content_hash) {
+                return (target_path, WriteAction::Identical);
+            }
+            counter += 1;
+            target_path = numbered_path(generated_path, counter);
+        }
+
+        let (data, large) = content;
+        let action = self.plan_action(&target_path, data, large);
+        (target_path, action)
+    }
+
+    /// What `action` puts at a target for `content`, the data or else the streamed entry
+    fn planned_content(
+        &self,
+        (data, large): (&[u8], Option<&LargeContent>),
+        hash: u64,
+        action: WriteAction,
+    ) -> PlannedContent {
+        PlannedContent {
+            hash,
+            size: large.map_or(data.len() as u64, |large| large.size()),
+            on_disk: action == WriteAction::Identical || (!self.dry_run && action.writes()),
+        }
+    }
+
+    /// Whether `content` is what was planned for `target_path`: a hash match is confirmed by
+    /// the size and, when the planned content is on disk, byte for byte
+    fn is_planned_content(
+        &self,
+        target_path: &Path,
+        planned: &PlannedContent,
+        (data, large): (&[u8], Option<&LargeContent>),
+        content_hash: u64,
+    ) -> bool {
+        let size = large.map_or(data.len() as u64, |large| large.size());
+        if planned.hash != content_hash || planned.size != size {
+            return false;
+        }
+        !planned.on_disk
+            || match large {
+                Some(large) => self.file_writer.contains_identical_stream(target_path, data, large),
+                None => self.file_writer.contains_identical_file(target_path, data),
+            }
+    }
+
+    fn content_hash(data: &[u8]) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        data.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Hash of a streamed entry's full content, read without holding it in memory
+    fn stream_hash(content: &LargeContent) -> Result<u64> {
+        struct HashingWriter(DefaultHasher);
+
+        impl std::io::Write for HashingWriter {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.write(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut hashing_writer = HashingWriter(DefaultHasher::new());
+        content.copy_to(&mut hashing_writer)?;
+        Ok(hashing_writer.0.finish())
+    }
+
+    fn plan_action(
+        &self,
+        target_path: &Path,
+        data: &[u8],
+        large: Option<&LargeContent>,
+    ) -> WriteAction {
+        let identical = match large {
+            Some(large) => self.file_writer.contains_identical_stream(target_path, data, large),
+            None => self.file_writer.contains_identical_file(target_path, data),
+        };
+
+        if identical {
+            WriteAction::Identical
+        } else if !self.file_writer.file_exists(target_path) {
+            WriteAction::New
+        } else if self.output_exists_policy == OutputExistsPolicy::Backup {
+            WriteAction::Backup
+        } else if self.force {
+            WriteAction::Overwrite
+        } else {
+            WriteAction::KeepExisting
+        }
+    }
 
-        Ok(self.file_writer.get_full_path(&target_path))
+    fn move_conflicting_file_aside(&self, target_path: &Path) -> Result<PathBuf> {
+        let backup_path = self
+            .file_writer
+            .move_aside(target_path)
+            .context("Failed to back up existing file")?;
+        Ok(self.file_writer.get_full_path(&backup_path))
     }
 
     fn extract_filename_from_path<'b>(&self, full_path: &'b str) -> &'b str {
-        full_path.rsplit('/').next().unwrap_or(full_path)
+        full_path.rsplit(['/', '\\']).next().unwrap_or(full_path)
+    }
+
+    fn record_provenance(&self, target_path: &Path, entry: &ZipEntry) -> Result<()> {
+        let Some(archive) = &self.provenance_archive else {
+            return Ok(());
+        };
+
+        let index_path = origin_index_path(target_path);
+        let file_name = target_path.file_name().unwrap_or_default().to_string_lossy();
+        let mut record = String::new();
+        if !self.file_writer.file_exists(&index_path) {
+            record.push_str(ORIGIN_INDEX_HEADER);
+        }
+        record.push_str(&origin_record(&file_name, archive, &entry.name));
+
+        self.file_writer
+            .append_file(&index_path, record.as_bytes())
+            .context("Failed to update provenance index")
     }
 
-    fn ensure_parent_directory_exists(&self, path: &std::path::Path) -> Result<()> {
+    fn ensure_parent_directory_exists(&self, path: &Path) -> Result<()> {
         if let Some(parent) = path.parent() {
             self.file_writer
                 .create_directory(parent)
@@ -103,24 +830,185 @@ impl<'a> PhotoOrganizer<'a> {
     }
 }
 
+/// Action taken (or planned, in a dry run) for an entry that passed the filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteAction {
+    /// Target does not exist yet
+    New,
+    /// Target exists with different content and gets replaced (--force)
+    Overwrite,
+    /// Target exists with different content and is left untouched
+    KeepExisting,
+    /// Target exists with different content and gets moved aside first
+    Backup,
+    /// Target already holds identical content, nothing to write
+    Identical,
+}
+
+impl WriteAction {
+    /// Label used in the itemized dry-run output
+    pub fn label(self) -> &'static str {
+        match self {
+            WriteAction::New => "new",
+            WriteAction::Overwrite => "overwrite",
+            WriteAction::KeepExisting => "keep",
+            WriteAction::Backup => "backup",
+            WriteAction::Identical => "identical",
+        }
+    }
+
+    pub fn writes(self) -> bool {
+        matches!(
+            self,
+            WriteAction::New | WriteAction::Overwrite | WriteAction::Backup
+        )
+    }
+}
+
+/// Targets and contents planned so far in a run, to number colliding names and to
+/// recognize content written before
+#[derive(Default)]
+struct RunPlan {
+    targets: HashMap<PathBuf, PlannedContent>,
+    contents: HashMap<u64, PathBuf>,
+}
+
+/// What a run put (or, in a dry run, would put) at a target
+#[derive(Clone, Copy)]
+struct PlannedContent {
+    hash: u64,
+    size: u64,
+    /// Whether the content is at the target on disk, to compare a repeat byte for byte;
+    /// a dry run only has the hash and size to go by
+    on_disk: bool,
+}
+
+/// What is known of when an entry was taken
+#[derive(Clone, Copy)]
+enum Dating {
+    Date(NaiveDate),
+    YearOnly(i32),
+    /// Dated, but by a source less trusted than `--min-confidence`
+    Review(NaiveDate),
+}
+
+/// Where both halves of a Live Photo go: the date they are filed under (and how far it is
+/// trusted), and the target of the half placed first, whose folder and name the second
+/// half takes over
+struct LivePhotoPlacement {
+    date: NaiveDate,
+    confidence: Confidence,
+    first_target: Option<PathBuf>,
+}
+
+/// What happened to a single entry that passed the filter
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntryOutcome {
+    pub action: WriteAction,
+    pub target_path: PathBuf,
+    pub backup_path: Option<PathBuf>,
+    /// Filename was shortened to stay within filesystem limits
+    pub shortened: bool,
+    /// Filename got a number because another entry in this run already used it
+    pub renamed: bool,
+    /// Only the year was known, from the Takeout folder: placed in `YYYY/YYYY-unknown/`
+    pub year_only: bool,
+    /// The date was less trusted than `--min-confidence`: placed in `review/`
+    pub review: bool,
+}
+
 /// Result of organization operation
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct OrganizeResult {
     pub total_files: usize,
     pub organized_files: usize,
     pub skipped_files: usize,
+    /// Subset of skipped files whose identical copy was already in the output
+    pub already_present_files: usize,
+    /// Subset of organized files that replaced (or moved aside) a different existing file
+    pub overwritten_files: usize,
+    /// Subset of skipped files that would have replaced a different existing file without --force
+    pub not_overwritten_files: usize,
+    /// Files that got a numbered name because another entry in this run already used the name
+    pub conflicting_files: usize,
+    /// Entries that passed the filter but were left out by --limit / --sample
+    pub unsampled_files: usize,
+    /// Entries the reader left out for being smaller than --min-size; not in `total_files`
+    pub tiny_files: usize,
+    /// Files dated only by their Takeout `Photos from YYYY` folder, placed in `YYYY/YYYY-unknown/`
+    pub year_only_files: usize,
+    /// Files dated less reliably than --min-confidence, placed in `review/YYYY/YYYY-MM-DD/`
+    pub review_files: usize,
+    /// Files whose embedded and JSON sidecar times differ by more than the mismatch
+    /// threshold, as `entry: EXIF <time>, JSON <time>`
+    pub date_mismatches: Vec<String>,
+    /// Entries whose filename was shortened to fit path limits, as `entry -> target`
+    pub shortened_paths: Vec<String>,
     pub errors: Vec<String>,
+    /// Number of errors per failure reason (the outermost error message)
+    pub error_categories: BTreeMap<String, usize>,
+    /// Why files got no date: per source as `source: reason` (e.g. `exif: no_exif`), or the
+    /// reason alone for a single extractor; `other` for failures without a known reason
+    pub undated_reasons: BTreeMap<String, usize>,
+    /// Total size of all entries read from the input
+    pub bytes_read: u64,
+    /// Total size of all files written to the output
+    pub bytes_written: u64,
+    pub read_duration: Duration,
+    pub index_duration: Duration,
+    pub process_duration: Duration,
+}
+
+impl OrganizeResult {
+    fn record(&mut self, action: WriteAction) {
+        match action {
+            WriteAction::Identical => {
+                self.skipped_files += 1;
+                self.already_present_files += 1;
+            }
+            WriteAction::Overwrite | WriteAction::Backup => {
+                self.organized_files += 1;
+                self.overwritten_files += 1;
+            }
+            WriteAction::KeepExisting => {
+                self.skipped_files += 1;
+                self.not_overwritten_files += 1;
+            }
+            WriteAction::New => self.organized_files += 1,
+        }
+    }
+
+    fn record_undated(&mut self, error: &anyhow::Error) {
+        let Some(reason) = DateExtractionError::of(error) else {
+            return;
+        };
+        let reasons: Vec<String> = match reason {
+            DateExtractionError::NoDate(failures) => failures
+                .iter()
+                .map(|failure| {
+                    let kind = failure.error.as_ref().map_or("other", DateExtractionError::kind);
+                    format!("{}: {}", failure.source, kind)
+                })
+                .collect(),
+            reason => vec![reason.kind().to_string()],
+        };
+        for reason in reasons {
+            *self.undated_reasons.entry(reason).or_default() += 1;
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::exif::ExifDateExtractor;
+    use crate::exif::{CompositeDateExtractor, ExifDateExtractor, FilenameBasedDateExtractor};
+    use crate::gps_privacy::tests::jpeg_with_gps;
     use crate::file_writer::RealFileSystemWriter;
     use crate::path_generator::PathGenerator;
     use crate::photo_filter::NoFilter;
+    use crate::zip_image_reader::{DirectoryImageReader, EntryIter, EntryMetadata, EntrySelectionReader};
+    use rstest::rstest;
     use std::fs;
-    use std::path::PathBuf;
 
     // Mock implementations for testing
     struct MockZipReader {
@@ -128,8 +1016,8 @@ mod tests {
     }
 
     impl ZipImageReader for MockZipReader {
-        fn read_entries(&self) -> Result<Vec<ZipEntry>> {
-            Ok(self.entries.clone())
+        fn entries(&self) -> Result<EntryIter<'_>> {
+            Ok(Box::new(self.entries.clone().into_iter().map(Ok)))
         }
     }
 
@@ -173,7 +1061,8 @@ mod tests {
         let zip_reader = MockZipReader {
             entries: vec![ZipEntry {
                 name: "photo1.jpg".to_string(),
-                data: test_image.to_vec(),
+                data: test_image.to_vec().into(),
+                ..Default::default()
             }],
         };
         let date_extractor = ExifDateExtractor::new();
@@ -219,11 +1108,13 @@ mod tests {
             entries: vec![
                 ZipEntry {
                     name: "photo1.jpg".to_string(),
-                    data: test_image.to_vec(),
+                    data: test_image.to_vec().into(),
+                    ..Default::default()
                 },
                 ZipEntry {
                     name: "photo2.jpg".to_string(),
-                    data: test_image.to_vec(),
+                    data: test_image.to_vec().into(),
+                    ..Default::default()
                 },
             ],
         };
@@ -267,7 +1158,8 @@ mod tests {
         let zip_reader = MockZipReader {
             entries: vec![ZipEntry {
                 name: "photo_oct.jpg".to_string(),
-                data: test_image.to_vec(),
+                data: test_image.to_vec().into(),
+                ..Default::default()
             }],
         };
         let date_extractor = ExifDateExtractor::new();
@@ -297,6 +1189,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::len_zero)]
     fn test_organize_file_without_exif_skipped() {
         // Arrange
         let temp_dir = "/tmp/test_org_no_exif";
@@ -304,7 +1197,8 @@ mod tests {
         let zip_reader = MockZipReader {
             entries: vec![ZipEntry {
                 name: "no_exif.jpg".to_string(),
-                data: vec![0xFF, 0xD8, 0xFF, 0xD9], // Minimal JPEG without EXIF
+                data: vec![0xFF, 0xD8, 0xFF, 0xD9].into(), // Minimal JPEG without EXIF
+                ..Default::default()
             }],
         };
         let date_extractor = ExifDateExtractor::new();
@@ -330,30 +1224,1202 @@ mod tests {
         assert_eq!(stats.organized_files, 0);
         assert_eq!(stats.skipped_files, 1);
         assert!(stats.errors.len() > 0);
+        assert_eq!(stats.error_categories.get("Failed to extract date"), Some(&1));
+        assert_eq!(stats.undated_reasons.get("no_exif"), Some(&1));
 
         // Cleanup
         fs::remove_dir_all(temp_dir).ok();
     }
 
     #[test]
-    fn test_organize_extracts_filename_from_zip_path() {
+    fn test_organize_places_low_confidence_files_in_review_folder() {
         // Arrange
-        use crate::file_writer::MockFileSystemWriter;
+        let temp_dir = "/tmp/test_org_min_confidence";
+        fs::remove_dir_all(temp_dir).ok();
+        let zip_reader = MockZipReader {
+            entries: vec![
+                ZipEntry {
+                    name: "photo.jpg".to_string(),
+                    data: include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg").to_vec().into(),
+                    ..Default::default()
+                },
+                ZipEntry {
+                    name: "Screenshot_2013-04-19-19-46-43.png".to_string(),
+                    data: vec![0xFF, 0xD8, 0xFF, 0xD9].into(),
+                    ..Default::default()
+                },
+            ],
+        };
+        let date_extractor = CompositeDateExtractor::new();
+        let file_writer = RealFileSystemWriter::new(temp_dir.to_string());
+        let path_generator = PathGenerator::new(&file_writer);
+        let filter = NoFilter::new();
+        let organizer = PhotoOrganizer::new(
+            &zip_reader,
+            &date_extractor,
+            &path_generator,
+            &file_writer,
+            &filter,
+        )
+        .with_min_confidence(Some(Confidence::Sidecar));
+
+        // Act
+        let result = organizer.organize().unwrap();
+
+        // Assert
+        assert_eq!(result.organized_files, 2);
+        assert_eq!(result.review_files, 1);
+        assert!(Path::new(temp_dir).join("2012/2012-10-06/photo.jpg").exists());
+        assert!(Path::new(temp_dir)
+            .join("review/2013/2013-04-19/Screenshot_2013-04-19-19-46-43.png")
+            .exists());
+
+        // Cleanup
+        fs::remove_dir_all(temp_dir).ok();
+    }
+
+    #[test]
+    fn test_organize_places_undated_takeout_files_by_year_folder() {
+        // Arrange
+        let temp_dir = "/tmp/test_org_year_only";
+        fs::remove_dir_all(temp_dir).ok();
+        // Every ZIP entry has a modification time, here the upload date
+        let entry = |name: &str| ZipEntry {
+            name: name.to_string(),
+            data: vec![0xFF, 0xD8, 0xFF, 0xD9].into(),
+            metadata: EntryMetadata {
+                modified: NaiveDate::from_ymd_opt(2019, 5, 1).unwrap().and_hms_opt(10, 0, 0),
+                ..EntryMetadata::default()
+            },
+            ..Default::default()
+        };
+        let zip_reader = MockZipReader {
+            entries: vec![
+                entry("Takeout/Google Photos/Photos from 2012/no_exif.jpg"),
+                entry("Takeout/Google Photos/Holiday/no_exif_either.jpg"),
+            ],
+        };
+        let date_extractor = CompositeDateExtractor::new();
+        let file_writer = RealFileSystemWriter::new(temp_dir.to_string());
+        let path_generator = PathGenerator::new(&file_writer);
+        let filter = NoFilter::new();
+        let organizer = PhotoOrganizer::new(
+            &zip_reader,
+            &date_extractor,
+            &path_generator,
+            &file_writer,
+            &filter,
+        );
+
+        // Act
+        let result = organizer.organize().unwrap();
 
+        // Assert
+        assert_eq!(result.organized_files, 2);
+        assert_eq!(result.year_only_files, 1);
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        assert!(Path::new(temp_dir).join("2012/2012-unknown/no_exif.jpg").exists());
+        assert!(Path::new(temp_dir).join("2019/2019-05-01/no_exif_either.jpg").exists());
+
+        // Cleanup
+        fs::remove_dir_all(temp_dir).ok();
+    }
+
+    #[test]
+    fn test_organize_skips_file_already_present_in_output() {
+        // Arrange
+        let temp_dir = "/tmp/test_org_already_present";
         let test_image = include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg");
         let zip_reader = MockZipReader {
             entries: vec![ZipEntry {
-                name: "Takeout/Google Photos/Photos from 2012/IMG_20121006_130932.jpg".to_string(),
-                data: test_image.to_vec(),
+                name: "photo1.jpg".to_string(),
+                data: test_image.to_vec().into(),
+                ..Default::default()
             }],
         };
         let date_extractor = ExifDateExtractor::new();
+        let file_writer = RealFileSystemWriter::new(temp_dir.to_string());
+        let path_generator = PathGenerator::new(&file_writer);
         let filter = NoFilter::new();
 
-        let mut mock_writer = MockFileSystemWriter::new();
-        mock_writer
-            .expect_find_existing_date_directory()
-            .returning(|_, _| None);
+        let organizer = PhotoOrganizer::new(
+            &zip_reader,
+            &date_extractor,
+            &path_generator,
+            &file_writer,
+            &filter,
+        );
+        organizer.organize().unwrap();
+
+        // Act
+        let result = organizer.organize();
+
+        // Assert
+        let stats = result.unwrap();
+        assert_eq!(stats.organized_files, 0);
+        assert_eq!(stats.skipped_files, 1);
+        assert_eq!(stats.already_present_files, 1);
+
+        // Cleanup
+        fs::remove_dir_all(temp_dir).ok();
+    }
+
+    #[test]
+    fn test_organize_require_empty_policy_aborts_on_non_empty_output() {
+        // Arrange
+        let temp_dir = "/tmp/test_org_require_empty";
+        fs::create_dir_all(format!("{}/2020", temp_dir)).unwrap();
+        let zip_reader = MockZipReader { entries: vec![] };
+        let date_extractor = ExifDateExtractor::new();
+        let file_writer = RealFileSystemWriter::new(temp_dir.to_string());
+        let path_generator = PathGenerator::new(&file_writer);
+        let filter = NoFilter::new();
+
+        let organizer = PhotoOrganizer::new(
+            &zip_reader,
+            &date_extractor,
+            &path_generator,
+            &file_writer,
+            &filter,
+        )
+        .with_output_exists_policy(OutputExistsPolicy::RequireEmpty);
+
+        // Act
+        let result = organizer.organize();
+
+        // Assert
+        assert!(result.is_err());
+
+        // Cleanup
+        fs::remove_dir_all(temp_dir).ok();
+    }
+
+    #[test]
+    fn test_organize_backup_policy_moves_conflicting_file_aside() {
+        // Arrange
+        let temp_dir = "/tmp/test_org_backup_policy";
+        let test_image = include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg");
+        let target_dir = PathBuf::from(temp_dir).join("2012").join("2012-10-06");
+        fs::create_dir_all(&target_dir).unwrap();
+        fs::write(target_dir.join("photo1.jpg"), b"older content").unwrap();
+
+        let zip_reader = MockZipReader {
+            entries: vec![ZipEntry {
+                name: "photo1.jpg".to_string(),
+                data: test_image.to_vec().into(),
+                ..Default::default()
+            }],
+        };
+        let date_extractor = ExifDateExtractor::new();
+        let file_writer = RealFileSystemWriter::new(temp_dir.to_string());
+        let path_generator = PathGenerator::new(&file_writer);
+        let filter = NoFilter::new();
+
+        let organizer = PhotoOrganizer::new(
+            &zip_reader,
+            &date_extractor,
+            &path_generator,
+            &file_writer,
+            &filter,
+        )
+        .with_output_exists_policy(OutputExistsPolicy::Backup);
+
+        // Act
+        let result = organizer.organize();
+
+        // Assert
+        assert_eq!(result.unwrap().organized_files, 1);
+        assert_eq!(fs::read(target_dir.join("photo1.jpg")).unwrap(), test_image);
+        assert_eq!(fs::read(target_dir.join("photo1.jpg.bak")).unwrap(), b"older content");
+
+        // Cleanup
+        fs::remove_dir_all(temp_dir).ok();
+    }
+
+    #[test]
+    fn test_organize_keeps_different_existing_file_without_force() {
+        // Arrange
+        let temp_dir = "/tmp/test_org_keep_existing";
+        let test_image = include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg");
+        let target_dir = PathBuf::from(temp_dir).join("2012").join("2012-10-06");
+        fs::create_dir_all(&target_dir).unwrap();
+        fs::write(target_dir.join("photo1.jpg"), b"older content").unwrap();
+
+        let zip_reader = MockZipReader {
+            entries: vec![ZipEntry {
+                name: "photo1.jpg".to_string(),
+                data: test_image.to_vec().into(),
+                ..Default::default()
+            }],
+        };
+        let date_extractor = ExifDateExtractor::new();
+        let file_writer = RealFileSystemWriter::new(temp_dir.to_string());
+        let path_generator = PathGenerator::new(&file_writer);
+        let filter = NoFilter::new();
+
+        let organizer = PhotoOrganizer::new(
+            &zip_reader,
+            &date_extractor,
+            &path_generator,
+            &file_writer,
+            &filter,
+        );
+
+        // Act
+        let result = organizer.organize().unwrap();
+        let forced_result = organizer.with_force(true).organize().unwrap();
+
+        // Assert
+        assert_eq!(result.organized_files, 0);
+        assert_eq!(result.not_overwritten_files, 1);
+        assert_eq!(forced_result.organized_files, 1);
+        assert_eq!(forced_result.overwritten_files, 1);
+        assert_eq!(fs::read(target_dir.join("photo1.jpg")).unwrap(), test_image);
+
+        // Cleanup
+        fs::remove_dir_all(temp_dir).ok();
+    }
+
+    #[test]
+    fn test_organize_dry_run_classifies_without_writing() {
+        // Arrange
+        let temp_dir = "/tmp/test_org_dry_run";
+        let test_image = include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg");
+        let target_dir = PathBuf::from(temp_dir).join("2012").join("2012-10-06");
+        fs::create_dir_all(&target_dir).unwrap();
+        fs::write(target_dir.join("same.jpg"), test_image).unwrap();
+        fs::write(target_dir.join("changed.jpg"), b"older content").unwrap();
+
+        let entry = |name: &str| ZipEntry {
+            name: name.to_string(),
+            data: test_image.to_vec().into(),
+            ..Default::default()
+        };
+        let zip_reader = MockZipReader {
+            entries: vec![
+                entry("new.jpg"),
+                entry("same.jpg"),
+                entry("changed.jpg"),
+                entry("other/new.jpg"),
+            ],
+        };
+        let date_extractor = ExifDateExtractor::new();
+        let file_writer = RealFileSystemWriter::new(temp_dir.to_string());
+        let path_generator = PathGenerator::new(&file_writer);
+        let filter = NoFilter::new();
+
+        let organizer = PhotoOrganizer::new(
+            &zip_reader,
+            &date_extractor,
+            &path_generator,
+            &file_writer,
+            &filter,
+        )
+        .with_dry_run(true);
+
+        // Act
+        let result = organizer.organize().unwrap();
+
+        // Assert
+        assert_eq!(result.organized_files, 1);
+        assert_eq!(result.already_present_files, 2);
+        assert_eq!(result.not_overwritten_files, 1);
+        assert!(!target_dir.join("new.jpg").exists());
+        assert_eq!(fs::read(target_dir.join("changed.jpg")).unwrap(), b"older content");
+
+        // Cleanup
+        fs::remove_dir_all(temp_dir).ok();
+    }
+
+    #[test]
+    fn test_organize_records_shortened_filenames() {
+        // Arrange
+        let temp_dir = "/tmp/test_org_shortened";
+        let test_image = include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg");
+        let long_name = format!("{}.jpg", "long".repeat(80));
+        let zip_reader = MockZipReader {
+            entries: vec![ZipEntry {
+                name: long_name.clone(),
+                data: test_image.to_vec().into(),
+                ..Default::default()
+            }],
+        };
+        let date_extractor = ExifDateExtractor::new();
+        let file_writer = RealFileSystemWriter::new(temp_dir.to_string());
+        let path_generator = PathGenerator::new(&file_writer);
+        let filter = NoFilter::new();
+
+        let organizer = PhotoOrganizer::new(
+            &zip_reader,
+            &date_extractor,
+            &path_generator,
+            &file_writer,
+            &filter,
+        );
+
+        // Act
+        let result = organizer.organize().unwrap();
+
+        // Assert
+        assert_eq!(result.organized_files, 1);
+        assert_eq!(result.shortened_paths.len(), 1);
+        assert!(result.shortened_paths[0].starts_with(&long_name));
+
+        // Cleanup
+        fs::remove_dir_all(temp_dir).ok();
+    }
+
+    #[test]
+    fn test_organize_renames_colliding_entries_with_different_content() {
+        // Arrange
+        let temp_dir = "/tmp/test_org_name_collision";
+        fs::remove_dir_all(temp_dir).ok();
+        let test_image = include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg");
+        let mut other_image = test_image.to_vec();
+        other_image.extend_from_slice(b"trailing bytes");
+
+        let zip_reader = MockZipReader {
+            entries: vec![
+                ZipEntry {
+                    name: "Album A/photo.jpg".to_string(),
+                    data: test_image.to_vec().into(),
+                    ..Default::default()
+                },
+                ZipEntry {
+                    name: "Album B/photo.jpg".to_string(),
+                    data: other_image.clone().into(),
+                    ..Default::default()
+                },
+                ZipEntry {
+                    name: "Album C/photo.jpg".to_string(),
+                    data: test_image.to_vec().into(),
+                    ..Default::default()
+                },
+            ],
+        };
+        let date_extractor = ExifDateExtractor::new();
+        let file_writer = RealFileSystemWriter::new(temp_dir.to_string());
+        let path_generator = PathGenerator::new(&file_writer);
+        let filter = NoFilter::new();
+
+        let organizer = PhotoOrganizer::new(
+            &zip_reader,
+            &date_extractor,
+            &path_generator,
+            &file_writer,
+            &filter,
+        );
+
+        // Act
+        let result = organizer.organize().unwrap();
+        let rerun_result = organizer.organize().unwrap();
+
+        // Assert
+        let target_dir = PathBuf::from(temp_dir).join("2012").join("2012-10-06");
+        assert_eq!(result.organized_files, 2);
+        assert_eq!(result.conflicting_files, 1);
+        assert_eq!(result.already_present_files, 1);
+        assert_eq!(fs::read(target_dir.join("photo.jpg")).unwrap(), test_image);
+        assert_eq!(fs::read(target_dir.join("photo(1).jpg")).unwrap(), other_image);
+        assert_eq!(rerun_result.organized_files, 0);
+        assert_eq!(rerun_result.already_present_files, 3);
+
+        // Cleanup
+        fs::remove_dir_all(temp_dir).ok();
+    }
+
+    #[test]
+    fn test_planned_content_hash_match_is_confirmed_by_size_and_bytes() {
+        // Arrange
+        let temp_dir = "/tmp/test_org_hash_collision";
+        fs::remove_dir_all(temp_dir).ok();
+        let written = b"first photo".to_vec();
+        let other = b"other photo".to_vec();
+        let file_writer = RealFileSystemWriter::new(temp_dir.to_string());
+        fs::create_dir_all(temp_dir).unwrap();
+        file_writer.write_file(Path::new("photo.jpg"), &written).unwrap();
+        let zip_reader = MockZipReader { entries: vec![] };
+        let date_extractor = ExifDateExtractor::new();
+        let path_generator = PathGenerator::new(&file_writer);
+        let filter = NoFilter::new();
+        let organizer = PhotoOrganizer::new(&zip_reader, &date_extractor, &path_generator, &file_writer, &filter);
+        // As if `other` had the hash of what was written: a collision
+        let colliding = PlannedContent {
+            hash: 42,
+            size: written.len() as u64,
+            on_disk: true,
+        };
+        let other_size = PlannedContent {
+            size: written.len() as u64 + 1,
+            on_disk: false,
+            ..colliding
+        };
+
+        // Act
+        let same = organizer.is_planned_content(Path::new("photo.jpg"), &colliding, (&written, None), 42);
+        let collision = organizer.is_planned_content(Path::new("photo.jpg"), &colliding, (&other, None), 42);
+        let size_differs = organizer.is_planned_content(Path::new("photo.jpg"), &other_size, (&written, None), 42);
+
+        // Assert
+        assert!(same);
+        assert!(!collision);
+        assert!(!size_differs);
+
+        // Cleanup
+        fs::remove_dir_all(temp_dir).ok();
+    }
+
+    #[test]
+    fn test_organize_sends_events_to_observer() {
+        // Arrange
+        use std::cell::RefCell;
+
+        struct RecordingObserver {
+            events: RefCell<Vec<OrganizeEvent>>,
+        }
+
+        impl OrganizeObserver for RecordingObserver {
+            fn on_event(&self, event: &OrganizeEvent) {
+                self.events.borrow_mut().push(event.clone());
+            }
+        }
+
+        let temp_dir = "/tmp/test_org_events";
+        fs::remove_dir_all(temp_dir).ok();
+        let test_image = include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg");
+        let zip_reader = MockZipReader {
+            entries: vec![
+                ZipEntry {
+                    name: "photo1.jpg".to_string(),
+                    data: test_image.to_vec().into(),
+                    ..Default::default()
+                },
+                ZipEntry {
+                    name: "no_exif.jpg".to_string(),
+                    data: vec![0xFF, 0xD8, 0xFF, 0xD9].into(),
+                    ..Default::default()
+                },
+            ],
+        };
+        let date_extractor = ExifDateExtractor::new();
+        let file_writer = RealFileSystemWriter::new(temp_dir.to_string());
+        let path_generator = PathGenerator::new(&file_writer);
+        let filter = NoFilter::new();
+        let observer = RecordingObserver {
+            events: RefCell::new(Vec::new()),
+        };
+
+        let organizer = PhotoOrganizer::new(
+            &zip_reader,
+            &date_extractor,
+            &path_generator,
+            &file_writer,
+            &filter,
+        )
+        .with_observer(&observer);
+
+        // Act
+        let result = organizer.organize().unwrap();
+
+        // Assert
+        let events = observer.events.into_inner();
+        assert_eq!(events.len(), 5);
+        assert_eq!(events[0], OrganizeEvent::Started { total_files: 2 });
+        assert!(matches!(
+            &events[2],
+            OrganizeEvent::FileProcessed { entry, outcome }
+                if entry == "photo1.jpg" && outcome.action == WriteAction::New
+        ));
+        assert!(matches!(&events[3], OrganizeEvent::FileFailed { entry, .. } if entry == "no_exif.jpg"));
+        assert_eq!(events[4], OrganizeEvent::Finished { result });
+
+        // Cleanup
+        fs::remove_dir_all(temp_dir).ok();
+    }
+
+    #[test]
+    fn test_organize_aborts_after_max_errors() {
+        // Arrange
+        let temp_dir = "/tmp/test_org_max_errors";
+        let failing_entry = |name: &str| ZipEntry {
+            name: name.to_string(),
+            data: vec![0xFF, 0xD8, 0xFF, 0xD9].into(),
+            ..Default::default()
+        };
+        let zip_reader = MockZipReader {
+            entries: vec![failing_entry("a.jpg"), failing_entry("b.jpg"), failing_entry("c.jpg")],
+        };
+        let date_extractor = ExifDateExtractor::new();
+        let file_writer = RealFileSystemWriter::new(temp_dir.to_string());
+        let path_generator = PathGenerator::new(&file_writer);
+        let filter = NoFilter::new();
+
+        let organizer = PhotoOrganizer::new(
+            &zip_reader,
+            &date_extractor,
+            &path_generator,
+            &file_writer,
+            &filter,
+        );
+
+        // Act
+        let unlimited = organizer.organize();
+        let limited = organizer.with_max_errors(Some(2)).organize();
+
+        // Assert
+        assert_eq!(unlimited.unwrap().errors.len(), 3);
+        let error = limited.unwrap_err().to_string();
+        assert!(error.contains("Aborted after 2 errors"), "got {}", error);
+        assert!(error.contains("b.jpg"));
+
+        // Cleanup
+        fs::remove_dir_all(temp_dir).ok();
+    }
+
+    #[rstest]
+    #[case(MotionPhotoPolicy::Keep, "MVIMG_20180101_120000.jpg", None)]
+    #[case(MotionPhotoPolicy::Strip, "MVIMG_20180101_120000.jpg", None)]
+    #[case(MotionPhotoPolicy::Split, "MVIMG_20180101_120000.jpg", Some("MVIMG_20180101_120000.mp4"))]
+    #[case(MotionPhotoPolicy::Split, "PXL_20180101_120000.MP", Some("PXL_20180101_120000.mp4"))]
+    fn test_organize_handles_motion_photo_video(
+        #[case] policy: MotionPhotoPolicy,
+        #[case] name: &str,
+        #[case] companion: Option<&str>,
+    ) {
+        // Arrange
+        let temp_dir = format!("/tmp/test_org_motion_{:?}_{}", policy, name);
+        fs::remove_dir_all(&temp_dir).ok();
+        let (data, video) = crate::motion_photo::tests::motion_photo();
+        let zip_reader = MockZipReader {
+            entries: vec![ZipEntry {
+                name: format!("Takeout/Google Photos/{}", name),
+                data: data.clone().into(),
+                ..Default::default()
+            }],
+        };
+        let date_extractor = FilenameBasedDateExtractor::new();
+        let file_writer = RealFileSystemWriter::new(temp_dir.clone());
+        let path_generator = PathGenerator::new(&file_writer);
+        let filter = NoFilter::new();
+        let organizer = PhotoOrganizer::new(
+            &zip_reader,
+            &date_extractor,
+            &path_generator,
+            &file_writer,
+            &filter,
+        )
+        .with_motion_photos(policy);
+
+        // Act
+        let result = organizer.organize().unwrap();
+
+        // Assert
+        assert_eq!(result.organized_files, 1);
+        let date_dir = format!("{}/2018/2018-01-01", temp_dir);
+        let image_name = match policy {
+            MotionPhotoPolicy::Keep => name.to_string(),
+            _ => crate::motion_photo::still_image_name(name),
+        };
+        let image = fs::read(format!("{}/{}", date_dir, image_name)).unwrap();
+        let expected_image = match policy {
+            MotionPhotoPolicy::Keep => &data[..],
+            _ => crate::motion_photo::tests::STILL,
+        };
+        assert_eq!(image, expected_image);
+        match companion {
+            Some(companion) => assert_eq!(fs::read(format!("{}/{}", date_dir, companion)).unwrap(), video),
+            None => assert_eq!(fs::read_dir(&date_dir).unwrap().count(), 1),
+        }
+
+        // Cleanup
+        fs::remove_dir_all(&temp_dir).ok();
+    }
+
+    #[test]
+    fn test_organize_places_live_photo_halves_together() {
+        // Arrange
+        use chrono::{NaiveDate, NaiveDateTime};
+
+        /// Only the video half (and the unrelated photo) carry a date
+        struct VideoDateExtractor;
+
+        impl DateExtractor for VideoDateExtractor {
+            fn extract_date_time(&self, filename: &str, _image_data: &[u8]) -> Result<NaiveDateTime> {
+                if filename.starts_with("Trip/") && !filename.ends_with(".MOV") {
+                    bail!("no EXIF");
+                }
+                Ok(NaiveDate::from_ymd_opt(2020, 5, 1).unwrap().and_hms_opt(12, 0, 0).unwrap())
+            }
+
+            fn confidence(&self) -> Confidence {
+                Confidence::Exif
+            }
+        }
+
+        let temp_dir = "/tmp/test_org_live_photo";
+        fs::remove_dir_all(temp_dir).ok();
+        let entry = |name: &str, data: &[u8]| ZipEntry {
+            name: name.to_string(),
+            data: data.to_vec().into(),
+            ..Default::default()
+        };
+        let zip_reader = MockZipReader {
+            entries: vec![
+                entry("Other/IMG_1234.HEIC", b"other photo"),
+                entry("Trip/IMG_1234.HEIC", b"still"),
+                entry("Trip/IMG_1234.MOV", b"video"),
+            ],
+        };
+        let date_extractor = VideoDateExtractor;
+        let file_writer = RealFileSystemWriter::new(temp_dir.to_string());
+        let path_generator = PathGenerator::new(&file_writer);
+        let filter = NoFilter::new();
+        let organizer = PhotoOrganizer::new(
+            &zip_reader,
+            &date_extractor,
+            &path_generator,
+            &file_writer,
+            &filter,
+        );
+
+        // Act
+        let result = organizer.organize().unwrap();
+
+        // Assert
+        assert_eq!(result.organized_files, 3);
+        let date_dir = format!("{}/2020/2020-05-01", temp_dir);
+        assert_eq!(fs::read(format!("{}/IMG_1234.HEIC", date_dir)).unwrap(), b"other photo");
+        assert_eq!(fs::read(format!("{}/IMG_1234(1).HEIC", date_dir)).unwrap(), b"still");
+        assert_eq!(fs::read(format!("{}/IMG_1234(1).MOV", date_dir)).unwrap(), b"video");
+
+        // Cleanup
+        fs::remove_dir_all(temp_dir).ok();
+    }
+
+    #[test]
+    fn test_organize_rounds_gps_coordinates_in_written_copies() {
+        // Arrange
+        let temp_dir = "/tmp/test_org_gps_precision";
+        fs::remove_dir_all(temp_dir).ok();
+        let original = jpeg_with_gps(
+            [(52, 1), (22, 1), (211234, 10000)],
+            [(4, 1), (53, 1), (419876, 10000)],
+        );
+        let zip_reader = MockZipReader {
+            entries: vec![ZipEntry {
+                name: "IMG_20230115_123456.jpg".to_string(),
+                data: original.clone().into(),
+                ..Default::default()
+            }],
+        };
+        let date_extractor = FilenameBasedDateExtractor::new();
+        let file_writer = RealFileSystemWriter::new(temp_dir.to_string());
+        let path_generator = PathGenerator::new(&file_writer);
+        let filter = NoFilter::new();
+        let organizer = PhotoOrganizer::new(
+            &zip_reader,
+            &date_extractor,
+            &path_generator,
+            &file_writer,
+            &filter,
+        )
+        .with_gps_precision(Some(2));
+
+        // Act
+        let result = organizer.organize().unwrap();
+
+        // Assert
+        assert_eq!(result.organized_files, 1);
+        let written =
+            fs::read(format!("{}/2023/2023-01-15/IMG_20230115_123456.jpg", temp_dir)).unwrap();
+        assert_ne!(written, original);
+        assert_eq!(Some(written), round_gps_coordinates(&original, 2));
+
+        // Cleanup
+        fs::remove_dir_all(temp_dir).ok();
+    }
+
+    #[test]
+    fn test_organize_writes_provenance_index_per_date_directory() {
+        // Arrange
+        let temp_dir = "/tmp/test_org_provenance";
+        fs::remove_dir_all(temp_dir).ok();
+        let entry = |name: &str, data: &[u8]| ZipEntry {
+            name: name.to_string(),
+            data: data.to_vec().into(),
+            ..Default::default()
+        };
+        let zip_reader = MockZipReader {
+            entries: vec![
+                entry("Takeout/Google Photos/Trip/IMG_20230115_120000.jpg", b"first"),
+                entry("Takeout/Google Photos/Trip/IMG_20230115_130000.jpg", b"second"),
+            ],
+        };
+        let date_extractor = FilenameBasedDateExtractor::new();
+        let file_writer = RealFileSystemWriter::new(temp_dir.to_string());
+        let path_generator = PathGenerator::new(&file_writer);
+        let filter = NoFilter::new();
+        let organizer = PhotoOrganizer::new(
+            &zip_reader,
+            &date_extractor,
+            &path_generator,
+            &file_writer,
+            &filter,
+        )
+        .with_provenance(Some("takeout-001.zip".to_string()));
+
+        // Act
+        let result = organizer.organize().unwrap();
+
+        // Assert
+        assert_eq!(result.organized_files, 2);
+        let index = fs::read_to_string(format!("{}/2023/2023-01-15/.origin.csv", temp_dir)).unwrap();
+        assert_eq!(
+            index,
+            "file,archive,entry\n\
+             IMG_20230115_120000.jpg,takeout-001.zip,Takeout/Google Photos/Trip/IMG_20230115_120000.jpg\n\
+             IMG_20230115_130000.jpg,takeout-001.zip,Takeout/Google Photos/Trip/IMG_20230115_130000.jpg\n"
+        );
+
+        // Cleanup
+        fs::remove_dir_all(temp_dir).ok();
+    }
+
+    #[rstest]
+    #[case("500", Sample::Limit(500))]
+    #[case("1%", Sample::Percent(1.0))]
+    #[case(" 0.5 %", Sample::Percent(0.5))]
+    fn test_parse_sample(#[case] value: &str, #[case] expected: Sample) {
+        // Act & Assert
+        assert_eq!(value.parse::<Sample>().unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case("0%")]
+    #[case("150%")]
+    #[case("lots")]
+    fn test_parse_sample_rejects_invalid_values(#[case] value: &str) {
+        // Act & Assert
+        assert!(value.parse::<Sample>().is_err(), "{}", value);
+    }
+
+    #[test]
+    fn test_sample_percent_spreads_selection_evenly() {
+        // Act
+        let selected: Vec<usize> = (0..20).filter(|&i| Sample::Percent(25.0).selects(i)).collect();
+
+        // Assert
+        assert_eq!(selected, vec![3, 7, 11, 15, 19]);
+    }
+
+    #[test]
+    fn test_organize_with_limit_processes_only_first_entries() {
+        // Arrange
+        let temp_dir = "/tmp/test_org_limit";
+        fs::remove_dir_all(temp_dir).ok();
+        let entries = (0..5)
+            .map(|i| ZipEntry {
+                name: format!("IMG_20230115_12000{}.jpg", i),
+                data: vec![i].into(),
+                ..Default::default()
+            })
+            .collect();
+        let zip_reader = MockZipReader { entries };
+        let date_extractor = FilenameBasedDateExtractor::new();
+        let file_writer = RealFileSystemWriter::new(temp_dir.to_string());
+        let path_generator = PathGenerator::new(&file_writer);
+        let filter = NoFilter::new();
+        let organizer = PhotoOrganizer::new(
+            &zip_reader,
+            &date_extractor,
+            &path_generator,
+            &file_writer,
+            &filter,
+        )
+        .with_sample(Some(Sample::Limit(2)));
+
+        // Act
+        let result = organizer.organize().unwrap();
+
+        // Assert
+        assert_eq!(result.total_files, 5);
+        assert_eq!(result.organized_files, 2);
+        assert_eq!(result.unsampled_files, 3);
+        assert!(Path::new(temp_dir).join("2023/2023-01-15/IMG_20230115_120001.jpg").exists());
+        assert!(!Path::new(temp_dir).join("2023/2023-01-15/IMG_20230115_120002.jpg").exists());
+
+        // Cleanup
+        fs::remove_dir_all(temp_dir).ok();
+    }
+
+    #[test]
+    fn test_organize_by_device_adds_device_folder() {
+        // Arrange
+        let temp_dir = "/tmp/test_org_by_device";
+        fs::remove_dir_all(temp_dir).ok();
+        let zip_reader = MockZipReader {
+            entries: vec![ZipEntry {
+                name: "PXL_20210502_101010.jpg".to_string(),
+                data: vec![0xFF, 0xD8, 0xFF, 0xD9].into(),
+                ..Default::default()
+            }],
+        };
+        let date_extractor = FilenameBasedDateExtractor::new();
+        let file_writer = RealFileSystemWriter::new(temp_dir.to_string());
+        let path_generator = PathGenerator::new(&file_writer);
+        let filter = NoFilter::new();
+        let organizer = PhotoOrganizer::new(
+            &zip_reader,
+            &date_extractor,
+            &path_generator,
+            &file_writer,
+            &filter,
+        )
+        .with_by_device(true);
+
+        // Act
+        let result = organizer.organize().unwrap();
+
+        // Assert
+        assert_eq!(result.organized_files, 1);
+        assert!(Path::new(temp_dir)
+            .join("2021/2021-05-02/Google Pixel/PXL_20210502_101010.jpg")
+            .exists());
+
+        // Cleanup
+        fs::remove_dir_all(temp_dir).ok();
+    }
+
+    #[test]
+    fn test_organize_merges_accounts_into_owner_folders_without_duplicates() {
+        // Arrange
+        let temp_dir = "/tmp/test_org_accounts";
+        fs::remove_dir_all(temp_dir).ok();
+        let entry = |name: &str, data: &[u8]| ZipEntry {
+            name: name.to_string(),
+            data: data.to_vec().into(),
+            ..Default::default()
+        };
+        let zip_reader = MockZipReader {
+            entries: vec![
+                entry("anna/Takeout/Google Photos/IMG_20230115_120000.jpg", b"shared"),
+                entry("ben/Takeout/Google Photos/IMG_20230115_120000.jpg", b"shared"),
+                entry("ben/Takeout/Google Photos/IMG_20230115_130000.jpg", b"own"),
+                entry("Takeout/Google Photos/IMG_20230115_140000.jpg", b"unlabelled"),
+            ],
+        };
+        let date_extractor = FilenameBasedDateExtractor::new();
+        let file_writer = RealFileSystemWriter::new(temp_dir.to_string());
+        let path_generator = PathGenerator::new(&file_writer);
+        let filter = NoFilter::new();
+        let organizer = PhotoOrganizer::new(
+            &zip_reader,
+            &date_extractor,
+            &path_generator,
+            &file_writer,
+            &filter,
+        )
+        .with_owner_folders(vec!["anna".to_string(), "ben".to_string()])
+        .with_deduplicate_content(true);
+
+        // Act
+        let result = organizer.organize().unwrap();
+
+        // Assert
+        assert_eq!(result.organized_files, 3);
+        assert_eq!(result.already_present_files, 1);
+        let date_dir = Path::new(temp_dir).join("2023/2023-01-15");
+        assert!(date_dir.join("anna/IMG_20230115_120000.jpg").exists());
+        assert!(!date_dir.join("ben/IMG_20230115_120000.jpg").exists());
+        assert!(date_dir.join("ben/IMG_20230115_130000.jpg").exists());
+        assert!(date_dir.join("IMG_20230115_140000.jpg").exists());
+
+        // Cleanup
+        fs::remove_dir_all(temp_dir).ok();
+    }
+
+    #[test]
+    fn test_organize_streams_large_entries_and_detects_them_on_rerun() {
+        // Arrange
+        let input_dir = "/tmp/test_org_large_input";
+        let temp_dir = "/tmp/test_org_large_output";
+        fs::remove_dir_all(temp_dir).ok();
+        fs::create_dir_all(input_dir).unwrap();
+        let video = vec![42u8; 4096];
+        fs::write(format!("{}/VID_20230115_120000.mp4", input_dir), &video).unwrap();
+        let mut zip_reader = DirectoryImageReader::new(input_dir.to_string());
+        zip_reader.large_entry_bytes = 100;
+        let date_extractor = FilenameBasedDateExtractor::new();
+        let file_writer = RealFileSystemWriter::new(temp_dir.to_string());
+        let path_generator = PathGenerator::new(&file_writer);
+        let filter = NoFilter::new();
+        let organizer = PhotoOrganizer::new(
+            &zip_reader,
+            &date_extractor,
+            &path_generator,
+            &file_writer,
+            &filter,
+        );
+
+        // Act
+        let first_run = organizer.organize().unwrap();
+        let second_run = organizer.organize().unwrap();
+
+        // Assert
+        assert_eq!(first_run.organized_files, 1);
+        assert_eq!(first_run.bytes_written, video.len() as u64);
+        let written = fs::read(format!("{}/2023/2023-01-15/VID_20230115_120000.mp4", temp_dir)).unwrap();
+        assert_eq!(written, video);
+        assert_eq!(second_run.already_present_files, 1);
+
+        // Cleanup
+        fs::remove_dir_all(input_dir).ok();
+        fs::remove_dir_all(temp_dir).ok();
+    }
+
+    #[rstest]
+    #[case("VID_20230115_120000.mov")]
+    #[case("VID_20230115_120000.3gp")]
+    #[case("VID_20230115_120000.avi")]
+    #[case("VID_20230115_120000.mkv")]
+    fn test_organize_places_videos_by_filename_date(#[case] name: &str) {
+        // Arrange
+        let input_dir = format!("/tmp/test_org_video_input_{}", name);
+        let temp_dir = format!("/tmp/test_org_video_output_{}", name);
+        fs::remove_dir_all(&temp_dir).ok();
+        fs::create_dir_all(&input_dir).unwrap();
+        fs::write(format!("{}/{}", input_dir, name), b"fake video data").unwrap();
+        let zip_reader = DirectoryImageReader::new(input_dir.clone());
+        let date_extractor = FilenameBasedDateExtractor::new();
+        let file_writer = RealFileSystemWriter::new(temp_dir.clone());
+        let path_generator = PathGenerator::new(&file_writer);
+        let filter = NoFilter::new();
+        let organizer = PhotoOrganizer::new(
+            &zip_reader,
+            &date_extractor,
+            &path_generator,
+            &file_writer,
+            &filter,
+        );
+
+        // Act
+        let result = organizer.organize().unwrap();
+
+        // Assert
+        assert_eq!(result.organized_files, 1);
+        assert!(Path::new(&format!("{}/2023/2023-01-15/{}", temp_dir, name)).exists());
+
+        // Cleanup
+        fs::remove_dir_all(input_dir).ok();
+        fs::remove_dir_all(temp_dir).ok();
+    }
+
+    #[test]
+    fn test_organize_passes_paired_sidecar_to_date_extractor() {
+        // Arrange
+        use chrono::NaiveDateTime;
+
+        struct SidecarDateExtractor;
+
+        impl DateExtractor for SidecarDateExtractor {
+            fn extract_date_time(&self, _filename: &str, _image_data: &[u8]) -> Result<NaiveDateTime> {
+                bail!("no sidecar")
+            }
+
+            fn extract_date_time_in_context(
+                &self,
+                _filename: &str,
+                _image_data: &[u8],
+                context: &EntryContext,
+            ) -> Result<NaiveDateTime> {
+                let taken = context.sidecar.and_then(|sidecar| sidecar.photo_taken_time);
+                Ok(taken.context("no sidecar")?.naive_utc())
+            }
+
+            fn confidence(&self) -> Confidence {
+                Confidence::Sidecar
+            }
+        }
+
+        let input_dir = "/tmp/test_org_sidecar_input";
+        let temp_dir = "/tmp/test_org_sidecar_output";
+        fs::remove_dir_all(temp_dir).ok();
+        fs::create_dir_all(input_dir).unwrap();
+        fs::write(format!("{}/photo.jpg", input_dir), b"no exif").unwrap();
+        fs::write(
+            format!("{}/photo.jpg.json", input_dir),
+            br#"{"photoTakenTime": {"timestamp": "1420113600"}}"#,
+        )
+        .unwrap();
+        let directory_reader = DirectoryImageReader::new(input_dir.to_string());
+        let zip_reader = EntrySelectionReader::new(&directory_reader);
+        let date_extractor = SidecarDateExtractor;
+        let file_writer = RealFileSystemWriter::new(temp_dir.to_string());
+        let path_generator = PathGenerator::new(&file_writer);
+        let filter = NoFilter::new();
+        let organizer = PhotoOrganizer::new(
+            &zip_reader,
+            &date_extractor,
+            &path_generator,
+            &file_writer,
+            &filter,
+        );
+
+        // Act
+        let result = organizer.organize().unwrap();
+
+        // Assert
+        assert_eq!(result.total_files, 1);
+        assert_eq!(result.organized_files, 1);
+        assert!(Path::new(&format!("{}/2015/2015-01-01/photo.jpg", temp_dir)).exists());
+
+        // Cleanup
+        fs::remove_dir_all(input_dir).ok();
+        fs::remove_dir_all(temp_dir).ok();
+    }
+
+    #[rstest]
+    #[case("1420113600", 1)]
+    #[case("1349528972", 0)]
+    fn test_organize_lists_files_whose_exif_and_sidecar_dates_differ(
+        #[case] sidecar_timestamp: &str,
+        #[case] expected_mismatches: usize,
+    ) {
+        // Arrange
+        let input_dir = format!("/tmp/test_org_mismatch_input_{}", sidecar_timestamp);
+        let temp_dir = format!("/tmp/test_org_mismatch_output_{}", sidecar_timestamp);
+        fs::remove_dir_all(&temp_dir).ok();
+        fs::create_dir_all(&input_dir).unwrap();
+        // DateTimeOriginal 2012:10:06 13:09:32
+        fs::write(
+            format!("{}/photo.jpg", input_dir),
+            include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg"),
+        )
+        .unwrap();
+        fs::write(
+            format!("{}/photo.jpg.json", input_dir),
+            format!(r#"{{"photoTakenTime": {{"timestamp": "{}"}}}}"#, sidecar_timestamp),
+        )
+        .unwrap();
+        let directory_reader = DirectoryImageReader::new(input_dir.clone());
+        let zip_reader = EntrySelectionReader::new(&directory_reader);
+        let date_extractor = CompositeDateExtractor::new();
+        let file_writer = RealFileSystemWriter::new(temp_dir.clone());
+        let path_generator = PathGenerator::new(&file_writer);
+        let filter = NoFilter::new();
+        let organizer = PhotoOrganizer::new(
+            &zip_reader,
+            &date_extractor,
+            &path_generator,
+            &file_writer,
+            &filter,
+        )
+        .with_date_mismatch_threshold(Some(TimeDelta::hours(24)));
+
+        // Act
+        let result = organizer.organize().unwrap();
+
+        // Assert
+        assert_eq!(result.organized_files, 1);
+        assert_eq!(result.date_mismatches.len(), expected_mismatches);
+        if let Some(mismatch) = result.date_mismatches.first() {
+            assert!(mismatch.contains("photo.jpg: EXIF 2012-10-06 13:09:32, JSON 201"), "{}", mismatch);
+        }
+
+        // Cleanup
+        fs::remove_dir_all(&input_dir).ok();
+        fs::remove_dir_all(&temp_dir).ok();
+    }
+
+    #[test]
+    fn test_organize_dates_facebook_export_by_its_posts_json() {
+        // Arrange
+        let input_dir = "/tmp/test_org_facebook_input";
+        let temp_dir = "/tmp/test_org_facebook_output";
+        fs::remove_dir_all(temp_dir).ok();
+        let media_dir = format!("{}/your_activity_across_facebook/posts/media/Mobileuploads_1", input_dir);
+        fs::create_dir_all(&media_dir).unwrap();
+        fs::write(format!("{}/123_456_n.jpg", media_dir), b"exif stripped").unwrap();
+        fs::write(
+            format!("{}/your_activity_across_facebook/posts/your_posts_1.json", input_dir),
+            br#"[{"attachments": [{"data": [{"media": {
+                "uri": "your_activity_across_facebook/posts/media/Mobileuploads_1/123_456_n.jpg",
+                "creation_timestamp": 1420113600}}]}]}]"#,
+        )
+        .unwrap();
+        let directory_reader = DirectoryImageReader::new(input_dir.to_string());
+        let zip_reader = EntrySelectionReader::new(&directory_reader);
+        let date_extractor = CompositeDateExtractor::new();
+        let file_writer = RealFileSystemWriter::new(temp_dir.to_string());
+        let path_generator = PathGenerator::new(&file_writer);
+        let filter = NoFilter::new();
+        let organizer = PhotoOrganizer::new(
+            &zip_reader,
+            &date_extractor,
+            &path_generator,
+            &file_writer,
+            &filter,
+        );
+
+        // Act
+        let result = organizer.organize().unwrap();
+
+        // Assert
+        assert_eq!(result.organized_files, 1);
+        assert!(Path::new(&format!("{}/2015/2015-01-01/123_456_n.jpg", temp_dir)).exists());
+
+        // Cleanup
+        fs::remove_dir_all(input_dir).ok();
+        fs::remove_dir_all(temp_dir).ok();
+    }
+
+    #[test]
+    fn test_extract_filename_handles_both_separators() {
+        // Arrange
+        let zip_reader = MockZipReader { entries: vec![] };
+        let date_extractor = ExifDateExtractor::new();
+        let file_writer = RealFileSystemWriter::new("/tmp/test_org_separators".to_string());
+        let path_generator = PathGenerator::new(&file_writer);
+        let filter = NoFilter::new();
+        let organizer = PhotoOrganizer::new(
+            &zip_reader,
+            &date_extractor,
+            &path_generator,
+            &file_writer,
+            &filter,
+        );
+
+        // Act & Assert
+        assert_eq!(organizer.extract_filename_from_path("a/b/photo.jpg"), "photo.jpg");
+        assert_eq!(organizer.extract_filename_from_path("a\\b\\photo.jpg"), "photo.jpg");
+        assert_eq!(organizer.extract_filename_from_path("photo.jpg"), "photo.jpg");
+    }
+
+    #[test]
+    #[allow(clippy::op_ref)]
+    fn test_organize_extracts_filename_from_zip_path() {
+        // Arrange
+        use crate::file_writer::MockFileSystemWriter;
+
+        let test_image = include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg");
+        let zip_reader = MockZipReader {
+            entries: vec![ZipEntry {
+                name: "Takeout/Google Photos/Photos from 2012/IMG_20121006_130932.jpg".to_string(),
+                data: test_image.to_vec().into(),
+                ..Default::default()
+            }],
+        };
+        let date_extractor = ExifDateExtractor::new();
+        let filter = NoFilter::new();
+
+        let mut mock_writer = MockFileSystemWriter::new();
+        mock_writer
+            .expect_find_existing_date_directory()
+            .returning(|_, _| None);
+        mock_writer
+            .expect_contains_identical_file()
+            .returning(|_, _| false);
+        mock_writer.expect_file_exists().returning(|_| false);
         mock_writer.expect_create_directory().returning(|_| Ok(()));
         mock_writer
             .expect_write_file()
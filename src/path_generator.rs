@@ -1,28 +1,127 @@
 use crate::file_writer::FileSystemWriter;
-use chrono::NaiveDate;
-use std::path::PathBuf;
+use chrono::{Datelike, NaiveDate};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Maximum length in bytes of a single path component on common filesystems
+const MAX_COMPONENT_BYTES: usize = 255;
+
+/// Maximum length in bytes of a full path
+#[cfg(windows)]
+const MAX_PATH_BYTES: usize = 260;
+#[cfg(not(windows))]
+const MAX_PATH_BYTES: usize = 4096;
+
+/// Folder in the output for files whose date needs checking
+pub const REVIEW_DIRECTORY: &str = "review";
+
+/// Length of the hash suffix appended to shortened names (`~` plus 8 hex digits)
+const HASH_SUFFIX_BYTES: usize = 9;
+
+/// Characters that are not allowed in filenames on Windows or break common tools
+const RESERVED_CHARACTERS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// Output root for a range of years, parsed from `FIRST..LAST=ROOT`.
+/// Both ends are inclusive (`..=` is accepted too) and either may be omitted.
+/// Relative roots are resolved against the output directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct YearRoot {
+    first_year: Option<i32>,
+    last_year: Option<i32>,
+    root: PathBuf,
+}
+
+impl YearRoot {
+    fn contains(&self, year: i32) -> bool {
+        self.first_year.is_none_or(|first| year >= first)
+            && self.last_year.is_none_or(|last| year <= last)
+    }
+}
+
+impl FromStr for YearRoot {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("expected FIRST..LAST=ROOT, got '{}'", value);
+        let (first, rest) = value.split_once("..").ok_or_else(invalid)?;
+        // `..=2014=ROOT` is an inclusive range, `2015..=ROOT` has an open end
+        let rest = match rest.strip_prefix('=') {
+            Some(after) if after.starts_with(|c: char| c.is_ascii_digit()) => after,
+            _ => rest,
+        };
+        let (last, root) = rest.split_once('=').ok_or_else(invalid)?;
+
+        let parse_year = |year: &str| -> Result<Option<i32>, String> {
+            if year.is_empty() {
+                return Ok(None);
+            }
+            year.parse()
+                .map(Some)
+                .map_err(|_| format!("invalid year '{}' in '{}'", year, value))
+        };
+
+        if root.is_empty() {
+            return Err(format!("missing root directory in '{}'", value));
+        }
+        Ok(Self {
+            first_year: parse_year(first)?,
+            last_year: parse_year(last)?,
+            root: PathBuf::from(root),
+        })
+    }
+}
 
 /// Generates target directory paths based on dates
 /// Single Responsibility: Only concerned with path generation logic
 pub struct PathGenerator<'a> {
     file_writer: &'a dyn FileSystemWriter,
+    sanitize_names: bool,
+    year_roots: Vec<YearRoot>,
 }
 
 impl<'a> PathGenerator<'a> {
     pub fn new(file_writer: &'a dyn FileSystemWriter) -> Self {
-        Self { file_writer }
+        Self {
+            file_writer,
+            sanitize_names: false,
+            year_roots: Vec::new(),
+        }
+    }
+
+    /// Place years in other roots than the output directory (first matching range wins)
+    pub fn with_year_roots(mut self, year_roots: Vec<YearRoot>) -> Self {
+        self.year_roots = year_roots;
+        self
+    }
+
+    /// Replace control characters, emoji and reserved characters in output filenames
+    pub fn with_sanitize_names(mut self, sanitize_names: bool) -> Self {
+        self.sanitize_names = sanitize_names;
+        self
     }
 
     /// Generates path in format: YYYY/YYYY-MM-DD
     /// If a directory with the date prefix already exists (e.g., YYYY-MM-DD_event_name),
-    /// it will reuse that directory instead of creating a plain YYYY-MM-DD directory
+    /// it will reuse that directory instead of creating a plain YYYY-MM-DD directory.
+    /// Years with a configured year root get that root as prefix.
     pub fn generate_path(&self, date: &NaiveDate, filename: &str) -> PathBuf {
-        let year = date.format("%Y").to_string();
+        self.generate_path_in_subdirectory(date, None, filename)
+    }
+
+    /// Like `generate_path`, with an extra folder beneath the date level
+    /// (e.g. `2021/2021-05-02/Pixel 5/photo.jpg`)
+    pub fn generate_path_in_subdirectory(
+        &self,
+        date: &NaiveDate,
+        subdirectory: Option<&Path>,
+        filename: &str,
+    ) -> PathBuf {
         let full_date = date.format("%Y-%m-%d").to_string();
+        let year_path = self.year_path(date.year());
 
         // Check if a directory with this date prefix already exists
         let date_dir = if let Some(existing_dir) = self.file_writer.find_existing_date_directory(
-            &PathBuf::from(&year),
+            &year_path,
             &full_date
         ) {
             existing_dir
@@ -30,17 +129,152 @@ impl<'a> PathGenerator<'a> {
             full_date
         };
 
-        PathBuf::from(year)
-            .join(date_dir)
-            .join(filename)
+        self.file_path(year_path.join(date_dir), subdirectory, filename)
+    }
+
+    /// Path for a file of which only the year is known: `YYYY/YYYY-unknown/`
+    pub fn generate_year_only_path_in_subdirectory(
+        &self,
+        year: i32,
+        subdirectory: Option<&Path>,
+        filename: &str,
+    ) -> PathBuf {
+        let year_path = self.year_path(year);
+        self.file_path(year_path.join(format!("{:04}-unknown", year)), subdirectory, filename)
+    }
+
+    /// Path for a file whose date is not trusted enough: `review/YYYY/YYYY-MM-DD/`, laid out
+    /// like the rest of the output so checked folders can be moved over as they are
+    pub fn generate_review_path_in_subdirectory(
+        &self,
+        date: &NaiveDate,
+        subdirectory: Option<&Path>,
+        filename: &str,
+    ) -> PathBuf {
+        let directory = Path::new(REVIEW_DIRECTORY)
+            .join(format!("{:04}", date.year()))
+            .join(date.format("%Y-%m-%d").to_string());
+        self.file_path(directory, subdirectory, filename)
+    }
+
+    fn year_path(&self, year: i32) -> PathBuf {
+        match self.year_roots.iter().find(|r| r.contains(year)) {
+            Some(year_root) => year_root.root.join(format!("{:04}", year)),
+            None => PathBuf::from(format!("{:04}", year)),
+        }
+    }
+
+    fn file_path(&self, mut directory: PathBuf, subdirectory: Option<&Path>, filename: &str) -> PathBuf {
+        if let Some(subdirectory) = subdirectory {
+            directory.push(subdirectory);
+        }
+        let max_filename_bytes = self.max_filename_bytes(&directory);
+        let filename = if self.sanitize_names {
+            sanitize_component(filename)
+        } else {
+            filename.to_string()
+        };
+
+        directory.join(shorten_component(&filename, max_filename_bytes))
+    }
+
+    /// Room left for the filename once the output directory and separator are accounted for
+    fn max_filename_bytes(&self, directory: &Path) -> usize {
+        let directory_bytes = self.file_writer.get_full_path(directory).as_os_str().len();
+        MAX_PATH_BYTES
+            .saturating_sub(directory_bytes + 1)
+            .min(MAX_COMPONENT_BYTES)
     }
 }
 
+/// Deterministically shortens a path component to at most `max_bytes` bytes.
+/// The extension is kept and the truncated stem gets a `~xxxxxxxx` hash of the
+/// original name, so different long names stay distinct after shortening. A budget
+/// too small for the hash and the extension drops the extension, one too small for
+/// the hash cuts the hash short.
+pub fn shorten_component(name: &str, max_bytes: usize) -> String {
+    if name.len() <= max_bytes {
+        return name.to_string();
+    }
+
+    let (stem, extension) = match name.rfind('.') {
+        Some(index) if index > 0 && name.len() - index <= 16 => name.split_at(index),
+        _ => (name, ""),
+    };
+    let extension = if extension.len() + HASH_SUFFIX_BYTES <= max_bytes { extension } else { "" };
+
+    let stem_budget = max_bytes.saturating_sub(extension.len() + HASH_SUFFIX_BYTES);
+    let mut stem_end = stem_budget.min(stem.len());
+    while !stem.is_char_boundary(stem_end) {
+        stem_end -= 1;
+    }
+
+    let mut shortened = format!("{}~{:08x}{}", &stem[..stem_end], fnv1a_hash(name), extension);
+    // Only a hash-only name can still be too long, and it is ASCII
+    shortened.truncate(max_bytes);
+    shortened
+}
+
+/// Replaces problematic characters with `_`. When anything was replaced, a
+/// `~xxxxxxxx` hash of the original name is appended to the stem so that
+/// names differing only in replaced characters don't collide.
+pub fn sanitize_component(name: &str) -> String {
+    let mut sanitized = String::with_capacity(name.len());
+    for c in name.chars() {
+        if is_problematic_character(c) {
+            if !sanitized.ends_with('_') {
+                sanitized.push('_');
+            }
+        } else {
+            sanitized.push(c);
+        }
+    }
+
+    let trimmed = sanitized.trim_end_matches(['.', ' ']);
+    if trimmed == name {
+        return name.to_string();
+    }
+
+    let (stem, extension) = match trimmed.rfind('.') {
+        Some(index) if index > 0 => trimmed.split_at(index),
+        _ => (trimmed, ""),
+    };
+    format!("{}~{:08x}{}", stem, fnv1a_hash(name), extension)
+}
+
+fn is_problematic_character(c: char) -> bool {
+    c.is_control()
+        || RESERVED_CHARACTERS.contains(&c)
+        || matches!(c,
+            '\u{200B}'..='\u{200F}'   // zero-width and direction marks
+            | '\u{2190}'..='\u{2BFF}' // arrows, symbols, dingbats
+            | '\u{FE00}'..='\u{FE0F}' // variation selectors
+            | '\u{10000}'..)          // emoji and other supplementary-plane characters
+}
+
+/// Inserts `(counter)` before the extension: `photo.jpg` becomes `photo(1).jpg`
+pub fn numbered_path(path: &Path, counter: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let numbered_name = match path.extension() {
+        Some(extension) => format!("{}({}).{}", stem, counter, extension.to_string_lossy()),
+        None => format!("{}({})", stem, counter),
+    };
+    path.with_file_name(numbered_name)
+}
+
+/// 32-bit FNV-1a, stable across platforms and Rust versions
+fn fnv1a_hash(value: &str) -> u32 {
+    value.bytes().fold(0x811c_9dc5, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x0100_0193)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::file_writer::MockFileSystemWriter;
     use chrono::NaiveDate;
+    use rstest::rstest;
 
     #[test]
     fn test_generate_path_correct_format() {
@@ -49,6 +283,9 @@ mod tests {
         mock_writer
             .expect_find_existing_date_directory()
             .returning(|_, _| None);
+        mock_writer
+            .expect_get_full_path()
+            .returning(|path| PathBuf::from("/output").join(path));
         let generator = PathGenerator::new(&mock_writer);
         let date = NaiveDate::from_ymd_opt(2024, 1, 5).unwrap();
         let filename = "IMG_1234.jpg";
@@ -67,6 +304,9 @@ mod tests {
         mock_writer
             .expect_find_existing_date_directory()
             .returning(|_, _| None);
+        mock_writer
+            .expect_get_full_path()
+            .returning(|path| PathBuf::from("/output").join(path));
         let generator = PathGenerator::new(&mock_writer);
         let date = NaiveDate::from_ymd_opt(2025, 10, 24).unwrap();
         let filename = "photo.png";
@@ -78,6 +318,39 @@ mod tests {
         assert_eq!(path, PathBuf::from("2025/2025-10-24/photo.png"));
     }
 
+    #[test]
+    fn test_generate_year_only_path() {
+        // Arrange
+        let mut mock_writer = MockFileSystemWriter::new();
+        mock_writer
+            .expect_get_full_path()
+            .returning(|path| PathBuf::from("/output").join(path));
+        let generator = PathGenerator::new(&mock_writer);
+
+        // Act
+        let path = generator.generate_year_only_path_in_subdirectory(2012, None, "scan.jpg");
+
+        // Assert
+        assert_eq!(path, PathBuf::from("2012/2012-unknown/scan.jpg"));
+    }
+
+    #[test]
+    fn test_generate_review_path() {
+        // Arrange
+        let mut mock_writer = MockFileSystemWriter::new();
+        mock_writer
+            .expect_get_full_path()
+            .returning(|path| PathBuf::from("/output").join(path));
+        let generator = PathGenerator::new(&mock_writer);
+        let date = NaiveDate::from_ymd_opt(2013, 4, 19).unwrap();
+
+        // Act
+        let path = generator.generate_review_path_in_subdirectory(&date, Some(Path::new("Pixel 5")), "scan.jpg");
+
+        // Assert
+        assert_eq!(path, PathBuf::from("review/2013/2013-04-19/Pixel 5/scan.jpg"));
+    }
+
     #[test]
     fn test_generate_path_single_digit_month_and_day() {
         // Arrange
@@ -85,6 +358,9 @@ mod tests {
         mock_writer
             .expect_find_existing_date_directory()
             .returning(|_, _| None);
+        mock_writer
+            .expect_get_full_path()
+            .returning(|path| PathBuf::from("/output").join(path));
         let generator = PathGenerator::new(&mock_writer);
         let date = NaiveDate::from_ymd_opt(2024, 3, 7).unwrap();
         let filename = "test.jpg";
@@ -98,6 +374,121 @@ mod tests {
     }
 
     #[test]
+    fn test_generate_path_shortens_overlong_filename() {
+        // Arrange
+        let mut mock_writer = MockFileSystemWriter::new();
+        mock_writer
+            .expect_find_existing_date_directory()
+            .returning(|_, _| None);
+        mock_writer
+            .expect_get_full_path()
+            .returning(|path| PathBuf::from("/output").join(path));
+        let generator = PathGenerator::new(&mock_writer);
+        let date = NaiveDate::from_ymd_opt(2024, 3, 7).unwrap();
+        let filename = format!("{}.jpg", "a".repeat(300));
+
+        // Act
+        let path = generator.generate_path(&date, &filename);
+
+        // Assert
+        let shortened = path.file_name().unwrap().to_str().unwrap();
+        assert_eq!(shortened.len(), MAX_COMPONENT_BYTES);
+        assert!(shortened.ends_with(".jpg"));
+        assert_eq!(path.parent().unwrap(), Path::new("2024/2024-03-07"));
+    }
+
+    #[test]
+    fn test_shorten_component_is_deterministic_and_distinct() {
+        // Arrange
+        let first = format!("{}1.jpg", "x".repeat(100));
+        let second = format!("{}2.jpg", "x".repeat(100));
+
+        // Act
+        let first_short = shorten_component(&first, 50);
+        let second_short = shorten_component(&second, 50);
+
+        // Assert
+        assert_eq!(first_short, shorten_component(&first, 50));
+        assert_ne!(first_short, second_short);
+        assert!(first_short.len() <= 50);
+        assert_eq!(shorten_component("short.jpg", 50), "short.jpg");
+    }
+
+    #[rstest]
+    #[case(12, "xxx~")]
+    #[case(9, "~")]
+    #[case(4, "~")]
+    fn test_shorten_component_stays_within_tiny_budgets(#[case] max_bytes: usize, #[case] expected_start: &str) {
+        // Arrange
+        let name = format!("{}.jpeg", "x".repeat(100));
+
+        // Act
+        let shortened = shorten_component(&name, max_bytes);
+
+        // Assert
+        assert_eq!(shortened.len(), max_bytes);
+        assert!(shortened.starts_with(expected_start));
+        assert!(!shortened.ends_with(".jpeg"));
+    }
+
+    #[test]
+    fn test_shorten_component_respects_char_boundaries() {
+        // Act
+        let shortened = shorten_component(&"é".repeat(40), 20);
+
+        // Assert
+        assert!(shortened.len() <= 20);
+        assert!(shortened.contains('~'));
+    }
+
+    #[test]
+    fn test_generate_path_sanitizes_names_when_enabled() {
+        // Arrange
+        let mut mock_writer = MockFileSystemWriter::new();
+        mock_writer
+            .expect_find_existing_date_directory()
+            .returning(|_, _| None);
+        mock_writer
+            .expect_get_full_path()
+            .returning(|path| PathBuf::from("/output").join(path));
+        let generator = PathGenerator::new(&mock_writer).with_sanitize_names(true);
+        let date = NaiveDate::from_ymd_opt(2024, 3, 7).unwrap();
+
+        // Act
+        let sanitized = generator.generate_path(&date, "beach 🏖️.jpg");
+        let untouched = generator.generate_path(&date, "Café.jpg");
+
+        // Assert
+        let sanitized_name = sanitized.file_name().unwrap().to_str().unwrap();
+        assert!(sanitized_name.starts_with("beach _~"), "got {}", sanitized_name);
+        assert!(sanitized_name.ends_with(".jpg"));
+        assert_eq!(untouched, PathBuf::from("2024/2024-03-07/Café.jpg"));
+    }
+
+    #[test]
+    fn test_sanitize_component_avoids_collisions() {
+        // Act
+        let question = sanitize_component("what?.jpg");
+        let star = sanitize_component("what*.jpg");
+
+        // Assert
+        assert_ne!(question, star);
+        assert!(!question.contains('?'));
+        assert_eq!(sanitize_component("tab\there.jpg").matches('\t').count(), 0);
+    }
+
+    #[test]
+    fn test_numbered_path() {
+        // Act & Assert
+        assert_eq!(
+            numbered_path(Path::new("2024/2024-03-07/photo.jpg"), 2),
+            PathBuf::from("2024/2024-03-07/photo(2).jpg")
+        );
+        assert_eq!(numbered_path(Path::new("2024/README"), 1), PathBuf::from("2024/README(1)"));
+    }
+
+    #[test]
+    #[allow(clippy::op_ref)]
     fn test_generate_path_reuses_existing_directory_with_suffix() {
         // Arrange
         let mut mock_writer = MockFileSystemWriter::new();
@@ -107,6 +498,9 @@ mod tests {
                 year_path == &PathBuf::from("2025") && date_prefix == "2025-10-28"
             })
             .returning(|_, _| Some("2025-10-28_special_event".to_string()));
+        mock_writer
+            .expect_get_full_path()
+            .returning(|path| PathBuf::from("/output").join(path));
         let generator = PathGenerator::new(&mock_writer);
         let date = NaiveDate::from_ymd_opt(2025, 10, 28).unwrap();
         let filename = "photo.jpg";
@@ -117,4 +511,87 @@ mod tests {
         // Assert
         assert_eq!(path, PathBuf::from("2025/2025-10-28_special_event/photo.jpg"));
     }
+
+    #[test]
+    fn test_generate_path_in_subdirectory() {
+        // Arrange
+        let mut mock_writer = MockFileSystemWriter::new();
+        mock_writer
+            .expect_find_existing_date_directory()
+            .returning(|_, _| None);
+        mock_writer
+            .expect_get_full_path()
+            .returning(|path| PathBuf::from("/output").join(path));
+        let generator = PathGenerator::new(&mock_writer);
+        let date = NaiveDate::from_ymd_opt(2021, 5, 2).unwrap();
+
+        // Act
+        let path = generator.generate_path_in_subdirectory(&date, Some(Path::new("Pixel 5")), "PXL_1.jpg");
+
+        // Assert
+        assert_eq!(path, PathBuf::from("2021/2021-05-02/Pixel 5/PXL_1.jpg"));
+    }
+
+    #[rstest]
+    #[case("1990..2014=/mnt/old", Some(1990), Some(2014), "/mnt/old")]
+    #[case("2015..=/mnt/new", Some(2015), None, "/mnt/new")]
+    #[case("..=1999=/mnt/ancient", None, Some(1999), "/mnt/ancient")]
+    #[case("2000..=2009=relative", Some(2000), Some(2009), "relative")]
+    fn test_parse_year_root(
+        #[case] value: &str,
+        #[case] first_year: Option<i32>,
+        #[case] last_year: Option<i32>,
+        #[case] root: &str,
+    ) {
+        // Act
+        let result: YearRoot = value.parse().unwrap();
+
+        // Assert
+        assert_eq!(
+            result,
+            YearRoot {
+                first_year,
+                last_year,
+                root: PathBuf::from(root)
+            }
+        );
+    }
+
+    #[rstest]
+    #[case("/mnt/old")]
+    #[case("1990-2014=/mnt/old")]
+    #[case("19x0..2014=/mnt/old")]
+    #[case("1990..2014=")]
+    fn test_parse_year_root_rejects_invalid_values(#[case] value: &str) {
+        // Act & Assert
+        assert!(value.parse::<YearRoot>().is_err(), "{}", value);
+    }
+
+    #[test]
+    fn test_generate_path_uses_year_root_and_looks_up_existing_directories_there() {
+        // Arrange
+        let mut mock_writer = MockFileSystemWriter::new();
+        mock_writer
+            .expect_find_existing_date_directory()
+            .withf(|year_path, _| year_path == Path::new("/mnt/old/2010"))
+            .returning(|_, _| Some("2010-06-01_wedding".to_string()));
+        mock_writer
+            .expect_find_existing_date_directory()
+            .withf(|year_path, _| year_path == Path::new("2020"))
+            .returning(|_, _| None);
+        mock_writer
+            .expect_get_full_path()
+            .returning(|path| PathBuf::from("/output").join(path));
+        let generator = PathGenerator::new(&mock_writer).with_year_roots(vec![
+            "..2014=/mnt/old".parse().unwrap(),
+        ]);
+
+        // Act
+        let old = generator.generate_path(&NaiveDate::from_ymd_opt(2010, 6, 1).unwrap(), "a.jpg");
+        let new = generator.generate_path(&NaiveDate::from_ymd_opt(2020, 6, 1).unwrap(), "b.jpg");
+
+        // Assert
+        assert_eq!(old, PathBuf::from("/mnt/old/2010/2010-06-01_wedding/a.jpg"));
+        assert_eq!(new, PathBuf::from("2020/2020-06-01/b.jpg"));
+    }
 }
@@ -1,8 +1,10 @@
+use crate::archive_index::ArchiveIndex;
+use crate::exif::ParsedExif;
 use exif::{In, Tag};
-use std::collections::HashSet;
+use serde::Deserialize;
 
 /// Google duplicate file patterns to filter (uppercase versions)
-const GOOGLE_DUPLICATE_PATTERNS: &[&str] = &[
+pub const GOOGLE_DUPLICATE_PATTERNS: &[&str] = &[
     "-MIX",
     "-EDITED",
     "-EFFECTS",
@@ -12,75 +14,178 @@ const GOOGLE_DUPLICATE_PATTERNS: &[&str] = &[
     "-PANO",
 ];
 
+/// Takeout folders generated by Google services rather than by taking photos
+/// (print subscription scans, photo book and print orders, auto-created movies)
+const SERVICE_FOLDER_NAMES: &[&str] = &[
+    "PRINT SUBSCRIPTIONS",
+    "PRINT ORDERS",
+    "PRINT ORDER",
+    "PHOTO BOOKS",
+    "PHOTOBOOKS",
+    "CANVAS PRINTS",
+    "AUTO-CREATED MOVIES",
+];
+
+/// Takeout folders of photos kept out of the library view: deleted, archived, and
+/// uploads Google could not process
+const TRASH_FOLDER_NAMES: &[&str] = &["TRASH", "FAILED VIDEOS"];
+const ARCHIVE_FOLDER_NAMES: &[&str] = &["ARCHIVE"];
+
 /// Trait for filtering photos based on criteria
 /// Following Interface Segregation Principle
 pub trait PhotoFilter {
-    fn should_include(&self, filename: &str, image_data: &[u8]) -> bool;
+    fn should_include(&self, filename: &str, image_data: &[u8], index: &ArchiveIndex) -> bool;
+
+    /// Like `should_include`, reading EXIF through the entry's shared `ParsedExif`. Filters
+    /// that don't look at EXIF keep this default; those wrapping others pass it on.
+    fn should_include_with_exif(
+        &self,
+        filename: &str,
+        image_data: &[u8],
+        index: &ArchiveIndex,
+        _exif: &ParsedExif,
+    ) -> bool {
+        self.should_include(filename, image_data, index)
+    }
+
+    /// Whether the entry is skipped on its name (and the index) alone. Rejected entries
+    /// are never read from the archive; `should_include` decides on the others.
+    fn rejects_by_name(&self, _filename: &str, _index: &ArchiveIndex) -> bool {
+        false
+    }
+}
+
+/// Languages whose Google Photos names edited copies in that language, for `--duplicate-locale`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateLocale {
+    De,
+    Es,
+    Fr,
+    It,
+    Nl,
+    Pt,
+}
+
+impl DuplicateLocale {
+    /// The suffixes Google Photos uses in this language (uppercase), besides the English ones
+    pub fn patterns(self) -> &'static [&'static str] {
+        match self {
+            DuplicateLocale::De => &["-BEARBEITET"],
+            DuplicateLocale::Es => &["-EDITADO", "-EDITADA"],
+            DuplicateLocale::Fr => &["-MODIFIÉ"],
+            DuplicateLocale::It => &["-MODIFICATO"],
+            DuplicateLocale::Nl => &["-BEWERKT"],
+            DuplicateLocale::Pt => &["-EDITADO", "-EDITADA"],
+        }
+    }
 }
 
 /// Filter that skips photos already in your existing collection
 /// (Lightroom-processed, DSLR cameras like Nikon, or Google-generated -MIX files)
 pub struct ExistingCollectionFilter {
-    all_filenames: HashSet<String>,
+    /// Suffixes of Google-generated copies (uppercase), `GOOGLE_DUPLICATE_PATTERNS` by default
+    duplicate_patterns: Vec<String>,
+}
+
+impl Default for ExistingCollectionFilter {
+    fn default() -> Self {
+        Self::with_duplicate_patterns(GOOGLE_DUPLICATE_PATTERNS)
+    }
 }
 
 impl ExistingCollectionFilter {
-    pub fn new(filenames: Vec<String>) -> Self {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Recognize Google-generated copies by exactly these suffixes (`-MIX`, `-edited`, ...)
+    /// instead of the built-in list
+    pub fn with_duplicate_patterns<S: AsRef<str>>(patterns: &[S]) -> Self {
         Self {
-            all_filenames: filenames.into_iter().collect(),
+            duplicate_patterns: Vec::new(),
         }
+        .with_added_duplicate_patterns(patterns)
     }
 
-    fn get_exif_field(&self, image_data: &[u8], tag: Tag) -> Option<String> {
-        let mut cursor = std::io::Cursor::new(image_data);
-        let exif_reader = exif::Reader::new();
+    /// Recognize these suffixes too; the case is ignored
+    pub fn with_added_duplicate_patterns<S: AsRef<str>>(mut self, patterns: &[S]) -> Self {
+        for pattern in patterns.iter().map(|pattern| pattern.as_ref().trim().to_uppercase()) {
+            if !pattern.is_empty() && !self.duplicate_patterns.contains(&pattern) {
+                self.duplicate_patterns.push(pattern);
+            }
+        }
+        self
+    }
 
-        let exif_data = exif_reader.read_from_container(&mut cursor).ok()?;
+    /// Recognize the suffixes Google Photos uses in these languages too
+    pub fn with_locales(self, locales: &[DuplicateLocale]) -> Self {
+        locales
+            .iter()
+            .fold(self, |filter, locale| filter.with_added_duplicate_patterns(locale.patterns()))
+    }
+
+    fn is_duplicate(&self, filename_upper: &str) -> bool {
+        self.duplicate_patterns
+            .iter()
+            .any(|pattern| filename_upper.contains(pattern.as_str()))
+    }
+
+    fn get_exif_field(&self, exif: &ParsedExif, image_data: &[u8], tag: Tag) -> Option<String> {
+        let exif_data = exif.get(image_data).ok()?;
         let field = exif_data.get_field(tag, In::PRIMARY)?;
 
         Some(field.display_value().to_string())
     }
 
-    fn has_original_file(&self, duplicate_filename: &str) -> bool {
+    fn has_original_file(&self, duplicate_filename: &str, index: &ArchiveIndex) -> bool {
         let mut original_name = duplicate_filename.to_string();
 
-        for pattern in GOOGLE_DUPLICATE_PATTERNS {
+        for pattern in &self.duplicate_patterns {
             original_name = original_name
-                .replace(pattern, "")
+                .replace(pattern.as_str(), "")
                 .replace(&pattern.to_lowercase(), "");
         }
 
-        self.all_filenames.contains(&original_name)
+        index.contains_entry(&original_name)
     }
 }
 
 impl PhotoFilter for ExistingCollectionFilter {
-    fn should_include(&self, filename: &str, image_data: &[u8]) -> bool {
+    fn should_include(&self, filename: &str, image_data: &[u8], index: &ArchiveIndex) -> bool {
+        self.should_include_with_exif(filename, image_data, index, &ParsedExif::new())
+    }
+
+    fn should_include_with_exif(
+        &self,
+        filename: &str,
+        image_data: &[u8],
+        index: &ArchiveIndex,
+        exif: &ParsedExif,
+    ) -> bool {
         let filename_upper = filename.to_uppercase();
 
         if filename_upper.ends_with(".GIF") {
             return false;
         }
 
-        for pattern in GOOGLE_DUPLICATE_PATTERNS {
-            if filename_upper.contains(pattern) {
-                return !self.has_original_file(filename);
-            }
+        if self.is_duplicate(&filename_upper) {
+            return !self.has_original_file(filename, index);
         }
 
-        if let Some(software) = self.get_exif_field(image_data, Tag::Software) {
+        if let Some(software) = self.get_exif_field(exif, image_data, Tag::Software) {
             if software.to_lowercase().contains("lightroom") {
                 return false;
             }
         }
 
-        if let Some(make) = self.get_exif_field(image_data, Tag::Make) {
+        if let Some(make) = self.get_exif_field(exif, image_data, Tag::Make) {
             if make.to_uppercase().contains("NIKON") {
                 return false;
             }
         }
 
-        if let Some(model) = self.get_exif_field(image_data, Tag::Model) {
+        if let Some(model) = self.get_exif_field(exif, image_data, Tag::Model) {
             if model.to_uppercase().contains("NIKON") {
                 return false;
             }
@@ -88,9 +193,17 @@ impl PhotoFilter for ExistingCollectionFilter {
 
         true
     }
+
+    fn rejects_by_name(&self, filename: &str, index: &ArchiveIndex) -> bool {
+        let filename_upper = filename.to_uppercase();
+
+        filename_upper.ends_with(".GIF")
+            || (self.is_duplicate(&filename_upper) && self.has_original_file(filename, index))
+    }
 }
 
 /// Filter that accepts all photos (no filtering)
+#[derive(Default)]
 pub struct NoFilter;
 
 impl NoFilter {
@@ -100,16 +213,184 @@ impl NoFilter {
 }
 
 impl PhotoFilter for NoFilter {
-    fn should_include(&self, _filename: &str, _image_data: &[u8]) -> bool {
+    fn should_include(&self, _filename: &str, _image_data: &[u8], _index: &ArchiveIndex) -> bool {
         true // Accept everything
     }
 }
 
+/// Whether an entry lives in a service-generated Takeout folder (see `SERVICE_FOLDER_NAMES`)
+pub fn is_in_service_folder(entry_name: &str) -> bool {
+    is_in_folder_named(entry_name, SERVICE_FOLDER_NAMES)
+}
+
+/// Whether any folder of the entry has one of `names` (uppercase)
+fn is_in_folder_named(entry_name: &str, names: &[&str]) -> bool {
+    let mut folders: Vec<&str> = entry_name.split(['/', '\\']).collect();
+    folders.pop();
+
+    folders
+        .iter()
+        .any(|folder| names.contains(&folder.trim().to_uppercase().as_str()))
+}
+
+/// Filter that skips entries in service-generated folders and otherwise defers to `inner`
+pub struct ServiceFolderFilter<'a> {
+    inner: &'a dyn PhotoFilter,
+}
+
+impl<'a> ServiceFolderFilter<'a> {
+    pub fn new(inner: &'a dyn PhotoFilter) -> Self {
+        Self { inner }
+    }
+}
+
+impl PhotoFilter for ServiceFolderFilter<'_> {
+    fn should_include(&self, filename: &str, image_data: &[u8], index: &ArchiveIndex) -> bool {
+        !is_in_service_folder(filename) && self.inner.should_include(filename, image_data, index)
+    }
+
+    fn should_include_with_exif(
+        &self,
+        filename: &str,
+        image_data: &[u8],
+        index: &ArchiveIndex,
+        exif: &ParsedExif,
+    ) -> bool {
+        !is_in_service_folder(filename)
+            && self.inner.should_include_with_exif(filename, image_data, index, exif)
+    }
+
+    fn rejects_by_name(&self, filename: &str, index: &ArchiveIndex) -> bool {
+        is_in_service_folder(filename) || self.inner.rejects_by_name(filename, index)
+    }
+}
+
+/// Filter that skips Takeout's `Trash/` and `Failed Videos/` folders and its `Archive/`
+/// folder, unless they are included, and otherwise defers to `inner`
+pub struct HiddenFolderFilter<'a> {
+    inner: &'a dyn PhotoFilter,
+    include_trash: bool,
+    include_archive: bool,
+}
+
+impl<'a> HiddenFolderFilter<'a> {
+    pub fn new(inner: &'a dyn PhotoFilter) -> Self {
+        Self {
+            inner,
+            include_trash: false,
+            include_archive: false,
+        }
+    }
+
+    /// Also keep deleted photos (`Trash/`) and unprocessed uploads (`Failed Videos/`)
+    pub fn with_trash(mut self, include_trash: bool) -> Self {
+        self.include_trash = include_trash;
+        self
+    }
+
+    /// Also keep archived photos (`Archive/`)
+    pub fn with_archive(mut self, include_archive: bool) -> Self {
+        self.include_archive = include_archive;
+        self
+    }
+
+    fn is_hidden(&self, filename: &str) -> bool {
+        (!self.include_trash && is_in_folder_named(filename, TRASH_FOLDER_NAMES))
+            || (!self.include_archive && is_in_folder_named(filename, ARCHIVE_FOLDER_NAMES))
+    }
+}
+
+impl PhotoFilter for HiddenFolderFilter<'_> {
+    fn should_include(&self, filename: &str, image_data: &[u8], index: &ArchiveIndex) -> bool {
+        !self.is_hidden(filename) && self.inner.should_include(filename, image_data, index)
+    }
+
+    fn should_include_with_exif(
+        &self,
+        filename: &str,
+        image_data: &[u8],
+        index: &ArchiveIndex,
+        exif: &ParsedExif,
+    ) -> bool {
+        !self.is_hidden(filename)
+            && self.inner.should_include_with_exif(filename, image_data, index, exif)
+    }
+
+    fn rejects_by_name(&self, filename: &str, index: &ArchiveIndex) -> bool {
+        self.is_hidden(filename) || self.inner.rejects_by_name(filename, index)
+    }
+}
+
+/// Filter that only keeps entries in album folders matching one of the glob patterns
+/// (e.g. `Wedding 2018` or `Holiday*`, case-insensitive) and otherwise defers to `inner`
+pub struct AlbumFilter<'a> {
+    inner: &'a dyn PhotoFilter,
+    albums: Vec<glob::Pattern>,
+}
+
+impl<'a> AlbumFilter<'a> {
+    pub fn new(inner: &'a dyn PhotoFilter, albums: Vec<glob::Pattern>) -> Self {
+        Self { inner, albums }
+    }
+
+    fn is_selected_album(&self, album: &str) -> bool {
+        let options = glob::MatchOptions {
+            case_sensitive: false,
+            ..Default::default()
+        };
+        self.albums
+            .iter()
+            .any(|pattern| pattern.matches_with(album, options))
+    }
+}
+
+impl PhotoFilter for AlbumFilter<'_> {
+    fn should_include(&self, filename: &str, image_data: &[u8], index: &ArchiveIndex) -> bool {
+        index
+            .album_of(filename)
+            .is_some_and(|album| self.is_selected_album(album))
+            && self.inner.should_include(filename, image_data, index)
+    }
+
+    fn should_include_with_exif(
+        &self,
+        filename: &str,
+        image_data: &[u8],
+        index: &ArchiveIndex,
+        exif: &ParsedExif,
+    ) -> bool {
+        index
+            .album_of(filename)
+            .is_some_and(|album| self.is_selected_album(album))
+            && self.inner.should_include_with_exif(filename, image_data, index, exif)
+    }
+
+    fn rejects_by_name(&self, filename: &str, index: &ArchiveIndex) -> bool {
+        !index
+            .album_of(filename)
+            .is_some_and(|album| self.is_selected_album(album))
+            || self.inner.rejects_by_name(filename, index)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::zip_image_reader::ZipEntry;
     use rstest::rstest;
 
+    fn index_of(names: &[&str]) -> ArchiveIndex {
+        let entries: Vec<ZipEntry> = names
+            .iter()
+            .map(|name| ZipEntry {
+                name: name.to_string(),
+                data: vec![].into(),
+                ..Default::default()
+            })
+            .collect();
+        ArchiveIndex::new(&entries)
+    }
+
     #[test]
     fn test_no_filter_accepts_all() {
         // Arrange
@@ -117,7 +398,7 @@ mod tests {
         let any_data = b"any data";
 
         // Act
-        let result = filter.should_include("any_file.jpg", any_data);
+        let result = filter.should_include("any_file.jpg", any_data, &index_of(&[]));
 
         // Assert
         assert!(result);
@@ -126,11 +407,12 @@ mod tests {
     #[test]
     fn test_existing_collection_filter_rejects_lightroom_photos() {
         // Arrange
-        let filter = ExistingCollectionFilter::new(vec!["DSC_9157.JPG".to_string()]);
+        let filter = ExistingCollectionFilter::new();
+        let index = index_of(&["DSC_9157.JPG"]);
         let lightroom_photo = include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg");
 
         // Act
-        let result = filter.should_include("DSC_9157.JPG", lightroom_photo);
+        let result = filter.should_include("DSC_9157.JPG", lightroom_photo, &index);
 
         // Assert
         assert!(
@@ -142,11 +424,12 @@ mod tests {
     #[test]
     fn test_existing_collection_filter_accepts_mobile_photos() {
         // Arrange
-        let filter = ExistingCollectionFilter::new(vec!["phone_photo.jpg".to_string()]);
+        let filter = ExistingCollectionFilter::new();
+        let index = index_of(&["phone_photo.jpg"]);
         let no_software_photo = &[0xFF, 0xD8, 0xFF, 0xD9];
 
         // Act
-        let result = filter.should_include("phone_photo.jpg", no_software_photo);
+        let result = filter.should_include("phone_photo.jpg", no_software_photo, &index);
 
         // Assert
         assert!(result, "Photo without Software field should be accepted");
@@ -155,11 +438,12 @@ mod tests {
     #[test]
     fn test_existing_collection_filter_accepts_photos_without_exif() {
         // Arrange
-        let filter = ExistingCollectionFilter::new(vec!["photo.jpg".to_string()]);
+        let filter = ExistingCollectionFilter::new();
+        let index = index_of(&["photo.jpg"]);
         let no_exif_photo = &[0xFF, 0xD8, 0xFF, 0xD9];
 
         // Act
-        let result = filter.should_include("photo.jpg", no_exif_photo);
+        let result = filter.should_include("photo.jpg", no_exif_photo, &index);
 
         // Assert
         assert!(result);
@@ -168,14 +452,12 @@ mod tests {
     #[test]
     fn test_existing_collection_filter_rejects_edited_files() {
         // Arrange
-        let filter = ExistingCollectionFilter::new(vec![
-            "DSC_9157.JPG".to_string(),
-            "DSC_9157-edited.JPG".to_string(),
-        ]);
+        let filter = ExistingCollectionFilter::new();
+        let index = index_of(&["DSC_9157.JPG", "DSC_9157-edited.JPG"]);
         let any_data = &[0xFF, 0xD8, 0xFF, 0xD9];
 
         // Act
-        let result = filter.should_include("DSC_9157-edited.JPG", any_data);
+        let result = filter.should_include("DSC_9157-edited.JPG", any_data, &index);
 
         // Assert
         assert!(
@@ -187,12 +469,12 @@ mod tests {
     #[test]
     fn test_existing_collection_filter_keeps_orphaned_edited_files() {
         // Arrange
-        let all_filenames = vec!["photo1.jpg".to_string(), "photo2-EDITED.jpg".to_string()];
-        let filter = ExistingCollectionFilter::new(all_filenames);
+        let filter = ExistingCollectionFilter::new();
+        let index = index_of(&["photo1.jpg", "photo2-EDITED.jpg"]);
         let any_data = &[0xFF, 0xD8, 0xFF, 0xD9];
 
         // Act
-        let result = filter.should_include("photo2-EDITED.jpg", any_data);
+        let result = filter.should_include("photo2-EDITED.jpg", any_data, &index);
 
         // Assert
         assert!(
@@ -201,17 +483,58 @@ mod tests {
         );
     }
 
+    #[rstest]
+    #[case("IMG_1-MOTION.jpg", false)]
+    #[case("IMG_2-edited.jpg", false)]
+    #[case("IMG_3-bearbeitet.jpg", false)]
+    #[case("IMG_4-modifié.jpg", true)]
+    fn test_existing_collection_filter_recognizes_added_suffixes_and_locales(
+        #[case] filename: &str,
+        #[case] expected: bool,
+    ) {
+        // Arrange
+        let filter = ExistingCollectionFilter::new()
+            .with_added_duplicate_patterns(&["-motion"])
+            .with_locales(&[DuplicateLocale::De]);
+        let original = filename.split_once('-').map(|(stem, _)| format!("{}.jpg", stem)).unwrap();
+        let index = index_of(&[original.as_str(), filename]);
+
+        // Act
+        let result = filter.should_include(filename, &[0xFF, 0xD8, 0xFF, 0xD9], &index);
+
+        // Assert
+        assert_eq!(result, expected);
+        assert_eq!(filter.rejects_by_name(filename, &index), !expected);
+    }
+
+    #[test]
+    fn test_existing_collection_filter_uses_only_given_suffixes() {
+        // Arrange
+        let filter = ExistingCollectionFilter::with_duplicate_patterns(&["-COLLAGE"]);
+        let index = index_of(&["IMG_1.jpg", "IMG_1-edited.jpg", "IMG_1-collage.jpg"]);
+        let any_data = &[0xFF, 0xD8, 0xFF, 0xD9];
+
+        // Act
+        let edited = filter.should_include("IMG_1-edited.jpg", any_data, &index);
+        let collage = filter.should_include("IMG_1-collage.jpg", any_data, &index);
+
+        // Assert
+        assert!(edited, "-edited is not in the list");
+        assert!(!collage);
+    }
+
     #[rstest]
     #[case("animation.gif")]
     #[case("PHOTO.GIF")]
     #[case("Image.Gif")]
     fn test_existing_collection_filter_rejects_gif_files(#[case] filename: &str) {
         // Arrange
-        let filter = ExistingCollectionFilter::new(vec![]);
+        let filter = ExistingCollectionFilter::new();
+        let index = index_of(&[]);
         let gif_data = &[0x47, 0x49, 0x46, 0x38, 0x39, 0x61]; // GIF89a header
 
         // Act
-        let result = filter.should_include(filename, gif_data);
+        let result = filter.should_include(filename, gif_data, &index);
 
         // Assert
         assert!(!result, "Should always reject GIF file: {}", filename);
@@ -230,15 +553,12 @@ mod tests {
         #[case] original_filename: &str,
     ) {
         // Arrange
-        let all_filenames = vec![
-            original_filename.to_string(),
-            duplicate_filename.to_string(),
-        ];
-        let filter = ExistingCollectionFilter::new(all_filenames);
+        let filter = ExistingCollectionFilter::new();
+        let index = index_of(&[original_filename, duplicate_filename]);
         let any_data = &[0xFF, 0xD8, 0xFF, 0xD9];
 
         // Act
-        let result = filter.should_include(duplicate_filename, any_data);
+        let result = filter.should_include(duplicate_filename, any_data, &index);
 
         // Assert
         assert!(
@@ -257,11 +577,12 @@ mod tests {
     #[case("DSC_9157-edited.JPG")]
     fn test_existing_collection_filter_keeps_orphaned_google_duplicates(#[case] filename: &str) {
         // Arrange - no original file exists
-        let filter = ExistingCollectionFilter::new(vec![filename.to_string()]);
+        let filter = ExistingCollectionFilter::new();
+        let index = index_of(&[filename]);
         let any_data = &[0xFF, 0xD8, 0xFF, 0xD9];
 
         // Act
-        let result = filter.should_include(filename, any_data);
+        let result = filter.should_include(filename, any_data, &index);
 
         // Assert
         assert!(
@@ -270,4 +591,121 @@ mod tests {
             filename
         );
     }
+
+    #[rstest]
+    #[case("Takeout/Google Photos/Print Subscriptions/scan_001.jpg", true)]
+    #[case("Takeout\\Google Photos\\Photo Books\\page.jpg", true)]
+    #[case("Takeout/Google Photos/print orders/order 12/photo.jpg", true)]
+    #[case("Takeout/Google Photos/Photos from 2014/IMG_1234.jpg", false)]
+    #[case("Takeout/Google Photos/Print Subscriptions.jpg", false)]
+    fn test_is_in_service_folder(#[case] entry_name: &str, #[case] expected: bool) {
+        // Act & Assert
+        assert_eq!(is_in_service_folder(entry_name), expected, "{}", entry_name);
+    }
+
+    #[test]
+    fn test_service_folder_filter_skips_service_folders_and_defers_to_inner() {
+        // Arrange
+        let inner = ExistingCollectionFilter::new();
+        let filter = ServiceFolderFilter::new(&inner);
+        let index = index_of(&[]);
+        let any_data = &[0xFF, 0xD8, 0xFF, 0xD9];
+
+        // Act & Assert
+        assert!(!filter.should_include("Google Photos/Print Subscriptions/a.jpg", any_data, &index));
+        assert!(!filter.should_include("Google Photos/Photos from 2014/a.gif", any_data, &index));
+        assert!(filter.should_include("Google Photos/Photos from 2014/a.jpg", any_data, &index));
+    }
+
+    #[rstest]
+    #[case("Photos from 2014/a.gif", true)]
+    #[case("Photos from 2014/IMG_1234-edited.jpg", true)]
+    #[case("Photos from 2014/IMG_5678-edited.jpg", false)]
+    #[case("Print Subscriptions/a.jpg", true)]
+    #[case("Photos from 2014/DSC_9157.JPG", false)]
+    fn test_rejects_by_name_without_data(#[case] name: &str, #[case] expected: bool) {
+        // Arrange
+        let inner = ExistingCollectionFilter::new();
+        let filter = ServiceFolderFilter::new(&inner);
+        let index = index_of(&["Photos from 2014/IMG_1234.jpg", "Photos from 2014/IMG_5678-edited.jpg"]);
+
+        // Act
+        let rejected = filter.rejects_by_name(name, &index);
+
+        // Assert
+        assert_eq!(rejected, expected, "{}", name);
+    }
+
+    #[rstest]
+    #[case("Takeout/Google Photos/Trash/IMG_1.jpg", false, false, true)]
+    #[case("Takeout/Google Photos/Failed Videos/VID_1.mp4", false, false, true)]
+    #[case("Takeout/Google Photos/Archive/IMG_1.jpg", false, false, true)]
+    #[case("Takeout/Google Photos/Trash/IMG_1.jpg", true, false, false)]
+    #[case("Takeout/Google Photos/Failed Videos/VID_1.mp4", true, false, false)]
+    #[case("Takeout/Google Photos/Archive/IMG_1.jpg", true, false, true)]
+    #[case("Takeout/Google Photos/Archive/IMG_1.jpg", false, true, false)]
+    #[case("Takeout/Google Photos/Photos from 2014/Trash.jpg", false, false, false)]
+    #[case("Takeout/Google Photos/Archived trip/IMG_1.jpg", false, false, false)]
+    fn test_hidden_folder_filter(
+        #[case] name: &str,
+        #[case] include_trash: bool,
+        #[case] include_archive: bool,
+        #[case] expected_rejected: bool,
+    ) {
+        // Arrange
+        let inner = NoFilter::new();
+        let filter = HiddenFolderFilter::new(&inner)
+            .with_trash(include_trash)
+            .with_archive(include_archive);
+        let index = index_of(&[name]);
+
+        // Act
+        let rejected = filter.rejects_by_name(name, &index);
+        let included = filter.should_include(name, &[], &index);
+
+        // Assert
+        assert_eq!(rejected, expected_rejected, "{}", name);
+        assert_eq!(included, !expected_rejected, "{}", name);
+    }
+
+    #[test]
+    fn test_album_filter_rejects_other_albums_by_name() {
+        // Arrange
+        let inner = NoFilter::new();
+        let filter = AlbumFilter::new(&inner, vec![glob::Pattern::new("Wedding*").unwrap()]);
+        let names = ["Takeout/Google Photos/Wedding 2018/a.jpg", "Takeout/Google Photos/Birthday/c.jpg"];
+        let index = index_of(&names);
+
+        // Act & Assert
+        assert!(!filter.rejects_by_name(names[0], &index));
+        assert!(filter.rejects_by_name(names[1], &index));
+    }
+
+    #[test]
+    fn test_album_filter_keeps_only_matching_albums() {
+        // Arrange
+        let inner = NoFilter::new();
+        let patterns = vec![
+            glob::Pattern::new("Wedding 2018").unwrap(),
+            glob::Pattern::new("holiday*").unwrap(),
+        ];
+        let filter = AlbumFilter::new(&inner, patterns);
+        let names = [
+            "Takeout/Google Photos/Wedding 2018/a.jpg",
+            "Takeout/Google Photos/Holiday Italy/b.jpg",
+            "Takeout/Google Photos/Birthday/c.jpg",
+            "Takeout/Google Photos/Photos from 2018/d.jpg",
+        ];
+        let index = index_of(&names);
+        let any_data = &[0xFF, 0xD8, 0xFF, 0xD9];
+
+        // Act
+        let included: Vec<bool> = names
+            .iter()
+            .map(|name| filter.should_include(name, any_data, &index))
+            .collect();
+
+        // Assert
+        assert_eq!(included, vec![true, true, false, false]);
+    }
 }
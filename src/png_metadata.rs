@@ -0,0 +1,191 @@
+// Dates in PNG text chunks, as screenshot tools and image editors write them
+
+use crate::exif::{start_of_day, Confidence, DateExtractionError, DateExtractor};
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Text keywords holding the creation time, most trusted first: the one the PNG
+/// specification defines, then ImageMagick's
+const CREATION_TIME_KEYWORDS: &[&str] = &["Creation Time", "date:create"];
+
+/// Extracts dates from the `Creation Time` (or `date:create`) keyword of a PNG's `tEXt` or
+/// uncompressed `iTXt` chunks. Dates in an `eXIf` chunk are read by `ExifDateExtractor`.
+#[derive(Default)]
+pub struct PngTextDateExtractor;
+
+impl PngTextDateExtractor {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl DateExtractor for PngTextDateExtractor {
+    fn extract_date_time(&self, _filename: &str, image_data: &[u8]) -> Result<NaiveDateTime> {
+        let rest = image_data
+            .strip_prefix(PNG_SIGNATURE)
+            .context(DateExtractionError::UnsupportedFormat("a PNG image"))?;
+        let texts: Vec<(String, String)> = png_chunks(rest).filter_map(text_of).collect();
+
+        CREATION_TIME_KEYWORDS
+            .iter()
+            .flat_map(|keyword| texts.iter().filter(move |(key, _)| key == keyword))
+            .find_map(|(_, value)| parse_text_date(value))
+            .context(DateExtractionError::NoDateTag("creation time in PNG text chunks"))
+    }
+
+    fn confidence(&self) -> Confidence {
+        Confidence::Exif
+    }
+}
+
+/// Chunks of a PNG after its signature, as (type, data)
+fn png_chunks(data: &[u8]) -> impl Iterator<Item = (&[u8], &[u8])> {
+    let mut rest = data;
+    std::iter::from_fn(move || {
+        let length = u32::from_be_bytes(rest.get(..4)?.try_into().ok()?) as usize;
+        let chunk_type = rest.get(4..8)?;
+        let chunk_data = rest.get(8..8usize.checked_add(length)?)?;
+        // Data is followed by a CRC
+        rest = rest.get(12 + length..).unwrap_or_default();
+        Some((chunk_type, chunk_data))
+    })
+}
+
+/// Keyword and text of a `tEXt` chunk (Latin-1) or an uncompressed `iTXt` chunk (UTF-8)
+fn text_of((chunk_type, data): (&[u8], &[u8])) -> Option<(String, String)> {
+    let (keyword, rest) = split_at_nul(data)?;
+    let keyword = latin1(keyword);
+    match chunk_type {
+        b"tEXt" => Some((keyword, latin1(rest))),
+        b"iTXt" => {
+            let [compression_flag, _compression_method, rest @ ..] = rest else {
+                return None;
+            };
+            if *compression_flag != 0 {
+                return None;
+            }
+            let (_language, rest) = split_at_nul(rest)?;
+            let (_translated_keyword, text) = split_at_nul(rest)?;
+            Some((keyword, String::from_utf8_lossy(text).into_owned()))
+        }
+        _ => None,
+    }
+}
+
+fn split_at_nul(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    let nul = data.iter().position(|&byte| byte == 0)?;
+    Some((&data[..nul], &data[nul + 1..]))
+}
+
+fn latin1(data: &[u8]) -> String {
+    data.iter().map(|&byte| byte as char).collect()
+}
+
+/// RFC 1123 (`Fri, 19 Apr 2013 19:46:43 +0200`, as the PNG specification suggests) and
+/// RFC 3339 times in their own offset, or a text starting with `YYYY-MM-DD` or `YYYY:MM:DD`
+/// (with an `HH:MM:SS` time after a space or `T`, else at midnight)
+fn parse_text_date(text: &str) -> Option<NaiveDateTime> {
+    let text = text.trim();
+    DateTime::parse_from_rfc2822(text)
+        .or_else(|_| DateTime::parse_from_rfc3339(text))
+        .map(|time| time.naive_local())
+        .ok()
+        .or_else(|| {
+            let date = NaiveDate::parse_from_str(&text.get(..10)?.replace(':', "-"), "%Y-%m-%d").ok()?;
+            let time = text
+                .get(11..19)
+                .filter(|_| matches!(text.as_bytes()[10], b' ' | b'T'))
+                .and_then(|time| chrono::NaiveTime::parse_from_str(time, "%H:%M:%S").ok());
+            Some(time.map_or_else(|| start_of_day(date), |time| date.and_time(time)))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exif::ExifDateExtractor;
+    use rstest::rstest;
+
+    fn png_chunk(chunk_type: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut chunk = (data.len() as u32).to_be_bytes().to_vec();
+        chunk.extend_from_slice(chunk_type);
+        chunk.extend_from_slice(data);
+        chunk.extend_from_slice(&crc32fast::hash(&chunk[4..]).to_be_bytes());
+        chunk
+    }
+
+    fn png_with(chunks: &[Vec<u8>]) -> Vec<u8> {
+        let mut ihdr = Vec::new();
+        ihdr.extend_from_slice(&1u32.to_be_bytes());
+        ihdr.extend_from_slice(&1u32.to_be_bytes());
+        ihdr.extend_from_slice(&[8, 0, 0, 0, 0]);
+        let mut png = PNG_SIGNATURE.to_vec();
+        png.extend(png_chunk(b"IHDR", &ihdr));
+        chunks.iter().for_each(|chunk| png.extend_from_slice(chunk));
+        png.extend(png_chunk(b"IEND", &[]));
+        png
+    }
+
+    #[rstest]
+    #[case(png_chunk(b"tEXt", b"Creation Time\0Fri, 19 Apr 2013 19:46:43 +0200"))]
+    #[case(png_chunk(b"tEXt", b"Creation Time\x002013:04:19 19:46:43"))]
+    #[case(png_chunk(b"tEXt", b"date:create\x002013-04-19T19:46:43+00:00"))]
+    #[case(png_chunk(b"iTXt", b"Creation Time\0\0\0en\0\x002013-04-19"))]
+    fn test_extract_date_from_png_text(#[case] chunk: Vec<u8>) {
+        // Arrange
+        let png = png_with(&[png_chunk(b"tEXt", b"Software\0Screenshot tool"), chunk]);
+
+        // Act
+        let date = PngTextDateExtractor::new().extract_date("Screenshot.png", &png);
+
+        // Assert
+        assert_eq!(date.unwrap(), NaiveDate::from_ymd_opt(2013, 4, 19).unwrap());
+    }
+
+    #[test]
+    fn test_creation_time_wins_over_imagemagick_date() {
+        // Arrange
+        let png = png_with(&[
+            png_chunk(b"tEXt", b"date:create\x002020-01-01T00:00:00+00:00"),
+            png_chunk(b"tEXt", b"Creation Time\x002013-04-19"),
+        ]);
+
+        // Act
+        let date = PngTextDateExtractor::new().extract_date("edited.png", &png);
+
+        // Assert
+        assert_eq!(date.unwrap(), NaiveDate::from_ymd_opt(2013, 4, 19).unwrap());
+    }
+
+    #[rstest]
+    #[case(png_with(&[png_chunk(b"tEXt", b"Software\0Screenshot tool")]))]
+    #[case(png_with(&[png_chunk(b"iTXt", b"Creation Time\0\x01\0\0\0x\x9c")]))]
+    #[case(vec![0xFF, 0xD8, 0xFF, 0xD9])]
+    fn test_extract_date_without_png_creation_time_returns_error(#[case] data: Vec<u8>) {
+        // Act & Assert
+        assert!(PngTextDateExtractor::new().extract_date("image.png", &data).is_err());
+    }
+
+    #[test]
+    fn test_exif_extractor_reads_png_exif_chunk() {
+        // Arrange
+        let field = exif::Field {
+            tag: exif::Tag::DateTimeOriginal,
+            ifd_num: exif::In::PRIMARY,
+            value: exif::Value::Ascii(vec![b"2013:04:19 19:46:43".to_vec()]),
+        };
+        let mut writer = exif::experimental::Writer::new();
+        writer.push_field(&field);
+        let mut tiff = std::io::Cursor::new(Vec::new());
+        writer.write(&mut tiff, false).unwrap();
+        let png = png_with(&[png_chunk(b"eXIf", &tiff.into_inner())]);
+
+        // Act
+        let date = ExifDateExtractor::new().extract_date("Screenshot.png", &png);
+
+        // Assert
+        assert_eq!(date.unwrap(), NaiveDate::from_ymd_opt(2013, 4, 19).unwrap());
+    }
+}
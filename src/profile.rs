@@ -0,0 +1,77 @@
+// Layouts of the photo services an input can come from
+
+use std::path::Path;
+
+/// Which service exported an input, for the handling that differs between them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ExportProfile {
+    /// Detect from the input names: Amazon Photos downloads are named `AmazonPhotos*.zip`,
+    /// anything else is read as a Takeout
+    #[default]
+    Auto,
+    /// Google Takeout; Facebook and Instagram exports are read the same way, their JSON
+    /// files are recognized by content
+    Takeout,
+    /// Amazon Photos downloads: no sidecars, so dates come from EXIF and file names, then
+    /// from dated folders (`2019-07 Tuscany/`, `2019/07/`)
+    Amazon,
+}
+
+impl ExportProfile {
+    /// Resolves `Auto` from the input paths; explicit profiles are kept
+    pub fn resolve<'p>(self, input_paths: impl IntoIterator<Item = &'p str>) -> ExportProfile {
+        if self != ExportProfile::Auto {
+            return self;
+        }
+
+        if input_paths.into_iter().any(is_amazon_photos_download) {
+            ExportProfile::Amazon
+        } else {
+            ExportProfile::Takeout
+        }
+    }
+
+    /// Whether folder names date the files that EXIF and file name leave undated
+    pub fn dates_from_folders(self) -> bool {
+        self == ExportProfile::Amazon
+    }
+}
+
+/// `AmazonPhotos.zip`, `AmazonPhotos (1).zip`, or an extracted `Amazon Photos` folder
+fn is_amazon_photos_download(path: &str) -> bool {
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase().replace(' ', ""))
+        .is_some_and(|name| name.starts_with("amazonphotos"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(&["AmazonPhotos.zip"], ExportProfile::Amazon)]
+    #[case(&["takeout-001.zip", "/downloads/AmazonPhotos (1).zip"], ExportProfile::Amazon)]
+    #[case(&["/home/anna/Amazon Photos"], ExportProfile::Amazon)]
+    #[case(&["takeout-001.zip"], ExportProfile::Takeout)]
+    #[case(&["/home/anna/Takeout"], ExportProfile::Takeout)]
+    fn test_auto_profile_detected_from_input_names(#[case] paths: &[&str], #[case] expected: ExportProfile) {
+        // Act
+        let profile = ExportProfile::Auto.resolve(paths.iter().copied());
+
+        // Assert
+        assert_eq!(profile, expected);
+    }
+
+    #[test]
+    fn test_explicit_profile_is_kept() {
+        // Act
+        let profile = ExportProfile::Takeout.resolve(["AmazonPhotos.zip"]);
+
+        // Assert
+        assert_eq!(profile, ExportProfile::Takeout);
+        assert!(!profile.dates_from_folders());
+        assert!(ExportProfile::Amazon.dates_from_folders());
+    }
+}
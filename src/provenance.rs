@@ -0,0 +1,56 @@
+use std::path::{Path, PathBuf};
+
+/// Name of the provenance index written into every date directory
+pub const ORIGIN_INDEX_FILE_NAME: &str = ".origin.csv";
+
+/// Header written when a provenance index is created
+pub const ORIGIN_INDEX_HEADER: &str = "file,archive,entry\n";
+
+/// Path of the provenance index next to an organized file
+pub fn origin_index_path(target_path: &Path) -> PathBuf {
+    target_path.with_file_name(ORIGIN_INDEX_FILE_NAME)
+}
+
+/// One CSV line mapping an organized file back to its archive and entry path
+pub fn origin_record(file_name: &str, archive: &str, entry_name: &str) -> String {
+    format!(
+        "{},{},{}\n",
+        csv_field(file_name),
+        csv_field(archive),
+        csv_field(entry_name)
+    )
+}
+
+/// Quotes a field when it contains a separator, quote or line break (RFC 4180)
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_origin_index_path_is_in_the_date_directory() {
+        // Act
+        let result = origin_index_path(Path::new("2023/2023-01-15/photo.jpg"));
+
+        // Assert
+        assert_eq!(result, PathBuf::from("2023/2023-01-15/.origin.csv"));
+    }
+
+    #[test]
+    fn test_origin_record_quotes_fields_when_needed() {
+        // Act
+        let plain = origin_record("photo.jpg", "takeout-001.zip", "Takeout/Google Photos/photo.jpg");
+        let quoted = origin_record("a,b.jpg", "takeout.zip", "Album \"Best\"/a,b.jpg");
+
+        // Assert
+        assert_eq!(plain, "photo.jpg,takeout-001.zip,Takeout/Google Photos/photo.jpg\n");
+        assert_eq!(quoted, "\"a,b.jpg\",takeout.zip,\"Album \"\"Best\"\"/a,b.jpg\"\n");
+    }
+}
@@ -0,0 +1,153 @@
+use crate::archive_index::ArchiveIndex;
+use crate::exif::{Confidence, DateExtractor, ParsedExif};
+use crate::photo_filter::PhotoFilter;
+use crate::meta_export::MetaMediaIndex;
+use crate::sidecar::{AlbumMetadataIndex, SidecarIndex};
+use crate::snapchat::SnapchatMemoryIndex;
+use crate::zip_image_reader::ZipImageReader;
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, NaiveDateTime};
+
+/// Everything the organizer would decide about one entry, without writing anything
+#[derive(Debug, Clone, PartialEq)]
+pub struct InventoryItem {
+    pub name: String,
+    pub size: u64,
+    pub album: Option<String>,
+    pub date: Option<NaiveDate>,
+    /// Capture time in local time, to the fraction of a second when the camera recorded it
+    pub time: Option<NaiveDateTime>,
+    /// Why no date could be determined, when `date` is `None`
+    pub date_error: Option<String>,
+    /// Whether the filter would organize this entry
+    pub included: bool,
+}
+
+/// Read-only listing of an archive, for building custom review flows on top of the library
+#[derive(Debug, Default, PartialEq)]
+pub struct Inventory {
+    pub items: Vec<InventoryItem>,
+}
+
+impl Inventory {
+    pub fn included(&self) -> impl Iterator<Item = &InventoryItem> {
+        self.items.iter().filter(|item| item.included)
+    }
+
+    pub fn undated(&self) -> impl Iterator<Item = &InventoryItem> {
+        self.items.iter().filter(|item| item.date.is_none())
+    }
+}
+
+/// Lists all entries of the input with their detected dates, album membership and
+/// filter decisions. Nothing is written.
+pub fn scan(
+    reader: &dyn ZipImageReader,
+    date_extractor: &dyn DateExtractor,
+    photo_filter: &dyn PhotoFilter,
+) -> Result<Inventory> {
+    let listing = reader.list_entries().context("Failed to read ZIP entries")?;
+    let sidecars = reader.sidecars().context("Failed to read JSON sidecars")?;
+    let media_names = || listing.iter().map(|entry| entry.name.as_str());
+    let index = ArchiveIndex::from_listing(&listing)
+        .with_sidecars(SidecarIndex::pair(media_names(), &sidecars))
+        .with_meta_media(MetaMediaIndex::pair(media_names(), &sidecars))
+        .with_snapchat_memories(SnapchatMemoryIndex::pair(media_names(), &sidecars))
+        .with_album_metadata(AlbumMetadataIndex::from_sidecars(&sidecars));
+
+    let mut items = Vec::with_capacity(listing.len());
+    for entry in reader.entries().context("Failed to read ZIP entries")? {
+        let entry = entry.context("Failed to read ZIP entries")?;
+        let exif = ParsedExif::new();
+        let context = index.context_of(&entry).with_exif(&exif);
+        let (time, date_error) = match date_extractor.extract_with_confidence_in_context(
+            &entry.name,
+            &entry.data,
+            &context,
+        ) {
+            // The organizer files these by year alone; January 1st is no capture date
+            Ok(extracted) if extracted.confidence == Confidence::Year => {
+                (None, Some(format!("Only the year {} is known", extracted.time.format("%Y"))))
+            }
+            Ok(extracted) => (Some(extracted.time), None),
+            Err(e) => (None, Some(format!("{:#}", e))),
+        };
+
+        items.push(InventoryItem {
+            name: entry.name.clone(),
+            size: entry.data.len() as u64,
+            album: index.album_of(&entry.name).map(str::to_string),
+            date: time.map(|time| time.date()),
+            time,
+            date_error,
+            included: photo_filter.should_include_with_exif(&entry.name, &entry.data, &index, &exif),
+        });
+    }
+
+    Ok(Inventory { items })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exif::CompositeDateExtractor;
+    use crate::photo_filter::ExistingCollectionFilter;
+    use crate::zip_image_reader::{EntryIter, ZipEntry};
+
+    struct MockZipReader {
+        entries: Vec<ZipEntry>,
+    }
+
+    impl ZipImageReader for MockZipReader {
+        fn entries(&self) -> Result<EntryIter<'_>> {
+            Ok(Box::new(self.entries.clone().into_iter().map(Ok)))
+        }
+    }
+
+    #[test]
+    fn test_scan_lists_dates_albums_and_filter_decisions() {
+        // Arrange
+        let test_image = include_bytes!("../tests/fixtures/single_pixel_with_exif.jpg");
+        let no_exif = vec![0xFF, 0xD8, 0xFF, 0xD9];
+        let reader = MockZipReader {
+            entries: vec![
+                ZipEntry {
+                    name: "Takeout/Google Photos/Trip/IMG_20150130_000000.jpg".to_string(),
+                    data: no_exif.clone().into(),
+                    ..Default::default()
+                },
+                ZipEntry {
+                    name: "Takeout/Google Photos/Photos from 2012/DSC_9157.JPG".to_string(),
+                    data: test_image.to_vec().into(),
+                    ..Default::default()
+                },
+                ZipEntry {
+                    name: "Takeout/Google Photos/Photos from 2012/random.jpg".to_string(),
+                    data: no_exif.into(),
+                    ..Default::default()
+                },
+            ],
+        };
+        let date_extractor = CompositeDateExtractor::new();
+        let filter = ExistingCollectionFilter::new();
+
+        // Act
+        let inventory = scan(&reader, &date_extractor, &filter).unwrap();
+
+        // Assert
+        assert_eq!(inventory.items.len(), 3);
+        let trip = &inventory.items[0];
+        assert_eq!(trip.album.as_deref(), Some("Trip"));
+        assert_eq!(trip.date, NaiveDate::from_ymd_opt(2015, 1, 30));
+        assert!(trip.included);
+
+        let lightroom = &inventory.items[1];
+        assert_eq!(lightroom.album, None);
+        assert!(!lightroom.included, "Lightroom photo is filtered out");
+
+        assert_eq!(inventory.included().count(), 2);
+        let undated: Vec<_> = inventory.undated().collect();
+        assert_eq!(undated.len(), 1);
+        assert_eq!(undated[0].date_error.as_deref(), Some("Only the year 2012 is known"));
+    }
+}
@@ -0,0 +1,576 @@
+// Google Takeout `.json` sidecars: parsing and pairing them with the media they describe
+
+use crate::zip_image_reader::ZipEntry;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Extension of Takeout sidecar files
+pub const SIDECAR_EXTENSION: &str = "json";
+
+/// Names Takeout gives the metadata file of an album folder, per export language
+const ALBUM_METADATA_NAMES: &[&str] = &["metadata.json", "Metadaten.json", "métadonnées.json"];
+
+/// Takeout cuts sidecar file names (including `.json`) to this many characters
+const MAX_SIDECAR_NAME_CHARS: usize = 51;
+
+/// What recent Takeouts put between the media's file name and `.json`
+const SUPPLEMENTAL_METADATA: &str = ".supplemental-metadata";
+
+/// Suffixes Google Photos adds to the copies its editor saves (lowercase)
+const EDIT_SUFFIXES: &[&str] = &["-edited", "-effects"];
+
+/// Metadata Google Photos exports next to a photo or video
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Sidecar {
+    /// Original file name as uploaded
+    pub title: Option<String>,
+    /// When the photo was taken, as Google Photos shows it
+    pub photo_taken_time: Option<DateTime<Utc>>,
+    pub geo: Option<GeoPosition>,
+    /// Names of the people tagged in the photo
+    pub people: Vec<String>,
+    pub favorited: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeoPosition {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: f64,
+}
+
+impl Sidecar {
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let raw: RawSidecar = serde_json::from_slice(data).context("Failed to parse JSON sidecar")?;
+
+        Ok(Self {
+            title: raw.title,
+            photo_taken_time: raw
+                .photo_taken_time
+                .and_then(|time| time.timestamp.parse().ok())
+                .and_then(|secs| DateTime::from_timestamp(secs, 0)),
+            // Google writes 0/0 when it has no location; the edited location wins over EXIF
+            geo: [raw.geo_data, raw.geo_data_exif]
+                .into_iter()
+                .flatten()
+                .find(|geo| geo.latitude != 0.0 || geo.longitude != 0.0),
+            people: raw.people.into_iter().map(|person| person.name).collect(),
+            favorited: raw.favorited,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawSidecar {
+    title: Option<String>,
+    photo_taken_time: Option<RawTimestamp>,
+    geo_data: Option<GeoPosition>,
+    geo_data_exif: Option<GeoPosition>,
+    #[serde(default)]
+    people: Vec<RawPerson>,
+    #[serde(default)]
+    favorited: bool,
+}
+
+#[derive(Deserialize)]
+struct RawTimestamp {
+    /// Seconds since the Unix epoch, as a string
+    timestamp: String,
+}
+
+#[derive(Deserialize)]
+struct RawPerson {
+    name: String,
+}
+
+impl<'de> Deserialize<'de> for GeoPosition {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct RawGeo {
+            latitude: f64,
+            longitude: f64,
+            #[serde(default)]
+            altitude: f64,
+        }
+
+        let raw = RawGeo::deserialize(deserializer)?;
+        Ok(GeoPosition {
+            latitude: raw.latitude,
+            longitude: raw.longitude,
+            altitude: raw.altitude,
+        })
+    }
+}
+
+/// Metadata Google Photos exports for an album, in the album folder's `metadata.json`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AlbumMetadata {
+    pub title: Option<String>,
+    /// The album's date, which Google Photos takes from its photos
+    pub date: Option<DateTime<Utc>>,
+    pub geo: Option<GeoPosition>,
+}
+
+impl AlbumMetadata {
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let raw: RawAlbumMetadata =
+            serde_json::from_slice(data).context("Failed to parse album metadata")?;
+
+        Ok(Self {
+            title: raw.title,
+            date: raw
+                .date
+                .and_then(|time| time.timestamp.parse().ok())
+                .and_then(|secs| DateTime::from_timestamp(secs, 0)),
+            geo: raw.geo_data.filter(|geo| geo.latitude != 0.0 || geo.longitude != 0.0),
+        })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawAlbumMetadata {
+    title: Option<String>,
+    date: Option<RawTimestamp>,
+    geo_data: Option<GeoPosition>,
+}
+
+/// Album metadata by the folder it describes
+#[derive(Debug, Default)]
+pub struct AlbumMetadataIndex {
+    by_folder: HashMap<String, AlbumMetadata>,
+}
+
+impl AlbumMetadataIndex {
+    /// Picks the album `metadata.json` files out of the sidecar entries. Files that are not
+    /// valid album metadata are ignored.
+    pub fn from_sidecars(sidecars: &[ZipEntry]) -> Self {
+        let by_folder = sidecars
+            .iter()
+            .filter_map(|entry| {
+                let (folder, file_name) = entry.name.rsplit_once('/')?;
+                if !ALBUM_METADATA_NAMES.contains(&file_name) {
+                    return None;
+                }
+                Some((folder.to_string(), AlbumMetadata::parse(&entry.data).ok()?))
+            })
+            .collect();
+
+        Self { by_folder }
+    }
+
+    /// Metadata of the album folder a media entry is in
+    pub fn get(&self, media_name: &str) -> Option<&AlbumMetadata> {
+        let (folder, _) = media_name.rsplit_once('/')?;
+        self.by_folder.get(folder)
+    }
+}
+
+/// Sidecars by the name of the media entry they belong to
+#[derive(Debug, Default)]
+pub struct SidecarIndex {
+    by_media: HashMap<String, Sidecar>,
+}
+
+impl SidecarIndex {
+    /// Pairs each media entry with a sidecar in the same folder. Sidecars that are not
+    /// valid Takeout JSON are ignored.
+    pub fn pair<'n>(media_names: impl IntoIterator<Item = &'n str>, sidecars: &[ZipEntry]) -> Self {
+        let parsed: HashMap<&str, Sidecar> = sidecars
+            .iter()
+            .filter_map(|entry| Some((entry.name.as_str(), Sidecar::parse(&entry.data).ok()?)))
+            .collect();
+        if parsed.is_empty() {
+            return Self::default();
+        }
+
+        let by_media = media_names
+            .into_iter()
+            .filter_map(|media_name| {
+                let sidecar = sidecar_candidates(media_name)
+                    .iter()
+                    .find_map(|candidate| parsed.get(candidate.as_str()))?;
+                Some((media_name.to_string(), sidecar.clone()))
+            })
+            .collect();
+
+        Self { by_media }
+    }
+
+    pub fn get(&self, media_name: &str) -> Option<&Sidecar> {
+        self.by_media.get(media_name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_media.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_media.is_empty()
+    }
+}
+
+/// Names the sidecar of a media entry can have, most specific first:
+/// `IMG_1234.jpg.json`, the newer `IMG_1234.jpg.supplemental-metadata.json`, both cut to
+/// Takeout's name length limit (`IMG_1234.jpg.supplemental-met.json`), and the older
+/// `IMG_1234.json`. A numbered duplicate, `IMG_1234(1).jpg`, has its number after the
+/// original's full name: `IMG_1234.jpg(1).json`, `IMG_1234.jpg.supplemental-metadata(1).json`.
+fn sidecar_candidates(media_name: &str) -> Vec<String> {
+    let (folder, file_name) = match media_name.rfind('/') {
+        Some(slash) => media_name.split_at(slash + 1),
+        None => ("", media_name),
+    };
+
+    let mut names = vec![format!("{}.{}", file_name, SIDECAR_EXTENSION)];
+    if let Some((original, number)) = numbered_duplicate(file_name) {
+        names.extend(full_name_candidates(&original, &format!("({})", number)));
+    }
+    names.extend(full_name_candidates(file_name, ""));
+    if let Some((stem, _extension)) = file_name.rsplit_once('.').filter(|(stem, _)| !stem.is_empty()) {
+        names.push(format!("{}.{}", stem, SIDECAR_EXTENSION));
+    }
+    // Edited copies usually come without a sidecar of their own
+    if let Some(original) = edited_original(file_name) {
+        names.extend(full_name_candidates(&original, ""));
+    }
+
+    let mut candidates: Vec<String> = Vec::with_capacity(names.len());
+    for name in names {
+        let candidate = format!("{}{}", folder, name);
+        if !candidates.contains(&candidate) {
+            candidates.push(candidate);
+        }
+    }
+    candidates
+}
+
+/// `{file_name}{counter}.json` and `{file_name}.supplemental-metadata{counter}.json`, cut to
+/// Takeout's name length limit before the counter
+fn full_name_candidates(file_name: &str, counter: &str) -> Vec<String> {
+    let suffix = format!("{}.{}", counter, SIDECAR_EXTENSION);
+    let max_base_chars = MAX_SIDECAR_NAME_CHARS - SIDECAR_EXTENSION.len() - 1;
+    let supplemental = format!("{}{}", file_name, SUPPLEMENTAL_METADATA);
+
+    [file_name, supplemental.as_str()]
+        .into_iter()
+        .map(|base| format!("{}{}", base.chars().take(max_base_chars).collect::<String>(), suffix))
+        .collect()
+}
+
+/// The name of the original of a copy saved by the Google Photos editor, `IMG_1234.jpg` for
+/// `IMG_1234-edited.jpg` or `IMG_1234-EFFECTS.jpg`
+pub fn edited_original(file_name: &str) -> Option<String> {
+    let file_name = file_name.rsplit('/').next().unwrap_or(file_name);
+    let (stem, extension) = match file_name.rsplit_once('.') {
+        Some((stem, extension)) => (stem, Some(extension)),
+        None => (file_name, None),
+    };
+    let original_stem = EDIT_SUFFIXES.iter().find_map(|suffix| {
+        let cut = stem.len().checked_sub(suffix.len())?;
+        (stem.is_char_boundary(cut) && stem[cut..].eq_ignore_ascii_case(suffix)).then(|| &stem[..cut])
+    })?;
+    if original_stem.is_empty() {
+        return None;
+    }
+    Some(match extension {
+        Some(extension) => format!("{}.{}", original_stem, extension),
+        None => original_stem.to_string(),
+    })
+}
+
+/// The original's name and the number of a numbered duplicate, `IMG_1234(1).jpg`
+fn numbered_duplicate(file_name: &str) -> Option<(String, u32)> {
+    let (stem, extension) = match file_name.rsplit_once('.') {
+        Some((stem, extension)) => (stem, Some(extension)),
+        None => (file_name, None),
+    };
+    let (original_stem, number) = stem.strip_suffix(')')?.rsplit_once('(')?;
+    if original_stem.is_empty() || number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let original = match extension {
+        Some(extension) => format!("{}.{}", original_stem, extension),
+        None => original_stem.to_string(),
+    };
+    Some((original, number.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn sidecar_entry(name: &str, timestamp: &str) -> ZipEntry {
+        ZipEntry {
+            name: name.to_string(),
+            data: format!(r#"{{"photoTakenTime": {{"timestamp": "{}"}}}}"#, timestamp).into_bytes().into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_takeout_sidecar() {
+        // Arrange
+        let json = br#"{
+            "title": "IMG_1234.jpg",
+            "description": "",
+            "imageViews": "12",
+            "creationTime": {"timestamp": "1421000000", "formatted": "11 Jan 2015, 18:13:20 UTC"},
+            "photoTakenTime": {"timestamp": "1420070400", "formatted": "1 Jan 2015, 00:00:00 UTC"},
+            "geoData": {"latitude": 0.0, "longitude": 0.0, "altitude": 0.0, "latitudeSpan": 0.0, "longitudeSpan": 0.0},
+            "geoDataExif": {"latitude": 52.37, "longitude": 4.89, "altitude": 1.5, "latitudeSpan": 0.0, "longitudeSpan": 0.0},
+            "people": [{"name": "Anna"}, {"name": "Ben"}],
+            "favorited": true,
+            "url": "https://photos.google.com/photo/abc"
+        }"#;
+
+        // Act
+        let sidecar = Sidecar::parse(json).unwrap();
+
+        // Assert
+        assert_eq!(sidecar.title.as_deref(), Some("IMG_1234.jpg"));
+        assert_eq!(
+            sidecar.photo_taken_time,
+            DateTime::from_timestamp(1_420_070_400, 0)
+        );
+        assert_eq!(
+            sidecar.geo,
+            Some(GeoPosition {
+                latitude: 52.37,
+                longitude: 4.89,
+                altitude: 1.5
+            })
+        );
+        assert_eq!(sidecar.people, vec!["Anna", "Ben"]);
+        assert!(sidecar.favorited);
+    }
+
+    #[test]
+    fn test_album_metadata_index_dates_entries_in_album_folder() {
+        // Arrange
+        let album_metadata = ZipEntry {
+            name: "Takeout/Google Photos/Holiday/metadata.json".to_string(),
+            data: br#"{
+                "title": "Holiday",
+                "description": "",
+                "access": "protected",
+                "date": {"timestamp": "1436824799", "formatted": "13 Jul 2015, 21:59:59 UTC"},
+                "geoData": {"latitude": 43.77, "longitude": 11.25, "altitude": 0.0}
+            }"#
+            .to_vec()
+            .into(),
+            ..Default::default()
+        };
+        let sidecars = vec![album_metadata, sidecar_entry("Takeout/Google Photos/Holiday/a.jpg.json", "1")];
+
+        // Act
+        let index = AlbumMetadataIndex::from_sidecars(&sidecars);
+
+        // Assert
+        let album = index.get("Takeout/Google Photos/Holiday/scan.jpg").unwrap();
+        assert_eq!(album.title.as_deref(), Some("Holiday"));
+        assert_eq!(album.date, DateTime::from_timestamp(1_436_824_799, 0));
+        assert_eq!(album.geo.map(|geo| geo.latitude), Some(43.77));
+        assert_eq!(index.get("Takeout/Google Photos/Other/scan.jpg"), None);
+        assert_eq!(index.get("Takeout/Google Photos/Holiday/sub/scan.jpg"), None);
+    }
+
+    #[test]
+    fn test_parse_minimal_sidecar() {
+        // Act
+        let sidecar = Sidecar::parse(b"{\"title\": \"a.jpg\"}").unwrap();
+
+        // Assert
+        assert_eq!(sidecar.photo_taken_time, None);
+        assert_eq!(sidecar.geo, None);
+        assert!(sidecar.people.is_empty());
+        assert!(!sidecar.favorited);
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_json() {
+        // Act
+        let result = Sidecar::parse(b"not json");
+
+        // Assert
+        assert!(result.is_err());
+    }
+
+    #[rstest]
+    #[case("Photos from 2015/IMG_1234.jpg", "Photos from 2015/IMG_1234.jpg.json")]
+    #[case("Photos from 2015/IMG_1234.jpg", "Photos from 2015/IMG_1234.json")]
+    #[case(
+        "Trip/Screenshot_20150101-120000_Some_Very_Long_App_Name.png",
+        "Trip/Screenshot_20150101-120000_Some_Very_Long_App_.json"
+    )]
+    #[case("IMG_1234.jpg", "IMG_1234.jpg.json")]
+    #[case("Photos from 2023/IMG_1234.jpg", "Photos from 2023/IMG_1234.jpg.supplemental-metadata.json")]
+    #[case("Photos from 2023/PXL_20230615_153045123.jpg", "Photos from 2023/PXL_20230615_153045123.jpg.supplemental-metada.json")]
+    #[case("Photos from 2023/PXL_20230615_153045123.MP.jpg", "Photos from 2023/PXL_20230615_153045123.MP.jpg.supplemental-met.json")]
+    #[case("Photos from 2023/Screenshot_20230101-120000_Messages_Chats.png", "Photos from 2023/Screenshot_20230101-120000_Messages_Chats.png..json")]
+    #[case("Photos from 2023/Screenshot_20230101-120000_Messages_Chat.png", "Photos from 2023/Screenshot_20230101-120000_Messages_Chat.png.s.json")]
+    #[case(
+        "Trip/Screenshot_20230101-120000_Some_Very_Long_App_Name.png",
+        "Trip/Screenshot_20230101-120000_Some_Very_Long_App_.json"
+    )]
+    fn test_pair_finds_sidecar(#[case] media_name: &str, #[case] sidecar_name: &str) {
+        // Arrange
+        let sidecars = vec![sidecar_entry(sidecar_name, "1420070400")];
+
+        // Act
+        let index = SidecarIndex::pair([media_name], &sidecars);
+
+        // Assert
+        let sidecar = index.get(media_name).expect("sidecar should be paired");
+        assert_eq!(sidecar.photo_taken_time, DateTime::from_timestamp(1_420_070_400, 0));
+    }
+
+    #[rstest]
+    #[case("Photos from 2015/IMG_1234(1).jpg", "Photos from 2015/IMG_1234.jpg(1).json")]
+    #[case("Photos from 2023/IMG_1234(2).HEIC", "Photos from 2023/IMG_1234.HEIC.supplemental-metadata(2).json")]
+    #[case("Photos from 2015/IMG_1234(1).jpg", "Photos from 2015/IMG_1234(1).jpg.json")]
+    #[case("Photos from 2015/IMG_1234(1).jpg", "Photos from 2015/IMG_1234(1).json")]
+    #[case(
+        "Trip/Screenshot_20150101-120000_Some_Very_Long_App_Name(1).png",
+        "Trip/Screenshot_20150101-120000_Some_Very_Long_App_(1).json"
+    )]
+    #[case(
+        "Photos from 2023/PXL_20230615_153045123(1).jpg",
+        "Photos from 2023/PXL_20230615_153045123.jpg.supplemental-metada(1).json"
+    )]
+    fn test_pair_finds_sidecar_of_numbered_duplicate(#[case] media_name: &str, #[case] sidecar_name: &str) {
+        // Arrange
+        let sidecars = vec![sidecar_entry(sidecar_name, "1420070400")];
+
+        // Act
+        let index = SidecarIndex::pair([media_name], &sidecars);
+
+        // Assert
+        let sidecar = index.get(media_name).expect("sidecar should be paired");
+        assert_eq!(sidecar.photo_taken_time, DateTime::from_timestamp(1_420_070_400, 0));
+    }
+
+    #[test]
+    fn test_pair_keeps_original_and_numbered_duplicate_apart() {
+        // Arrange
+        let sidecars = vec![
+            sidecar_entry("a/IMG_1234.jpg.json", "1"),
+            sidecar_entry("a/IMG_1234.jpg(1).json", "2"),
+            sidecar_entry("a/IMG_1234.jpg(2).json", "3"),
+        ];
+
+        // Act
+        let index = SidecarIndex::pair(["a/IMG_1234.jpg", "a/IMG_1234(1).jpg", "a/IMG_1234(2).jpg", "a/IMG_1234(3).jpg"], &sidecars);
+
+        // Assert
+        let taken = |name: &str| index.get(name).and_then(|sidecar| sidecar.photo_taken_time);
+        assert_eq!(taken("a/IMG_1234.jpg"), DateTime::from_timestamp(1, 0));
+        assert_eq!(taken("a/IMG_1234(1).jpg"), DateTime::from_timestamp(2, 0));
+        assert_eq!(taken("a/IMG_1234(2).jpg"), DateTime::from_timestamp(3, 0));
+        assert_eq!(taken("a/IMG_1234(3).jpg"), None);
+    }
+
+    #[rstest]
+    #[case("IMG_1234(1).jpg", Some(("IMG_1234.jpg", 1)))]
+    #[case("IMG_1234(12)", Some(("IMG_1234", 12)))]
+    #[case("Holiday (2019)(3).png", Some(("Holiday (2019).png", 3)))]
+    #[case("IMG_1234.jpg", None)]
+    #[case("IMG_1234().jpg", None)]
+    #[case("(1).jpg", None)]
+    #[case("IMG_(a).jpg", None)]
+    fn test_numbered_duplicate(#[case] file_name: &str, #[case] expected: Option<(&str, u32)>) {
+        // Act
+        let duplicate = numbered_duplicate(file_name);
+
+        // Assert
+        assert_eq!(
+            duplicate.as_ref().map(|(original, number)| (original.as_str(), *number)),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case("IMG_1234-edited.jpg", Some("IMG_1234.jpg"))]
+    #[case("Trip/IMG_1234-EDITED.JPG", Some("IMG_1234.JPG"))]
+    #[case("PXL_20230615_153045123-EFFECTS.jpg", Some("PXL_20230615_153045123.jpg"))]
+    #[case("-edited.jpg", None)]
+    #[case("IMG_1234.jpg", None)]
+    #[case("IMG_1234-MIX.jpg", None)]
+    fn test_edited_original(#[case] file_name: &str, #[case] expected: Option<&str>) {
+        // Act
+        let original = edited_original(file_name);
+
+        // Assert
+        assert_eq!(original.as_deref(), expected);
+    }
+
+    #[test]
+    fn test_pair_gives_edited_copy_the_sidecar_of_its_original() {
+        // Arrange
+        let sidecars = vec![
+            sidecar_entry("a/IMG_1234.jpg.supplemental-metadata.json", "1"),
+            sidecar_entry("a/IMG_5678.jpg.json", "2"),
+            sidecar_entry("a/IMG_5678-edited.jpg.json", "3"),
+        ];
+
+        // Act
+        let index = SidecarIndex::pair(["a/IMG_1234-edited.jpg", "a/IMG_5678-edited.jpg"], &sidecars);
+
+        // Assert
+        let taken = |name: &str| index.get(name).and_then(|sidecar| sidecar.photo_taken_time);
+        assert_eq!(taken("a/IMG_1234-edited.jpg"), DateTime::from_timestamp(1, 0));
+        assert_eq!(taken("a/IMG_5678-edited.jpg"), DateTime::from_timestamp(3, 0), "Own sidecar first");
+    }
+
+    #[test]
+    fn test_pair_prefers_sidecar_named_after_full_file_name() {
+        // Arrange
+        let sidecars = vec![
+            sidecar_entry("a/IMG_1234.json", "1"),
+            sidecar_entry("a/IMG_1234.jpg.supplemental-metadata.json", "2"),
+        ];
+
+        // Act
+        let index = SidecarIndex::pair(["a/IMG_1234.jpg", "a/IMG_1234.png"], &sidecars);
+
+        // Assert
+        assert_eq!(
+            index.get("a/IMG_1234.jpg").unwrap().photo_taken_time,
+            DateTime::from_timestamp(2, 0)
+        );
+        assert_eq!(
+            index.get("a/IMG_1234.png").unwrap().photo_taken_time,
+            DateTime::from_timestamp(1, 0)
+        );
+    }
+
+    #[test]
+    fn test_pair_prefers_full_name_and_stays_in_folder() {
+        // Arrange
+        let sidecars = vec![
+            sidecar_entry("a/IMG_1234.json", "1"),
+            sidecar_entry("a/IMG_1234.jpg.json", "2"),
+            sidecar_entry("b/IMG_5678.jpg.json", "3"),
+            ZipEntry {
+                name: "a/broken.jpg.json".to_string(),
+                data: b"{".to_vec().into(),
+                ..Default::default()
+            },
+        ];
+
+        // Act
+        let index = SidecarIndex::pair(["a/IMG_1234.jpg", "a/IMG_5678.jpg", "a/broken.jpg"], &sidecars);
+
+        // Assert
+        assert_eq!(index.len(), 1);
+        assert_eq!(
+            index.get("a/IMG_1234.jpg").unwrap().photo_taken_time,
+            DateTime::from_timestamp(2, 0)
+        );
+        assert!(index.get("a/IMG_5678.jpg").is_none());
+        assert!(index.get("a/broken.jpg").is_none());
+    }
+}
@@ -0,0 +1,206 @@
+// Snapchat's "My Data" export: dates of saved Memories from memories_history.json
+
+use crate::zip_image_reader::ZipEntry;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Name of the file listing every saved Memory with its date
+const MEMORIES_HISTORY: &str = "memories_history.json";
+
+/// `2019-07-04_<id>-main.jpg` and its `-overlay.png`, the layer of drawings and captions
+static MEMORY_NAME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(\d{4}-\d{2}-\d{2})_([^.]+?)(?:-(?:main|overlay))?\.[^.]+$").expect("valid regex")
+});
+
+/// What `memories_history.json` records about one Memory
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SnapchatMemory {
+    /// When the snap was taken
+    pub date_time: DateTime<Utc>,
+}
+
+/// Memories of a Snapchat export by the name of their entry
+#[derive(Debug, Default)]
+pub struct SnapchatMemoryIndex {
+    by_media: HashMap<String, SnapchatMemory>,
+}
+
+impl SnapchatMemoryIndex {
+    /// Pairs media entries with the `Saved Media` records of `memories_history.json`. A record
+    /// names its media only in the download link, by the `mid` that the export puts in the
+    /// file name; media without a matching id take the record of their day when it is the
+    /// only one.
+    pub fn pair<'n>(media_names: impl IntoIterator<Item = &'n str>, json_entries: &[ZipEntry]) -> Self {
+        let records: Vec<(Option<String>, SnapchatMemory)> = json_entries
+            .iter()
+            .filter(|entry| file_name(&entry.name).eq_ignore_ascii_case(MEMORIES_HISTORY))
+            .filter_map(|entry| serde_json::from_slice::<Value>(&entry.data).ok())
+            .flat_map(|value| saved_media(&value))
+            .collect();
+        if records.is_empty() {
+            return Self::default();
+        }
+
+        let by_id: HashMap<&str, SnapchatMemory> = records
+            .iter()
+            .filter_map(|(id, memory)| Some((id.as_deref()?, *memory)))
+            .collect();
+        let mut by_day: HashMap<NaiveDate, Vec<SnapchatMemory>> = HashMap::new();
+        for (_, memory) in &records {
+            by_day.entry(memory.date_time.date_naive()).or_default().push(*memory);
+        }
+
+        let by_media = media_names
+            .into_iter()
+            .filter_map(|media_name| {
+                let captures = MEMORY_NAME.captures(file_name(media_name))?;
+                let id = captures[2].to_lowercase();
+                let memory = by_id.get(id.as_str()).copied().or_else(|| {
+                    let day = NaiveDate::parse_from_str(&captures[1], "%Y-%m-%d").ok()?;
+                    match by_day.get(&day)?.as_slice() {
+                        [memory] => Some(*memory),
+                        _ => None,
+                    }
+                })?;
+                Some((media_name.to_string(), memory))
+            })
+            .collect();
+
+        Self { by_media }
+    }
+
+    pub fn get(&self, media_name: &str) -> Option<&SnapchatMemory> {
+        self.by_media.get(media_name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_media.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_media.is_empty()
+    }
+}
+
+fn file_name(name: &str) -> &str {
+    name.rsplit('/').next().unwrap_or(name)
+}
+
+/// The `Saved Media` records as (lowercase `mid`, memory):
+/// `{"Date": "2019-07-04 18:23:45 UTC", "Download Link": "https://...&mid=...&ts=..."}`
+fn saved_media(value: &Value) -> Vec<(Option<String>, SnapchatMemory)> {
+    let Some(records) = value.get("Saved Media").and_then(Value::as_array) else {
+        return Vec::new();
+    };
+    records
+        .iter()
+        .filter_map(|record| {
+            let date = record.get("Date")?.as_str()?;
+            let date_time = NaiveDateTime::parse_from_str(date.trim_end_matches(" UTC"), "%Y-%m-%d %H:%M:%S")
+                .ok()?
+                .and_utc();
+            let id = ["Download Link", "Media Download Url"]
+                .iter()
+                .filter_map(|key| record.get(*key)?.as_str())
+                .find_map(media_id);
+            Some((id, SnapchatMemory { date_time }))
+        })
+        .collect()
+}
+
+/// The `mid` query parameter of a download link
+fn media_id(link: &str) -> Option<String> {
+    let (_, query) = link.split_once('?')?;
+    query
+        .split('&')
+        .find_map(|parameter| parameter.strip_prefix("mid="))
+        .filter(|id| !id.is_empty())
+        .map(str::to_lowercase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json_entry(name: &str, json: &str) -> ZipEntry {
+        ZipEntry {
+            name: name.to_string(),
+            data: json.as_bytes().to_vec().into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_pair_memories_by_media_id() {
+        // Arrange
+        let history = json_entry(
+            "mydata~1690000000/json/memories_history.json",
+            r#"{"Saved Media": [
+                {"Date": "2019-07-04 18:23:45 UTC", "Media Type": "Image",
+                 "Download Link": "https://app.snapchat.com/dmd/memories?uid=u1&sid=s1&mid=AB12-CD34&ts=1&sig=x"},
+                {"Date": "2019-07-04 21:00:00 UTC", "Media Type": "Video",
+                 "Download Link": "https://app.snapchat.com/dmd/memories?uid=u1&sid=s2&mid=EF56&ts=2&sig=y"}
+            ]}"#,
+        );
+
+        // Act
+        let index = SnapchatMemoryIndex::pair(
+            [
+                "mydata~1690000000/memories/2019-07-04_ab12-cd34-main.jpg",
+                "mydata~1690000000/memories/2019-07-04_ab12-cd34-overlay.png",
+                "mydata~1690000000/memories/2019-07-04_ef56-main.mp4",
+                "mydata~1690000000/memories/2019-07-04_unknown-main.jpg",
+            ],
+            &[history],
+        );
+
+        // Assert
+        assert_eq!(index.len(), 3);
+        let expected = DateTime::parse_from_rfc3339("2019-07-04T18:23:45Z").unwrap().to_utc();
+        for name in ["2019-07-04_ab12-cd34-main.jpg", "2019-07-04_ab12-cd34-overlay.png"] {
+            let memory = index.get(&format!("mydata~1690000000/memories/{}", name)).unwrap();
+            assert_eq!(memory.date_time, expected);
+        }
+        assert!(index.get("mydata~1690000000/memories/2019-07-04_unknown-main.jpg").is_none());
+    }
+
+    #[test]
+    fn test_pair_falls_back_to_the_only_memory_of_the_day() {
+        // Arrange
+        let history = json_entry(
+            "json/memories_history.json",
+            r#"{"Saved Media": [{"Date": "2020-01-15 08:00:01 UTC", "Media Type": "Image", "Download Link": ""}]}"#,
+        );
+
+        // Act
+        let index = SnapchatMemoryIndex::pair(
+            ["memories/2020-01-15_x1y2-main.jpg", "memories/2020-01-16_z3-main.jpg"],
+            &[history],
+        );
+
+        // Assert
+        assert_eq!(index.len(), 1);
+        assert_eq!(
+            index.get("memories/2020-01-15_x1y2-main.jpg").unwrap().date_time,
+            DateTime::parse_from_rfc3339("2020-01-15T08:00:01Z").unwrap().to_utc()
+        );
+    }
+
+    #[test]
+    fn test_pair_ignores_other_json_files() {
+        // Arrange
+        let other = json_entry(
+            "json/chat_history.json",
+            r#"{"Saved Media": [{"Date": "2020-01-15 08:00:01 UTC"}]}"#,
+        );
+
+        // Act
+        let index = SnapchatMemoryIndex::pair(["memories/2020-01-15_x1y2-main.jpg"], &[other]);
+
+        // Assert
+        assert!(index.is_empty());
+    }
+}
@@ -0,0 +1,348 @@
+// Split (spanned) ZIP sets as written by `zip -s`: `photos.z01`, `photos.z02`, ...,
+// `photos.zip`. The segments are joined into one single-disk archive, with the
+// central directory offsets rewritten to point into the joined file.
+use anyhow::{bail, Context, Result};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Signature some tools put at the start of the first segment
+const SPLIT_SIGNATURES: [u32; 2] = [0x0807_4b50, 0x3030_4b50];
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+const CENTRAL_HEADER_LEN: usize = 46;
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x0605_4b50;
+const END_OF_CENTRAL_DIRECTORY_LEN: usize = 22;
+const MAX_COMMENT_LEN: u64 = u16::MAX as u64;
+
+/// The segments of the split set `path` belongs to, in disk order (`.z01` ... `.zip`),
+/// or `None` when `path` is an ordinary archive
+pub fn split_parts(path: &Path) -> Option<Vec<PathBuf>> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    let last = if extension == "zip" {
+        path.to_path_buf()
+    } else if is_segment_extension(&extension) {
+        path.with_extension("zip")
+    } else {
+        return None;
+    };
+
+    let mut parts: Vec<PathBuf> = (1..100)
+        .map(|disk| last.with_extension(format!("z{:02}", disk)))
+        .take_while(|segment| segment.exists())
+        .collect();
+    if parts.is_empty() || !last.exists() {
+        return None;
+    }
+
+    parts.push(last);
+    Some(parts)
+}
+
+fn is_segment_extension(extension: &str) -> bool {
+    extension.len() == 3
+        && extension.starts_with('z')
+        && extension[1..].chars().all(|c| c.is_ascii_digit())
+}
+
+/// Writes the archive split over `parts` to `out` as a single-disk archive
+pub fn join_split_zip(parts: &[PathBuf], out: &mut File) -> Result<()> {
+    // Offset of each disk in the joined file; the first can start before 0 when
+    // its split signature is dropped
+    let mut disk_starts: Vec<i64> = Vec::with_capacity(parts.len());
+    let mut position: i64 = 0;
+
+    for (disk, part) in parts.iter().enumerate() {
+        let mut file = File::open(part)
+            .with_context(|| format!("Failed to open split archive segment: {}", part.display()))?;
+        let mut start = position;
+
+        if disk == 0 {
+            let mut signature = [0u8; 4];
+            let has_signature = file.read_exact(&mut signature).is_ok()
+                && SPLIT_SIGNATURES.contains(&u32::from_le_bytes(signature));
+            if has_signature {
+                start -= 4;
+            } else {
+                file.rewind()?;
+            }
+        }
+
+        disk_starts.push(start);
+        position += std::io::copy(&mut file, out)
+            .with_context(|| format!("Failed to copy split archive segment: {}", part.display()))?
+            as i64;
+    }
+
+    let end = read_end_of_central_directory(out, position as u64)?;
+    let central_directory_start = disk_start(&disk_starts, end.central_directory_disk)?
+        + end.central_directory_offset as i64;
+
+    let mut central_directory = vec![0u8; end.central_directory_size as usize];
+    out.seek(SeekFrom::Start(central_directory_start as u64))?;
+    out.read_exact(&mut central_directory)
+        .context("Failed to read central directory of split archive")?;
+    let entries = rewrite_central_directory(&mut central_directory, &disk_starts)?;
+
+    // Replace the split central directory and end record with single-disk ones
+    out.set_len(central_directory_start as u64)?;
+    out.seek(SeekFrom::Start(central_directory_start as u64))?;
+    out.write_all(&central_directory)?;
+
+    let entries = u16::try_from(entries).context("Too many entries in split archive")?;
+    let central_directory_offset = to_u32(central_directory_start)?;
+    out.write_all(&END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes())?;
+    out.write_all(&0u16.to_le_bytes())?; // this disk
+    out.write_all(&0u16.to_le_bytes())?; // disk with the central directory
+    out.write_all(&entries.to_le_bytes())?;
+    out.write_all(&entries.to_le_bytes())?;
+    out.write_all(&(central_directory.len() as u32).to_le_bytes())?;
+    out.write_all(&central_directory_offset.to_le_bytes())?;
+    out.write_all(&(end.comment.len() as u16).to_le_bytes())?;
+    out.write_all(&end.comment)?;
+
+    Ok(())
+}
+
+struct EndOfCentralDirectory {
+    central_directory_disk: u16,
+    central_directory_size: u32,
+    central_directory_offset: u32,
+    comment: Vec<u8>,
+}
+
+fn read_end_of_central_directory(file: &mut File, len: u64) -> Result<EndOfCentralDirectory> {
+    let tail_len = len.min(END_OF_CENTRAL_DIRECTORY_LEN as u64 + MAX_COMMENT_LEN);
+    let mut tail = vec![0u8; tail_len as usize];
+    file.seek(SeekFrom::Start(len - tail_len))?;
+    file.read_exact(&mut tail)?;
+
+    let record = (0..=tail.len().saturating_sub(END_OF_CENTRAL_DIRECTORY_LEN))
+        .rev()
+        .map(|start| &tail[start..])
+        .find(|record| {
+            record
+                .get(..4)
+                .is_some_and(|signature| read_u32(signature, 0) == END_OF_CENTRAL_DIRECTORY_SIGNATURE)
+        })
+        .context("Split archive has no end of central directory record")?;
+    let Some(fixed) = record.get(..END_OF_CENTRAL_DIRECTORY_LEN) else {
+        bail!("Split archive ends in a truncated end of central directory record");
+    };
+
+    let central_directory_disk = read_u16(fixed, 6);
+    let central_directory_size = read_u32(fixed, 12);
+    let central_directory_offset = read_u32(fixed, 16);
+    if central_directory_disk == u16::MAX
+        || central_directory_size == u32::MAX
+        || central_directory_offset == u32::MAX
+    {
+        bail!("ZIP64 split archives are not supported; join them with `zip -s 0` first");
+    }
+
+    let comment_len = read_u16(fixed, 20) as usize;
+    let comment = record
+        .get(END_OF_CENTRAL_DIRECTORY_LEN..END_OF_CENTRAL_DIRECTORY_LEN + comment_len)
+        .unwrap_or_default()
+        .to_vec();
+
+    Ok(EndOfCentralDirectory {
+        central_directory_disk,
+        central_directory_size,
+        central_directory_offset,
+        comment,
+    })
+}
+
+/// Points every central directory header at disk 0 of the joined file; returns the
+/// number of entries
+fn rewrite_central_directory(central_directory: &mut [u8], disk_starts: &[i64]) -> Result<usize> {
+    let mut entries = 0;
+    let mut position = 0;
+
+    while position + CENTRAL_HEADER_LEN <= central_directory.len() {
+        let header = &mut central_directory[position..];
+        if read_u32(header, 0) != CENTRAL_HEADER_SIGNATURE {
+            bail!("Corrupt central directory in split archive at offset {}", position);
+        }
+
+        let disk = read_u16(header, 34);
+        let local_header_offset = read_u32(header, 42);
+        if disk == u16::MAX || local_header_offset == u32::MAX {
+            bail!("ZIP64 split archives are not supported; join them with `zip -s 0` first");
+        }
+
+        let joined_offset = to_u32(disk_start(disk_starts, disk)? + local_header_offset as i64)?;
+        header[34..36].copy_from_slice(&0u16.to_le_bytes());
+        header[42..46].copy_from_slice(&joined_offset.to_le_bytes());
+
+        let variable_len =
+            read_u16(header, 28) as usize + read_u16(header, 30) as usize + read_u16(header, 32) as usize;
+        position += CENTRAL_HEADER_LEN + variable_len;
+        entries += 1;
+    }
+
+    Ok(entries)
+}
+
+fn disk_start(disk_starts: &[i64], disk: u16) -> Result<i64> {
+    disk_starts
+        .get(disk as usize)
+        .copied()
+        .with_context(|| format!("Split archive refers to missing segment {}", disk + 1))
+}
+
+fn to_u32(offset: i64) -> Result<u32> {
+    u32::try_from(offset).context("Joined split archive exceeds 4 GB, which needs ZIP64")
+}
+
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zip_image_reader::{FileZipImageReader, ZipImageReader};
+    use std::io::Cursor;
+    use zip::write::{FileOptions, ZipWriter};
+
+    fn create_zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options: FileOptions<()> =
+            FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        for (name, data) in files {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    /// Splits a single-disk archive into two segments at `split_at` (a position
+    /// before the central directory), like `zip -s` would
+    fn split_zip(archive: &[u8], split_at: usize) -> (Vec<u8>, Vec<u8>) {
+        let end = archive.len() - END_OF_CENTRAL_DIRECTORY_LEN;
+        let central_directory_start = read_u32(archive, end + 16) as usize;
+
+        let mut first = 0x0807_4b50u32.to_le_bytes().to_vec();
+        first.extend_from_slice(&archive[..split_at]);
+        let mut second = archive[split_at..].to_vec();
+
+        let mut position = central_directory_start - split_at;
+        while read_u32(&second, position) == CENTRAL_HEADER_SIGNATURE {
+            let offset = read_u32(&second, position + 42) as usize;
+            let (disk, disk_offset) = if offset < split_at {
+                (0u16, offset + 4)
+            } else {
+                (1u16, offset - split_at)
+            };
+            second[position + 34..position + 36].copy_from_slice(&disk.to_le_bytes());
+            second[position + 42..position + 46].copy_from_slice(&(disk_offset as u32).to_le_bytes());
+            let variable_len = read_u16(&second, position + 28) as usize
+                + read_u16(&second, position + 30) as usize
+                + read_u16(&second, position + 32) as usize;
+            position += CENTRAL_HEADER_LEN + variable_len;
+        }
+
+        let end = second.len() - END_OF_CENTRAL_DIRECTORY_LEN;
+        second[end + 4..end + 6].copy_from_slice(&1u16.to_le_bytes());
+        second[end + 6..end + 8].copy_from_slice(&1u16.to_le_bytes());
+        second[end + 16..end + 20]
+            .copy_from_slice(&((central_directory_start - split_at) as u32).to_le_bytes());
+        (first, second)
+    }
+
+    #[test]
+    fn test_split_parts_finds_segments_in_order() {
+        // Arrange
+        let dir = Path::new("/tmp/test_split_parts");
+        std::fs::create_dir_all(dir).unwrap();
+        for name in ["photos.z01", "photos.z02", "photos.zip", "other.zip"] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+
+        // Act
+        let from_last = split_parts(&dir.join("photos.zip"));
+        let from_first = split_parts(&dir.join("photos.z01"));
+        let ordinary = split_parts(&dir.join("other.zip"));
+
+        // Assert
+        let expected = vec![dir.join("photos.z01"), dir.join("photos.z02"), dir.join("photos.zip")];
+        assert_eq!(from_last, Some(expected.clone()));
+        assert_eq!(from_first, Some(expected));
+        assert_eq!(ordinary, None);
+
+        // Cleanup
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn test_join_split_zip_produces_readable_archive() {
+        // Arrange
+        let dir = Path::new("/tmp/test_join_split_zip");
+        std::fs::create_dir_all(dir).unwrap();
+        let archive = create_zip(&[
+            ("Takeout/photo1.jpg", b"first photo data"),
+            ("Takeout/photo2.jpg", b"second photo data"),
+        ]);
+        // Split in the middle of the first entry's data
+        let (first, second) = split_zip(&archive, 50);
+        let parts = vec![dir.join("photos.z01"), dir.join("photos.zip")];
+        std::fs::write(&parts[0], first).unwrap();
+        std::fs::write(&parts[1], second).unwrap();
+        let joined_path = dir.join("joined.zip");
+
+        // Act
+        let mut joined = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&joined_path)
+            .unwrap();
+        join_split_zip(&parts, &mut joined).unwrap();
+        drop(joined);
+        let entries = FileZipImageReader::new(joined_path.to_string_lossy().into_owned())
+            .read_entries()
+            .unwrap();
+
+        // Assert
+        assert_eq!(std::fs::read(&joined_path).unwrap(), archive);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].data, b"first photo data");
+        assert_eq!(entries[1].data, b"second photo data");
+
+        // Cleanup
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn test_join_split_zip_rejects_truncated_end_record() {
+        // Arrange
+        let dir = Path::new("/tmp/test_join_truncated_split_zip");
+        std::fs::create_dir_all(dir).unwrap();
+        let mut truncated = END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes().to_vec();
+        truncated.extend_from_slice(&[0, 0, 1, 0, 1, 0]);
+        let parts = vec![dir.join("photos.zip")];
+        std::fs::write(&parts[0], truncated).unwrap();
+        let mut joined = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(dir.join("joined.zip"))
+            .unwrap();
+
+        // Act
+        let result = join_split_zip(&parts, &mut joined);
+
+        // Assert
+        assert!(result.unwrap_err().to_string().contains("truncated"));
+
+        // Cleanup
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,140 @@
+// Temporary copies of inputs that can't be read in place: an archive piped on stdin
+// (`--input -`), since ZIP archives need random access to their central directory,
+// and split archives joined into one
+use crate::zip_image_reader::InputFormat;
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The `--input` path that reads the archive from stdin
+pub const STDIN_INPUT: &str = "-";
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const TAR_MAGIC_OFFSET: usize = 257;
+const TAR_MAGIC: &[u8] = b"ustar";
+
+static SPOOL_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A temporary file holding an input, removed again when dropped
+#[derive(Debug)]
+pub struct SpooledInput {
+    path: PathBuf,
+}
+
+impl SpooledInput {
+    /// Copies `source` to a new file in `dir`
+    pub fn spool(source: &mut dyn Read, dir: &Path) -> Result<Self> {
+        Self::create(dir, |file| {
+            std::io::copy(source, file).context("Failed to read input from stdin")?;
+            Ok(())
+        })
+    }
+
+    /// Creates a new file in `dir` (opened for reading and writing) and lets `write` fill it
+    pub fn create(dir: &Path, write: impl FnOnce(&mut File) -> Result<()>) -> Result<Self> {
+        let counter = SPOOL_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let path = dir.join(format!(
+            "organize-photo-zip-{}-{}",
+            std::process::id(),
+            counter
+        ));
+        // Constructed first so the file is removed again when writing fails
+        let spooled = Self { path };
+
+        let mut file = File::options()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&spooled.path)
+            .with_context(|| format!("Failed to create spool file: {}", spooled.path.display()))?;
+        write(&mut file)?;
+        file.flush()?;
+
+        Ok(spooled)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Archive format sniffed from the leading bytes, as a spooled stream has no extension
+    pub fn format(&self) -> Result<InputFormat> {
+        let mut head = Vec::new();
+        File::open(&self.path)?
+            .take((TAR_MAGIC_OFFSET + TAR_MAGIC.len()) as u64)
+            .read_to_end(&mut head)?;
+
+        Ok(if head.starts_with(GZIP_MAGIC) {
+            InputFormat::Tgz
+        } else if head.get(TAR_MAGIC_OFFSET..) == Some(TAR_MAGIC) {
+            InputFormat::Tar
+        } else {
+            InputFormat::Zip
+        })
+    }
+}
+
+impl Drop for SpooledInput {
+    fn drop(&mut self) {
+        std::fs::remove_file(&self.path).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_spooled_input_is_removed_when_dropped() {
+        // Arrange
+        let dir = Path::new("/tmp/test_spool_removed");
+        std::fs::create_dir_all(dir).unwrap();
+
+        // Act
+        let spooled = SpooledInput::spool(&mut Cursor::new(b"PK\x03\x04data".to_vec()), dir).unwrap();
+        let path = spooled.path().to_path_buf();
+        let content = std::fs::read(&path).unwrap();
+        drop(spooled);
+
+        // Assert
+        assert_eq!(content, b"PK\x03\x04data");
+        assert!(!path.exists());
+
+        // Cleanup
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn test_spooled_input_format_is_sniffed() {
+        // Arrange
+        let dir = Path::new("/tmp/test_spool_format");
+        std::fs::create_dir_all(dir).unwrap();
+        let mut tar = vec![0u8; 512];
+        tar[TAR_MAGIC_OFFSET..TAR_MAGIC_OFFSET + TAR_MAGIC.len()].copy_from_slice(TAR_MAGIC);
+
+        // Act
+        let zip_format = SpooledInput::spool(&mut Cursor::new(b"PK\x03\x04".to_vec()), dir)
+            .unwrap()
+            .format()
+            .unwrap();
+        let gzip_format = SpooledInput::spool(&mut Cursor::new(vec![0x1f, 0x8b, 8, 0]), dir)
+            .unwrap()
+            .format()
+            .unwrap();
+        let tar_format = SpooledInput::spool(&mut Cursor::new(tar), dir)
+            .unwrap()
+            .format()
+            .unwrap();
+
+        // Assert
+        assert_eq!(zip_format, InputFormat::Zip);
+        assert_eq!(gzip_format, InputFormat::Tgz);
+        assert_eq!(tar_format, InputFormat::Tar);
+
+        // Cleanup
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,305 @@
+use crate::events::{ConsoleObserver, OrganizeEvent, OrganizeObserver};
+use std::cell::Cell;
+use std::os::unix::net::UnixDatagram;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Duration;
+
+const JOURNAL_SOCKET: &str = "/run/systemd/journal/socket";
+const STATUS_UPDATE_INTERVAL: usize = 100;
+
+const PRIORITY_ERROR: u8 = 3;
+const PRIORITY_INFO: u8 = 6;
+
+/// Observer for running as a systemd service: reports readiness, status and
+/// watchdog pings via `sd_notify`, and logs every event as a structured journald
+/// entry. Falls back to console output when the journal socket is unavailable.
+///
+/// Readiness is reported on construction and watchdog pings come from a
+/// background thread, so slow listing or a single huge entry cannot trip the
+/// watchdog while the process is still alive.
+pub struct SystemdObserver {
+    notify_socket: Option<PathBuf>,
+    journal: Option<UnixDatagram>,
+    journal_socket: PathBuf,
+    /// Dropping this stops the watchdog thread
+    _watchdog_stop: Option<Sender<()>>,
+    total_files: Cell<usize>,
+    processed_files: Cell<usize>,
+    console: ConsoleObserver,
+}
+
+impl SystemdObserver {
+    /// Configures itself from `NOTIFY_SOCKET` and `WATCHDOG_USEC` as set by systemd
+    pub fn from_environment(dry_run: bool) -> Self {
+        let notify_socket = std::env::var_os("NOTIFY_SOCKET").map(PathBuf::from);
+        let watchdog_interval = std::env::var("WATCHDOG_USEC")
+            .ok()
+            .and_then(|usec| usec.parse::<u64>().ok())
+            .map(|usec| Duration::from_micros(usec / 2));
+
+        Self::new(notify_socket, Path::new(JOURNAL_SOCKET), watchdog_interval, dry_run)
+    }
+
+    pub fn new(
+        notify_socket: Option<PathBuf>,
+        journal_socket: &Path,
+        watchdog_interval: Option<Duration>,
+        dry_run: bool,
+    ) -> Self {
+        let journal = journal_socket
+            .exists()
+            .then(UnixDatagram::unbound)
+            .and_then(Result::ok);
+
+        sd_notify(notify_socket.as_deref(), "READY=1");
+        let watchdog_stop = notify_socket
+            .clone()
+            .zip(watchdog_interval.filter(|interval| !interval.is_zero()))
+            .map(|(socket_path, interval)| spawn_watchdog(socket_path, interval));
+
+        Self {
+            notify_socket,
+            journal,
+            journal_socket: journal_socket.to_path_buf(),
+            _watchdog_stop: watchdog_stop,
+            total_files: Cell::new(0),
+            processed_files: Cell::new(0),
+            console: ConsoleObserver::new(dry_run),
+        }
+    }
+
+    fn sd_notify(&self, state: &str) {
+        sd_notify(self.notify_socket.as_deref(), state);
+    }
+
+    fn log(&self, priority: u8, message: &str, fields: &[(&str, &str)], event: &OrganizeEvent) {
+        let Some(journal) = &self.journal else {
+            self.console.on_event(event);
+            return;
+        };
+
+        let mut datagram = Vec::new();
+        append_journal_field(&mut datagram, "MESSAGE", message);
+        append_journal_field(&mut datagram, "PRIORITY", &priority.to_string());
+        append_journal_field(&mut datagram, "SYSLOG_IDENTIFIER", "organize-photo-zip");
+        for (key, value) in fields {
+            append_journal_field(&mut datagram, key, value);
+        }
+
+        if journal.send_to(&datagram, &self.journal_socket).is_err() {
+            self.console.on_event(event);
+        }
+    }
+
+    fn count_processed_file(&self) {
+        let processed = self.processed_files.get() + 1;
+        self.processed_files.set(processed);
+
+        if processed.is_multiple_of(STATUS_UPDATE_INTERVAL) {
+            self.sd_notify(&format!(
+                "STATUS=Processed {} of {} files",
+                processed,
+                self.total_files.get()
+            ));
+        }
+    }
+}
+
+fn sd_notify(socket_path: Option<&Path>, state: &str) {
+    let Some(socket_path) = socket_path else {
+        return;
+    };
+    // Notification failures must never break the run itself
+    if let Ok(socket) = UnixDatagram::unbound() {
+        socket.send_to(state.as_bytes(), socket_path).ok();
+    }
+}
+
+/// Pings the watchdog every `interval` until the returned sender is dropped
+fn spawn_watchdog(socket_path: PathBuf, interval: Duration) -> Sender<()> {
+    let (stop, stopped) = mpsc::channel::<()>();
+    thread::spawn(move || {
+        while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+            sd_notify(Some(&socket_path), "WATCHDOG=1");
+        }
+    });
+    stop
+}
+
+/// Appends one field in journald's native protocol. Values containing newlines
+/// use the length-prefixed binary form.
+fn append_journal_field(datagram: &mut Vec<u8>, key: &str, value: &str) {
+    datagram.extend_from_slice(key.as_bytes());
+    if value.contains('\n') {
+        datagram.push(b'\n');
+        datagram.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        datagram.push(b'=');
+    }
+    datagram.extend_from_slice(value.as_bytes());
+    datagram.push(b'\n');
+}
+
+impl OrganizeObserver for SystemdObserver {
+    fn on_event(&self, event: &OrganizeEvent) {
+        match event {
+            OrganizeEvent::Started { total_files } => {
+                self.total_files.set(*total_files);
+                self.sd_notify(&format!("STATUS=Organizing {} files", total_files));
+            }
+            OrganizeEvent::Indexed { entries, albums, .. } => self.log(
+                PRIORITY_INFO,
+                &format!("Indexed {} entries ({} albums)", entries, albums),
+                &[],
+                event,
+            ),
+            OrganizeEvent::FileFiltered { entry } => {
+                self.count_processed_file();
+                self.log(
+                    PRIORITY_INFO,
+                    &format!("{}: filtered out", entry),
+                    &[("ENTRY_NAME", entry), ("ACTION", "filtered")],
+                    event,
+                );
+            }
+            OrganizeEvent::FileProcessed { entry, outcome } => {
+                self.count_processed_file();
+                let target = outcome.target_path.display().to_string();
+                self.log(
+                    PRIORITY_INFO,
+                    &format!("{}: {} {}", entry, outcome.action.label(), target),
+                    &[
+                        ("ENTRY_NAME", entry),
+                        ("ACTION", outcome.action.label()),
+                        ("TARGET_PATH", &target),
+                    ],
+                    event,
+                );
+            }
+            OrganizeEvent::FileFailed { entry, error } => {
+                self.count_processed_file();
+                self.log(
+                    PRIORITY_ERROR,
+                    &format!("{}: error - {}", entry, error),
+                    &[("ENTRY_NAME", entry), ("ACTION", "error"), ("ERROR", error)],
+                    event,
+                );
+            }
+            OrganizeEvent::Finished { result } => {
+                self.sd_notify(&format!(
+                    "STOPPING=1\nSTATUS=Done: {} organized, {} skipped, {} errors",
+                    result.organized_files,
+                    result.skipped_files,
+                    result.errors.len()
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::organizer::OrganizeResult;
+    use std::fs;
+
+    fn receive_string(socket: &UnixDatagram) -> String {
+        let mut buffer = [0u8; 4096];
+        let length = socket.recv(&mut buffer).unwrap();
+        String::from_utf8_lossy(&buffer[..length]).to_string()
+    }
+
+    #[test]
+    fn test_sd_notify_reports_ready_and_stopping() {
+        // Arrange
+        let notify_path = PathBuf::from("/tmp/test_systemd_notify.sock");
+        fs::remove_file(&notify_path).ok();
+        let notify_socket = UnixDatagram::bind(&notify_path).unwrap();
+        let observer = SystemdObserver::new(
+            Some(notify_path.clone()),
+            Path::new("/tmp/nonexistent_journal.sock"),
+            None,
+            false,
+        );
+
+        // Act
+        observer.on_event(&OrganizeEvent::Started { total_files: 5 });
+        observer.on_event(&OrganizeEvent::Finished {
+            result: OrganizeResult::default(),
+        });
+
+        // Assert
+        assert_eq!(receive_string(&notify_socket), "READY=1");
+        assert_eq!(receive_string(&notify_socket), "STATUS=Organizing 5 files");
+        assert!(receive_string(&notify_socket).starts_with("STOPPING=1\n"));
+
+        // Cleanup
+        fs::remove_file(&notify_path).ok();
+    }
+
+    #[test]
+    fn test_watchdog_is_pinged_without_events() {
+        // Arrange
+        let notify_path = PathBuf::from("/tmp/test_systemd_watchdog.sock");
+        fs::remove_file(&notify_path).ok();
+        let notify_socket = UnixDatagram::bind(&notify_path).unwrap();
+        notify_socket.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+
+        // Act
+        let observer = SystemdObserver::new(
+            Some(notify_path.clone()),
+            Path::new("/tmp/nonexistent_journal.sock"),
+            Some(Duration::from_millis(10)),
+            false,
+        );
+
+        // Assert
+        assert_eq!(receive_string(&notify_socket), "READY=1");
+        assert_eq!(receive_string(&notify_socket), "WATCHDOG=1");
+
+        // Cleanup
+        drop(observer);
+        fs::remove_file(&notify_path).ok();
+    }
+
+    #[test]
+    fn test_events_are_logged_as_structured_journal_entries() {
+        // Arrange
+        let journal_path = PathBuf::from("/tmp/test_systemd_journal.sock");
+        fs::remove_file(&journal_path).ok();
+        let journal_socket = UnixDatagram::bind(&journal_path).unwrap();
+        let observer = SystemdObserver::new(None, &journal_path, None, false);
+
+        // Act
+        observer.on_event(&OrganizeEvent::FileFailed {
+            entry: "a.jpg".to_string(),
+            error: "Failed to extract date".to_string(),
+        });
+
+        // Assert
+        let datagram = receive_string(&journal_socket);
+        assert!(datagram.contains("MESSAGE=a.jpg: error - Failed to extract date\n"));
+        assert!(datagram.contains("PRIORITY=3\n"));
+        assert!(datagram.contains("ENTRY_NAME=a.jpg\n"));
+
+        // Cleanup
+        fs::remove_file(&journal_path).ok();
+    }
+
+    #[test]
+    fn test_append_journal_field_uses_binary_form_for_multiline_values() {
+        // Arrange
+        let mut datagram = Vec::new();
+
+        // Act
+        append_journal_field(&mut datagram, "ERROR", "a\nb");
+
+        // Assert
+        let mut expected = b"ERROR\n".to_vec();
+        expected.extend_from_slice(&3u64.to_le_bytes());
+        expected.extend_from_slice(b"a\nb\n");
+        assert_eq!(datagram, expected);
+    }
+}
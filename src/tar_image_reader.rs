@@ -0,0 +1,387 @@
+use crate::zip_image_reader::{
+    is_selected, normalize_entry_name, read_content, sidecar_extensions, EntryInfo, EntryIter, ExtensionSet, LargeContent,
+    EntryMetadata, ZipEntry, ZipImageReader, DEFAULT_LARGE_ENTRY_BYTES,
+};
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+
+/// Reads the files of a tar archive: a gzip-compressed Takeout export (`.tgz` /
+/// `.tar.gz`, see `with_gzip`) or an uncompressed, repackaged one
+pub struct TarImageReader {
+    path: String,
+    gzip: bool,
+    large_entry_bytes: u64,
+}
+
+impl TarImageReader {
+    pub fn new(path: String) -> Self {
+        Self {
+            path,
+            gzip: false,
+            large_entry_bytes: DEFAULT_LARGE_ENTRY_BYTES,
+        }
+    }
+
+    /// Decompress the archive with gzip while reading it
+    pub fn with_gzip(mut self, gzip: bool) -> Self {
+        self.gzip = gzip;
+        self
+    }
+
+    fn settings(&self) -> TarSettings {
+        TarSettings {
+            path: PathBuf::from(&self.path),
+            gzip: self.gzip,
+            large_entry_bytes: self.large_entry_bytes,
+            extensions: None,
+            names: None,
+        }
+    }
+}
+
+impl ZipImageReader for TarImageReader {
+    fn entries(&self) -> Result<EntryIter<'_>> {
+        self.settings().entries()
+    }
+
+    fn entries_named(&self, names: HashSet<String>) -> Result<EntryIter<'_>> {
+        TarSettings {
+            names: Some(names),
+            ..self.settings()
+        }
+        .entries()
+    }
+
+    fn list_entries(&self) -> Result<Vec<EntryInfo>> {
+        self.settings().list_entries()
+    }
+
+    fn sidecars(&self) -> Result<Vec<ZipEntry>> {
+        self.settings().sidecars()
+    }
+}
+
+/// Everything needed to read a tar archive, owned so it can move to the reading thread
+#[derive(Clone)]
+struct TarSettings {
+    path: PathBuf,
+    gzip: bool,
+    large_entry_bytes: u64,
+    /// Only entries with these extensions are read (all when `None`)
+    extensions: Option<ExtensionSet>,
+    /// Only entries with these names are read (see `ZipImageReader::entries_named`)
+    names: Option<HashSet<String>>,
+}
+
+impl TarSettings {
+    /// A tar archive can only be read front to back and its entries borrow the
+    /// archive, so a thread reads them and hands them over one at a time
+    fn entries(&self) -> Result<EntryIter<'static>> {
+        let archive = open_tar(&self.path, self.gzip)?;
+        let settings = self.clone();
+        let (sender, receiver) = mpsc::sync_channel(1);
+
+        thread::spawn(move || {
+            if let Err(e) = settings.send_entries(archive, &sender) {
+                sender.send(Err(e)).ok();
+            }
+        });
+
+        Ok(Box::new(receiver.into_iter()))
+    }
+
+    fn send_entries(
+        &self,
+        mut archive: tar::Archive<Box<dyn Read + Send>>,
+        sender: &mpsc::SyncSender<Result<ZipEntry>>,
+    ) -> Result<()> {
+        let entries = archive.entries().context("Failed to read tar archive")?;
+
+        for (index, entry) in entries.enumerate() {
+            let mut entry = entry.with_context(|| format!("Failed to read entry at index {}", index))?;
+            let Some(name) = self
+                .file_name(&entry)
+                .filter(|name| is_selected(self.names.as_ref(), name))
+            else {
+                continue;
+            };
+
+            let size = entry.size();
+            let data = read_content(&mut entry, size, self.large_entry_bytes)
+                .with_context(|| format!("Failed to read data for file: {}", name))?;
+            let large = (size > self.large_entry_bytes).then(|| LargeContent::TarEntry {
+                archive: self.path.clone(),
+                gzip: self.gzip,
+                index,
+                size,
+            });
+
+            let modified_secs = entry.header().mtime().ok().map(|secs| secs as i64);
+            let entry = ZipEntry {
+                name,
+                data: data.into(),
+                large,
+                metadata: EntryMetadata::from_unix_time(modified_secs, size),
+            };
+
+            if sender.send(Ok(entry)).is_err() {
+                // The consumer stopped reading
+                return Ok(());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn list_entries(&self) -> Result<Vec<EntryInfo>> {
+        let mut archive = open_tar(&self.path, self.gzip)?;
+        let mut listing = Vec::new();
+
+        for (index, entry) in archive.entries().context("Failed to read tar archive")?.enumerate() {
+            let entry = entry.with_context(|| format!("Failed to read entry at index {}", index))?;
+            if let Some(name) = self.file_name(&entry) {
+                listing.push(EntryInfo {
+                    name,
+                    size: entry.size(),
+                });
+            }
+        }
+
+        Ok(listing)
+    }
+
+    /// Another pass over the archive that only keeps the JSON sidecars
+    fn sidecars(&self) -> Result<Vec<ZipEntry>> {
+        let settings = TarSettings {
+            extensions: Some(sidecar_extensions()),
+            large_entry_bytes: u64::MAX,
+            ..self.clone()
+        };
+        settings.entries()?.collect()
+    }
+
+    /// Normalized name of a regular file, `None` for everything else
+    fn file_name<R: Read>(&self, entry: &tar::Entry<R>) -> Option<String> {
+        if !entry.header().entry_type().is_file() {
+            return None;
+        }
+
+        let path = entry.path().ok()?;
+        let name = normalize_entry_name(&path.to_string_lossy());
+        let name = name.trim_start_matches("./").to_string();
+
+        self.extensions
+            .as_ref()
+            .is_none_or(|extensions| extensions.accepts(&name, false))
+            .then_some(name)
+    }
+}
+
+fn open_tar(path: &Path, gzip: bool) -> Result<tar::Archive<Box<dyn Read + Send>>> {
+    let file = File::open(path)
+        .with_context(|| format!("Failed to open tar file: {}", path.display()))?;
+    let reader: Box<dyn Read + Send> = if gzip {
+        Box::new(GzDecoder::new(BufReader::new(file)))
+    } else {
+        Box::new(BufReader::new(file))
+    };
+    Ok(tar::Archive::new(reader))
+}
+
+/// Copies entry `index` of a tar archive to `out`, reading the archive up to that entry
+pub(crate) fn copy_tar_entry(
+    path: &Path,
+    gzip: bool,
+    index: usize,
+    out: &mut dyn Write,
+) -> Result<u64> {
+    let mut archive = open_tar(path, gzip)?;
+    let mut entry = archive
+        .entries()
+        .context("Failed to read tar archive")?
+        .nth(index)
+        .with_context(|| format!("No entry at index {} in {}", index, path.display()))?
+        .with_context(|| format!("Failed to read entry at index {}", index))?;
+
+    Ok(std::io::copy(&mut entry, out)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_writer::StreamContent;
+    use crate::zip_image_reader::EntrySelectionReader;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    fn append_files<W: Write>(builder: &mut tar::Builder<W>, files: &[(&str, &[u8])]) -> Result<()> {
+        for (name, data) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(1_563_100_000);
+            header.set_cksum();
+            builder.append_data(&mut header, name, *data)?;
+        }
+        Ok(())
+    }
+
+    fn create_test_tar_gz(path: &str, files: &[(&str, &[u8])]) -> Result<()> {
+        let encoder = GzEncoder::new(File::create(path)?, Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        append_files(&mut builder, files)?;
+        builder.into_inner()?.finish()?;
+        Ok(())
+    }
+
+    fn create_test_tar(path: &str, files: &[(&str, &[u8])]) -> Result<()> {
+        let mut builder = tar::Builder::new(File::create(path)?);
+        append_files(&mut builder, files)?;
+        builder.finish()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_tar_gz_entries() {
+        // Arrange
+        let tar_path = "/tmp/test_read_entries.tgz";
+        create_test_tar_gz(
+            tar_path,
+            &[
+                ("Takeout/Google Photos/photo1.jpg", b"fake jpg data"),
+                ("Takeout/Google Photos/metadata.json", b"{}"),
+                ("Takeout/Google Photos/clip.mp4", b"fake mp4 data"),
+            ],
+        )
+        .unwrap();
+
+        let tar_reader = TarImageReader::new(tar_path.to_string()).with_gzip(true);
+
+        // Act
+        let reader = EntrySelectionReader::new(&tar_reader);
+        let entries = reader.read_entries().unwrap();
+        let listing = reader.list_entries().unwrap();
+        let photos_only = EntrySelectionReader::new(&tar_reader)
+            .with_videos(false)
+            .read_entries()
+            .unwrap();
+
+        // Assert
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["Takeout/Google Photos/photo1.jpg", "Takeout/Google Photos/clip.mp4"]
+        );
+        assert_eq!(entries[0].data, b"fake jpg data");
+        assert_eq!(listing[1].size, 13);
+        assert_eq!(photos_only.len(), 1);
+
+        // Cleanup
+        std::fs::remove_file(tar_path).ok();
+    }
+
+    #[test]
+    fn test_large_tar_entries_are_streamed() {
+        // Arrange
+        let tar_path = "/tmp/test_large_entries.tgz";
+        let large_data = vec![9u8; 5000];
+        create_test_tar_gz(tar_path, &[("small.jpg", b"small"), ("large.mp4", &large_data)]).unwrap();
+        let reader = TarImageReader {
+            large_entry_bytes: 100,
+            ..TarImageReader::new(tar_path.to_string()).with_gzip(true)
+        };
+
+        // Act
+        let entries = reader.read_entries().unwrap();
+
+        // Assert
+        assert!(entries[0].large.is_none());
+        let large = entries[1].large.as_ref().unwrap();
+        assert_eq!(large.size(), 5000);
+        let mut streamed = Vec::new();
+        large.copy_to(&mut streamed).unwrap();
+        assert_eq!(streamed, large_data);
+
+        // Cleanup
+        std::fs::remove_file(tar_path).ok();
+    }
+
+    #[test]
+    fn test_read_plain_tar_entries() {
+        // Arrange
+        let tar_path = "/tmp/test_read_entries.tar";
+        let large_data = vec![7u8; 3000];
+        create_test_tar(
+            tar_path,
+            &[
+                ("Takeout/Google Photos/photo1.jpg", b"fake jpg data"),
+                ("Takeout/Google Photos/notes.txt", b"notes"),
+                ("Takeout/Google Photos/clip.mp4", &large_data),
+            ],
+        )
+        .unwrap();
+        let reader = TarImageReader {
+            large_entry_bytes: 1000,
+            ..TarImageReader::new(tar_path.to_string())
+        };
+
+        // Act
+        let entries = EntrySelectionReader::new(&reader).read_entries().unwrap();
+
+        // Assert
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].name, "Takeout/Google Photos/photo1.jpg");
+        assert_eq!(entries[0].data, b"fake jpg data");
+        assert_eq!(entries[0].metadata, EntryMetadata::from_unix_time(Some(1_563_100_000), 13));
+        let mut streamed = Vec::new();
+        entries[1].large.as_ref().unwrap().copy_to(&mut streamed).unwrap();
+        assert_eq!(streamed, large_data);
+
+        // Cleanup
+        std::fs::remove_file(tar_path).ok();
+    }
+
+    #[test]
+    fn test_tar_reader_reads_sidecars_separately() {
+        // Arrange
+        let tar_path = "/tmp/test_tar_sidecars.tgz";
+        create_test_tar_gz(
+            tar_path,
+            &[
+                ("Takeout/Google Photos/photo1.jpg", b"fake jpg data"),
+                ("Takeout/Google Photos/photo1.jpg.json", b"{}"),
+            ],
+        )
+        .unwrap();
+        let tar_reader = TarImageReader::new(tar_path.to_string()).with_gzip(true);
+        let reader = EntrySelectionReader::new(&tar_reader);
+
+        // Act
+        let entries = reader.read_entries().unwrap();
+        let sidecars = reader.sidecars().unwrap();
+
+        // Assert
+        assert_eq!(entries.len(), 1);
+        assert_eq!(sidecars.len(), 1);
+        assert_eq!(sidecars[0].name, "Takeout/Google Photos/photo1.jpg.json");
+        assert_eq!(sidecars[0].data, b"{}");
+
+        // Cleanup
+        std::fs::remove_file(tar_path).ok();
+    }
+
+    #[test]
+    fn test_read_nonexistent_tar_returns_error() {
+        // Act
+        let result = TarImageReader::new("/tmp/nonexistent_file.tgz".to_string()).with_gzip(true).read_entries();
+
+        // Assert
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,471 @@
+// Dates of videos: QuickTime, MP4 and 3GPP from the boxes (atoms) of their ISO base media
+// file format, AVI from the chunks of its RIFF container
+
+use crate::exif::{start_of_day, Confidence, DateExtractionError, DateExtractor, DateTimeZone, EntryContext};
+use crate::isobmff::{boxes, find_box};
+use crate::xmp::{packet_date_time, xmp_packet};
+use anyhow::{Context, Result};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, Utc};
+use std::path::Path;
+
+/// Extensions of the videos read as ISO base media files
+const ISO_MEDIA_EXTENSIONS: &[&str] = &["mp4", "mov", "m4v", "3gp", "3g2"];
+const AVI_EXTENSION: &str = "avi";
+
+/// AVI chunks holding the recording date: the digitization time camera firmware writes in
+/// the header list, and the creation date of the `INFO` list
+const RIFF_DATE_CHUNKS: [&[u8]; 2] = [b"IDIT", b"ICRD"];
+
+/// Seconds from the QuickTime epoch (1904-01-01 UTC) to the Unix epoch
+const QUICKTIME_EPOCH_OFFSET_SECS: i64 = 2_082_844_800;
+
+/// Apple's metadata key for the local capture time, e.g. `2019-07-14T10:30:20+0200`
+const APPLE_CREATION_DATE_KEY: &[u8] = b"com.apple.quicktime.creationdate";
+
+/// Samsung's metadata key for the UTC offset of the phone's clock, e.g. `+0900`
+const SAMSUNG_UTC_OFFSET_KEY: &[u8] = b"com.samsung.android.utc_offset";
+
+/// The QuickTime user data and iTunes-style item with the recording date, `©day`
+const DAY_ITEM: &[u8; 4] = b"\xa9day";
+
+/// Type of the `uuid` box Adobe defined for XMP in MP4 and QuickTime files
+const XMP_UUID: [u8; 16] = [
+    0xBE, 0x7A, 0xCF, 0xCB, 0x97, 0xA9, 0x42, 0xE8, 0x9C, 0x71, 0x99, 0x94, 0x91, 0xE3, 0xAF, 0xAC,
+];
+
+/// Extracts dates from `.mp4`, `.mov`, `.m4v` and `.3gp` videos, most trusted first: the
+/// capture time iPhones write as `com.apple.quicktime.creationdate` (local to where the
+/// video was shot), the `©day` recording date of the user data, the date of XMP embedded
+/// in the file, and else the movie header's (`mvhd`) creation time. That one is UTC, taken
+/// to the offset in Samsung's `com.samsung.android.utc_offset` key when there is one, except
+/// on GoPro cameras, which write their local clock.
+/// Entries only read in part (large files) are dated when their `moov` box comes first, as
+/// it does for most phone videos. `.avi` videos are dated by their `IDIT` or `ICRD` chunk,
+/// in local time.
+#[derive(Default)]
+pub struct VideoDateExtractor;
+
+impl VideoDateExtractor {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// `©day` of `moov/udta`, as QuickTime text or as an iTunes-style item in
+    /// `moov/udta/meta/ilst`, e.g. `2019-07-14T10:30:20+0200`; a time in UTC (`Z`) is taken
+    /// to `time_zone`
+    fn recording_day(moov: &[u8], time_zone: DateTimeZone) -> Option<NaiveDateTime> {
+        let udta = find_box(moov, b"udta")?;
+        let value = match find_box(udta, DAY_ITEM) {
+            // Text length and language, then the text
+            Some(item) if find_box(item, b"data").is_none() => item.get(4..)?,
+            Some(item) => find_box(item, b"data")?.get(8..)?,
+            None => {
+                let meta = find_box(udta, b"meta")?.get(4..)?;
+                find_box(find_box(find_box(meta, b"ilst")?, DAY_ITEM)?, b"data")?.get(8..)?
+            }
+        };
+        let value = std::str::from_utf8(value).ok()?.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+        match value.strip_suffix('Z') {
+            Some(utc) => {
+                let time = NaiveDateTime::parse_from_str(utc, "%Y-%m-%dT%H:%M:%S").ok()?;
+                Some(time_zone.local_time_of(time.and_utc()))
+            }
+            None => parse_creation_date(value),
+        }
+    }
+}
+
+impl DateExtractor for VideoDateExtractor {
+    fn extract_date_time(&self, filename: &str, image_data: &[u8]) -> Result<NaiveDateTime> {
+        self.extract_date_time_in_context(filename, image_data, &EntryContext::default())
+    }
+
+    fn extract_date_time_in_context(
+        &self,
+        filename: &str,
+        image_data: &[u8],
+        context: &EntryContext,
+    ) -> Result<NaiveDateTime> {
+        let extension = Path::new(filename)
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if extension == AVI_EXTENSION {
+            return avi_date_time(image_data).context(DateExtractionError::NoDateTag("IDIT or ICRD date in AVI"));
+        }
+        if !ISO_MEDIA_EXTENSIONS.contains(&extension.as_str()) {
+            return Err(DateExtractionError::UnsupportedFormat("a QuickTime, MP4, 3GPP or AVI video").into());
+        }
+
+        let moov = find_box(image_data, b"moov").context(DateExtractionError::NoDateTag("moov box in video"))?;
+        if let Some(time) = metadata_value(moov, APPLE_CREATION_DATE_KEY)
+            .and_then(parse_creation_date)
+            .or_else(|| Self::recording_day(moov, context.time_zone))
+            .or_else(|| video_xmp_date_time(image_data, moov))
+        {
+            return Ok(time);
+        }
+        let creation_time =
+            movie_creation_time(moov).context(DateExtractionError::NoDateTag("creation time in video"))?;
+        if is_gopro(moov) {
+            return Ok(creation_time.naive_utc());
+        }
+        if let Some(offset) = metadata_value(moov, SAMSUNG_UTC_OFFSET_KEY).and_then(parse_utc_offset) {
+            return Ok(creation_time.with_timezone(&offset).naive_local());
+        }
+        Ok(context.time_zone.local_time_of(creation_time))
+    }
+
+    fn confidence(&self) -> Confidence {
+        Confidence::Exif
+    }
+}
+
+/// Creation time in the movie header; cameras without a clock write 0, which is left out
+fn movie_creation_time(moov: &[u8]) -> Option<DateTime<Utc>> {
+    let mvhd = find_box(moov, b"mvhd")?;
+    let secs = match mvhd.first()? {
+        0 => u32::from_be_bytes(mvhd.get(4..8)?.try_into().ok()?) as i64,
+        1 => i64::try_from(u64::from_be_bytes(mvhd.get(4..12)?.try_into().ok()?)).ok()?,
+        _ => return None,
+    };
+    if secs == 0 {
+        return None;
+    }
+    DateTime::from_timestamp(secs - QUICKTIME_EPOCH_OFFSET_SECS, 0)
+}
+
+/// Text value of a metadata key like `com.apple.quicktime.creationdate`, from the `keys`
+/// and `ilst` boxes of `moov/meta`
+fn metadata_value<'m>(moov: &'m [u8], wanted: &[u8]) -> Option<&'m str> {
+    let meta = find_box(moov, b"meta")?;
+    // QuickTime's meta box has no version and flags, the MP4 one does
+    let meta = if meta.get(4..8) == Some(b"hdlr") { meta } else { meta.get(4..)? };
+
+    let key_index = metadata_keys(find_box(meta, b"keys")?).position(|key| key == wanted)? as u32 + 1;
+    let item = boxes(find_box(meta, b"ilst")?)
+        .find(|(item_type, _)| *item_type == key_index.to_be_bytes())
+        .map(|(_, content)| content)?;
+    // The data box starts with a type indicator and a locale
+    let value = find_box(item, b"data")?.get(8..)?;
+    std::str::from_utf8(value).ok()
+}
+
+/// `+0900` or `-05:30`
+fn parse_utc_offset(value: &str) -> Option<FixedOffset> {
+    let value = value.trim_matches(|c: char| c == '\0' || c.is_whitespace()).replace(':', "");
+    let sign = match value.get(..1)? {
+        "+" => 1,
+        "-" => -1,
+        _ => return None,
+    };
+    let digits = value.get(1..)?;
+    if digits.len() != 4 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let (hours, minutes): (i32, i32) = (digits[..2].parse().ok()?, digits[2..].parse().ok()?);
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// `2019-07-14T10:30:20+0200` as the local time written, or a bare `2019-07-14`
+fn parse_creation_date(value: &str) -> Option<NaiveDateTime> {
+    DateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%z")
+        .map(|time| time.naive_local())
+        .ok()
+        .or_else(|| NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok().map(start_of_day))
+}
+
+/// The date of an XMP packet in `moov/udta/XMP_`, in Adobe's top-level `uuid` box or
+/// anywhere else in the movie box
+fn video_xmp_date_time(data: &[u8], moov: &[u8]) -> Option<NaiveDateTime> {
+    let in_uuid_box = boxes(data)
+        .filter(|(box_type, content)| *box_type == b"uuid" && content.get(..16) == Some(&XMP_UUID[..]))
+        .find_map(|(_, content)| xmp_packet(&content[16..]));
+    let in_udta = find_box(moov, b"udta").and_then(|udta| find_box(udta, b"XMP_"));
+    in_udta
+        .and_then(xmp_packet)
+        .or(in_uuid_box)
+        .or_else(|| xmp_packet(moov))
+        .and_then(packet_date_time)
+}
+
+/// GoPro cameras keep their firmware version in `moov/udta/FIRM`
+fn is_gopro(moov: &[u8]) -> bool {
+    find_box(moov, b"udta").is_some_and(|udta| find_box(udta, b"FIRM").is_some())
+}
+
+/// Key names of a `keys` box, in order
+fn metadata_keys(keys: &[u8]) -> impl Iterator<Item = &[u8]> {
+    // Version and flags, then the entry count
+    let mut rest = keys.get(8..).unwrap_or_default();
+    std::iter::from_fn(move || {
+        let size = u32::from_be_bytes(rest.get(..4)?.try_into().ok()?) as usize;
+        // Each key has its size, a namespace (`mdta`) and the name
+        let key = rest.get(8..size)?;
+        rest = &rest[size..];
+        Some(key)
+    })
+}
+
+/// First date chunk of an AVI, searched through its lists
+fn avi_date_time(data: &[u8]) -> Option<NaiveDateTime> {
+    if data.get(..4) != Some(b"RIFF") || data.get(8..12) != Some(b"AVI ") {
+        return None;
+    }
+    riff_date_time(data.get(12..)?)
+}
+
+fn riff_date_time(data: &[u8]) -> Option<NaiveDateTime> {
+    riff_chunks(data).find_map(|(chunk_id, content)| match chunk_id {
+        b"LIST" => riff_date_time(content.get(4..)?),
+        _ if RIFF_DATE_CHUNKS.contains(&chunk_id) => parse_riff_date_time(content),
+        _ => None,
+    })
+}
+
+/// Chunks directly inside `data`, as (id, content); sizes are little-endian and chunks are
+/// padded to an even length
+pub(crate) fn riff_chunks(data: &[u8]) -> impl Iterator<Item = (&[u8], &[u8])> {
+    let mut rest = data;
+    std::iter::from_fn(move || {
+        let chunk_id = rest.get(..4)?;
+        let size = u32::from_le_bytes(rest.get(4..8)?.try_into().ok()?) as usize;
+        let end = 8usize.checked_add(size)?.min(rest.len());
+        let content = &rest[8..end];
+        rest = &rest[(end + size % 2).min(rest.len())..];
+        Some((chunk_id, content))
+    })
+}
+
+/// `THU OCT 26 16:46:04 2006` (as `ctime` writes it), or a date starting with
+/// `YYYY-MM-DD`, `YYYY:MM:DD` or `YYYY/MM/DD`
+fn parse_riff_date_time(content: &[u8]) -> Option<NaiveDateTime> {
+    let text = String::from_utf8_lossy(content);
+    let text = text.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+
+    NaiveDateTime::parse_from_str(text, "%a %b %e %H:%M:%S %Y")
+        .ok()
+        .or_else(|| NaiveDate::parse_from_str(&text.get(..10)?.replace([':', '/'], "-"), "%Y-%m-%d").ok().map(start_of_day))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use chrono::Local;
+    use rstest::rstest;
+
+    pub(crate) fn mp4_box(box_type: &[u8; 4], content: &[u8]) -> Vec<u8> {
+        let mut data = ((content.len() + 8) as u32).to_be_bytes().to_vec();
+        data.extend_from_slice(box_type);
+        data.extend_from_slice(content);
+        data
+    }
+
+    /// Version 0 movie header created at `unix_secs`
+    fn mvhd(unix_secs: i64) -> Vec<u8> {
+        let mut mvhd = vec![0; 4];
+        mvhd.extend_from_slice(&((unix_secs + QUICKTIME_EPOCH_OFFSET_SECS) as u32).to_be_bytes());
+        mvhd.extend_from_slice(&[0; 92]);
+        mp4_box(b"mvhd", &mvhd)
+    }
+
+    /// A video with only a movie header created at `unix_secs`
+    pub(crate) fn mp4_created_at(unix_secs: i64) -> Vec<u8> {
+        let mut data = mp4_box(b"ftyp", b"isom\0\0\0\0");
+        data.extend(mp4_box(b"moov", &mvhd(unix_secs)));
+        data
+    }
+
+    fn mov_with_creation_date(creation_date: &str, movie_unix_secs: i64) -> Vec<u8> {
+        mov_with_key(APPLE_CREATION_DATE_KEY, creation_date, movie_unix_secs)
+    }
+
+    fn mov_with_key(key: &[u8], value: &str, movie_unix_secs: i64) -> Vec<u8> {
+        let mut keys = vec![0, 0, 0, 0, 0, 0, 0, 2];
+        for key in [&b"com.apple.quicktime.make"[..], key] {
+            keys.extend_from_slice(&((key.len() + 8) as u32).to_be_bytes());
+            keys.extend_from_slice(b"mdta");
+            keys.extend_from_slice(key);
+        }
+        let mut data_content = vec![0, 0, 0, 1, 0, 0, 0, 0];
+        data_content.extend_from_slice(value.as_bytes());
+        let mut make_content = vec![0, 0, 0, 1, 0, 0, 0, 0];
+        make_content.extend_from_slice(b"Apple");
+        let mut ilst = mp4_box(&1u32.to_be_bytes(), &mp4_box(b"data", &make_content));
+        ilst.extend(mp4_box(&2u32.to_be_bytes(), &mp4_box(b"data", &data_content)));
+        let mut meta = mp4_box(b"hdlr", &[0; 24]);
+        meta.extend(mp4_box(b"keys", &keys));
+        meta.extend(mp4_box(b"ilst", &ilst));
+
+        let mut moov = mvhd(movie_unix_secs);
+        moov.extend(mp4_box(b"meta", &meta));
+        let mut data = mp4_box(b"ftyp", b"qt  \0\0\0\0");
+        data.extend(mp4_box(b"moov", &moov));
+        data
+    }
+
+    #[test]
+    fn test_extract_date_from_movie_header() {
+        // Arrange
+        let extractor = VideoDateExtractor::new();
+        let noon_utc = DateTime::from_timestamp(1_563_105_600, 0).unwrap();
+        let data = mp4_created_at(noon_utc.timestamp());
+
+        // Act
+        let date = extractor.extract_date("VID_0001.mp4", &data);
+
+        // Assert
+        assert_eq!(date.unwrap(), noon_utc.with_timezone(&Local).date_naive());
+    }
+
+    #[test]
+    fn test_extract_date_prefers_apple_creation_date() {
+        // Arrange
+        let extractor = VideoDateExtractor::new();
+        // Shot late in the evening in California: the next day in UTC
+        let data = mov_with_creation_date("2019-07-14T23:30:20-0700", 1_563_172_220);
+
+        // Act
+        let date = extractor.extract_date("IMG_0001.MOV", &data);
+        let time = extractor.extract_date_time("IMG_0001.MOV", &data);
+
+        // Assert
+        assert_eq!(date.unwrap(), NaiveDate::from_ymd_opt(2019, 7, 14).unwrap());
+        assert_eq!(time.unwrap(), NaiveDate::from_ymd_opt(2019, 7, 14).unwrap().and_hms_opt(23, 30, 20).unwrap());
+    }
+
+    /// A video made at `movie_unix_secs` with `udta` holding these boxes
+    fn mp4_with_user_data(movie_unix_secs: i64, udta: &[u8]) -> Vec<u8> {
+        let mut moov = mvhd(movie_unix_secs);
+        moov.extend(mp4_box(b"udta", udta));
+        let mut data = mp4_box(b"ftyp", b"isom\0\0\0\0");
+        data.extend(mp4_box(b"moov", &moov));
+        data
+    }
+
+    #[rstest]
+    #[case(mp4_box(DAY_ITEM, b"\0\x19\x55\xc42019-07-14T23:30:20-0700"), "2019-07-14 23:30:20")]
+    #[case(mp4_box(DAY_ITEM, &mp4_box(b"data", b"\0\0\0\x01\0\0\0\x002019-07-14")), "2019-07-14 00:00:00")]
+    #[case(
+        mp4_box(b"meta", &[&[0, 0, 0, 0][..], &mp4_box(b"ilst", &mp4_box(DAY_ITEM, &mp4_box(b"data", b"\0\0\0\x01\0\0\0\x002019-07-14T23:30:20+0000")))].concat()),
+        "2019-07-14 23:30:20"
+    )]
+    #[case(mp4_box(b"XMP_", br#"<x:xmpmeta><rdf:Description xmpDM:shotDate="2019-07-14T23:30:20"/></x:xmpmeta>"#), "2019-07-14 23:30:20")]
+    fn test_extract_date_from_user_data(#[case] udta: Vec<u8>, #[case] expected: &str) {
+        // Arrange: the movie header says a day later
+        let data = mp4_with_user_data(1_563_200_000, &udta);
+
+        // Act
+        let time = VideoDateExtractor::new().extract_date_time("VID_0001.mp4", &data);
+
+        // Assert
+        assert_eq!(time.unwrap(), NaiveDateTime::parse_from_str(expected, "%Y-%m-%d %H:%M:%S").unwrap());
+    }
+
+    #[test]
+    fn test_extract_date_from_xmp_uuid_box() {
+        // Arrange
+        let mut data = mp4_created_at(-QUICKTIME_EPOCH_OFFSET_SECS);
+        let mut uuid = XMP_UUID.to_vec();
+        uuid.extend_from_slice(br#"<x:xmpmeta><rdf:Description xmp:CreateDate="2016-03-02T08:15:00+01:00"/></x:xmpmeta>"#);
+        data.extend(mp4_box(b"uuid", &uuid));
+
+        // Act
+        let time = VideoDateExtractor::new().extract_date_time("GOPR0001.MP4", &data);
+
+        // Assert
+        assert_eq!(time.unwrap(), NaiveDate::from_ymd_opt(2016, 3, 2).unwrap().and_hms_opt(8, 15, 0).unwrap());
+    }
+
+    #[test]
+    fn test_samsung_utc_offset_localizes_movie_header() {
+        // Arrange: 2019-07-14 23:30 UTC, shot in Tokyo
+        let data = mov_with_key(SAMSUNG_UTC_OFFSET_KEY, "+0900", 1_563_147_000);
+        let extractor = VideoDateExtractor::new();
+        let context = EntryContext::default().with_time_zone(DateTimeZone::Named(chrono_tz::America::Los_Angeles));
+
+        // Act
+        let time = extractor.extract_date_time_in_context("20190715_083000.mp4", &data, &context);
+
+        // Assert
+        assert_eq!(time.unwrap(), NaiveDate::from_ymd_opt(2019, 7, 15).unwrap().and_hms_opt(8, 30, 0).unwrap());
+    }
+
+    #[test]
+    fn test_gopro_movie_header_is_local_time() {
+        // Arrange
+        let noon = NaiveDate::from_ymd_opt(2016, 3, 2).unwrap().and_hms_opt(12, 0, 0).unwrap();
+        let data = mp4_with_user_data(noon.and_utc().timestamp(), &mp4_box(b"FIRM", b"HD5.02.02.60.00"));
+        let extractor = VideoDateExtractor::new();
+        let context = EntryContext::default().with_time_zone(DateTimeZone::Named(chrono_tz::Asia::Tokyo));
+
+        // Act
+        let time = extractor.extract_date_time_in_context("GOPR0001.MP4", &data, &context);
+
+        // Assert
+        assert_eq!(time.unwrap(), noon);
+    }
+
+    #[test]
+    fn test_extract_date_from_3gp_movie_header() {
+        // Arrange
+        let extractor = VideoDateExtractor::new();
+        let noon_utc = DateTime::from_timestamp(1_163_505_600, 0).unwrap();
+        let mut data = mp4_box(b"ftyp", b"3gp4\0\0\0\0");
+        data.extend_from_slice(&mp4_created_at(noon_utc.timestamp())[16..]);
+
+        // Act
+        let date = extractor.extract_date("VID_0001.3gp", &data);
+
+        // Assert
+        assert_eq!(date.unwrap(), noon_utc.with_timezone(&Local).date_naive());
+    }
+
+    fn riff_chunk(chunk_id: &[u8; 4], content: &[u8]) -> Vec<u8> {
+        let mut data = chunk_id.to_vec();
+        data.extend_from_slice(&(content.len() as u32).to_le_bytes());
+        data.extend_from_slice(content);
+        if content.len() % 2 == 1 {
+            data.push(0);
+        }
+        data
+    }
+
+    fn avi_with(list_type: &[u8; 4], chunk_id: &[u8; 4], date: &str) -> Vec<u8> {
+        let mut list = list_type.to_vec();
+        list.extend(riff_chunk(b"avih", &[0; 56]));
+        list.extend(riff_chunk(chunk_id, format!("{}\0", date).as_bytes()));
+        let mut riff = b"AVI ".to_vec();
+        riff.extend(riff_chunk(b"LIST", &list));
+        riff.extend(riff_chunk(b"JUNK", &[0; 3]));
+        riff_chunk(b"RIFF", &riff)
+    }
+
+    #[rstest]
+    #[case(b"hdrl", b"IDIT", "THU OCT 26 16:46:04 2006\n")]
+    #[case(b"hdrl", b"IDIT", "2006:10:26 16:46:04")]
+    #[case(b"INFO", b"ICRD", "2006-10-26")]
+    #[case(b"INFO", b"ICRD", "2006/10/26 16:46")]
+    fn test_extract_date_from_avi(#[case] list_type: &[u8; 4], #[case] chunk_id: &[u8; 4], #[case] date: &str) {
+        // Arrange
+        let data = avi_with(list_type, chunk_id, date);
+
+        // Act
+        let result = VideoDateExtractor::new().extract_date("MOV00001.AVI", &data);
+
+        // Assert
+        assert_eq!(result.unwrap(), NaiveDate::from_ymd_opt(2006, 10, 26).unwrap());
+    }
+
+    #[rstest]
+    #[case("IMG_0001.jpg", mp4_created_at(1_563_105_600))]
+    #[case("MOV00001.avi", avi_with(b"hdrl", b"ISFT", "2006-10-26"))]
+    #[case("MOV00001.avi", mp4_created_at(1_563_105_600))]
+    #[case("VID_0001.mp4", mp4_created_at(-QUICKTIME_EPOCH_OFFSET_SECS))]
+    #[case("VID_0001.mp4", mp4_box(b"ftyp", b"isom\0\0\0\0"))]
+    #[case("VID_0001.m4v", vec![0, 0, 0, 4, b'm'])]
+    fn test_extract_date_from_other_files_returns_error(#[case] filename: &str, #[case] data: Vec<u8>) {
+        // Act & Assert
+        assert!(VideoDateExtractor::new().extract_date(filename, &data).is_err());
+    }
+}
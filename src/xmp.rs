@@ -0,0 +1,165 @@
+// XMP packets embedded in images by Lightroom, Photoshop and other editors
+
+use crate::exif::{Confidence, DateExtractionError, DateExtractor};
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use once_cell::sync::Lazy;
+use regex::bytes::Regex;
+
+const XMP_PACKET_START: &[u8] = b"<x:xmpmeta";
+const XMP_PACKET_END: &[u8] = b"</x:xmpmeta>";
+
+/// XMP properties holding the capture date, most trusted first; `xmpDM:shotDate` is the one
+/// of video editors
+const DATE_PROPERTIES: &[&str] = &[
+    "photoshop:DateCreated",
+    "exif:DateTimeOriginal",
+    "xmpDM:shotDate",
+    "xmp:CreateDate",
+];
+
+/// One pattern per property in `DATE_PROPERTIES`, in the same order, for
+/// `property="2019-07-14T10:30:20+02:00"` or `<property>2019-07-14</property>`
+static DATE_PROPERTY_PATTERNS: Lazy<Vec<Regex>> = Lazy::new(|| {
+    DATE_PROPERTIES
+        .iter()
+        .map(|property| {
+            Regex::new(&format!(
+                r#"{}\s*(?:=\s*["']|>)\s*(\d{{4}})-(\d{{2}})-(\d{{2}})(?:T(\d{{2}}):(\d{{2}})(?::(\d{{2}}))?)?"#,
+                regex::escape(property)
+            ))
+            .expect("valid regex")
+        })
+        .collect()
+});
+
+/// Extracts dates from the XMP packet of an image, which editors keep even when they strip
+/// EXIF: `photoshop:DateCreated`, `exif:DateTimeOriginal`, `xmpDM:shotDate` or
+/// `xmp:CreateDate`, written as attributes or elements. The time is taken as written, in the time zone of the capture;
+/// a date without a time is dated at midnight.
+#[derive(Default)]
+pub struct XmpDateExtractor;
+
+impl XmpDateExtractor {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl DateExtractor for XmpDateExtractor {
+    fn extract_date_time(&self, _filename: &str, image_data: &[u8]) -> Result<NaiveDateTime> {
+        let packet = xmp_packet(image_data).context(DateExtractionError::NoDateTag("XMP packet"))?;
+        packet_date_time(packet).context(DateExtractionError::NoDateTag("creation date in XMP packet"))
+    }
+
+    fn confidence(&self) -> Confidence {
+        Confidence::Exif
+    }
+}
+
+/// The `x:xmpmeta` element, wherever the container put it
+pub(crate) fn xmp_packet(data: &[u8]) -> Option<&[u8]> {
+    let start = find(data, XMP_PACKET_START)?;
+    let length = find(&data[start..], XMP_PACKET_END)? + XMP_PACKET_END.len();
+    Some(&data[start..start + length])
+}
+
+/// The capture time in an XMP packet, by the most trusted property it has
+pub(crate) fn packet_date_time(packet: &[u8]) -> Option<NaiveDateTime> {
+    DATE_PROPERTY_PATTERNS
+        .iter()
+        .find_map(|pattern| property_date_time(packet, pattern))
+}
+
+fn find(data: &[u8], needle: &[u8]) -> Option<usize> {
+    data.windows(needle.len()).position(|window| window == needle)
+}
+
+/// Time of the property `pattern` (one of `DATE_PROPERTY_PATTERNS`) matches
+fn property_date_time(packet: &[u8], pattern: &Regex) -> Option<NaiveDateTime> {
+    let captures = pattern.captures(packet)?;
+    let number = |group: usize| -> Option<u32> {
+        std::str::from_utf8(captures.get(group)?.as_bytes()).ok()?.parse().ok()
+    };
+
+    let date = NaiveDate::from_ymd_opt(number(1)? as i32, number(2)?, number(3)?)?;
+    let time = match number(4) {
+        Some(hour) => NaiveTime::from_hms_opt(hour, number(5)?, number(6).unwrap_or(0))?,
+        None => NaiveTime::MIN,
+    };
+    Some(date.and_time(time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn jpeg_with_xmp(xmp: &str) -> Vec<u8> {
+        let mut app1 = b"http://ns.adobe.com/xap/1.0/\0".to_vec();
+        app1.extend_from_slice(b"<?xpacket begin='\xef\xbb\xbf' id='W5M0MpCehiHzreSzNTczkc9d'?>");
+        app1.extend_from_slice(xmp.as_bytes());
+        app1.extend_from_slice(b"<?xpacket end='w'?>");
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE1];
+        jpeg.extend_from_slice(&((app1.len() + 2) as u16).to_be_bytes());
+        jpeg.extend(app1);
+        jpeg.extend_from_slice(&[0xFF, 0xD9]);
+        jpeg
+    }
+
+    #[rstest]
+    #[case(r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF><rdf:Description xmp:CreateDate="2019-07-14T10:30:20.12+02:00" xmp:ModifyDate="2023-01-01T00:00:00"/></rdf:RDF></x:xmpmeta>"#)]
+    #[case(r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF><rdf:Description><photoshop:DateCreated>2019-07-14</photoshop:DateCreated></rdf:Description></rdf:RDF></x:xmpmeta>"#)]
+    #[case(r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:Description exif:DateTimeOriginal = '2019-07-14T10:30:20'/></x:xmpmeta>"#)]
+    fn test_extract_date_from_xmp(#[case] xmp: &str) {
+        // Arrange
+        let jpeg = jpeg_with_xmp(xmp);
+
+        // Act
+        let date = XmpDateExtractor::new().extract_date("edited.jpg", &jpeg);
+
+        // Assert
+        assert_eq!(date.unwrap(), NaiveDate::from_ymd_opt(2019, 7, 14).unwrap());
+    }
+
+    #[rstest]
+    #[case(r#"<x:xmpmeta><rdf:Description xmp:CreateDate="2019-07-14T10:30:20.12+02:00"/></x:xmpmeta>"#, NaiveTime::from_hms_opt(10, 30, 20).unwrap())]
+    #[case(r#"<x:xmpmeta><rdf:Description xmp:CreateDate="2019-07-14T10:30"/></x:xmpmeta>"#, NaiveTime::from_hms_opt(10, 30, 0).unwrap())]
+    #[case(r#"<x:xmpmeta><photoshop:DateCreated>2019-07-14</photoshop:DateCreated></x:xmpmeta>"#, NaiveTime::from_hms_opt(0, 0, 0).unwrap())]
+    fn test_extract_date_time_from_xmp(#[case] xmp: &str, #[case] expected: NaiveTime) {
+        // Arrange
+        let jpeg = jpeg_with_xmp(xmp);
+
+        // Act
+        let time = XmpDateExtractor::new().extract_date_time("edited.jpg", &jpeg);
+
+        // Assert
+        assert_eq!(
+            time.unwrap(),
+            NaiveDate::from_ymd_opt(2019, 7, 14).unwrap().and_time(expected)
+        );
+    }
+
+    #[test]
+    fn test_photoshop_date_wins_over_xmp_create_date() {
+        // Arrange
+        let jpeg = jpeg_with_xmp(
+            r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:Description xmp:CreateDate="2020-02-02T00:00:00" photoshop:DateCreated="2019-07-14"/></x:xmpmeta>"#,
+        );
+
+        // Act
+        let date = XmpDateExtractor::new().extract_date("scan.jpg", &jpeg);
+
+        // Assert
+        assert_eq!(date.unwrap(), NaiveDate::from_ymd_opt(2019, 7, 14).unwrap());
+    }
+
+    #[rstest]
+    #[case(jpeg_with_xmp(r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:Description xmp:ModifyDate="2023-01-01"/></x:xmpmeta>"#))]
+    #[case(b"xmp:CreateDate=\"2019-07-14\" outside any packet".to_vec())]
+    #[case(vec![0xFF, 0xD8, 0xFF, 0xD9])]
+    fn test_extract_date_without_xmp_date_returns_error(#[case] data: Vec<u8>) {
+        // Act & Assert
+        assert!(XmpDateExtractor::new().extract_date("image.jpg", &data).is_err());
+    }
+}
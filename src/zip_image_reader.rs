@@ -1,337 +1,2566 @@
+use crate::file_writer::StreamContent;
+use crate::http_zip_reader::{copy_http_zip_entry, is_http_url};
+use crate::sidecar::SIDECAR_EXTENSION;
+use crate::s3::{copy_s3_object, is_s3_url, S3Credentials, S3Location};
+use crate::tar_image_reader::copy_tar_entry;
 use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime};
+use memmap2::Mmap;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::cell::Cell;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::iter::Peekable;
 use std::fs::{self, File};
-use std::io::Read;
-use std::path::Path;
+use std::io::{Read, Seek, Write};
+use std::ops::{Deref, Range};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::UNIX_EPOCH;
+
+/// Entries larger than this are streamed to the output instead of loaded into memory
+pub const DEFAULT_LARGE_ENTRY_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Entries smaller than this are left out: Takeout's zero-byte placeholders
+pub const DEFAULT_MIN_ENTRY_BYTES: u64 = 1;
+
+/// Bytes at the start of a large entry kept in memory for date extraction and filters
+pub(crate) const LARGE_ENTRY_HEAD_BYTES: u64 = 1024 * 1024;
 
 /// Represents a file entry in a ZIP archive
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ZipEntry {
     pub name: String,
-    pub data: Vec<u8>,
+    /// Entry content, or only its first bytes when the entry is `large`
+    pub data: EntryData,
+    /// Where to stream the full content from, for entries too large to hold in memory
+    pub large: Option<LargeContent>,
+    pub metadata: EntryMetadata,
+}
+
+/// Content of an entry: read into memory, or a slice of a memory-mapped archive for
+/// entries stored without compression
+#[derive(Clone)]
+pub enum EntryData {
+    Owned(Vec<u8>),
+    Mapped { map: Arc<Mmap>, range: Range<usize> },
+}
+
+impl Default for EntryData {
+    fn default() -> Self {
+        EntryData::Owned(Vec::new())
+    }
+}
+
+impl From<Vec<u8>> for EntryData {
+    fn from(data: Vec<u8>) -> Self {
+        EntryData::Owned(data)
+    }
+}
+
+impl Deref for EntryData {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            EntryData::Owned(data) => data,
+            EntryData::Mapped { map, range } => &map[range.clone()],
+        }
+    }
+}
+
+impl AsRef<[u8]> for EntryData {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl<T: AsRef<[u8]> + ?Sized> PartialEq<T> for EntryData {
+    fn eq(&self, other: &T) -> bool {
+        **self == *other.as_ref()
+    }
+}
+
+impl fmt::Debug for EntryData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            EntryData::Owned(_) => "Owned",
+            EntryData::Mapped { .. } => "Mapped",
+        };
+        write!(f, "{}({} bytes)", kind, self.len())
+    }
+}
+
+/// What the archive (or file system) records about an entry besides its content
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EntryMetadata {
+    /// Last-modified time; ZIP archives store local time without a zone, tar archives
+    /// and directories are converted to local time
+    pub modified: Option<NaiveDateTime>,
+    /// Uncompressed size as recorded by the source
+    pub size: Option<u64>,
+    /// CRC-32 of the content, when the format stores one (ZIP)
+    pub crc32: Option<u32>,
+    /// Whether `modified` is the file's modification time on disk (directory input)
+    /// rather than a time recorded in an archive
+    pub from_file_system: bool,
+}
+
+impl EntryMetadata {
+    pub(crate) fn from_zip_file(zip_file: &zip::read::ZipFile) -> Self {
+        Self {
+            modified: zip_file.last_modified().and_then(zip_date_time),
+            size: Some(zip_file.size()),
+            crc32: Some(zip_file.crc32()),
+            from_file_system: false,
+        }
+    }
+
+    /// Metadata from a Unix timestamp, as stored by tar and file systems
+    pub(crate) fn from_unix_time(modified_secs: Option<i64>, size: u64) -> Self {
+        Self {
+            modified: modified_secs
+                .and_then(|secs| DateTime::from_timestamp(secs, 0))
+                .map(|time| time.with_timezone(&Local).naive_local()),
+            size: Some(size),
+            crc32: None,
+            from_file_system: false,
+        }
+    }
+}
+
+fn zip_date_time(time: zip::DateTime) -> Option<NaiveDateTime> {
+    NaiveDate::from_ymd_opt(time.year() as i32, time.month() as u32, time.day() as u32)?
+        .and_hms_opt(time.hour() as u32, time.minute() as u32, time.second() as u32)
+}
+
+impl ZipEntry {
+    /// Full (uncompressed) size of the entry
+    pub fn size(&self) -> u64 {
+        match &self.large {
+            Some(large) => large.size(),
+            None => self.data.len() as u64,
+        }
+    }
+}
+
+/// Location of the content of a large entry, read again when it is written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LargeContent {
+    /// Entry `index` of the ZIP archive at `archive`
+    ZipEntry {
+        archive: PathBuf,
+        index: usize,
+        size: u64,
+    },
+    File {
+        path: PathBuf,
+        size: u64,
+    },
+    /// Entry `index` of the (optionally gzip-compressed) tar archive at `archive`
+    TarEntry {
+        archive: PathBuf,
+        gzip: bool,
+        index: usize,
+        size: u64,
+    },
+    /// Entry `index` of the ZIP archive at the HTTP(S) `url`, signed for S3 when
+    /// credentials are given
+    HttpZipEntry {
+        url: String,
+        credentials: Option<S3Credentials>,
+        index: usize,
+        size: u64,
+    },
+    /// An S3 object, from an extracted Takeout stored in a bucket
+    S3Object {
+        url: String,
+        credentials: S3Credentials,
+        size: u64,
+    },
+}
+
+impl StreamContent for LargeContent {
+    fn size(&self) -> u64 {
+        match self {
+            LargeContent::ZipEntry { size, .. }
+            | LargeContent::File { size, .. }
+            | LargeContent::TarEntry { size, .. }
+            | LargeContent::HttpZipEntry { size, .. }
+            | LargeContent::S3Object { size, .. } => *size,
+        }
+    }
+
+    fn copy_to(&self, out: &mut dyn Write) -> Result<u64> {
+        match self {
+            LargeContent::ZipEntry { archive, index, .. } => {
+                let file = File::open(archive)
+                    .with_context(|| format!("Failed to open ZIP file: {}", archive.display()))?;
+                let mut zip_archive = zip::ZipArchive::new(file).context("Failed to read ZIP archive")?;
+                let mut zip_file = zip_archive
+                    .by_index(*index)
+                    .with_context(|| format!("Failed to read entry at index {}", index))?;
+                Ok(std::io::copy(&mut zip_file, out)?)
+            }
+            LargeContent::File { path, .. } => {
+                let mut file = File::open(path)
+                    .with_context(|| format!("Failed to open file: {}", path.display()))?;
+                Ok(std::io::copy(&mut file, out)?)
+            }
+            LargeContent::TarEntry {
+                archive,
+                gzip,
+                index,
+                ..
+            } => copy_tar_entry(archive, *gzip, *index, out),
+            LargeContent::HttpZipEntry {
+                url,
+                credentials,
+                index,
+                ..
+            } => copy_http_zip_entry(url, credentials.as_ref(), *index, out),
+            LargeContent::S3Object {
+                url, credentials, ..
+            } => copy_s3_object(url, credentials, out),
+        }
+    }
+}
+
+/// Reads the whole content, or only the head of a content larger than `threshold`
+pub(crate) fn read_content(reader: &mut dyn Read, size: u64, threshold: u64) -> std::io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    if size > threshold {
+        reader.take(LARGE_ENTRY_HEAD_BYTES).read_to_end(&mut data)?;
+    } else {
+        reader.read_to_end(&mut data)?;
+    }
+    Ok(data)
+}
+
+/// Converts Windows-style `\\` separators in entry names to `/`
+pub fn normalize_entry_name(name: &str) -> String {
+    name.replace('\\', "/")
+}
+
+/// Decodes a raw ZIP entry name. The zip crate already honours the UTF-8 flag and the
+/// Info-ZIP Unicode Path extra field, and decodes unflagged names as CP437, but many
+/// tools write UTF-8 names without setting the flag, which then come out garbled.
+/// Names that are valid UTF-8 are therefore taken as UTF-8. A flagged name that is not
+/// valid UTF-8 comes out of the crate with replacement characters; it is decoded as
+/// CP437 instead, which maps every byte. Everything else keeps the crate's decoding.
+fn decode_entry_name(raw_name: &[u8], decoded_name: &str) -> String {
+    match std::str::from_utf8(raw_name) {
+        Ok(utf8_name) => utf8_name.to_string(),
+        Err(_) if decoded_name.contains(char::REPLACEMENT_CHARACTER) => decode_cp437(raw_name),
+        Err(_) => decoded_name.to_string(),
+    }
+}
+
+/// Code page 437 characters for bytes 0x80 to 0xFF; lower bytes are ASCII
+const CP437_HIGH_HALF: &str = "ÇüéâäàåçêëèïîìÄÅÉæÆôöòûùÿÖÜ¢£¥₧ƒ\
+    áíóúñÑªº¿⌐¬½¼¡«»░▒▓│┤╡╢╖╕╣║╗╝╜╛┐\
+    └┴┬├─┼╞╟╚╔╩╦╠═╬╧╨╤╥╙╘╒╓╫╪┘┌█▄▌▐▀\
+    αßΓπΣσµτΦΘΩδ∞φε∩≡±≥≤⌠⌡÷≈°∙·√ⁿ²■\u{a0}";
+
+fn decode_cp437(raw_name: &[u8]) -> String {
+    raw_name
+        .iter()
+        .map(|&byte| match byte {
+            0x00..=0x7f => byte as char,
+            _ => CP437_HIGH_HALF
+                .chars()
+                .nth((byte - 0x80) as usize)
+                .unwrap_or(char::REPLACEMENT_CHARACTER),
+        })
+        .collect()
+}
+
+/// Folders and files that macOS and Windows leave in archives they create or re-zip
+const OS_JUNK_FOLDERS: &[&str] = &["__MACOSX", ".Spotlight-V100", ".Trashes", ".fseventsd"];
+const OS_JUNK_FILES: &[&str] = &[".ds_store", "thumbs.db", "ehthumbs.db", "desktop.ini"];
+/// Prefix of AppleDouble resource fork files (`._IMG_0001.JPG`)
+const APPLE_DOUBLE_PREFIX: &str = "._";
+
+/// Whether an entry is a resource fork, thumbnail cache or folder settings file
+/// rather than a real file from the export
+pub fn is_os_junk(name: &str) -> bool {
+    let mut components = name.split(['/', '\\']).filter(|component| !component.is_empty());
+    let Some(filename) = components.next_back() else {
+        return false;
+    };
+
+    components.any(|folder| OS_JUNK_FOLDERS.contains(&folder))
+        || filename.starts_with(APPLE_DOUBLE_PREFIX)
+        || OS_JUNK_FILES.contains(&filename.to_lowercase().as_str())
+}
+
+const DEFAULT_IMAGE_EXTENSIONS: &[&str] = &[
+    "jpg", "jpeg", "png", "heic", "heif", "gif", "webp", "bmp", "tiff", "tif", "avif", "jxl",
+    "mpo",
+    // Motion photos saved without their .jpg extension
+    "mp",
+    // Camera RAW
+    "nef", "cr2", "arw", "dng", "raf", "orf", "rw2",
+];
+const DEFAULT_VIDEO_EXTENSIONS: &[&str] = &["mp4", "mov", "m4v", "3gp", "avi", "mkv"];
+
+/// File extensions (lowercase, without dot) of the photos and videos passed on by
+/// `EntrySelectionReader`; extend or trim it from the config file or CLI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtensionSet {
+    images: BTreeSet<String>,
+    videos: BTreeSet<String>,
+}
+
+impl Default for ExtensionSet {
+    fn default() -> Self {
+        Self {
+            images: DEFAULT_IMAGE_EXTENSIONS.iter().map(|e| e.to_string()).collect(),
+            videos: DEFAULT_VIDEO_EXTENSIONS.iter().map(|e| e.to_string()).collect(),
+        }
+    }
+}
+
+impl ExtensionSet {
+    /// Accept exactly these extensions instead of the built-in list; the built-in video
+    /// extensions among them still count as videos
+    pub fn only<S: AsRef<str>>(extensions: &[S]) -> Self {
+        let (videos, images) = extensions
+            .iter()
+            .map(|e| Self::normalize(e.as_ref()))
+            .filter(|e| !e.is_empty())
+            .partition(|e| DEFAULT_VIDEO_EXTENSIONS.contains(&e.as_str()));
+        Self { images, videos }
+    }
+
+    /// Accept these extensions too (`"dng"`, `".DNG"` and `"Dng"` are the same)
+    pub fn with_added<S: AsRef<str>>(mut self, extensions: &[S]) -> Self {
+        for extension in extensions.iter().map(|e| Self::normalize(e.as_ref())) {
+            if !self.videos.contains(&extension) {
+                self.images.insert(extension);
+            }
+        }
+        self
+    }
+
+    /// Stop accepting these extensions
+    pub fn with_removed<S: AsRef<str>>(mut self, extensions: &[S]) -> Self {
+        for extension in extensions.iter().map(|e| Self::normalize(e.as_ref())) {
+            self.images.remove(&extension);
+            self.videos.remove(&extension);
+        }
+        self
+    }
+
+    pub fn contains(&self, filename: &str) -> bool {
+        Self::extension_of(filename)
+            .is_some_and(|e| self.images.contains(&e) || self.videos.contains(&e))
+    }
+
+    pub fn is_video(&self, filename: &str) -> bool {
+        Self::extension_of(filename).is_some_and(|e| self.videos.contains(&e))
+    }
+
+    /// Whether an entry is picked up: a known extension, not a video unless videos are
+    /// included, and no operating system artifact
+    pub(crate) fn accepts(&self, filename: &str, include_videos: bool) -> bool {
+        self.contains(filename)
+            && (include_videos || !self.is_video(filename))
+            && !is_os_junk(filename)
+    }
+
+    fn normalize(extension: &str) -> String {
+        extension.trim().trim_start_matches('.').to_lowercase()
+    }
+
+    fn extension_of(filename: &str) -> Option<String> {
+        Path::new(filename)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase())
+    }
+}
+
+/// `--include` / `--exclude` globs on entry paths (e.g. `*/Trash/*`); `*` also matches `/`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EntryPatterns {
+    include: Vec<glob::Pattern>,
+    exclude: Vec<glob::Pattern>,
+}
+
+impl EntryPatterns {
+    pub fn new(include: Vec<glob::Pattern>, exclude: Vec<glob::Pattern>) -> Self {
+        Self { include, exclude }
+    }
+
+    /// Whether an entry is read: it matches an include glob (or none are given) and no
+    /// exclude glob
+    pub fn matches(&self, name: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|pattern| pattern.matches(name)))
+            && !self.exclude.iter().any(|pattern| pattern.matches(name))
+    }
+}
+
+/// Name and uncompressed size of an entry, known without reading its data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryInfo {
+    pub name: String,
+    pub size: u64,
 }
 
+/// Lazily read entries, see `ZipImageReader::entries`
+pub type EntryIter<'a> = Box<dyn Iterator<Item = Result<ZipEntry>> + 'a>;
+
 /// Trait for reading images from ZIP archives
 pub trait ZipImageReader {
-    fn read_entries(&self) -> Result<Vec<ZipEntry>>;
+    /// Yields the accepted entries one at a time, so only the entry being processed
+    /// has to be in memory. Entries come sorted by name where the source allows reading
+    /// in any order (tar archives can only be read front to back), so repeated runs
+    /// process, and number colliding names, the same way.
+    fn entries(&self) -> Result<EntryIter<'_>>;
+
+    /// Names and sizes of the accepted entries. The default reads all entries;
+    /// readers override it with a listing that doesn't touch entry data.
+    fn list_entries(&self) -> Result<Vec<EntryInfo>> {
+        self.entries()?
+            .map(|entry| {
+                entry.map(|entry| EntryInfo {
+                    size: entry.size(),
+                    name: entry.name,
+                })
+            })
+            .collect()
+    }
+
+    /// Like `entries`, but only yields the entries named in `names`, so the others are
+    /// skipped without reading (or decompressing) their data. The default reads every
+    /// entry and drops the others afterwards.
+    fn entries_named(&self, names: HashSet<String>) -> Result<EntryIter<'_>> {
+        Ok(Box::new(self.entries()?.filter(move |entry| {
+            entry.as_ref().map_or(true, |entry| names.contains(&entry.name))
+        })))
+    }
+
+    /// Reads all accepted entries into memory
+    fn read_entries(&self) -> Result<Vec<ZipEntry>> {
+        self.entries()?.collect()
+    }
+
+    /// Takeout's `.json` sidecars, read completely (they are a few KB each). The default
+    /// reads the `.json` entries of `list_entries`.
+    fn sidecars(&self) -> Result<Vec<ZipEntry>> {
+        let extensions = sidecar_extensions();
+        let names = self
+            .list_entries()?
+            .into_iter()
+            .map(|info| info.name)
+            .filter(|name| extensions.accepts(name, false))
+            .collect();
+        self.entries_named(names)?.collect()
+    }
+
+    /// Number of entries left out for being too small, known once `list_entries` ran.
+    /// The default leaves none out.
+    fn tiny_entry_count(&self) -> usize {
+        0
+    }
+}
+
+/// Whether an entry is read, given the names passed to `entries_named` (all when `None`)
+pub(crate) fn is_selected(names: Option<&HashSet<String>>, name: &str) -> bool {
+    names.is_none_or(|names| names.contains(name))
+}
+
+/// Archive indices sorted by entry name, so entries are read in the same order however
+/// the archive happens to store them
+pub(crate) fn indices_by_name<R: Read + Seek>(archive: &zip::ZipArchive<R>) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..archive.len()).collect();
+    indices.sort_by_key(|&index| archive.name_for_index(index));
+    indices
 }
 
-/// Concrete implementation that reads images from ZIP files on disk
+/// Extensions a reader accepts in its sidecar pass
+pub(crate) fn sidecar_extensions() -> ExtensionSet {
+    ExtensionSet::only(&[SIDECAR_EXTENSION])
+}
+
+/// Concrete implementation that reads the files of a ZIP archive on disk
+#[derive(Clone)]
 pub struct FileZipImageReader {
     path: String,
+    large_entry_bytes: u64,
+    memory_map: bool,
+    threads: usize,
 }
 
 impl FileZipImageReader {
     pub fn new(path: String) -> Self {
-        Self { path }
+        Self {
+            path,
+            large_entry_bytes: DEFAULT_LARGE_ENTRY_BYTES,
+            memory_map: true,
+            threads: 1,
+        }
+    }
+
+    /// Decompress entries on this many threads; they still come out in archive order
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// When true (the default), entries stored without compression are handed out as
+    /// slices of the memory-mapped archive instead of being copied into memory. The
+    /// archive must not be modified while it is read.
+    pub fn with_memory_map(mut self, memory_map: bool) -> Self {
+        self.memory_map = memory_map;
+        self
+    }
+}
+
+impl ZipImageReader for FileZipImageReader {
+    fn entries(&self) -> Result<EntryIter<'_>> {
+        self.read_selected(None)
+    }
+
+    fn entries_named(&self, names: HashSet<String>) -> Result<EntryIter<'_>> {
+        self.read_selected(Some(names))
+    }
+
+    fn list_entries(&self) -> Result<Vec<EntryInfo>> {
+        let mut archive = self.open_archive()?;
+        let mut listing = Vec::new();
+
+        for i in indices_by_name(&archive) {
+            let zip_file = archive.by_index_raw(i)
+                .with_context(|| format!("Failed to read entry at index {}", i))?;
+
+            if let Some(name) = zip_file_name(&zip_file) {
+                listing.push(EntryInfo {
+                    name,
+                    size: zip_file.size(),
+                });
+            }
+        }
+
+        Ok(listing)
+    }
+}
+
+impl FileZipImageReader {
+    fn open_archive(&self) -> Result<zip::ZipArchive<File>> {
+        let file = File::open(&self.path)
+            .with_context(|| format!("Failed to open ZIP file: {}", self.path))?;
+
+        zip::ZipArchive::new(file)
+            .context("Failed to read ZIP archive")
+    }
+
+    fn read_selected(&self, names: Option<HashSet<String>>) -> Result<EntryIter<'_>> {
+        let mut archive = self.open_archive()?;
+        let map = if self.memory_map { self.map_archive()? } else { None };
+        let order = indices_by_name(&archive);
+        if self.threads > 1 && order.len() > 1 {
+            return self.read_in_parallel(order, map, names);
+        }
+
+        Ok(Box::new(order.into_iter().filter_map(move |i| {
+            self.read_zip_entry(&mut archive, map.as_ref(), i, names.as_ref()).transpose()
+        })))
+    }
+
+    /// Each worker opens the archive itself and reads every `threads`-th entry of
+    /// `order`, handing them over through its own bounded channel. Taking from the
+    /// channels in turn keeps that order and at most two entries per worker in memory.
+    fn read_in_parallel(
+        &self,
+        order: Vec<usize>,
+        map: Option<Arc<Mmap>>,
+        names: Option<HashSet<String>>,
+    ) -> Result<EntryIter<'static>> {
+        let count = order.len();
+        let threads = self.threads.min(count);
+        let order = Arc::new(order);
+        let names = Arc::new(names);
+        let mut receivers = Vec::with_capacity(threads);
+
+        for worker in 0..threads {
+            let reader = self.clone();
+            let mut archive = reader.open_archive()?;
+            let map = map.clone();
+            let order = Arc::clone(&order);
+            let names = Arc::clone(&names);
+            let (sender, receiver) = mpsc::sync_channel(1);
+
+            thread::spawn(move || {
+                for position in (worker..count).step_by(threads) {
+                    let index = order[position];
+                    let entry = reader.read_zip_entry(&mut archive, map.as_ref(), index, names.as_ref().as_ref());
+                    if sender.send(entry).is_err() {
+                        // The consumer stopped reading
+                        return;
+                    }
+                }
+            });
+            receivers.push(receiver);
+        }
+
+        Ok(Box::new((0..count).filter_map(move |position| {
+            match receivers[position % threads].recv() {
+                Ok(entry) => entry.transpose(),
+                Err(_) => Some(Err(anyhow::anyhow!("Failed to read entry at index {}", order[position]))),
+            }
+        })))
+    }
+
+    /// Maps the archive file into memory; `None` where mapping isn't possible (e.g. an
+    /// empty file), the entries are then read as usual
+    fn map_archive(&self) -> Result<Option<Arc<Mmap>>> {
+        let file = File::open(&self.path)
+            .with_context(|| format!("Failed to open ZIP file: {}", self.path))?;
+        // SAFETY: the input archive is only read during a run; a concurrent writer
+        // could change the mapped bytes, which is documented on `with_memory_map`
+        Ok(unsafe { Mmap::map(&file) }.ok().map(Arc::new))
+    }
+
+    /// The entry content as a slice of the mapped archive, for entries stored without
+    /// compression. Small entries are checked against their CRC here, since the zip
+    /// crate doesn't see these bytes; large ones are checked when they are streamed.
+    fn mapped_content(
+        &self,
+        zip_file: &zip::read::ZipFile,
+        map: &Arc<Mmap>,
+        name: &str,
+    ) -> Result<Option<EntryData>> {
+        if zip_file.compression() != zip::CompressionMethod::Stored || zip_file.encrypted() {
+            return Ok(None);
+        }
+
+        let size = zip_file.size();
+        let head = if size > self.large_entry_bytes { size.min(LARGE_ENTRY_HEAD_BYTES) } else { size };
+        let start = usize::try_from(zip_file.data_start())?;
+        let end = start
+            .checked_add(usize::try_from(head)?)
+            .filter(|end| *end <= map.len())
+            .with_context(|| format!("Entry data lies outside the archive: {}", name))?;
+        if size <= self.large_entry_bytes && crc32fast::hash(&map[start..end]) != zip_file.crc32() {
+            anyhow::bail!("Invalid checksum for file: {}", name);
+        }
+
+        Ok(Some(EntryData::Mapped {
+            map: Arc::clone(map),
+            range: start..end,
+        }))
+    }
+
+    fn read_zip_entry(
+        &self,
+        archive: &mut zip::ZipArchive<File>,
+        map: Option<&Arc<Mmap>>,
+        index: usize,
+        names: Option<&HashSet<String>>,
+    ) -> Result<Option<ZipEntry>> {
+        let mut zip_file = archive.by_index(index)
+            .with_context(|| format!("Failed to read entry at index {}", index))?;
+
+        let Some(name) = zip_file_name(&zip_file).filter(|name| is_selected(names, name)) else {
+            return Ok(None);
+        };
+
+        let size = zip_file.size();
+        let mapped = match map {
+            Some(map) => self.mapped_content(&zip_file, map, &name)?,
+            None => None,
+        };
+        let data = match mapped {
+            Some(mapped) => mapped,
+            None => read_content(&mut zip_file, size, self.large_entry_bytes)
+                .with_context(|| format!("Failed to read data for file: {}", name))?
+                .into(),
+        };
+        let large = (size > self.large_entry_bytes).then(|| LargeContent::ZipEntry {
+            archive: PathBuf::from(&self.path),
+            index,
+            size,
+        });
+        let metadata = EntryMetadata::from_zip_file(&zip_file);
+
+        Ok(Some(ZipEntry {
+            name,
+            data,
+            large,
+            metadata,
+        }))
+    }
+}
+
+/// Normalized name of a ZIP entry, `None` for directories
+pub(crate) fn zip_file_name(zip_file: &zip::read::ZipFile) -> Option<String> {
+    // Skip directories
+    if zip_file.is_dir() {
+        return None;
+    }
+
+    Some(normalize_entry_name(&decode_entry_name(
+        zip_file.name_raw(),
+        zip_file.name(),
+    )))
+}
+
+/// Kind of archive (or directory) an input is read as
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum InputFormat {
+    /// Detect from the path: directories (and `s3://bucket/prefix/`), `.tar`, `.tgz` /
+    /// `.tar.gz`, anything else as ZIP
+    #[default]
+    Auto,
+    Zip,
+    Tar,
+    Tgz,
+    Dir,
+}
+
+impl InputFormat {
+    /// Resolves `Auto` to the format the path looks like; explicit formats are kept
+    pub fn resolve(self, path: &str) -> InputFormat {
+        if self != InputFormat::Auto {
+            return self;
+        }
+
+        let lower = path.to_lowercase();
+        let is_s3_prefix = is_s3_url(path) && S3Location::parse(path).is_ok_and(|l| l.is_prefix());
+        if Path::new(path).is_dir() || is_s3_prefix {
+            InputFormat::Dir
+        } else if lower.ends_with(".tgz") || lower.ends_with(".tar.gz") {
+            InputFormat::Tgz
+        } else if lower.ends_with(".tar") {
+            InputFormat::Tar
+        } else {
+            InputFormat::Zip
+        }
+    }
+}
+
+/// One `--input`: an archive or directory, optionally labelled with the account it
+/// belongs to (`anna=takeout-a.zip`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputSpec {
+    pub label: Option<String>,
+    pub path: String,
+}
+
+impl FromStr for InputSpec {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let labelled = value
+            .split_once('=')
+            .filter(|(label, _)| !Path::new(value).exists() && !label.contains(['/', '\\']));
+
+        match labelled {
+            Some((label, path)) if label.is_empty() || path.is_empty() => {
+                Err(format!("expected LABEL=PATH or PATH, got '{}'", value))
+            }
+            Some((label, path)) => Ok(Self {
+                label: Some(label.to_string()),
+                path: path.to_string(),
+            }),
+            None => Ok(Self {
+                label: None,
+                path: value.to_string(),
+            }),
+        }
+    }
+}
+
+impl InputSpec {
+    /// Expands a glob path (`takeout-*.zip`) into one input per match, sorted by path,
+    /// so all parts of a split Takeout can be given at once. Paths without glob
+    /// characters, or that exist as given, are kept as they are. A folder holding the
+    /// downloaded parts of an export (`takeout-20240101T000000Z-001.zip`, ...) expands
+    /// into those parts.
+    pub fn expand(self) -> Result<Vec<InputSpec>> {
+        if Path::new(&self.path).is_dir() {
+            let parts = takeout_parts(Path::new(&self.path))?;
+            if !parts.is_empty() {
+                return Ok(self.with_paths(parts));
+            }
+        }
+
+        if Path::new(&self.path).exists()
+            || is_http_url(&self.path)
+            || is_s3_url(&self.path)
+            || !self.path.contains(['*', '?', '['])
+        {
+            return Ok(vec![self]);
+        }
+
+        let mut paths: Vec<String> = glob::glob(&self.path)
+            .with_context(|| format!("Invalid input pattern: {}", self.path))?
+            .flatten()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect();
+        paths.sort();
+
+        if paths.is_empty() {
+            anyhow::bail!("No input matches: {}", self.path);
+        }
+
+        Ok(self.with_paths(paths))
+    }
+
+    fn with_paths(&self, paths: Vec<String>) -> Vec<InputSpec> {
+        paths
+            .into_iter()
+            .map(|path| InputSpec {
+                label: self.label.clone(),
+                path,
+            })
+            .collect()
+    }
+}
+
+/// File name of one part of a Takeout export (`takeout-20240101T120000Z-001.zip`)
+static TAKEOUT_PART: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^takeout-\d{8}T\d{6}Z-\d+\.(zip|tgz|tar\.gz)$").expect("valid regex"));
+
+/// Paths of the Takeout part archives directly inside `dir`, sorted by part number
+fn takeout_parts(dir: &Path) -> Result<Vec<String>> {
+    let mut parts: Vec<String> = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read input folder: {}", dir.display()))?
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_file()))
+        .filter(|entry| TAKEOUT_PART.is_match(&entry.file_name().to_string_lossy()))
+        .map(|entry| entry.path().to_string_lossy().into_owned())
+        .collect();
+    // Part numbers are zero-padded, so path order is part order
+    parts.sort();
+    Ok(parts)
+}
+
+/// Reads several inputs as one run. Entries of a labelled input are namespaced as
+/// `<label>/<entry name>`, so the label travels with the entry (owner folders,
+/// provenance) and entries of different accounts never share a name. An entry name
+/// that occurs in several inputs (a re-export repeating files across parts) is read
+/// once, from the copy with the richest metadata.
+#[derive(Default)]
+pub struct MultiInputReader<'a> {
+    inputs: Vec<(Option<String>, &'a dyn ZipImageReader)>,
+}
+
+impl<'a> MultiInputReader<'a> {
+    pub fn new() -> Self {
+        Self { inputs: Vec::new() }
+    }
+
+    pub fn with_input(mut self, label: Option<String>, reader: &'a dyn ZipImageReader) -> Self {
+        self.inputs.push((label, reader));
+        self
+    }
+
+    fn list_all_copies(&self) -> Result<Vec<EntryInfo>> {
+        let mut listing = Vec::new();
+
+        for (label, reader) in &self.inputs {
+            listing.extend(reader.list_entries()?.into_iter().map(|info| EntryInfo {
+                name: labelled_name(label.as_deref(), &info.name),
+                ..info
+            }));
+        }
+
+        Ok(listing)
     }
 
-    fn is_image_file(filename: &str) -> bool {
-        let lower = filename.to_lowercase();
-        lower.ends_with(".jpg")
-            || lower.ends_with(".jpeg")
-            || lower.ends_with(".png")
-            || lower.ends_with(".heic")
-            || lower.ends_with(".heif")
-            || lower.ends_with(".gif")
-            || lower.ends_with(".webp")
-            || lower.ends_with(".bmp")
-            || lower.ends_with(".tiff")
-            || lower.ends_with(".tif")
-            || lower.ends_with(".mp4")
-    }
-}
+    /// Wraps `entries` so each name found in more than one input is yielded once
+    fn deduplicated(&self, entries: EntryIter<'a>) -> Result<EntryIter<'a>> {
+        if self.inputs.len() < 2 {
+            return Ok(entries);
+        }
+
+        let mut copies: HashMap<String, usize> = HashMap::new();
+        for info in self.list_all_copies()? {
+            *copies.entry(info.name).or_default() += 1;
+        }
+        copies.retain(|_, count| *count > 1);
+        if copies.is_empty() {
+            return Ok(entries);
+        }
+
+        Ok(Box::new(DeduplicatedEntries {
+            entries,
+            remaining_copies: copies,
+            best_copies: BTreeMap::new(),
+        }))
+    }
+}
+
+impl ZipImageReader for MultiInputReader<'_> {
+    fn entries(&self) -> Result<EntryIter<'_>> {
+        let mut iterators = Vec::new();
+
+        for (label, reader) in &self.inputs {
+            let label = label.clone();
+            let iterator: EntryIter<'_> = Box::new(reader.entries()?.map(move |entry| {
+                entry.map(|entry| ZipEntry {
+                    name: labelled_name(label.as_deref(), &entry.name),
+                    ..entry
+                })
+            }));
+            iterators.push(iterator.peekable());
+        }
+
+        self.deduplicated(Box::new(MergedEntries { inputs: iterators }))
+    }
+
+    fn entries_named(&self, names: HashSet<String>) -> Result<EntryIter<'_>> {
+        let mut iterators = Vec::new();
+
+        for (label, reader) in &self.inputs {
+            let input_names = names
+                .iter()
+                .filter_map(|name| match label {
+                    Some(label) => name.strip_prefix(label.as_str())?.strip_prefix('/'),
+                    None => Some(name.as_str()),
+                })
+                .map(str::to_string)
+                .collect();
+            let label = label.clone();
+            let iterator: EntryIter<'_> = Box::new(reader.entries_named(input_names)?.map(move |entry| {
+                entry.map(|entry| ZipEntry {
+                    name: labelled_name(label.as_deref(), &entry.name),
+                    ..entry
+                })
+            }));
+            iterators.push(iterator.peekable());
+        }
+
+        self.deduplicated(Box::new(MergedEntries { inputs: iterators }))
+    }
+
+    fn list_entries(&self) -> Result<Vec<EntryInfo>> {
+        let mut listing = self.list_all_copies()?;
+        listing.sort_by(|a, b| a.name.cmp(&b.name));
+        let mut seen = HashSet::new();
+        listing.retain(|info| seen.insert(info.name.clone()));
+        Ok(listing)
+    }
+
+    fn sidecars(&self) -> Result<Vec<ZipEntry>> {
+        let mut sidecars = Vec::new();
+
+        for (label, reader) in &self.inputs {
+            sidecars.extend(reader.sidecars()?.into_iter().map(|entry| ZipEntry {
+                name: labelled_name(label.as_deref(), &entry.name),
+                ..entry
+            }));
+        }
+
+        Ok(sidecars)
+    }
+}
+
+/// Leaves out entries smaller than `min_size` bytes (zero-byte placeholders, stray
+/// thumbnails) of another reader; sidecars are passed on whatever their size
+pub struct MinSizeReader<'a> {
+    inner: &'a dyn ZipImageReader,
+    min_size: u64,
+    tiny_entries: Cell<usize>,
+}
+
+impl<'a> MinSizeReader<'a> {
+    pub fn new(inner: &'a dyn ZipImageReader) -> Self {
+        Self {
+            inner,
+            min_size: DEFAULT_MIN_ENTRY_BYTES,
+            tiny_entries: Cell::new(0),
+        }
+    }
+
+    pub fn with_min_size(mut self, min_size: u64) -> Self {
+        self.min_size = min_size;
+        self
+    }
+
+    fn keep_large_enough<'i>(&self, entries: EntryIter<'i>) -> EntryIter<'i> {
+        let min_size = self.min_size;
+        Box::new(entries.filter(move |entry| entry.as_ref().map_or(true, |entry| entry.size() >= min_size)))
+    }
+}
+
+impl ZipImageReader for MinSizeReader<'_> {
+    fn entries(&self) -> Result<EntryIter<'_>> {
+        Ok(self.keep_large_enough(self.inner.entries()?))
+    }
+
+    fn entries_named(&self, names: HashSet<String>) -> Result<EntryIter<'_>> {
+        Ok(self.keep_large_enough(self.inner.entries_named(names)?))
+    }
+
+    fn list_entries(&self) -> Result<Vec<EntryInfo>> {
+        let (listing, tiny): (Vec<EntryInfo>, Vec<EntryInfo>) = self
+            .inner
+            .list_entries()?
+            .into_iter()
+            .partition(|info| info.size >= self.min_size);
+        self.tiny_entries.set(tiny.len());
+        Ok(listing)
+    }
+
+    fn sidecars(&self) -> Result<Vec<ZipEntry>> {
+        self.inner.sidecars()
+    }
+
+    fn tiny_entry_count(&self) -> usize {
+        self.tiny_entries.get()
+    }
+}
+
+/// Passes on only the photos and videos of another reader (by `ExtensionSet`, leaving
+/// out operating system artifacts) that match the `--include` / `--exclude` globs. The
+/// other entries are left out of the listing, so their data is never read. The globs
+/// apply to the sidecars as well.
+pub struct EntrySelectionReader<'a> {
+    inner: &'a dyn ZipImageReader,
+    include_videos: bool,
+    extensions: ExtensionSet,
+    patterns: EntryPatterns,
+}
+
+impl<'a> EntrySelectionReader<'a> {
+    pub fn new(inner: &'a dyn ZipImageReader) -> Self {
+        Self {
+            inner,
+            include_videos: true,
+            extensions: ExtensionSet::default(),
+            patterns: EntryPatterns::default(),
+        }
+    }
+
+    /// When false, only still images are passed on and videos are left out
+    pub fn with_videos(mut self, include_videos: bool) -> Self {
+        self.include_videos = include_videos;
+        self
+    }
+
+    pub fn with_extensions(mut self, extensions: ExtensionSet) -> Self {
+        self.extensions = extensions;
+        self
+    }
+
+    pub fn with_patterns(mut self, patterns: EntryPatterns) -> Self {
+        self.patterns = patterns;
+        self
+    }
+
+    fn selects(&self, name: &str) -> bool {
+        self.extensions.accepts(name, self.include_videos) && self.patterns.matches(name)
+    }
+}
+
+impl ZipImageReader for EntrySelectionReader<'_> {
+    fn entries(&self) -> Result<EntryIter<'_>> {
+        let names = self.list_entries()?.into_iter().map(|info| info.name).collect();
+        self.inner.entries_named(names)
+    }
+
+    fn entries_named(&self, names: HashSet<String>) -> Result<EntryIter<'_>> {
+        self.inner
+            .entries_named(names.into_iter().filter(|name| self.selects(name)).collect())
+    }
+
+    fn list_entries(&self) -> Result<Vec<EntryInfo>> {
+        let mut listing = self.inner.list_entries()?;
+        listing.retain(|info| self.selects(&info.name));
+        Ok(listing)
+    }
+
+    fn sidecars(&self) -> Result<Vec<ZipEntry>> {
+        let mut sidecars = self.inner.sidecars()?;
+        sidecars.retain(|entry| self.patterns.matches(&entry.name));
+        Ok(sidecars)
+    }
+
+    fn tiny_entry_count(&self) -> usize {
+        self.inner.tiny_entry_count()
+    }
+}
+
+/// Merges the entries of several inputs, each sorted by name, into one sorted sequence
+struct MergedEntries<'a> {
+    inputs: Vec<Peekable<EntryIter<'a>>>,
+}
+
+impl Iterator for MergedEntries<'_> {
+    type Item = Result<ZipEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Input holding the next entry, with its name; read errors are passed on first
+        let mut next: Option<(usize, Option<&str>)> = None;
+        for (i, input) in self.inputs.iter_mut().enumerate() {
+            match input.peek() {
+                Some(Err(_)) => {
+                    next = Some((i, None));
+                    break;
+                }
+                Some(Ok(entry))
+                    if next.is_none_or(|(_, name)| name.is_some_and(|name| entry.name.as_str() < name)) =>
+                {
+                    next = Some((i, Some(entry.name.as_str())));
+                }
+                _ => {}
+            }
+        }
+
+        let (i, _) = next?;
+        self.inputs[i].next()
+    }
+}
+
+/// Holds back the copies of a repeated name until the last one has been read, then
+/// yields the one with the richest metadata
+struct DeduplicatedEntries<'a> {
+    entries: EntryIter<'a>,
+    /// Copies still to come, by name
+    remaining_copies: HashMap<String, usize>,
+    /// Best copy read so far with its metadata richness, by name; ordered so leftovers
+    /// come out in the same order as the merged entries
+    best_copies: BTreeMap<String, (ZipEntry, (usize, u64))>,
+}
+
+impl Iterator for DeduplicatedEntries<'_> {
+    type Item = Result<ZipEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let entry = match self.entries.next() {
+                Some(Ok(entry)) => entry,
+                Some(Err(e)) => return Some(Err(e)),
+                // Copies that failed to read never arrive; yield the best one read
+                None => return self.best_copies.pop_first().map(|(_, (best, _))| Ok(best)),
+            };
+            let Some(remaining) = self.remaining_copies.get_mut(&entry.name) else {
+                return Some(Ok(entry));
+            };
+
+            *remaining = remaining.saturating_sub(1);
+            let richness = metadata_richness(&entry);
+            let best = match self.best_copies.remove(&entry.name) {
+                Some((best, best_richness)) if best_richness >= richness => (best, best_richness),
+                _ => (entry, richness),
+            };
+            if *remaining == 0 {
+                return Some(Ok(best.0));
+            }
+            self.best_copies.insert(best.0.name.clone(), best);
+        }
+    }
+}
+
+/// Ranks copies of the same file: more EXIF fields first, then more content (a copy
+/// whose metadata was stripped is smaller)
+fn metadata_richness(entry: &ZipEntry) -> (usize, u64) {
+    let exif_fields = crate::exif::read_exif(&entry.data)
+        .map(|exif| exif.fields().count())
+        .unwrap_or(0);
+    (exif_fields, entry.size())
+}
+
+fn labelled_name(label: Option<&str>, name: &str) -> String {
+    match label {
+        Some(label) => format!("{}/{}", label, name.trim_start_matches('/')),
+        None => name.to_string(),
+    }
+}
+
+/// Concrete implementation that reads the files of a directory on disk
+#[derive(Clone)]
+pub struct DirectoryImageReader {
+    path: String,
+    pub(crate) large_entry_bytes: u64,
+    symlinks: SymlinkPolicy,
+    max_depth: Option<usize>,
+}
+
+/// Whether the directory walk follows symbolic links to files and directories
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum SymlinkPolicy {
+    /// Follow links; a directory reached twice (e.g. through a link cycle) is read once
+    #[default]
+    Follow,
+    /// Ignore links
+    Skip,
+}
+
+impl DirectoryImageReader {
+    pub fn new(path: String) -> Self {
+        Self {
+            path,
+            large_entry_bytes: DEFAULT_LARGE_ENTRY_BYTES,
+            symlinks: SymlinkPolicy::default(),
+            max_depth: None,
+        }
+    }
+
+    pub fn with_symlinks(mut self, symlinks: SymlinkPolicy) -> Self {
+        self.symlinks = symlinks;
+        self
+    }
+
+    /// Limits how many folder levels below the input directory are read (0: only the
+    /// input directory itself); unlimited when `None`
+    pub fn with_max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+}
+
+impl ZipImageReader for DirectoryImageReader {
+    fn entries(&self) -> Result<EntryIter<'_>> {
+        let paths = self.find_files(Path::new(&self.path))?;
+
+        Ok(Box::new(
+            paths.into_iter().filter_map(|path| self.try_read_file(&path).map(Ok)),
+        ))
+    }
+
+    fn entries_named(&self, names: HashSet<String>) -> Result<EntryIter<'_>> {
+        let mut paths = self.find_files(Path::new(&self.path))?;
+        paths.retain(|path| {
+            path.to_str()
+                .is_some_and(|filename| names.contains(&normalize_entry_name(filename)))
+        });
+
+        Ok(Box::new(
+            paths.into_iter().filter_map(|path| self.try_read_file(&path).map(Ok)),
+        ))
+    }
+
+    fn list_entries(&self) -> Result<Vec<EntryInfo>> {
+        let paths = self.find_files(Path::new(&self.path))?;
+
+        Ok(paths
+            .iter()
+            .filter_map(|path| {
+                Some(EntryInfo {
+                    name: normalize_entry_name(path.to_str()?),
+                    size: fs::metadata(path).ok()?.len(),
+                })
+            })
+            .collect())
+    }
+}
+
+impl DirectoryImageReader {
+    fn find_files(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+        self.collect_files(dir, 0, &mut HashSet::new(), &mut paths)?;
+        paths.sort();
+        Ok(paths)
+    }
+
+    fn collect_files(
+        &self,
+        dir: &Path,
+        depth: usize,
+        visited: &mut HashSet<PathBuf>,
+        paths: &mut Vec<PathBuf>,
+    ) -> Result<()> {
+        // A linked directory pointing back up the tree would otherwise be walked forever
+        if let Ok(canonical) = fs::canonicalize(dir) {
+            if !visited.insert(canonical) {
+                return Ok(());
+            }
+        }
+
+        let dir_entries = fs::read_dir(dir)
+            .with_context(|| format!("Failed to read directory: {}", dir.display()))?;
+
+        for entry in dir_entries.flatten() {
+            let path = entry.path();
+            let is_symlink = entry.file_type().is_ok_and(|file_type| file_type.is_symlink());
+            if is_symlink && self.symlinks == SymlinkPolicy::Skip {
+                continue;
+            }
+
+            if path.is_dir() {
+                if self.max_depth.is_none_or(|max_depth| depth < max_depth) {
+                    self.collect_files(&path, depth + 1, visited, paths)?;
+                }
+            } else if path.to_str().is_some() {
+                paths.push(path);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn try_read_file(&self, path: &Path) -> Option<ZipEntry> {
+        let filename = path.to_str()?;
+        let mut file = File::open(path).ok()?;
+        let file_metadata = file.metadata().ok()?;
+        let size = file_metadata.len();
+        let modified_secs = file_metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|since_epoch| since_epoch.as_secs() as i64);
+        let data = read_content(&mut file, size, self.large_entry_bytes).ok()?;
+        let large = (size > self.large_entry_bytes).then(|| LargeContent::File {
+            path: path.to_path_buf(),
+            size,
+        });
+
+        Some(ZipEntry {
+            name: normalize_entry_name(filename),
+            data: data.into(),
+            large,
+            metadata: EntryMetadata {
+                from_file_system: true,
+                ..EntryMetadata::from_unix_time(modified_secs, size)
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::fs::File;
+    use std::io::Write;
+    use zip::write::{FileOptions, ZipWriter};
+
+    fn create_test_zip(path: &str, files: &[(&str, &[u8])]) -> Result<()> {
+        let file = File::create(path)?;
+        let mut zip = ZipWriter::new(file);
+
+        let options: FileOptions<()> = FileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+
+        for (name, data) in files {
+            zip.start_file(*name, options)?;
+            zip.write_all(data)?;
+        }
+
+        zip.finish()?;
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("takeout.zip", InputFormat::Zip)]
+    #[case("takeout.tgz", InputFormat::Tgz)]
+    #[case("Takeout.TAR.GZ", InputFormat::Tgz)]
+    #[case("takeout.tar", InputFormat::Tar)]
+    #[case("/tmp", InputFormat::Dir)]
+    fn test_input_format_detected_from_path(#[case] path: &str, #[case] expected: InputFormat) {
+        // Act
+        let format = InputFormat::Auto.resolve(path);
+
+        // Assert
+        assert_eq!(format, expected);
+    }
+
+    #[test]
+    fn test_explicit_input_format_is_kept() {
+        // Act
+        let format = InputFormat::Tar.resolve("export.bin");
+
+        // Assert
+        assert_eq!(format, InputFormat::Tar);
+    }
+
+    #[test]
+    fn test_read_empty_zip() {
+        // Arrange
+        let zip_path = "/tmp/test_empty.zip";
+        create_test_zip(zip_path, &[]).expect("Failed to create test zip");
+        let reader = FileZipImageReader::new(zip_path.to_string());
+
+        // Act
+        let result = reader.read_entries();
+
+        // Assert
+        assert!(result.is_ok());
+        let entries = result.unwrap();
+        assert_eq!(entries.len(), 0);
+
+        // Cleanup
+        std::fs::remove_file(zip_path).ok();
+    }
+
+    #[test]
+    fn test_read_zip_with_single_file() {
+        // Arrange
+        let zip_path = "/tmp/test_single.zip";
+        let test_data = b"Hello, World!";
+        create_test_zip(zip_path, &[("test.jpg", test_data)])
+            .expect("Failed to create test zip");
+        let reader = FileZipImageReader::new(zip_path.to_string());
+
+        // Act
+        let result = reader.read_entries();
+
+        // Assert
+        assert!(result.is_ok());
+        let entries = result.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "test.jpg");
+        assert_eq!(entries[0].data, test_data);
+
+        // Cleanup
+        std::fs::remove_file(zip_path).ok();
+    }
+
+    #[test]
+    fn test_read_zip_with_multiple_files() {
+        // Arrange
+        let zip_path = "/tmp/test_multiple.zip";
+        create_test_zip(
+            zip_path,
+            &[
+                ("photo1.jpg", b"fake jpg data 1"),
+                ("photo2.jpg", b"fake jpg data 2"),
+                ("photo3.png", b"fake png data"),
+            ],
+        )
+        .expect("Failed to create test zip");
+        let reader = FileZipImageReader::new(zip_path.to_string());
+
+        // Act
+        let result = reader.read_entries();
+
+        // Assert
+        assert!(result.is_ok());
+        let entries = result.unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].name, "photo1.jpg");
+        assert_eq!(entries[1].name, "photo2.jpg");
+        assert_eq!(entries[2].name, "photo3.png");
+
+        // Cleanup
+        std::fs::remove_file(zip_path).ok();
+    }
+
+    #[test]
+    fn test_read_nonexistent_zip_returns_error() {
+        // Arrange
+        let reader = FileZipImageReader::new("/tmp/nonexistent_file.zip".to_string());
+
+        // Act
+        let result = reader.read_entries();
+
+        // Assert
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_skip_non_image_files() {
+        // Arrange
+        let zip_path = "/tmp/test_skip_non_images.zip";
+        create_test_zip(
+            zip_path,
+            &[
+                ("photo1.jpg", b"fake jpg data"),
+                ("metadata.json", b"{\"key\": \"value\"}"),
+                ("photo2.png", b"fake png data"),
+                ("document.txt", b"text file"),
+                ("photo3.heic", b"fake heic data"),
+            ],
+        )
+        .expect("Failed to create test zip");
+        let zip_reader = FileZipImageReader::new(zip_path.to_string());
+        let reader = EntrySelectionReader::new(&zip_reader);
+
+        // Act
+        let result = reader.read_entries();
+
+        // Assert
+        assert!(result.is_ok());
+        let entries = result.unwrap();
+        assert_eq!(entries.len(), 3, "Should only include image files");
+        assert_eq!(zip_reader.read_entries().unwrap().len(), 5);
+        assert_eq!(entries[0].name, "photo1.jpg");
+        assert_eq!(entries[1].name, "photo2.png");
+        assert_eq!(entries[2].name, "photo3.heic");
+
+        // Cleanup
+        std::fs::remove_file(zip_path).ok();
+    }
+
+    #[test]
+    fn test_image_extension_case_insensitive() {
+        // Arrange
+        let zip_path = "/tmp/test_case_insensitive.zip";
+        create_test_zip(
+            zip_path,
+            &[
+                ("photo.JPG", b"uppercase"),
+                ("photo.Jpg", b"mixed case"),
+                ("photo.jpeg", b"lowercase"),
+            ],
+        )
+        .expect("Failed to create test zip");
+        let reader = FileZipImageReader::new(zip_path.to_string());
+
+        // Act
+        let result = reader.read_entries();
+
+        // Assert
+        assert!(result.is_ok());
+        let entries = result.unwrap();
+        assert_eq!(entries.len(), 3, "Should recognize all case variations");
+
+        // Cleanup
+        std::fs::remove_file(zip_path).ok();
+    }
+
+    #[test]
+    fn test_backslash_separated_entry_names_are_normalized() {
+        // Arrange
+        let zip_path = "/tmp/test_backslash_names.zip";
+        create_test_zip(
+            zip_path,
+            &[("Takeout\\Google Photos\\photo.jpg", b"fake jpg data")],
+        )
+        .expect("Failed to create test zip");
+        let reader = FileZipImageReader::new(zip_path.to_string());
+
+        // Act
+        let result = reader.read_entries();
+
+        // Assert
+        let entries = result.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "Takeout/Google Photos/photo.jpg");
+
+        // Cleanup
+        std::fs::remove_file(zip_path).ok();
+    }
+
+    #[rstest]
+    #[case::unflagged_utf8(b"caf\xc3\xa9.jpg", "cafÃ©.jpg", "café.jpg")]
+    #[case::cp437(b"caf\x82.jpg", "café.jpg", "café.jpg")]
+    #[case::ascii(b"photo.jpg", "photo.jpg", "photo.jpg")]
+    #[case::flagged_cp437(b"\x8e\x99 caf\x82 \xff.jpg", "\u{fffd}\u{fffd} caf\u{fffd} \u{fffd}.jpg", "ÄÖ café \u{a0}.jpg")]
+    fn test_decode_entry_name(
+        #[case] raw_name: &[u8],
+        #[case] decoded_name: &str,
+        #[case] expected: &str,
+    ) {
+        // Act
+        let result = decode_entry_name(raw_name, decoded_name);
+
+        // Assert
+        assert_eq!(result, expected);
+    }
+
+    #[rstest]
+    #[case("__MACOSX/Takeout/Google Photos/._IMG_0001.JPG", true)]
+    #[case("Takeout/Google Photos/._IMG_0001.JPG", true)]
+    #[case("Takeout/Google Photos/.DS_Store", true)]
+    #[case("Takeout\\Google Photos\\Thumbs.db", true)]
+    #[case("Takeout/Google Photos/desktop.ini", true)]
+    #[case("Takeout/Google Photos/IMG_0001.JPG", false)]
+    #[case("Takeout/Google Photos/__MACOSX.jpg", false)]
+    fn test_is_os_junk(#[case] name: &str, #[case] expected: bool) {
+        assert_eq!(is_os_junk(name), expected);
+    }
+
+    #[test]
+    fn test_readers_skip_os_junk() {
+        // Arrange
+        let zip_path = "/tmp/test_os_junk.zip";
+        let files: &[(&str, &[u8])] = &[
+            ("Takeout/IMG_0001.JPG", b"photo"),
+            ("__MACOSX/Takeout/._IMG_0001.JPG", b"resource fork"),
+            ("Takeout/.DS_Store", b"finder"),
+        ];
+        create_test_zip(zip_path, files).expect("Failed to create test zip");
+
+        let zip_reader = FileZipImageReader::new(zip_path.to_string());
+
+        // Act
+        let entries = EntrySelectionReader::new(&zip_reader).read_entries().unwrap();
+
+        // Assert
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "Takeout/IMG_0001.JPG");
+
+        // Cleanup
+        std::fs::remove_file(zip_path).ok();
+    }
+
+    #[test]
+    fn test_unicode_path_extra_field_is_honoured() {
+        // Arrange: an old-style entry with an ASCII stand-in name and the real name in
+        // an Info-ZIP Unicode Path field (0x7075). The zip writer refuses to write that
+        // field, so it is written under an unused ID and patched afterwards.
+        let zip_path = "/tmp/test_unicode_path_field.zip";
+        let stand_in = "caf_.jpg";
+        let mut field = vec![1u8];
+        field.extend_from_slice(&crc32(stand_in.as_bytes()).to_le_bytes());
+        field.extend_from_slice("café.jpg".as_bytes());
+        let marker = [0x74, 0x70, field.len() as u8, 0, 1];
+        let mut options: FileOptions<zip::write::ExtendedFileOptions> =
+            FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        options.add_extra_data(0x7074, field.into_boxed_slice(), false).unwrap();
+        let mut zip = ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        zip.start_file(stand_in, options).unwrap();
+        zip.write_all(b"fake jpg data").unwrap();
+        let mut archive = zip.finish().unwrap().into_inner();
+        let positions: Vec<usize> = archive
+            .windows(marker.len())
+            .enumerate()
+            .filter(|(_, window)| *window == marker)
+            .map(|(position, _)| position)
+            .collect();
+        for position in positions {
+            archive[position] = 0x75;
+        }
+        std::fs::write(zip_path, archive).unwrap();
+
+        // Act
+        let entries = FileZipImageReader::new(zip_path.to_string()).read_entries().unwrap();
+
+        // Assert
+        assert_eq!(entries[0].name, "café.jpg");
+
+        // Cleanup
+        std::fs::remove_file(zip_path).ok();
+    }
+
+    /// CRC-32 (IEEE), as used by the Unicode Path extra field
+    fn crc32(data: &[u8]) -> u32 {
+        let mut crc = !0u32;
+        for &byte in data {
+            crc ^= byte as u32;
+            for _ in 0..8 {
+                crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+            }
+        }
+        !crc
+    }
+
+    #[rstest]
+    #[case("video.mp4")]
+    #[case("VIDEO.MP4")]
+    #[case("Video.Mp4")]
+    fn test_is_image_file_accepts_mp4(#[case] filename: &str) {
+        // Act
+        let result = ExtensionSet::default().contains(filename);
+
+        // Assert
+        assert!(result, "Should accept MP4 file: {}", filename);
+    }
+
+    #[rstest]
+    #[case("IMG_0001.MOV")]
+    #[case("VID-20150108-WA0000.3gp")]
+    #[case("clip.avi")]
+    #[case("movie.mkv")]
+    fn test_default_extensions_accept_videos(#[case] filename: &str) {
+        // Act
+        let extensions = ExtensionSet::default();
+
+        // Assert
+        assert!(extensions.contains(filename), "Should accept video file: {}", filename);
+        assert!(extensions.is_video(filename), "Should treat as video: {}", filename);
+    }
+
+    #[rstest]
+    #[case("DSC_0001.NEF")]
+    #[case("IMG_0001.CR2")]
+    #[case("DSC00001.ARW")]
+    #[case("PXL_20230115_120000000.dng")]
+    #[case("DSCF0001.RAF")]
+    #[case("P1010001.ORF")]
+    #[case("PXL_20230115_120000000.avif")]
+    #[case("photo.JXL")]
+    #[case("DSCF0001.MPO")]
+    fn test_default_extensions_accept_raw_and_modern_photos(#[case] filename: &str) {
+        // Act
+        let extensions = ExtensionSet::default();
+
+        // Assert
+        assert!(extensions.contains(filename), "Should accept photo file: {}", filename);
+        assert!(!extensions.is_video(filename));
+    }
+
+    #[test]
+    fn test_only_replaces_default_extensions() {
+        // Act
+        let extensions = ExtensionSet::only(&["JPG", ".heic", "mp4", " x3f "]);
+
+        // Assert
+        assert!(extensions.contains("a.jpg"));
+        assert!(extensions.contains("a.HEIC"));
+        assert!(extensions.contains("a.x3f"));
+        assert!(!extensions.contains("a.png"));
+        assert!(!extensions.contains("a.mov"));
+        assert!(extensions.is_video("a.mp4"));
+        assert!(!extensions.is_video("a.x3f"));
+    }
+
+    #[test]
+    fn test_photos_only_skips_videos() {
+        // Arrange
+        let zip_path = "/tmp/test_photos_only.zip";
+        create_test_zip(
+            zip_path,
+            &[("photo.jpg", b"fake jpg data"), ("clip.MP4", b"fake mp4 data")],
+        )
+        .expect("Failed to create test zip");
+
+        let reader = FileZipImageReader::new(zip_path.to_string());
+
+        // Act
+        let all = EntrySelectionReader::new(&reader).read_entries().unwrap();
+        let photos_only = EntrySelectionReader::new(&reader)
+            .with_videos(false)
+            .read_entries()
+            .unwrap();
+
+        // Assert
+        assert_eq!(all.len(), 2);
+        assert_eq!(photos_only.len(), 1);
+        assert_eq!(photos_only[0].name, "photo.jpg");
+
+        // Cleanup
+        std::fs::remove_file(zip_path).ok();
+    }
+
+    #[test]
+    fn test_directory_reader_reads_files_from_directory() {
+        // Arrange
+        let test_dir = "/tmp/test_dir_reader";
+        std::fs::create_dir_all(test_dir).unwrap();
+        std::fs::write(format!("{}/photo1.jpg", test_dir), b"fake jpg data").unwrap();
+        std::fs::write(format!("{}/photo2.png", test_dir), b"fake png data").unwrap();
+        std::fs::write(format!("{}/readme.txt", test_dir), b"should skip").unwrap();
+
+        let directory_reader = DirectoryImageReader::new(test_dir.to_string());
+        let reader = EntrySelectionReader::new(&directory_reader);
+
+        // Act
+        let result = reader.read_entries();
+
+        // Assert
+        assert!(result.is_ok());
+        let entries = result.unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().any(|e| e.name.ends_with("photo1.jpg")));
+        assert!(entries.iter().any(|e| e.name.ends_with("photo2.png")));
+
+        // Cleanup
+        std::fs::remove_dir_all(test_dir).ok();
+    }
+
+    #[rstest]
+    #[case(None, 3)]
+    #[case(Some(1), 2)]
+    #[case(Some(0), 1)]
+    fn test_directory_reader_walks_nested_folders_up_to_max_depth(
+        #[case] max_depth: Option<usize>,
+        #[case] expected: usize,
+    ) {
+        // Arrange
+        let test_dir = format!("/tmp/test_dir_reader_depth_{:?}", max_depth);
+        let nested = format!("{}/Takeout/Google Photos/Photos from 2014", test_dir);
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(format!("{}/top.jpg", test_dir), b"top").unwrap();
+        std::fs::write(format!("{}/Takeout/middle.jpg", test_dir), b"middle").unwrap();
+        std::fs::write(format!("{}/photo.jpg", nested), b"nested").unwrap();
+
+        // Act
+        let entries = DirectoryImageReader::new(test_dir.clone())
+            .with_max_depth(max_depth)
+            .read_entries()
+            .unwrap();
+
+        // Assert
+        assert_eq!(entries.len(), expected);
+
+        // Cleanup
+        std::fs::remove_dir_all(&test_dir).ok();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_directory_reader_symlink_policy() {
+        // Arrange
+        let test_dir = "/tmp/test_dir_reader_symlinks";
+        let outside = "/tmp/test_dir_reader_symlinks_target";
+        std::fs::create_dir_all(format!("{}/album", test_dir)).unwrap();
+        std::fs::create_dir_all(outside).unwrap();
+        std::fs::write(format!("{}/album/photo.jpg", test_dir), b"photo").unwrap();
+        std::fs::write(format!("{}/linked.jpg", outside), b"linked").unwrap();
+        std::os::unix::fs::symlink(outside, format!("{}/album/link", test_dir)).unwrap();
+        // A cycle back to the input directory
+        std::os::unix::fs::symlink(test_dir, format!("{}/album/loop", test_dir)).unwrap();
+
+        // Act
+        let followed = DirectoryImageReader::new(test_dir.to_string()).read_entries().unwrap();
+        let skipped = DirectoryImageReader::new(test_dir.to_string())
+            .with_symlinks(SymlinkPolicy::Skip)
+            .read_entries()
+            .unwrap();
+
+        // Assert
+        assert_eq!(followed.len(), 2);
+        assert!(followed.iter().any(|e| e.name.ends_with("link/linked.jpg")));
+        assert_eq!(skipped.len(), 1);
+        assert!(skipped[0].name.ends_with("album/photo.jpg"));
+
+        // Cleanup
+        std::fs::remove_dir_all(test_dir).ok();
+        std::fs::remove_dir_all(outside).ok();
+    }
+
+    #[test]
+    fn test_zip_entries_carry_archive_metadata() {
+        // Arrange
+        let zip_path = "/tmp/test_entry_metadata.zip";
+        let mut zip = ZipWriter::new(File::create(zip_path).unwrap());
+        let options: FileOptions<()> = FileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated)
+            .last_modified_time(zip::DateTime::from_date_and_time(2019, 7, 14, 10, 30, 20).unwrap());
+        zip.start_file("photo.jpg", options).unwrap();
+        zip.write_all(b"fake jpg data").unwrap();
+        zip.finish().unwrap();
+
+        // Act
+        let entries = FileZipImageReader::new(zip_path.to_string()).read_entries().unwrap();
+
+        // Assert
+        let metadata = &entries[0].metadata;
+        assert_eq!(
+            metadata.modified,
+            NaiveDate::from_ymd_opt(2019, 7, 14).unwrap().and_hms_opt(10, 30, 20)
+        );
+        assert_eq!(metadata.size, Some(13));
+        assert_eq!(metadata.crc32, Some(563834700));
+
+        // Cleanup
+        std::fs::remove_file(zip_path).ok();
+    }
+
+    #[test]
+    fn test_directory_entries_carry_file_metadata() {
+        // Arrange
+        let test_dir = "/tmp/test_dir_entry_metadata";
+        std::fs::create_dir_all(test_dir).unwrap();
+        std::fs::write(format!("{}/photo.jpg", test_dir), b"fake jpg data").unwrap();
+
+        // Act
+        let entries = DirectoryImageReader::new(test_dir.to_string()).read_entries().unwrap();
+
+        // Assert
+        let metadata = &entries[0].metadata;
+        assert!(metadata.modified.is_some());
+        assert_eq!(metadata.size, Some(13));
+        assert_eq!(metadata.crc32, None);
+
+        // Cleanup
+        std::fs::remove_dir_all(test_dir).ok();
+    }
+
+    #[rstest]
+    #[case("Takeout/Google Photos/Trash/a.jpg", false)]
+    #[case("Takeout/Google Photos/Photos from 2019/a.jpg", true)]
+    #[case("Takeout/Google Photos/Photos from 2018/a.jpg", false)]
+    fn test_entry_patterns_match(#[case] name: &str, #[case] expected: bool) {
+        // Arrange
+        let patterns = EntryPatterns::new(
+            vec![glob::Pattern::new("*2019*").unwrap()],
+            vec![glob::Pattern::new("*/Trash/*").unwrap()],
+        );
+
+        // Act
+        let matches = patterns.matches(name);
+
+        // Assert
+        assert_eq!(matches, expected);
+    }
+
+    #[test]
+    fn test_zip_reader_applies_entry_patterns() {
+        // Arrange
+        let zip_path = "/tmp/test_entry_patterns.zip";
+        let files: &[(&str, &[u8])] = &[
+            ("Takeout/Trash/deleted.jpg", b"deleted"),
+            ("Takeout/Album/kept.jpg", b"kept"),
+        ];
+        create_test_zip(zip_path, files).expect("Failed to create test zip");
+        let zip_reader = FileZipImageReader::new(zip_path.to_string());
+        let reader = EntrySelectionReader::new(&zip_reader).with_patterns(EntryPatterns::new(
+            Vec::new(),
+            vec![glob::Pattern::new("*/Trash/*").unwrap()],
+        ));
+
+        // Act
+        let entries = reader.read_entries().unwrap();
+        let listing = reader.list_entries().unwrap();
+
+        // Assert
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "Takeout/Album/kept.jpg");
+        assert_eq!(listing.len(), 1);
+
+        // Cleanup
+        std::fs::remove_file(zip_path).ok();
+    }
+
+    #[test]
+    fn test_zip_reader_reads_sidecars_separately() {
+        // Arrange
+        let zip_path = "/tmp/test_zip_sidecars.zip";
+        let files: &[(&str, &[u8])] = &[
+            ("Takeout/Album/photo.jpg", b"fake jpg data"),
+            ("Takeout/Album/photo.jpg.json", b"{\"title\": \"photo.jpg\"}"),
+            ("Takeout/Trash/deleted.jpg.json", b"{}"),
+        ];
+        create_test_zip(zip_path, files).expect("Failed to create test zip");
+        let zip_reader = FileZipImageReader::new(zip_path.to_string());
+        let reader = EntrySelectionReader::new(&zip_reader).with_patterns(EntryPatterns::new(
+            Vec::new(),
+            vec![glob::Pattern::new("*/Trash/*").unwrap()],
+        ));
+        let labelled = MultiInputReader::new().with_input(Some("anna".to_string()), &reader);
+
+        // Act
+        let entries = reader.read_entries().unwrap();
+        let sidecars = reader.sidecars().unwrap();
+        let labelled_sidecars = labelled.sidecars().unwrap();
+
+        // Assert
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "Takeout/Album/photo.jpg");
+        assert_eq!(sidecars.len(), 1);
+        assert_eq!(sidecars[0].name, "Takeout/Album/photo.jpg.json");
+        assert_eq!(sidecars[0].data, b"{\"title\": \"photo.jpg\"}");
+        assert!(sidecars[0].large.is_none());
+        assert_eq!(labelled_sidecars[0].name, "anna/Takeout/Album/photo.jpg.json");
+
+        // Cleanup
+        std::fs::remove_file(zip_path).ok();
+    }
+
+    #[test]
+    fn test_entries_named_does_not_read_other_entries() {
+        // Arrange
+        let zip_path = "/tmp/test_entries_named.zip";
+        let files: &[(&str, &[u8])] = &[
+            ("Takeout/kept.jpg", b"kept data"),
+            ("Takeout/skipped.jpg", b"damaged data"),
+        ];
+        create_test_zip(zip_path, files).expect("Failed to create test zip");
+        // Break the CRC of the skipped entry, reading it now fails
+        let mut bytes = std::fs::read(zip_path).unwrap();
+        let damaged = bytes.windows(12).position(|window| window == b"damaged data").unwrap();
+        bytes[damaged] = b'D';
+        std::fs::write(zip_path, bytes).unwrap();
+        let reader = FileZipImageReader::new(zip_path.to_string());
+        let labelled = MultiInputReader::new().with_input(Some("anna".to_string()), &reader);
+        let names = HashSet::from(["Takeout/kept.jpg".to_string()]);
+        let labelled_names = HashSet::from(["anna/Takeout/kept.jpg".to_string()]);
+
+        // Act
+        let all = reader.read_entries();
+        let named: Vec<ZipEntry> = reader.entries_named(names).unwrap().collect::<Result<_>>().unwrap();
+        let labelled_named: Vec<ZipEntry> = labelled
+            .entries_named(labelled_names)
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+
+        // Assert
+        assert!(all.is_err());
+        assert_eq!(named.len(), 1);
+        assert_eq!(named[0].data, b"kept data");
+        assert_eq!(labelled_named.len(), 1);
+        assert_eq!(labelled_named[0].name, "anna/Takeout/kept.jpg");
+
+        // Cleanup
+        std::fs::remove_file(zip_path).ok();
+    }
+
+    #[test]
+    fn test_stored_entries_are_memory_mapped() {
+        // Arrange
+        let zip_path = "/tmp/test_zip_memory_map.zip";
+        let file = File::create(zip_path).unwrap();
+        let mut zip = ZipWriter::new(file);
+        let stored: FileOptions<()> = FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        let deflated: FileOptions<()> = FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        zip.start_file("stored.jpg", stored).unwrap();
+        zip.write_all(b"stored data").unwrap();
+        zip.start_file("deflated.jpg", deflated).unwrap();
+        zip.write_all(b"deflated data").unwrap();
+        zip.finish().unwrap();
+
+        // Act
+        let mapped = FileZipImageReader::new(zip_path.to_string()).read_entries().unwrap();
+        let copied = FileZipImageReader::new(zip_path.to_string())
+            .with_memory_map(false)
+            .read_entries()
+            .unwrap();
 
-impl ZipImageReader for FileZipImageReader {
-    fn read_entries(&self) -> Result<Vec<ZipEntry>> {
-        let file = File::open(&self.path)
-            .with_context(|| format!("Failed to open ZIP file: {}", self.path))?;
+        // Assert
+        assert!(matches!(mapped[1].data, EntryData::Mapped { .. }));
+        assert_eq!(mapped[1].data, b"stored data");
+        assert!(matches!(mapped[0].data, EntryData::Owned(_)));
+        assert_eq!(mapped[0].data, b"deflated data");
+        assert!(matches!(copied[1].data, EntryData::Owned(_)));
+        assert_eq!(copied[1].data, b"stored data");
 
-        let mut archive = zip::ZipArchive::new(file)
-            .context("Failed to read ZIP archive")?;
+        // Cleanup
+        std::fs::remove_file(zip_path).ok();
+    }
 
-        let mut entries = Vec::new();
+    #[test]
+    fn test_large_stored_entry_maps_only_its_head() {
+        // Arrange
+        let zip_path = "/tmp/test_zip_memory_map_large.zip";
+        let large_data = vec![7u8; (LARGE_ENTRY_HEAD_BYTES + 10) as usize];
+        create_test_zip(zip_path, &[("clip.mp4", &large_data)]).unwrap();
+        let reader = FileZipImageReader {
+            large_entry_bytes: 100,
+            ..FileZipImageReader::new(zip_path.to_string())
+        };
 
-        for i in 0..archive.len() {
-            let mut zip_file = archive.by_index(i)
-                .with_context(|| format!("Failed to read entry at index {}", i))?;
+        // Act
+        let entries = reader.read_entries().unwrap();
 
-            // Skip directories
-            if zip_file.is_dir() {
-                continue;
-            }
+        // Assert
+        assert!(matches!(entries[0].data, EntryData::Mapped { .. }));
+        assert_eq!(entries[0].data.len() as u64, LARGE_ENTRY_HEAD_BYTES);
+        assert_eq!(entries[0].size(), large_data.len() as u64);
 
-            let name = zip_file.name().to_string();
+        // Cleanup
+        std::fs::remove_file(zip_path).ok();
+    }
 
-            // Skip non-image files
-            if !Self::is_image_file(&name) {
-                continue;
-            }
+    #[test]
+    fn test_parallel_reading_keeps_archive_order() {
+        // Arrange
+        let zip_path = "/tmp/test_zip_parallel.zip";
+        let file = File::create(zip_path).unwrap();
+        let mut zip = ZipWriter::new(file);
+        let deflated: FileOptions<()> = FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        for i in 0..20 {
+            let name = if i % 3 == 0 { format!("notes_{}.txt", i) } else { format!("photo_{:02}.jpg", i) };
+            zip.start_file(name, deflated).unwrap();
+            zip.write_all(format!("content {}", i).repeat(100).as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+        let sequential = FileZipImageReader::new(zip_path.to_string());
+        let parallel = FileZipImageReader::new(zip_path.to_string()).with_threads(4);
+        let names = HashSet::from(["photo_02.jpg".to_string(), "photo_17.jpg".to_string()]);
 
-            let mut data = Vec::new();
-            zip_file.read_to_end(&mut data)
-                .with_context(|| format!("Failed to read data for file: {}", name))?;
+        // Act
+        let expected = sequential.read_entries().unwrap();
+        let entries = parallel.read_entries().unwrap();
+        let named: Vec<ZipEntry> = parallel.entries_named(names).unwrap().collect::<Result<_>>().unwrap();
 
-            entries.push(ZipEntry { name, data });
+        // Assert
+        assert_eq!(entries.len(), expected.len());
+        for (entry, expected) in entries.iter().zip(&expected) {
+            assert_eq!(entry.name, expected.name);
+            assert_eq!(entry.data, &expected.data[..]);
         }
+        let named: Vec<&str> = named.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(named, vec!["photo_02.jpg", "photo_17.jpg"]);
 
-        Ok(entries)
+        // Cleanup
+        std::fs::remove_file(zip_path).ok();
     }
-}
 
-/// Concrete implementation that reads images from a directory on disk
-pub struct DirectoryImageReader {
-    path: String,
-}
+    #[test]
+    fn test_parallel_reading_reports_damaged_entries() {
+        // Arrange
+        let zip_path = "/tmp/test_zip_parallel_damaged.zip";
+        let files: &[(&str, &[u8])] = &[
+            ("a.jpg", b"first data"),
+            ("b.jpg", b"damaged data"),
+            ("c.jpg", b"third data"),
+        ];
+        create_test_zip(zip_path, files).unwrap();
+        let mut bytes = std::fs::read(zip_path).unwrap();
+        let damaged = bytes.windows(12).position(|window| window == b"damaged data").unwrap();
+        bytes[damaged] = b'D';
+        std::fs::write(zip_path, bytes).unwrap();
+        let reader = FileZipImageReader::new(zip_path.to_string()).with_threads(2);
 
-impl DirectoryImageReader {
-    pub fn new(path: String) -> Self {
-        Self { path }
+        // Act
+        let results: Vec<Result<ZipEntry>> = reader.entries().unwrap().collect();
+
+        // Assert
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+        assert!(results[2].is_ok());
+
+        // Cleanup
+        std::fs::remove_file(zip_path).ok();
     }
-}
 
-impl ZipImageReader for DirectoryImageReader {
-    fn read_entries(&self) -> Result<Vec<ZipEntry>> {
-        Self::read_directory_recursive(Path::new(&self.path))
+    #[test]
+    fn test_extension_set_additions_and_removals() {
+        // Arrange
+        let extensions = ExtensionSet::default()
+            .with_added(&[".DNG", "mp4"])
+            .with_removed(&["gif"]);
+
+        // Act & Assert
+        assert!(extensions.contains("raw/IMG_0001.dng"));
+        assert!(!extensions.contains("animation.gif"));
+        assert!(extensions.contains("photo.JPG"));
+        assert!(extensions.is_video("clip.mp4"), "Re-adding a video extension keeps it a video");
+        assert!(!extensions.contains("no_extension"));
     }
-}
 
-impl DirectoryImageReader {
-    fn read_directory_recursive(dir: &Path) -> Result<Vec<ZipEntry>> {
-        let dir_entries = fs::read_dir(dir)
-            .with_context(|| format!("Failed to read directory: {}", dir.display()))?;
+    #[test]
+    fn test_readers_share_configured_extensions() {
+        // Arrange
+        let zip_path = "/tmp/test_configured_extensions.zip";
+        let test_dir = "/tmp/test_dir_configured_extensions";
+        let files: &[(&str, &[u8])] = &[("photo.jpg", b"jpg"), ("raw.dng", b"dng"), ("anim.gif", b"gif")];
+        create_test_zip(zip_path, files).expect("Failed to create test zip");
+        std::fs::create_dir_all(test_dir).unwrap();
+        for (name, data) in files {
+            std::fs::write(format!("{}/{}", test_dir, name), data).unwrap();
+        }
+        let extensions = ExtensionSet::default().with_added(&["dng"]).with_removed(&["gif"]);
 
-        let mut entries = Vec::new();
+        let zip_reader = FileZipImageReader::new(zip_path.to_string());
+        let directory_reader = DirectoryImageReader::new(test_dir.to_string());
 
-        for entry in dir_entries.flatten() {
-            let path = entry.path();
+        // Act
+        let zip_entries = EntrySelectionReader::new(&zip_reader)
+            .with_extensions(extensions.clone())
+            .read_entries()
+            .unwrap();
+        let directory_entries = EntrySelectionReader::new(&directory_reader)
+            .with_extensions(extensions)
+            .read_entries()
+            .unwrap();
 
-            if path.is_dir() {
-                entries.extend(Self::read_directory_recursive(&path)?);
-            } else if let Some(zip_entry) = Self::try_read_image_file(&path) {
-                entries.push(zip_entry);
-            }
+        // Assert
+        for entries in [&zip_entries, &directory_entries] {
+            assert_eq!(entries.len(), 2);
+            assert!(entries.iter().any(|e| e.name.ends_with("raw.dng")));
+            assert!(!entries.iter().any(|e| e.name.ends_with("anim.gif")));
         }
 
-        Ok(entries)
+        // Cleanup
+        std::fs::remove_file(zip_path).ok();
+        std::fs::remove_dir_all(test_dir).ok();
     }
 
-    fn try_read_image_file(path: &Path) -> Option<ZipEntry> {
-        let filename = path.to_str()?;
+    #[rstest]
+    #[case("takeout.zip", None, "takeout.zip")]
+    #[case("anna=takeout-a.zip", Some("anna"), "takeout-a.zip")]
+    #[case("ben=/data/exports/ben=2.zip", Some("ben"), "/data/exports/ben=2.zip")]
+    #[case("/data/exports/a=b.zip", None, "/data/exports/a=b.zip")]
+    fn test_parse_input_spec(
+        #[case] value: &str,
+        #[case] label: Option<&str>,
+        #[case] path: &str,
+    ) {
+        // Act
+        let result: InputSpec = value.parse().unwrap();
+
+        // Assert
+        assert_eq!(result.label.as_deref(), label);
+        assert_eq!(result.path, path);
+    }
 
-        if !FileZipImageReader::is_image_file(filename) {
-            return None;
+    #[test]
+    fn test_expand_input_glob_into_sorted_parts() {
+        // Arrange
+        let test_dir = "/tmp/test_expand_input_glob";
+        std::fs::create_dir_all(test_dir).unwrap();
+        for part in ["takeout-002.zip", "takeout-001.zip", "other.zip"] {
+            std::fs::write(format!("{}/{}", test_dir, part), b"").unwrap();
         }
+        let input: InputSpec = format!("anna={}/takeout-*.zip", test_dir).parse().unwrap();
 
-        let data = fs::read(path).ok()?;
+        // Act
+        let expanded = input.expand().unwrap();
+        let unmatched = InputSpec {
+            label: None,
+            path: format!("{}/missing-*.zip", test_dir),
+        }
+        .expand();
 
-        Some(ZipEntry {
-            name: filename.to_string(),
-            data,
-        })
-    }
-}
+        // Assert
+        let paths: Vec<String> = expanded.iter().map(|i| i.path.clone()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                format!("{}/takeout-001.zip", test_dir),
+                format!("{}/takeout-002.zip", test_dir)
+            ]
+        );
+        assert!(expanded.iter().all(|i| i.label.as_deref() == Some("anna")));
+        assert!(unmatched.is_err());
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use rstest::rstest;
-    use std::fs::File;
-    use std::io::Write;
-    use zip::write::{FileOptions, ZipWriter};
+        // Cleanup
+        std::fs::remove_dir_all(test_dir).ok();
+    }
 
-    fn create_test_zip(path: &str, files: &[(&str, &[u8])]) -> Result<()> {
-        let file = File::create(path)?;
-        let mut zip = ZipWriter::new(file);
+    #[test]
+    fn test_expand_folder_of_takeout_parts() {
+        // Arrange
+        let test_dir = "/tmp/test_expand_takeout_folder";
+        let extracted_dir = "/tmp/test_expand_extracted_folder";
+        std::fs::create_dir_all(test_dir).unwrap();
+        std::fs::create_dir_all(format!("{}/Takeout", extracted_dir)).unwrap();
+        for part in [
+            "takeout-20240101T000000Z-002.zip",
+            "takeout-20240101T000000Z-001.zip",
+            "takeout-20240101T000000Z-003.tgz",
+            "notes.zip",
+        ] {
+            std::fs::write(format!("{}/{}", test_dir, part), b"").unwrap();
+        }
+        let input: InputSpec = format!("anna={}", test_dir).parse().unwrap();
+        let extracted: InputSpec = extracted_dir.parse().unwrap();
 
-        let options: FileOptions<()> = FileOptions::default()
-            .compression_method(zip::CompressionMethod::Stored);
+        // Act
+        let expanded = input.expand().unwrap();
+        let kept = extracted.expand().unwrap();
 
-        for (name, data) in files {
-            zip.start_file(*name, options)?;
-            zip.write_all(data)?;
-        }
+        // Assert
+        let paths: Vec<String> = expanded.iter().map(|i| i.path.clone()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                format!("{}/takeout-20240101T000000Z-001.zip", test_dir),
+                format!("{}/takeout-20240101T000000Z-002.zip", test_dir),
+                format!("{}/takeout-20240101T000000Z-003.tgz", test_dir),
+            ]
+        );
+        assert!(expanded.iter().all(|i| i.label.as_deref() == Some("anna")));
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].path, extracted_dir);
 
-        zip.finish()?;
-        Ok(())
+        // Cleanup
+        std::fs::remove_dir_all(test_dir).ok();
+        std::fs::remove_dir_all(extracted_dir).ok();
     }
 
-    #[test]
-    fn test_read_empty_zip() {
+    #[rstest]
+    #[case(1)]
+    #[case(3)]
+    fn test_zip_entries_are_read_sorted_by_name(#[case] threads: usize) {
         // Arrange
-        let zip_path = "/tmp/test_empty.zip";
-        create_test_zip(zip_path, &[]).expect("Failed to create test zip");
-        let reader = FileZipImageReader::new(zip_path.to_string());
+        let zip_path = format!("/tmp/test_zip_sorted_{}.zip", threads);
+        create_test_zip(
+            &zip_path,
+            &[("b/2.jpg", b"4"), ("a/1.jpg", b"1"), ("b/1.jpg", b"3"), ("a/2.jpg", b"2")],
+        )
+        .unwrap();
+        let reader = FileZipImageReader::new(zip_path.clone()).with_threads(threads);
 
         // Act
-        let result = reader.read_entries();
+        let entries = reader.read_entries().unwrap();
+        let listing = reader.list_entries().unwrap();
 
         // Assert
-        assert!(result.is_ok());
-        let entries = result.unwrap();
-        assert_eq!(entries.len(), 0);
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["a/1.jpg", "a/2.jpg", "b/1.jpg", "b/2.jpg"]);
+        let listed: Vec<&str> = listing.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(listed, names);
 
         // Cleanup
         std::fs::remove_file(zip_path).ok();
     }
 
     #[test]
-    fn test_read_zip_with_single_file() {
+    fn test_multi_input_reader_merges_inputs_by_name() {
         // Arrange
-        let zip_path = "/tmp/test_single.zip";
-        let test_data = b"Hello, World!";
-        create_test_zip(zip_path, &[("test.jpg", test_data)])
-            .expect("Failed to create test zip");
-        let reader = FileZipImageReader::new(zip_path.to_string());
+        let first_zip = "/tmp/test_multi_input_merge_a.zip";
+        let second_zip = "/tmp/test_multi_input_merge_b.zip";
+        create_test_zip(first_zip, &[("Takeout/2.jpg", b"2"), ("Takeout/4.jpg", b"4")]).unwrap();
+        create_test_zip(second_zip, &[("Takeout/1.jpg", b"1"), ("Takeout/3.jpg", b"3")]).unwrap();
+        let first = FileZipImageReader::new(first_zip.to_string());
+        let second = FileZipImageReader::new(second_zip.to_string());
+        let reader = MultiInputReader::new()
+            .with_input(None, &first)
+            .with_input(None, &second);
 
         // Act
-        let result = reader.read_entries();
+        let entries = reader.read_entries().unwrap();
 
         // Assert
-        assert!(result.is_ok());
-        let entries = result.unwrap();
-        assert_eq!(entries.len(), 1);
-        assert_eq!(entries[0].name, "test.jpg");
-        assert_eq!(entries[0].data, test_data);
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["Takeout/1.jpg", "Takeout/2.jpg", "Takeout/3.jpg", "Takeout/4.jpg"]
+        );
 
         // Cleanup
-        std::fs::remove_file(zip_path).ok();
+        std::fs::remove_file(first_zip).ok();
+        std::fs::remove_file(second_zip).ok();
     }
 
     #[test]
-    fn test_read_zip_with_multiple_files() {
+    fn test_multi_input_reader_reads_repeated_entries_once() {
         // Arrange
-        let zip_path = "/tmp/test_multiple.zip";
+        let first_zip = "/tmp/test_multi_input_repeated_a.zip";
+        let second_zip = "/tmp/test_multi_input_repeated_b.zip";
+        let with_exif = crate::gps_privacy::tests::jpeg_with_gps([(52, 1), (0, 1), (0, 1)], [(4, 1), (0, 1), (0, 1)]);
         create_test_zip(
-            zip_path,
-            &[
-                ("photo1.jpg", b"fake jpg data 1"),
-                ("photo2.jpg", b"fake jpg data 2"),
-                ("photo3.png", b"fake png data"),
-            ],
+            first_zip,
+            &[("Takeout/photo.jpg", b"stripped"), ("Takeout/first.jpg", b"a")],
         )
-        .expect("Failed to create test zip");
-        let reader = FileZipImageReader::new(zip_path.to_string());
+        .unwrap();
+        create_test_zip(
+            second_zip,
+            &[("Takeout/photo.jpg", &with_exif), ("Takeout/second.jpg", b"b")],
+        )
+        .unwrap();
+        let first = FileZipImageReader::new(first_zip.to_string());
+        let second = FileZipImageReader::new(second_zip.to_string());
+        let reader = MultiInputReader::new()
+            .with_input(None, &first)
+            .with_input(None, &second);
 
         // Act
-        let result = reader.read_entries();
+        let entries = reader.read_entries().unwrap();
+        let listing = reader.list_entries().unwrap();
 
         // Assert
-        assert!(result.is_ok());
-        let entries = result.unwrap();
-        assert_eq!(entries.len(), 3);
-        assert_eq!(entries[0].name, "photo1.jpg");
-        assert_eq!(entries[1].name, "photo2.jpg");
-        assert_eq!(entries[2].name, "photo3.png");
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["Takeout/first.jpg", "Takeout/photo.jpg", "Takeout/second.jpg"]
+        );
+        assert_eq!(entries[1].data, with_exif);
+        assert_eq!(listing.len(), 3);
 
         // Cleanup
-        std::fs::remove_file(zip_path).ok();
+        std::fs::remove_file(first_zip).ok();
+        std::fs::remove_file(second_zip).ok();
     }
 
     #[test]
-    fn test_read_nonexistent_zip_returns_error() {
+    fn test_deduplicated_entries_yields_leftover_copies_in_name_order() {
         // Arrange
-        let reader = FileZipImageReader::new("/tmp/nonexistent_file.zip".to_string());
+        let names = ["Takeout/a.jpg", "Takeout/b.jpg", "Takeout/c.jpg", "Takeout/d.jpg"];
+        let entries = names.map(|name| {
+            Ok(ZipEntry {
+                name: name.to_string(),
+                data: b"photo".to_vec().into(),
+                ..Default::default()
+            })
+        });
+        let deduplicated = DeduplicatedEntries {
+            entries: Box::new(entries.into_iter()),
+            // The second copy of each failed to read and never arrives
+            remaining_copies: names.iter().map(|name| (name.to_string(), 2)).collect(),
+            best_copies: BTreeMap::new(),
+        };
 
         // Act
-        let result = reader.read_entries();
+        let yielded: Vec<String> = deduplicated.map(|entry| entry.unwrap().name).collect();
 
         // Assert
-        assert!(result.is_err());
+        assert_eq!(yielded, names);
     }
 
-    #[test]
-    fn test_skip_non_image_files() {
+    #[rstest]
+    #[case(DEFAULT_MIN_ENTRY_BYTES, vec!["Takeout/photo.jpg", "Takeout/thumb.jpg"], 1)]
+    #[case(10, vec!["Takeout/photo.jpg"], 2)]
+    #[case(0, vec!["Takeout/empty.jpg", "Takeout/photo.jpg", "Takeout/thumb.jpg"], 0)]
+    fn test_min_size_reader_leaves_out_tiny_entries(
+        #[case] min_size: u64,
+        #[case] expected_names: Vec<&str>,
+        #[case] expected_tiny: usize,
+    ) {
         // Arrange
-        let zip_path = "/tmp/test_skip_non_images.zip";
+        let zip_path = format!("/tmp/test_min_size_{}.zip", min_size);
         create_test_zip(
-            zip_path,
+            &zip_path,
             &[
-                ("photo1.jpg", b"fake jpg data"),
-                ("metadata.json", b"{\"key\": \"value\"}"),
-                ("photo2.png", b"fake png data"),
-                ("document.txt", b"text file"),
-                ("photo3.heic", b"fake heic data"),
+                ("Takeout/empty.jpg", b""),
+                ("Takeout/thumb.jpg", b"tiny"),
+                ("Takeout/photo.jpg", b"a real photo"),
+                ("Takeout/empty.jpg.json", b""),
             ],
         )
-        .expect("Failed to create test zip");
-        let reader = FileZipImageReader::new(zip_path.to_string());
+        .unwrap();
+        let zip_reader = FileZipImageReader::new(zip_path.clone());
+        let inner = EntrySelectionReader::new(&zip_reader);
+        let reader = MinSizeReader::new(&inner).with_min_size(min_size);
 
         // Act
-        let result = reader.read_entries();
+        let listing = reader.list_entries().unwrap();
+        let entries = reader.read_entries().unwrap();
+        let sidecars = reader.sidecars().unwrap();
 
         // Assert
-        assert!(result.is_ok());
-        let entries = result.unwrap();
-        assert_eq!(entries.len(), 3, "Should only include image files");
-        assert_eq!(entries[0].name, "photo1.jpg");
-        assert_eq!(entries[1].name, "photo2.png");
-        assert_eq!(entries[2].name, "photo3.heic");
+        let listed: Vec<&str> = listing.iter().map(|info| info.name.as_str()).collect();
+        let read: Vec<&str> = entries.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(listed, expected_names);
+        assert_eq!(read, expected_names);
+        assert_eq!(reader.tiny_entry_count(), expected_tiny);
+        assert_eq!(sidecars.len(), 1, "Sidecars are kept whatever their size");
+
+        // Cleanup
+        std::fs::remove_file(&zip_path).ok();
+    }
+
+    #[test]
+    fn test_multi_input_reader_namespaces_labelled_inputs() {
+        // Arrange
+        let first_zip = "/tmp/test_multi_input_a.zip";
+        let second_zip = "/tmp/test_multi_input_b.zip";
+        create_test_zip(first_zip, &[("Takeout/photo.jpg", b"a")]).unwrap();
+        create_test_zip(second_zip, &[("Takeout/photo.jpg", b"b")]).unwrap();
+        let first = FileZipImageReader::new(first_zip.to_string());
+        let second = FileZipImageReader::new(second_zip.to_string());
+        let reader = MultiInputReader::new()
+            .with_input(Some("anna".to_string()), &first)
+            .with_input(None, &second);
+
+        // Act
+        let entries = reader.read_entries().unwrap();
+
+        // Assert
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["Takeout/photo.jpg", "anna/Takeout/photo.jpg"]);
+
+        // Cleanup
+        std::fs::remove_file(first_zip).ok();
+        std::fs::remove_file(second_zip).ok();
+    }
+
+    #[test]
+    fn test_list_entries_returns_names_and_sizes_without_data() {
+        // Arrange
+        let zip_path = "/tmp/test_list_entries.zip";
+        let test_dir = "/tmp/test_dir_list_entries";
+        let files: &[(&str, &[u8])] = &[("a.jpg", b"12345"), ("notes.txt", b"skip"), ("b.png", b"12")];
+        create_test_zip(zip_path, files).expect("Failed to create test zip");
+        std::fs::create_dir_all(test_dir).unwrap();
+        for (name, data) in files {
+            std::fs::write(format!("{}/{}", test_dir, name), data).unwrap();
+        }
+
+        let zip_reader = FileZipImageReader::new(zip_path.to_string());
+        let directory_reader = DirectoryImageReader::new(test_dir.to_string());
+
+        // Act
+        let zip_listing = EntrySelectionReader::new(&zip_reader).list_entries().unwrap();
+        let mut directory_listing = EntrySelectionReader::new(&directory_reader)
+            .list_entries()
+            .unwrap();
+        directory_listing.sort_by(|a, b| a.name.cmp(&b.name));
+
+        // Assert
+        let expected = |prefix: &str| {
+            vec![
+                EntryInfo { name: format!("{}a.jpg", prefix), size: 5 },
+                EntryInfo { name: format!("{}b.png", prefix), size: 2 },
+            ]
+        };
+        assert_eq!(zip_listing, expected(""));
+        assert_eq!(directory_listing, expected(&format!("{}/", test_dir)));
 
         // Cleanup
         std::fs::remove_file(zip_path).ok();
+        std::fs::remove_dir_all(test_dir).ok();
     }
 
     #[test]
-    fn test_image_extension_case_insensitive() {
+    fn test_entries_are_read_lazily() {
         // Arrange
-        let zip_path = "/tmp/test_case_insensitive.zip";
-        create_test_zip(
-            zip_path,
-            &[
-                ("photo.JPG", b"uppercase"),
-                ("photo.Jpg", b"mixed case"),
-                ("photo.jpeg", b"lowercase"),
-            ],
-        )
-        .expect("Failed to create test zip");
+        let zip_path = "/tmp/test_entries_lazy.zip";
+        create_test_zip(zip_path, &[("a.jpg", b"first"), ("b.jpg", b"second")])
+            .expect("Failed to create test zip");
         let reader = FileZipImageReader::new(zip_path.to_string());
 
         // Act
-        let result = reader.read_entries();
+        let mut entries = reader.entries().unwrap();
+        let first = entries.next().unwrap().unwrap();
 
         // Assert
-        assert!(result.is_ok());
-        let entries = result.unwrap();
-        assert_eq!(entries.len(), 3, "Should recognize all case variations");
+        assert_eq!(first.name, "a.jpg");
+        assert_eq!(first.data, b"first");
+        assert_eq!(entries.next().unwrap().unwrap().name, "b.jpg");
+        assert!(entries.next().is_none());
 
         // Cleanup
         std::fs::remove_file(zip_path).ok();
     }
 
-    #[rstest]
-    #[case("video.mp4")]
-    #[case("VIDEO.MP4")]
-    #[case("Video.Mp4")]
-    fn test_is_image_file_accepts_mp4(#[case] filename: &str) {
+    #[test]
+    fn test_read_zip64_archive() {
+        // Arrange
+        let zip_path = "/tmp/test_zip64.zip";
+        let file = File::create(zip_path).unwrap();
+        let mut zip = ZipWriter::new(file);
+        let options: FileOptions<()> = FileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored)
+            .large_file(true);
+        zip.start_file("video.mp4", options).unwrap();
+        zip.write_all(b"zip64 entry").unwrap();
+        zip.finish().unwrap();
+        let reader = FileZipImageReader::new(zip_path.to_string());
+
         // Act
-        let result = FileZipImageReader::is_image_file(filename);
+        let entries = reader.read_entries().unwrap();
 
         // Assert
-        assert!(result, "Should accept MP4 file: {}", filename);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].data, b"zip64 entry");
+
+        // Cleanup
+        std::fs::remove_file(zip_path).ok();
     }
 
     #[test]
-    fn test_directory_reader_reads_files_from_directory() {
+    fn test_large_entries_keep_only_head_in_memory_and_stream_the_rest() {
         // Arrange
-        let test_dir = "/tmp/test_dir_reader";
+        let zip_path = "/tmp/test_large_entries.zip";
+        let test_dir = "/tmp/test_dir_large_entries";
+        let large_data = vec![7u8; (LARGE_ENTRY_HEAD_BYTES + 10) as usize];
+        create_test_zip(zip_path, &[("small.jpg", b"small"), ("large.mp4", &large_data)])
+            .expect("Failed to create test zip");
         std::fs::create_dir_all(test_dir).unwrap();
-        std::fs::write(format!("{}/photo1.jpg", test_dir), b"fake jpg data").unwrap();
-        std::fs::write(format!("{}/photo2.png", test_dir), b"fake png data").unwrap();
-        std::fs::write(format!("{}/readme.txt", test_dir), b"should skip").unwrap();
+        std::fs::write(format!("{}/large.mp4", test_dir), &large_data).unwrap();
 
-        let reader = DirectoryImageReader::new(test_dir.to_string());
+        let zip_reader = FileZipImageReader {
+            large_entry_bytes: 100,
+            ..FileZipImageReader::new(zip_path.to_string())
+        };
+        let directory_reader = DirectoryImageReader {
+            large_entry_bytes: 100,
+            ..DirectoryImageReader::new(test_dir.to_string())
+        };
 
         // Act
-        let result = reader.read_entries();
+        let zip_entries = zip_reader.read_entries().unwrap();
+        let directory_entries = directory_reader.read_entries().unwrap();
 
         // Assert
-        assert!(result.is_ok());
-        let entries = result.unwrap();
-        assert_eq!(entries.len(), 2);
-        assert!(entries.iter().any(|e| e.name.ends_with("photo1.jpg")));
-        assert!(entries.iter().any(|e| e.name.ends_with("photo2.png")));
+        assert!(zip_entries[1].large.is_none());
+        assert_eq!(zip_entries[1].data, b"small");
+        for large_entry in [&zip_entries[0], &directory_entries[0]] {
+            assert_eq!(large_entry.data.len() as u64, LARGE_ENTRY_HEAD_BYTES);
+            assert_eq!(large_entry.size(), large_data.len() as u64);
+            let mut streamed = Vec::new();
+            large_entry.large.as_ref().unwrap().copy_to(&mut streamed).unwrap();
+            assert_eq!(streamed, large_data);
+        }
 
         // Cleanup
+        std::fs::remove_file(zip_path).ok();
         std::fs::remove_dir_all(test_dir).ok();
     }
 }